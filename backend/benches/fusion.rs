@@ -0,0 +1,35 @@
+//! Fusion filter throughput
+//!
+//! Run with `cargo bench --bench fusion`. Each filter is run several times
+//! on the same seeded readings; the spread between runs shows how stable
+//! the numbers are on this machine.
+
+use sensor_fusion_backend::bench::{run_benchmark, BenchConfig};
+use sensor_fusion_backend::fusion::FilterKind;
+
+/// Timed runs per filter (after one warm-up run)
+const RUNS: usize = 5;
+
+fn main() {
+    for filter in FilterKind::ALL {
+        let config = BenchConfig {
+            filter,
+            updates: 100_000,
+            ..BenchConfig::default()
+        };
+        run_benchmark(&config);
+
+        let rates: Vec<f64> = (0..RUNS).map(|_| run_benchmark(&config).updates_per_sec).collect();
+        let mean = rates.iter().sum::<f64>() / RUNS as f64;
+        let spread = rates.iter().fold(0.0_f64, |worst, rate| worst.max((rate - mean).abs() / mean));
+        println!(
+            "{:<14} {:>12.0} updates/s  {:>8.1} ns/update  ±{:.1}% over {} runs (seed {})",
+            filter.name(),
+            mean,
+            1e9 / mean,
+            spread * 100.0,
+            RUNS,
+            config.seed,
+        );
+    }
+}
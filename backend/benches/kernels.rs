@@ -0,0 +1,60 @@
+//! Scalar vs SIMD batched gyro integration
+//!
+//! Run with `cargo bench --bench kernels --features simd`. Both paths
+//! integrate the same batch of gyro updates from the same start; the
+//! speedup is the ratio of their mean times, and the final orientations
+//! and norm deviations are compared to show the batch agrees within
+//! tolerance.
+
+use sensor_fusion_backend::fusion::kernels::{integrate_gyro_batch, simd};
+use sensor_fusion_backend::models::{Quaternion, Vec3};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Gyro updates per timed batch
+const BATCH: usize = 100_000;
+
+/// Timed runs per path (after one warm-up run)
+const RUNS: usize = 5;
+
+/// IMU time step (s), the default 50 Hz rate
+const DT: f64 = 0.02;
+
+/// Integrate the whole batch; best mean time per update (ns) and the
+/// result
+fn time_batch(kernel: impl Fn(Quaternion, &[Vec3], f64, u32) -> (Quaternion, f64), rates: &[Vec3], steps: u32) -> (f64, (Quaternion, f64)) {
+    let mut best = Duration::MAX;
+    let mut result = (Quaternion::identity(), 0.0);
+    for run in 0..=RUNS {
+        let started = Instant::now();
+        result = black_box(kernel(black_box(Quaternion::identity()), black_box(rates), DT, steps));
+        let elapsed = started.elapsed();
+        if run > 0 {
+            best = best.min(elapsed);
+        }
+    }
+    (best.as_secs_f64() * 1e9 / rates.len() as f64, result)
+}
+
+fn main() {
+    let rates: Vec<Vec3> = (0..BATCH)
+        .map(|i| {
+            let phase = i as f64 * 0.013;
+            Vec3::new(0.8 * phase.sin(), -0.5 * phase.cos(), 0.3 * (2.0 * phase).sin())
+        })
+        .collect();
+
+    for steps in [1, 4, 16] {
+        let (scalar, (scalar_q, scalar_deviation)) = time_batch(integrate_gyro_batch, &rates, steps);
+        let (simd, (simd_q, simd_deviation)) = time_batch(simd::integrate_gyro_batch, &rates, steps);
+        println!(
+            "{:>2} substeps  scalar {:>7.1} ns/update  simd {:>7.1} ns/update  speedup {:.2}x  final angle diff {:.1e} rad  deviation diff {:.1e}",
+            steps,
+            scalar,
+            simd,
+            scalar / simd,
+            scalar_q.angle_to(&simd_q),
+            (scalar_deviation - simd_deviation).abs(),
+        );
+    }
+}
@@ -0,0 +1,11 @@
+//! Stream Analysis
+//! 
+//! Optional analyses run over the published frame stream, off the fusion
+//! loop. Their results are pushed to streaming clients as typed messages.
+
+pub mod spectrum;
+pub mod stats;
+
+// Re-export commonly used types
+pub use spectrum::{Spectrum, SpectrumAnalyzer, SpectrumConfig};
+pub use stats::{FieldStats, FieldStatsConfig, FieldStatsReport};
@@ -0,0 +1,220 @@
+//! Vibration Spectrum
+//!
+//! Frequency content of the accelerometer for vibration monitoring:
+//! - Sliding window of acceleration magnitude (gravity included, removed
+//!   as the window mean)
+//! - Hann-windowed FFT (rustfft)
+//! - Sample rate measured from frame timestamps, so throttled broadcasts
+//!   and replays are analysed at their actual rate
+//!
+//! Frequencies above half the sample rate alias; at the default 50 Hz IMU
+//! rate only vibration below 25 Hz is resolved.
+
+use crate::models::duration_secs;
+use chrono::{DateTime, Utc};
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Settings for the spectrum analysis
+#[derive(Debug, Clone, Serialize)]
+pub struct SpectrumConfig {
+    /// Samples per FFT; a power of two from 8 to 4096
+    pub window: usize,
+
+    /// Time between published spectra
+    #[serde(rename = "interval_secs", serialize_with = "duration_secs::serialize")]
+    pub interval: Duration,
+}
+
+impl Default for SpectrumConfig {
+    fn default() -> Self {
+        Self {
+            window: 256,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl SpectrumConfig {
+    /// Check the window size is a usable FFT length
+    pub fn is_valid(&self) -> bool {
+        self.window.is_power_of_two() && (8..=4096).contains(&self.window) && !self.interval.is_zero()
+    }
+}
+
+/// One-sided amplitude spectrum of the acceleration magnitude
+///
+/// Serializes as a `{"type": "spectrum", ...}` message.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename = "spectrum")]
+pub struct Spectrum {
+    /// Timestamp of the newest sample in the window
+    pub timestamp: DateTime<Utc>,
+
+    /// Measured sample rate in Hz
+    pub sample_rate_hz: f64,
+
+    /// Frequency spacing of `bins` in Hz (bin `k` is at `k * bin_hz`)
+    pub bin_hz: f64,
+
+    /// Amplitude (m/s²) from 0 Hz up to half the sample rate
+    pub bins: Vec<f64>,
+
+    /// Frequency of the strongest non-DC bin in Hz
+    pub dominant_hz: f64,
+
+    /// Amplitude of the strongest non-DC bin (m/s²)
+    pub dominant_amplitude: f64,
+}
+
+/// Sliding-window FFT over acceleration magnitude
+pub struct SpectrumAnalyzer {
+    /// FFT length
+    window: usize,
+
+    /// Most recent samples (timestamp, |acceleration|), oldest first
+    samples: VecDeque<(DateTime<Utc>, f64)>,
+
+    /// Hann window coefficients
+    taper: Vec<f64>,
+
+    /// Forward FFT planned for `window` samples
+    fft: Arc<dyn Fft<f64>>,
+}
+
+impl SpectrumAnalyzer {
+    /// Create an analyzer with the given FFT length
+    ///
+    /// # Panics
+    /// If `window` is not a power of two of at least 8.
+    pub fn new(window: usize) -> Self {
+        assert!(window.is_power_of_two() && window >= 8, "window must be a power of two >= 8");
+        let taper = (0..window)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / window as f64).cos())
+            .collect();
+        Self {
+            window,
+            samples: VecDeque::with_capacity(window),
+            taper,
+            fft: FftPlanner::new().plan_fft_forward(window),
+        }
+    }
+
+    /// Add an acceleration magnitude sample
+    ///
+    /// Non-finite samples are skipped.
+    pub fn push(&mut self, timestamp: DateTime<Utc>, magnitude: f64) {
+        if !magnitude.is_finite() {
+            return;
+        }
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((timestamp, magnitude));
+    }
+
+    /// Check whether a full window has been collected
+    pub fn is_ready(&self) -> bool {
+        self.samples.len() == self.window
+    }
+
+    /// Discard all samples (e.g. after a gap in the stream)
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Compute the spectrum of the current window
+    ///
+    /// Returns `None` until the window is full, or when the timestamps
+    /// don't advance (no usable sample rate).
+    pub fn spectrum(&self) -> Option<Spectrum> {
+        if !self.is_ready() {
+            return None;
+        }
+        let (first, _) = self.samples.front()?;
+        let (last, _) = self.samples.back()?;
+        let span = (*last - *first).num_nanoseconds()? as f64 * 1e-9;
+        if span <= 0.0 {
+            return None;
+        }
+        let sample_rate_hz = (self.window - 1) as f64 / span;
+
+        // Remove the mean (gravity and any DC offset) and taper the edges
+        let mean = self.samples.iter().map(|(_, m)| m).sum::<f64>() / self.window as f64;
+        let mut buffer: Vec<Complex<f64>> = self
+            .samples
+            .iter()
+            .zip(&self.taper)
+            .map(|((_, m), w)| Complex::new((m - mean) * w, 0.0))
+            .collect();
+        self.fft.process(&mut buffer);
+
+        // One-sided amplitude, corrected for the Hann window's 0.5 gain
+        let scale = 4.0 / self.window as f64;
+        let bins: Vec<f64> = buffer[..=self.window / 2]
+            .iter()
+            .map(|c| c.re.hypot(c.im) * scale)
+            .collect();
+
+        let bin_hz = sample_rate_hz / self.window as f64;
+        let (peak, dominant_amplitude) = bins
+            .iter()
+            .copied()
+            .enumerate()
+            .skip(1)
+            .fold((0, 0.0), |best, (k, a)| if a > best.1 { (k, a) } else { best });
+
+        Some(Spectrum {
+            timestamp: *last,
+            sample_rate_hz,
+            bin_hz,
+            bins,
+            dominant_hz: peak as f64 * bin_hz,
+            dominant_amplitude,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `count` samples of gravity plus a sinusoid, sampled at 50 Hz
+    fn feed(analyzer: &mut SpectrumAnalyzer, count: usize, freq_hz: f64, amplitude: f64) {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        for i in 0..count {
+            let t = i as f64 / 50.0;
+            let timestamp = start + chrono::Duration::milliseconds(i as i64 * 20);
+            analyzer.push(timestamp, 9.81 + amplitude * (2.0 * PI * freq_hz * t).sin());
+        }
+    }
+
+    #[test]
+    fn test_pure_sinusoid_peaks_at_its_frequency() {
+        let mut analyzer = SpectrumAnalyzer::new(256);
+        // 6.25 Hz falls exactly on bin 32 of a 256-point FFT at 50 Hz
+        feed(&mut analyzer, 256, 6.25, 2.0);
+
+        let spectrum = analyzer.spectrum().expect("full window gives a spectrum");
+        assert!((spectrum.sample_rate_hz - 50.0).abs() < 1e-9);
+        assert_eq!(spectrum.bins.len(), 129);
+        assert!((spectrum.dominant_hz - 6.25).abs() < 1e-9, "peak at {} Hz", spectrum.dominant_hz);
+        assert!((spectrum.dominant_amplitude - 2.0).abs() < 0.05, "amplitude {}", spectrum.dominant_amplitude);
+
+        // Gravity is removed as the mean, leaving no DC
+        assert!(spectrum.bins[0] < 1e-6);
+    }
+
+    #[test]
+    fn test_no_spectrum_until_window_is_full() {
+        let mut analyzer = SpectrumAnalyzer::new(64);
+        feed(&mut analyzer, 63, 5.0, 1.0);
+        assert!(!analyzer.is_ready());
+        assert!(analyzer.spectrum().is_none());
+    }
+}
@@ -0,0 +1,208 @@
+//! Rolling Field Statistics
+//! 
+//! Min, max, mean, and standard deviation of key frame fields over a
+//! sliding time window, so dashboards don't each have to compute them.
+//! Every sample in the window counts equally, so a fault inside the window
+//! shows up in the extremes and spread until it ages out.
+//! 
+//! Angles are treated as plain numbers: a yaw crossing ±180° within the
+//! window widens its range and spread accordingly.
+
+use crate::models::{duration_secs, FusedSensorData};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of tracked fields
+const FIELD_COUNT: usize = 6;
+
+/// Settings for rolling field statistics
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldStatsConfig {
+    /// Span of frames (by timestamp) the statistics cover
+    #[serde(rename = "window_secs", serialize_with = "duration_secs::serialize")]
+    pub window: Duration,
+
+    /// Time between published summaries
+    #[serde(rename = "interval_secs", serialize_with = "duration_secs::serialize")]
+    pub interval: Duration,
+}
+
+impl Default for FieldStatsConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(10),
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Statistics of one field over the window
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Stats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation
+    pub stddev: f64,
+}
+
+impl Stats {
+    /// Compute statistics of a non-empty sequence
+    fn of(values: impl Iterator<Item = f64> + Clone) -> Self {
+        let (mut min, mut max, mut sum, mut n) = (f64::INFINITY, f64::NEG_INFINITY, 0.0, 0usize);
+        for v in values.clone() {
+            min = min.min(v);
+            max = max.max(v);
+            sum += v;
+            n += 1;
+        }
+        let mean = sum / n as f64;
+        let variance = values.map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+        Self {
+            min,
+            max,
+            mean,
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+/// Statistics of each tracked field
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldSummaries {
+    pub confidence: Stats,
+    pub system_health: Stats,
+    pub gps_speed: Stats,
+    pub roll: Stats,
+    pub pitch: Stats,
+    pub yaw: Stats,
+}
+
+/// Rolling statistics message
+/// 
+/// Serializes as a `{"type": "field_stats", ...}` message.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename = "field_stats")]
+pub struct FieldStatsReport {
+    /// Timestamp of the newest frame in the window
+    pub timestamp: DateTime<Utc>,
+
+    /// Configured window length in seconds
+    pub window_secs: f64,
+
+    /// Frames in the window
+    pub samples: usize,
+
+    /// Per-field statistics
+    pub fields: FieldSummaries,
+}
+
+/// Sliding-window statistics over published frames
+pub struct FieldStats {
+    /// Window length
+    window: Duration,
+
+    /// Tracked field values of frames in the window, oldest first
+    samples: VecDeque<(DateTime<Utc>, [f64; FIELD_COUNT])>,
+}
+
+impl FieldStats {
+    /// Create statistics over the given window
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Add a frame and drop frames older than the window
+    /// 
+    /// A frame older than the newest one seen (e.g. a replay restarting)
+    /// starts a fresh window.
+    pub fn push(&mut self, frame: &FusedSensorData) {
+        if self.samples.back().is_some_and(|(t, _)| frame.timestamp < *t) {
+            self.samples.clear();
+        }
+
+        let (roll, pitch, yaw) = frame.euler_degrees;
+        self.samples.push_back((
+            frame.timestamp,
+            [frame.confidence, frame.system_health, frame.gps_speed, roll, pitch, yaw],
+        ));
+
+        let window = chrono::Duration::from_std(self.window).unwrap_or(chrono::Duration::MAX);
+        let cutoff = frame.timestamp - window;
+        while self.samples.front().is_some_and(|(t, _)| *t < cutoff) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Summarize the current window, or `None` when it is empty
+    pub fn report(&self) -> Option<FieldStatsReport> {
+        let (timestamp, _) = *self.samples.back()?;
+        let field = |i: usize| Stats::of(self.samples.iter().map(move |(_, values)| values[i]));
+        Some(FieldStatsReport {
+            timestamp,
+            window_secs: self.window.as_secs_f64(),
+            samples: self.samples.len(),
+            fields: FieldSummaries {
+                confidence: field(0),
+                system_health: field(1),
+                gps_speed: field(2),
+                roll: field(3),
+                pitch: field(4),
+                yaw: field(5),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn frame_at(secs: i64, speed: f64, roll: f64) -> FusedSensorData {
+        FusedSensorData {
+            timestamp: DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap(),
+            gps_speed: speed,
+            euler_degrees: (roll, 0.0, 0.0),
+            confidence: 0.9,
+            system_health: 1.0,
+            ..FusedSensorData::default()
+        }
+    }
+
+    #[test]
+    fn test_known_sequence_matches_hand_computed_stats() {
+        let mut stats = FieldStats::new(Duration::from_secs(10));
+        for (i, speed) in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0].into_iter().enumerate() {
+            stats.push(&frame_at(i as i64, speed, 0.0));
+        }
+
+        let report = stats.report().unwrap();
+        assert_eq!(report.samples, 8);
+        assert_eq!(report.fields.gps_speed, Stats { min: 2.0, max: 9.0, mean: 5.0, stddev: 2.0 });
+        assert!(report.fields.confidence.stddev < 1e-12);
+    }
+
+    #[test]
+    fn test_spike_shows_until_it_ages_out() {
+        let mut stats = FieldStats::new(Duration::from_secs(3));
+        for secs in 0..4 {
+            let roll = if secs == 1 { 90.0 } else { 0.0 };
+            stats.push(&frame_at(secs, 1.0, roll));
+        }
+        let roll = stats.report().unwrap().fields.roll;
+        assert_eq!((roll.max, roll.mean), (90.0, 22.5));
+        assert!((roll.stddev - 38.971).abs() < 1e-3, "stddev {}", roll.stddev);
+
+        // Four seconds after the spike it has left the 3 s window
+        stats.push(&frame_at(5, 1.0, 0.0));
+        let report = stats.report().unwrap();
+        assert_eq!(report.samples, 3);
+        assert_eq!(report.fields.roll.max, 0.0);
+    }
+}
@@ -0,0 +1,149 @@
+//! Fusion Benchmark Harness
+//!
+//! Runs a fusion filter over readings from seeded simulators and times it,
+//! so benchmarks (criterion or a plain bench binary) measure every filter
+//! the same way. Simulator and RNG setup and the generation of the
+//! readings happen before the clock starts; only the filter updates are
+//! timed. Updates use an explicit time step, so a given seed always
+//! produces the same readings and the same estimate.
+
+use crate::fusion::FilterKind;
+use crate::models::{FusedSensorData, GpsData, ImuData};
+use crate::sensors::rng::{GPS_STREAM, IMU_STREAM};
+use crate::sensors::{GpsSimulator, ImuSimulator, RngBackend, SimRngConfig};
+use serde::Serialize;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Benchmark run settings
+#[derive(Debug, Clone, Copy)]
+pub struct BenchConfig {
+    /// Filter to benchmark
+    pub filter: FilterKind,
+
+    /// Filter alpha parameter
+    pub alpha: f64,
+
+    /// Number of fused updates to time
+    pub updates: usize,
+
+    /// IMU rate (Hz); sets the update time step
+    pub imu_rate_hz: u32,
+
+    /// GPS fix rate (Hz)
+    pub gps_rate_hz: u32,
+
+    /// Simulator RNG algorithm
+    pub rng: RngBackend,
+
+    /// Simulator seed
+    pub seed: u64,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            filter: FilterKind::Complementary,
+            alpha: 0.98,
+            updates: 10_000,
+            imu_rate_hz: 50,
+            gps_rate_hz: 1,
+            rng: RngBackend::Std,
+            seed: 42,
+        }
+    }
+}
+
+/// Timing of a benchmark run
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    /// Filter that was run
+    pub filter: FilterKind,
+
+    /// Fused updates timed
+    pub updates: usize,
+
+    /// Time spent in filter updates
+    pub elapsed: Duration,
+
+    /// Updates per second
+    pub updates_per_sec: f64,
+
+    /// Mean time per update (ns)
+    pub mean_update_ns: f64,
+
+    /// Last fused frame, to check runs with the same seed agree
+    #[serde(skip)]
+    pub last_frame: Option<FusedSensorData>,
+}
+
+/// Readings for `config.updates` fusion ticks from seeded simulators
+///
+/// Follows the sensor loop: the IMU is read every tick, the GPS advances
+/// at its own rate, and every tick sees the latest (noisy) fix.
+pub fn simulated_readings(config: &BenchConfig) -> Vec<(ImuData, GpsData)> {
+    let rng = SimRngConfig { backend: config.rng, seed: Some(config.seed) };
+    let mut imu = ImuSimulator::new();
+    let mut gps = GpsSimulator::new();
+    imu.set_read_rate(config.imu_rate_hz);
+    imu.set_rng(rng.build(IMU_STREAM));
+    gps.set_rng(rng.build(GPS_STREAM));
+
+    let ticks_per_fix = (config.imu_rate_hz / config.gps_rate_hz.max(1)).max(1) as usize;
+    (0..config.updates)
+        .map(|tick| {
+            if tick % ticks_per_fix == 0 {
+                gps.update();
+            }
+            (imu.read(), gps.get_latest())
+        })
+        .collect()
+}
+
+/// Time `config.updates` fused updates of the configured filter
+pub fn run_benchmark(config: &BenchConfig) -> BenchReport {
+    let readings = simulated_readings(config);
+    let mut filter = config.filter.build(config.alpha);
+    let dt = 1.0 / config.imu_rate_hz.max(1) as f64;
+
+    let start = Instant::now();
+    let mut last_frame = None;
+    for (imu, gps) in readings {
+        last_frame = Some(black_box(filter.update_with_dt(imu, gps, dt)));
+    }
+    let elapsed = start.elapsed();
+
+    let secs = elapsed.as_secs_f64();
+    BenchReport {
+        filter: config.filter,
+        updates: config.updates,
+        elapsed,
+        updates_per_sec: if secs > 0.0 { config.updates as f64 / secs } else { f64::INFINITY },
+        mean_update_ns: if config.updates > 0 { secs * 1e9 / config.updates as f64 } else { 0.0 },
+        last_frame,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Final estimate of a run, without the wall-clock timestamp
+    fn fingerprint(report: &BenchReport) -> ([f64; 4], (f64, f64, f64), [f64; 3]) {
+        let frame = report.last_frame.as_ref().unwrap();
+        (frame.orientation.to_array(), frame.position, frame.velocity.to_array())
+    }
+
+    #[test]
+    fn test_same_seed_gives_the_same_estimate() {
+        let config = BenchConfig { updates: 500, ..BenchConfig::default() };
+        let first = run_benchmark(&config);
+        let second = run_benchmark(&config);
+        assert_eq!(first.updates, 500);
+        assert!(first.updates_per_sec > 0.0 && first.mean_update_ns > 0.0);
+        assert_eq!(fingerprint(&first), fingerprint(&second));
+
+        let other = run_benchmark(&BenchConfig { seed: 7, ..config });
+        assert_ne!(fingerprint(&first), fingerprint(&other));
+    }
+}
@@ -0,0 +1,36 @@
+//! Error Types
+//!
+//! Typed errors returned at the library's public boundaries (server
+//! startup, connection handling, configuration validation) so callers can
+//! match on the kind of failure. The binary wraps these in `anyhow`.
+
+use thiserror::Error;
+use tokio_tungstenite::tungstenite;
+
+/// Errors produced by the sensor fusion backend components
+#[derive(Debug, Error)]
+pub enum SensorFusionError {
+    /// The server could not bind its listening socket
+    #[error("failed to bind to {addr}: {source}")]
+    Bind {
+        addr: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// A client connection failed the WebSocket handshake
+    #[error("WebSocket handshake failed: {0}")]
+    Handshake(#[source] Box<tungstenite::Error>),
+
+    /// Sending to or receiving from an established WebSocket failed
+    #[error("WebSocket transport error: {0}")]
+    Transport(#[source] Box<tungstenite::Error>),
+
+    /// A message could not be serialized or deserialized
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// A configuration value is out of range
+    #[error("invalid configuration: {0}")]
+    Config(String),
+}
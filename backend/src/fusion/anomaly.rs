@@ -0,0 +1,274 @@
+//! Built-in Anomaly Detectors
+//! 
+//! In-process detectors scoring each fused frame, alongside (or instead
+//! of) an external ML service. Each implements `AnomalyDetector`, and a
+//! `DetectorSet` runs several and reports the highest score.
+//! 
+//! `ZScoreDetector` is the default:
+//! - Rolling (exponentially weighted) mean and variance of accelerometer
+//!   and gyroscope magnitude
+//! - Score from the larger z-score of the newest reading: 0 up to 3σ,
+//!   rising to 1 at 8σ
+//! - Peak hold with decay, so a one-sample spike stays visible for a
+//!   fraction of a second instead of a single frame
+//! 
+//! Sustained changes (e.g. a noisier sensor) are absorbed into the rolling
+//! statistics over the window and stop scoring; it flags sudden outliers.
+//! `ThresholdDetector` covers those with fixed physical limits instead.
+
+use crate::models::{FusedSensorData, GpsData, ImuData};
+use serde::Serialize;
+
+/// Samples the rolling statistics effectively average over
+const WINDOW_SAMPLES: f64 = 250.0;
+
+/// Samples needed before scores are reported
+const WARMUP_SAMPLES: u32 = 50;
+
+/// z-score below which a reading is normal
+const Z_NORMAL: f64 = 3.0;
+
+/// z-score at which the score saturates at 1.0
+const Z_SATURATED: f64 = 8.0;
+
+/// Per-sample decay of the held peak score
+const PEAK_DECAY: f64 = 0.95;
+
+/// Smallest standard deviation used for z-scores, so a near-constant
+/// signal doesn't turn tiny deviations into huge scores
+const MIN_STD: f64 = 0.01;
+
+/// Exponentially weighted mean and variance of one signal
+#[derive(Debug, Clone, Copy, Default)]
+struct RollingStats {
+    mean: f64,
+    variance: f64,
+}
+
+impl RollingStats {
+    /// z-score of `value` against the statistics so far, then fold it in
+    fn observe(&mut self, value: f64, first: bool) -> f64 {
+        if first {
+            self.mean = value;
+            return 0.0;
+        }
+        let deviation = value - self.mean;
+        let z = deviation.abs() / self.variance.sqrt().max(MIN_STD);
+
+        let weight = 1.0 / WINDOW_SAMPLES;
+        self.mean += weight * deviation;
+        self.variance = (1.0 - weight) * (self.variance + weight * deviation * deviation);
+        z
+    }
+}
+
+/// Scores fused frames for anomalies
+/// 
+/// Scores run from 0.0 (normal) to 1.0 (certainly anomalous). `None` means
+/// the detector has no opinion on this frame (e.g. still warming up).
+pub trait AnomalyDetector: Send {
+    /// Score the newest frame, given the readings it was fused from
+    fn score(&mut self, fused: &FusedSensorData, imu: &ImuData, gps: &GpsData) -> Option<f64>;
+
+    /// Forget any history (default: nothing to forget)
+    fn reset(&mut self) {}
+}
+
+/// Rolling z-score anomaly detector on IMU magnitudes
+#[derive(Debug, Clone, Default)]
+pub struct ZScoreDetector {
+    accel: RollingStats,
+    gyro: RollingStats,
+    samples: u32,
+    score: f64,
+}
+
+impl ZScoreDetector {
+    /// Create a detector with no history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one IMU reading's magnitudes and get the current score
+    /// 
+    /// Returns `None` during warm-up. Non-finite readings are skipped.
+    pub fn observe(&mut self, accel_magnitude: f64, gyro_magnitude: f64) -> Option<f64> {
+        if accel_magnitude.is_finite() && gyro_magnitude.is_finite() {
+            let first = self.samples == 0;
+            let z = self
+                .accel
+                .observe(accel_magnitude, first)
+                .max(self.gyro.observe(gyro_magnitude, first));
+            let instant = ((z - Z_NORMAL) / (Z_SATURATED - Z_NORMAL)).clamp(0.0, 1.0);
+            self.score = instant.max(self.score * PEAK_DECAY);
+            self.samples = self.samples.saturating_add(1);
+        }
+        (self.samples >= WARMUP_SAMPLES).then_some(self.score)
+    }
+
+    /// Forget all history
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl AnomalyDetector for ZScoreDetector {
+    fn score(&mut self, fused: &FusedSensorData, _imu: &ImuData, _gps: &GpsData) -> Option<f64> {
+        self.observe(fused.raw_acceleration.magnitude(), fused.raw_gyroscope.magnitude())
+    }
+
+    fn reset(&mut self) {
+        ZScoreDetector::reset(self);
+    }
+}
+
+/// Fixed-limit anomaly detector on IMU magnitudes
+/// 
+/// Scores 1.0 while the accelerometer or gyroscope magnitude is beyond
+/// its limit and 0.0 otherwise. Unlike the z-score detector it keeps
+/// flagging a sustained excess.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ThresholdDetector {
+    /// Largest normal accelerometer magnitude (m/s²)
+    pub max_acceleration: f64,
+
+    /// Largest normal gyroscope magnitude (rad/s)
+    pub max_angular_rate: f64,
+}
+
+impl Default for ThresholdDetector {
+    fn default() -> Self {
+        Self {
+            max_acceleration: 30.0, // ~3 g
+            max_angular_rate: 5.0,
+        }
+    }
+}
+
+impl ThresholdDetector {
+    /// Check that both limits are positive and finite
+    pub fn is_valid(&self) -> bool {
+        [self.max_acceleration, self.max_angular_rate]
+            .iter()
+            .all(|limit| limit.is_finite() && *limit > 0.0)
+    }
+}
+
+impl AnomalyDetector for ThresholdDetector {
+    fn score(&mut self, fused: &FusedSensorData, _imu: &ImuData, _gps: &GpsData) -> Option<f64> {
+        let exceeded = fused.raw_acceleration.magnitude() > self.max_acceleration
+            || fused.raw_gyroscope.magnitude() > self.max_angular_rate;
+        Some(if exceeded { 1.0 } else { 0.0 })
+    }
+}
+
+/// Detectors run together, reporting the highest score
+#[derive(Default)]
+pub struct DetectorSet {
+    detectors: Vec<Box<dyn AnomalyDetector>>,
+}
+
+impl DetectorSet {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a detector
+    pub fn push(&mut self, detector: impl AnomalyDetector + 'static) {
+        self.detectors.push(Box::new(detector));
+    }
+
+    /// Number of detectors in the set
+    pub fn len(&self) -> usize {
+        self.detectors.len()
+    }
+
+    /// Check whether the set has no detectors
+    pub fn is_empty(&self) -> bool {
+        self.detectors.is_empty()
+    }
+
+    /// Score a frame with every detector and return the highest score
+    /// 
+    /// Every detector sees every frame, so their histories stay current.
+    /// `None` when no detector has an opinion; non-finite scores are
+    /// ignored and the rest are clamped to 0.0 - 1.0.
+    pub fn score(&mut self, fused: &FusedSensorData, imu: &ImuData, gps: &GpsData) -> Option<f64> {
+        self.detectors
+            .iter_mut()
+            .filter_map(|detector| detector.score(fused, imu, gps))
+            .filter(|score| score.is_finite())
+            .map(|score| score.clamp(0.0, 1.0))
+            .reduce(f64::max)
+    }
+
+    /// Reset every detector
+    pub fn reset(&mut self) {
+        for detector in &mut self.detectors {
+            detector.reset();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Vec3;
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Plays back a fixed list of scores, counting resets
+    struct MockDetector {
+        scores: Vec<Option<f64>>,
+        resets: Arc<AtomicUsize>,
+    }
+
+    impl AnomalyDetector for MockDetector {
+        fn score(&mut self, _fused: &FusedSensorData, _imu: &ImuData, _gps: &GpsData) -> Option<f64> {
+            self.scores.remove(0)
+        }
+
+        fn reset(&mut self) {
+            self.resets.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn mock(scores: &[Option<f64>]) -> MockDetector {
+        MockDetector { scores: scores.to_vec(), resets: Default::default() }
+    }
+
+    fn score(set: &mut DetectorSet, fused: &FusedSensorData) -> Option<f64> {
+        set.score(fused, &ImuData::new(Vec3::zero(), Vec3::zero()), &GpsData::new(0.0, 0.0, 0.0))
+    }
+
+    #[test]
+    fn test_mock_detector_score_reaches_the_set() {
+        let mut set = DetectorSet::new();
+        set.push(mock(&[Some(0.7), None, Some(f64::NAN), Some(3.0)]));
+        let frame = FusedSensorData::new();
+
+        assert_eq!(score(&mut set, &frame), Some(0.7));
+        assert_eq!(score(&mut set, &frame), None);
+        assert_eq!(score(&mut set, &frame), None, "NaN should be ignored");
+        assert_eq!(score(&mut set, &frame), Some(1.0), "scores are clamped to 1.0");
+    }
+
+    #[test]
+    fn test_set_reports_the_highest_score_and_resets_all() {
+        let resets = Arc::new(AtomicUsize::new(0));
+        let mut set = DetectorSet::new();
+        set.push(MockDetector { scores: vec![Some(0.4), Some(0.4)], resets: resets.clone() });
+        set.push(ThresholdDetector::default());
+        assert_eq!(set.len(), 2);
+
+        let mut frame = FusedSensorData::new();
+        assert_eq!(score(&mut set, &frame), Some(0.4));
+        frame.raw_acceleration = Vec3::new(50.0, 0.0, 0.0);
+        assert_eq!(score(&mut set, &frame), Some(1.0));
+
+        set.reset();
+        assert_eq!(resets.load(Ordering::Relaxed), 1);
+    }
+}
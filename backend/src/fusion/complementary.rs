@@ -6,15 +6,30 @@
 //! The filter leverages:
 //! - Gyroscope for short-term orientation accuracy
 //! - Accelerometer for long-term orientation correction (gravity reference)
-//! - GPS for absolute position reference
+//! - GPS for absolute position reference (low-pass blend or Kalman filter)
+//! - GPS course-over-ground for yaw correction when moving fast enough
+//! - Dead-reckoning from the last velocity estimate while GPS has no fix
+//! - Per-axis accelerometer bias removal, set or estimated while stationary
+//! - Optional SLERP smoothing of the reported orientation (display only)
+//! - Optional zeroed reference the reported orientation is relative to
+//! - Gyro yaw rate vs GPS course rate consistency check
+//! - Optional watchdog on GPS position diverging from IMU dead reckoning
 //! 
 //! Alpha parameter (typically 0.95-0.98) controls trust ratio:
 //! - Higher alpha = more trust in gyroscope (responsive but drifts)
 //! - Lower alpha = more trust in accelerometer (stable but noisy)
 
-use crate::models::{ImuData, GpsData, FusedSensorData, Vec3, Quaternion};
+use crate::models::{AltitudeDatum, ImuData, GpsData, FusedSensorData, StatusFlags, Vec3, Quaternion, finite_or, geodetic_to_enu, enu_to_geodetic, METERS_PER_DEGREE};
+use serde::Serialize;
 use std::f64::consts::PI;
 
+use super::diagnostics::FilterDiagnostics;
+use super::filter::{FilterKind, FusionFilter};
+use super::gps_motion::GpsMotion;
+use super::kernels;
+use super::position::{AxisKalman, PositionStrategy};
+use super::watchdog::{DriftWatchdog, DriftWatchdogConfig};
+
 /// Complementary filter for IMU and GPS sensor fusion
 pub struct ComplementaryFilter {
     /// Filter coefficient (0.0 to 1.0)
@@ -27,17 +42,314 @@ pub struct ComplementaryFilter {
     /// Current position estimate (lat, lon, alt)
     position: (f64, f64, f64),
     
+    /// Local ENU origin (lat, lon, alt), set from the first valid GPS fix
+    origin: (f64, f64, f64),
+    
     /// Current velocity estimate (m/s)
     velocity: Vec3,
     
     /// Time of last update
     last_update: Option<std::time::Instant>,
     
-    /// Accumulated gyroscope drift compensation
+    /// Accumulated gyroscope drift compensation (the estimated gyro bias)
     gyro_drift_compensation: Vec3,
     
+    /// Integral gain of the gyro bias estimator (1/s²; off when unset)
+    gyro_bias_gain: Option<f64>,
+    
+    /// Per-axis accelerometer bias subtracted from readings (m/s²)
+    accel_bias: Vec3,
+    
+    /// Stationary accelerometer calibration in progress
+    accel_calibration: Option<AccelCalibration>,
+    
+    /// The accelerometer corrected orientation on the last update
+    accel_correction_applied: bool,
+    
+    /// Distance from unit norm before normalization on the last update
+    norm_deviation: f64,
+    
+    /// Largest `norm_deviation` seen
+    max_norm_deviation: f64,
+    
     /// Filter initialization flag
     initialized: bool,
+    
+    /// Minimum GPS ground speed (m/s) before course-over-ground is trusted for yaw
+    gps_yaw_min_speed: f64,
+    
+    /// GPS-derived acceleration (m/s²) above which accel correction is down-weighted
+    gps_accel_gate: Option<f64>,
+    
+    /// Acceleration and course rate derived from recent GPS fixes
+    gps_motion: GpsMotion,
+    
+    /// Gyro yaw rate disagreed with the GPS course rate on the last update
+    consistency_fault: bool,
+    
+    /// Position is being propagated from velocity because GPS has no fix
+    dead_reckoning: bool,
+    
+    /// Estimated horizontal position error (1σ, meters)
+    position_uncertainty: f64,
+    
+    /// GPS weight of the low-pass position update by HDOP
+    position_weights: PositionWeights,
+    
+    /// Sensor weights and HDOP thresholds for confidence (normalized)
+    confidence_weights: ConfidenceWeights,
+    
+    /// IMU-integrated velocity, relaxed toward GPS velocity (reset without a fix)
+    imu_velocity: Option<Vec3>,
+    
+    /// Time constant (s) over which integrated IMU velocity decays to GPS velocity
+    velocity_window: f64,
+    
+    /// How GPS fixes are fused into the position estimate
+    position_strategy: PositionStrategy,
+    
+    /// Per-axis (east, north, up) Kalman state, created on first use
+    kalman: Option<[AxisKalman; 3]>,
+    
+    /// Output orientation smoothing factor in [0, 1) (off when unset)
+    orientation_smoothing: Option<f64>,
+    
+    /// Smoothed orientation last reported
+    smoothed_orientation: Option<Quaternion>,
+    
+    /// Captured orientation that reported orientation is relative to
+    orientation_reference: Option<Quaternion>,
+    
+    /// Gyro integration steps per update (each covers dt / substeps)
+    integration_substeps: u32,
+    
+    /// Accelerometer corrections left before the orientation estimate
+    /// counts as settled after start-up
+    settling_updates: u32,
+    
+    /// Bias-corrected gyro components smaller than this are zeroed (rad/s)
+    gyro_deadband: f64,
+    
+    /// Largest orientation change accepted per second (deg/s; off when unset)
+    max_rotation_rate: Option<f64>,
+    
+    /// GPS vs IMU position divergence check (off when unset)
+    drift_watchdog: Option<DriftWatchdog>,
+    
+    /// Timestamp of the last GPS fix the watchdog checked
+    last_watchdog_fix: Option<chrono::DateTime<chrono::Utc>>,
+    
+    /// Updates left before a freshly switched-in filter counts as converged
+    reconverge_updates: u32,
+    
+    /// Time (s) since the GPS fix passed to updates arrived, as reported by the caller
+    gps_fix_age: f64,
+    
+    /// Fixes older than this (s) are stale and trusted less the older they get
+    gps_timeout: f64,
+}
+
+/// Default minimum ground speed for GPS course yaw correction (m/s)
+pub const DEFAULT_GPS_YAW_MIN_SPEED: f64 = 2.0;
+
+/// Minimum satellites in view for a usable 3D fix
+pub const MIN_FIX_SATELLITES: u8 = 4;
+
+/// HDOP above which a GPS fix is treated as lost
+pub const MAX_FIX_HDOP: f64 = 5.0;
+
+/// Per-unit-HDOP GPS position error (m), used to report fix uncertainty
+const GPS_RANGE_ERROR_M: f64 = 2.5;
+
+/// Growth of position error while dead-reckoning (m per second of outage)
+const DEAD_RECKONING_DRIFT_MPS: f64 = 0.5;
+
+/// Vertical GPS error relative to horizontal
+const GPS_VERTICAL_ERROR_FACTOR: f64 = 1.5;
+
+/// Largest accepted gyro deadband (rad/s, ~2.9°/s); beyond this genuine
+/// slow rotation is swallowed along with the noise
+pub const MAX_GYRO_DEADBAND: f64 = 0.05;
+
+/// Largest gyro bias the estimator attributes to the sensor per axis
+/// (rad/s, ~5.7°/s); keeps a long stretch of bad accelerometer data from
+/// winding the estimate up
+pub const MAX_GYRO_BIAS: f64 = 0.1;
+
+/// Default IMU velocity integration window (s)
+pub const DEFAULT_VELOCITY_WINDOW_SECS: f64 = 1.0;
+
+/// Default age (s) beyond which a GPS fix is stale: a few missed 1 Hz updates
+pub const DEFAULT_GPS_TIMEOUT_SECS: f64 = 3.0;
+
+/// Filter time constants a switched-in filter is flagged as reconverging for
+const RECONVERGE_TIME_CONSTANTS: f64 = 3.0;
+
+/// Longest reconvergence flag (updates), for alphas close to 1
+const MAX_RECONVERGE_UPDATES: f64 = 500.0;
+
+/// Standard gravity (m/s²)
+const GRAVITY: f64 = 9.81;
+
+/// Updates the orientation blend takes to settle: a few filter time
+/// constants of `alpha / (1 - alpha)` updates each
+fn convergence_updates(alpha: f64) -> u32 {
+    let alpha = alpha.clamp(0.0, 1.0);
+    let time_constant = alpha / (1.0 - alpha).max(f64::EPSILON);
+    (RECONVERGE_TIME_CONSTANTS * time_constant).ceil().clamp(1.0, MAX_RECONVERGE_UPDATES) as u32
+}
+
+/// Confidence is scaled by this while the drift watchdog is faulted
+const DIVERGENCE_CONFIDENCE_FACTOR: f64 = 0.5;
+
+/// Kalman process noise: unmodelled acceleration, incl. tilt error (m/s²)
+const KALMAN_ACCEL_NOISE: f64 = 1.0;
+
+/// Largest accepted accelerometer bias per axis (m/s², ~0.2 g); beyond
+/// this the sensor wasn't level and still during calibration
+pub const MAX_ACCEL_BIAS: f64 = 2.0;
+
+/// How strongly each GPS fix pulls the low-pass position estimate
+/// 
+/// The weight falls off continuously with HDOP: `max_weight` up to
+/// `reference_hdop`, then `max_weight × reference_hdop / hdop`, clamped
+/// to `min_weight`. With `min_weight` 0 a very poor fix barely moves the
+/// position, which then rides on dead reckoning.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PositionWeights {
+    /// Weight of a fix at or below `reference_hdop`
+    pub max_weight: f64,
+    
+    /// Floor of the weight however poor the fix
+    pub min_weight: f64,
+    
+    /// HDOP above which the weight starts falling
+    pub reference_hdop: f64,
+}
+
+impl Default for PositionWeights {
+    fn default() -> Self {
+        Self {
+            max_weight: 0.3,
+            min_weight: 0.0,
+            reference_hdop: 3.0,
+        }
+    }
+}
+
+impl PositionWeights {
+    /// Check that 0 <= min_weight <= max_weight <= 1 and the reference
+    /// HDOP is positive
+    pub fn is_valid(&self) -> bool {
+        (0.0..=1.0).contains(&self.max_weight)
+            && (0.0..=self.max_weight).contains(&self.min_weight)
+            && self.reference_hdop.is_finite()
+            && self.reference_hdop > 0.0
+    }
+    
+    /// Weight of a fix with this HDOP (`min_weight` when HDOP is unusable)
+    pub fn weight(&self, hdop: f64) -> f64 {
+        if hdop.is_nan() || hdop <= 0.0 {
+            return self.min_weight;
+        }
+        (self.max_weight * (self.reference_hdop / hdop).min(1.0)).clamp(self.min_weight, self.max_weight)
+    }
+}
+
+/// How sensor quality is combined into the frame `confidence`
+/// 
+/// Weights are relative: they are normalized to sum to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ConfidenceWeights {
+    /// Weight of IMU confidence (1 − noise level)
+    pub imu: f64,
+    
+    /// Weight of GPS confidence (from HDOP; zero while dead-reckoning)
+    pub gps: f64,
+    
+    /// HDOP below this counts as a good fix (GPS confidence 1.0)
+    pub hdop_good: f64,
+    
+    /// HDOP below this counts as a fair fix (0.7); anything worse is 0.3
+    pub hdop_fair: f64,
+}
+
+impl Default for ConfidenceWeights {
+    fn default() -> Self {
+        Self {
+            imu: 0.6,
+            gps: 0.4,
+            hdop_good: 2.0,
+            hdop_fair: 5.0,
+        }
+    }
+}
+
+impl ConfidenceWeights {
+    /// Check that the weights are non-negative with a positive sum and the
+    /// HDOP thresholds are positive and ordered
+    pub fn is_valid(&self) -> bool {
+        let weights_ok = self.imu.is_finite() && self.gps.is_finite()
+            && self.imu >= 0.0 && self.gps >= 0.0 && self.imu + self.gps > 0.0;
+        let hdop_ok = self.hdop_good.is_finite() && self.hdop_fair.is_finite()
+            && self.hdop_good > 0.0 && self.hdop_good <= self.hdop_fair;
+        weights_ok && hdop_ok
+    }
+    
+    /// Same settings with the weights scaled to sum to 1
+    fn normalized(self) -> Self {
+        let total = self.imu + self.gps;
+        Self {
+            imu: self.imu / total,
+            gps: self.gps / total,
+            ..self
+        }
+    }
+}
+
+/// Complementary filter internals on the latest update
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ComplementaryDiagnostics {
+    /// Configured gyro trust
+    pub alpha: f64,
+
+    /// Gyro trust after the GPS acceleration gate scaled the accelerometer
+    /// correction down
+    pub effective_alpha: f64,
+
+    /// Gyro bias subtracted before integration (rad/s)
+    pub gyro_drift_compensation: Vec3,
+
+    /// True gyro bias of the simulated IMU (rad/s), to check the estimate
+    /// in `gyro_drift_compensation` against; filled in by the caller, and
+    /// absent with real sensors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulated_gyro_bias: Option<Vec3>,
+
+    /// Scale of the accelerometer correction (1.0 = not gated)
+    pub accel_trust: f64,
+
+    /// The GPS acceleration gate down-weighted the accelerometer
+    pub accel_gate_rejected: bool,
+
+    /// SLERP weight from the accelerometer toward the gyro orientation
+    /// (unset when the accelerometer was unusable and no blend ran)
+    pub slerp_weight: Option<f64>,
+
+    /// Largest distance from unit norm of the integrated orientation
+    /// before normalization, over the latest update's substeps
+    pub norm_deviation: f64,
+
+    /// Largest `norm_deviation` since the filter started
+    pub max_norm_deviation: f64,
+}
+
+/// Running sum of accelerometer readings taken while stationary
+#[derive(Debug, Clone, Copy)]
+struct AccelCalibration {
+    sum: Vec3,
+    samples: u32,
+    target: u32,
 }
 
 impl ComplementaryFilter {
@@ -50,10 +362,41 @@ impl ComplementaryFilter {
             alpha: alpha.clamp(0.0, 1.0),
             orientation: Quaternion::identity(),
             position: (0.0, 0.0, 0.0),
+            origin: (0.0, 0.0, 0.0),
             velocity: Vec3::zero(),
             last_update: None,
             gyro_drift_compensation: Vec3::zero(),
+            gyro_bias_gain: None,
+            accel_bias: Vec3::zero(),
+            accel_calibration: None,
+            accel_correction_applied: false,
+            norm_deviation: 0.0,
+            max_norm_deviation: 0.0,
             initialized: false,
+            gps_yaw_min_speed: DEFAULT_GPS_YAW_MIN_SPEED,
+            gps_accel_gate: None,
+            gps_motion: GpsMotion::new(),
+            consistency_fault: false,
+            dead_reckoning: false,
+            position_uncertainty: 0.0,
+            position_weights: PositionWeights::default(),
+            confidence_weights: ConfidenceWeights::default(),
+            imu_velocity: None,
+            velocity_window: DEFAULT_VELOCITY_WINDOW_SECS,
+            position_strategy: PositionStrategy::default(),
+            kalman: None,
+            orientation_smoothing: None,
+            smoothed_orientation: None,
+            orientation_reference: None,
+            integration_substeps: 1,
+            settling_updates: convergence_updates(alpha),
+            gyro_deadband: 0.0,
+            max_rotation_rate: None,
+            drift_watchdog: None,
+            last_watchdog_fix: None,
+            reconverge_updates: 0,
+            gps_fix_age: 0.0,
+            gps_timeout: DEFAULT_GPS_TIMEOUT_SECS,
         }
     }
 
@@ -70,90 +413,270 @@ impl ComplementaryFilter {
             0.02 // Default 50Hz = 0.02 seconds
         };
         self.last_update = Some(now);
+        self.fuse(imu, gps, dt, now)
+    }
+
+    /// Update with an explicit time step instead of the wall clock
+    /// 
+    /// The filter's clock advances by `dt` per call, so the same readings
+    /// always give the same estimate however fast they are fed (replays,
+    /// benchmarks, tests). Call `reset_timing` before switching back to
+    /// `update`. A non-finite or negative `dt` counts as zero.
+    pub fn update_with_dt(&mut self, imu: ImuData, gps: GpsData, dt: f64) -> FusedSensorData {
+        let dt = if dt.is_finite() { dt.max(0.0) } else { 0.0 };
+        let now = match self.last_update {
+            Some(last) => std::time::Duration::try_from_secs_f64(dt)
+                .ok()
+                .and_then(|step| last.checked_add(step))
+                .unwrap_or(last),
+            None => std::time::Instant::now(),
+        };
+        self.last_update = Some(now);
+        self.fuse(imu, gps, dt, now)
+    }
+
+    /// One fusion step of `dt` seconds ending at `now`
+    fn fuse(&mut self, imu: ImuData, gps: GpsData, dt: f64, now: std::time::Instant) -> FusedSensorData {
+        // Remove the accelerometer bias before anything uses the reading
+        // (frames still report the raw reading)
+        let raw_acceleration = imu.acceleration;
+        self.observe_accel_calibration(raw_acceleration);
+        let imu = ImuData {
+            acceleration: Vec3::new(
+                raw_acceleration.x - self.accel_bias.x,
+                raw_acceleration.y - self.accel_bias.y,
+                raw_acceleration.z - self.accel_bias.z,
+            ),
+            ..imu
+        };
         
-        // Initialize position on first update
-        if !self.initialized {
+        // Initialize position on first valid GPS fix
+        let has_fix = gps_has_fix(&gps);
+        if !self.initialized && has_fix {
             self.position = (gps.latitude, gps.longitude, gps.altitude);
+            self.origin = self.position;
             self.initialized = true;
         }
         
         // Step 1: Integrate gyroscope for orientation (high frequency, short-term accurate)
-        let gyro_orientation = self.integrate_gyroscope(&imu.gyroscope, dt);
+        let (gyro_orientation, norm_deviation) = self.integrate_gyroscope(&imu.gyroscope, dt);
+        self.norm_deviation = norm_deviation;
+        self.max_norm_deviation = self.max_norm_deviation.max(norm_deviation);
         
         // Step 2: Calculate orientation from accelerometer (low frequency, long-term accurate)
         let accel_orientation = self.orientation_from_accelerometer(&imu.acceleration);
         
-        // Step 3: Complementary filter fusion
-        self.orientation = self.fuse_orientations(gyro_orientation, accel_orientation);
+        // Step 3: Complementary filter fusion (gyro only if accel is unusable)
+        let previous_orientation = self.orientation;
+        self.gps_motion.observe(&gps, now);
+        self.accel_correction_applied = accel_orientation.is_some();
+        if self.accel_correction_applied {
+            self.settling_updates = self.settling_updates.saturating_sub(1);
+        }
+        self.orientation = match accel_orientation {
+            Some(accel_q) => self.fuse_orientations(gyro_orientation, accel_q),
+            None => gyro_orientation,
+        };
+        
+        // Step 3a: Learn the gyro bias from what the accelerometer corrected
+        self.estimate_gyro_bias(gyro_orientation, &imu.acceleration, dt);
+        
+        // Step 3b: Correct yaw drift from GPS course-over-ground when moving
+        if has_fix {
+            self.orientation = self.correct_yaw_from_gps(&gps);
+        }
+        
+        // Step 3c: Bound the step so a gyro spike can't flip the estimate
+        self.orientation = self.limit_rotation(previous_orientation, dt);
+        
+        // Step 4: Update position with GPS, or dead-reckon through an outage
+        match self.position_strategy {
+            PositionStrategy::LowPass if has_fix => self.update_position(&gps, dt),
+            PositionStrategy::LowPass => self.dead_reckon(dt),
+            PositionStrategy::Kalman => self.update_position_kalman(&imu, &gps, has_fix, dt),
+        }
+        
+        // Step 5: Estimate velocity (held at its last value without a fix;
+        // IMU integration restarts from GPS once the fix returns)
+        if has_fix {
+            self.update_velocity(&imu, &gps, dt);
+        } else {
+            self.imu_velocity = None;
+        }
         
-        // Step 4: Update position with GPS
-        self.update_position(&gps, dt);
+        // Step 5b: Cross-check gyro yaw rate against the GPS course rate
+        self.consistency_fault = has_fix && self.gps_motion.turn_rates_disagree(self.orientation, imu.gyroscope, &gps, self.gps_yaw_min_speed);
         
-        // Step 5: Estimate velocity
-        self.update_velocity(&imu, &gps, dt);
+        // Step 5c: Cross-check the GPS track against IMU dead reckoning
+        self.update_drift_watchdog(&imu, &gps, has_fix, dt);
         
         // Step 6: Calculate confidence metrics
         let confidence = self.calculate_confidence(&imu, &gps);
         let system_health = self.calculate_system_health(&imu, &gps);
         
+        // Presentation smoothing and zeroing; the internal estimate is left untouched
+        let orientation = self.smooth_output_orientation();
+        let orientation = match self.orientation_reference {
+            Some(reference) => reference.inverse() * orientation,
+            None => orientation,
+        };
+        
         // Convert quaternion to Euler angles for convenience
-        let (roll, pitch, yaw) = self.orientation.to_euler();
+        let (roll, pitch, yaw) = orientation.to_euler();
         let euler_degrees = (
             roll.to_degrees(),
             pitch.to_degrees(),
             yaw.to_degrees(),
         );
         
+        let mut status = StatusFlags::empty();
+        status.set(StatusFlags::DEAD_RECKONING, self.dead_reckoning);
+        status.set(StatusFlags::SENSOR_CONSISTENCY_FAULT, self.consistency_fault);
+        status.set(StatusFlags::GPS_FIX_VALID, has_fix);
+        status.set(StatusFlags::POSITION_DIVERGENCE, self.position_divergence());
+        status.set(StatusFlags::RECONVERGING, self.reconverge_updates > 0);
+        status.set(StatusFlags::STALE_GPS, self.is_gps_stale());
+        self.reconverge_updates = self.reconverge_updates.saturating_sub(1);
+        
         // Build fused sensor data output
-        FusedSensorData {
+        let mut fused = FusedSensorData {
             timestamp: chrono::Utc::now(),
-            orientation: self.orientation,
+            orientation,
             euler_degrees,
             position: self.position,
+            altitude_datum: AltitudeDatum::Msl,
+            local_position: geodetic_to_enu(self.origin, self.position),
+            grid_cell: None,
             velocity: self.velocity,
-            raw_acceleration: imu.acceleration,
-            raw_gyroscope: imu.gyroscope,
-            gps_speed: gps.speed,
-            gps_heading: gps.heading,
-            confidence,
-            system_health,
+            raw_acceleration: raw_acceleration.finite_or_zero(),
+            raw_gyroscope: imu.gyroscope.finite_or_zero(),
+            world_angular_velocity: None,
+            estimated_gyro_bias: self.gyro_drift_compensation,
+            gps_speed: finite_or(gps.speed, 0.0),
+            gps_heading: finite_or(gps.heading, 0.0),
+            confidence: finite_or(confidence, 0.0),
+            system_health: finite_or(system_health, 0.0),
+            position_uncertainty: finite_or(self.position_uncertainty, 0.0),
+            // Flag booleans are filled in from `status` below
+            status_flags: StatusFlags::empty(),
+            dead_reckoning: false,
+            sensor_consistency_fault: false,
+            gimbal_lock_warning: false,
+            gps_fix_valid: false,
+            stale_anomaly: false,
+            position_divergence: false,
+            reconverging: false,
+            stale_gps: false,
+            unsettled_world_accel: false,
             anomaly_score: None, // Set by ML service
+            inputs: None,
+        };
+        fused.set_status_flags(status);
+        fused
+    }
+
+    /// Fold a raw accelerometer reading into a running calibration and
+    /// take the new bias once enough readings are in
+    /// 
+    /// The sensor is assumed level and still, so the expected reading is
+    /// gravity on +z alone. Non-finite readings are skipped.
+    fn observe_accel_calibration(&mut self, accel: Vec3) {
+        let Some(calibration) = self.accel_calibration.as_mut() else {
+            return;
+        };
+        if !accel.is_finite() {
+            return;
+        }
+        calibration.sum = Vec3::new(
+            calibration.sum.x + accel.x,
+            calibration.sum.y + accel.y,
+            calibration.sum.z + accel.z,
+        );
+        calibration.samples += 1;
+        if calibration.samples < calibration.target {
+            return;
         }
+        
+        let n = calibration.samples as f64;
+        let bias = Vec3::new(
+            calibration.sum.x / n,
+            calibration.sum.y / n,
+            calibration.sum.z / n - GRAVITY,
+        );
+        self.accel_calibration = None;
+        self.set_accel_bias(bias);
     }
 
     /// Integrate gyroscope readings to update orientation
-    fn integrate_gyroscope(&self, gyro: &Vec3, dt: f64) -> Quaternion {
-        // Compensate for known drift
+    /// 
+    /// Also returns the largest distance from unit norm before each
+    /// substep's normalization (0.0 when nothing was integrated).
+    fn integrate_gyroscope(&self, gyro: &Vec3, dt: f64) -> (Quaternion, f64) {
+        // A corrupt reading would poison the orientation for good
+        if !gyro.is_finite() || !dt.is_finite() {
+            return (self.orientation, 0.0);
+        }
+        
+        // Compensate for known drift, then drop noise-level rates so they
+        // don't integrate into drift while stationary
+        let deadband = |rate: f64| if rate.abs() < self.gyro_deadband { 0.0 } else { rate };
         let corrected_gyro = Vec3::new(
-            gyro.x - self.gyro_drift_compensation.x,
-            gyro.y - self.gyro_drift_compensation.y,
-            gyro.z - self.gyro_drift_compensation.z,
+            deadband(gyro.x - self.gyro_drift_compensation.x),
+            deadband(gyro.y - self.gyro_drift_compensation.y),
+            deadband(gyro.z - self.gyro_drift_compensation.z),
         );
         
-        // Quaternion derivative from angular velocity
-        let half_dt = dt / 2.0;
-        let dq = Quaternion::new(
-            0.0,
-            corrected_gyro.x * half_dt,
-            corrected_gyro.y * half_dt,
-            corrected_gyro.z * half_dt,
+        // Smaller steps cut the truncation error of large dt at low IMU rates
+        kernels::integrate_gyro_steps(self.orientation, corrected_gyro, dt, self.integration_substeps)
+    }
+
+    /// Update the gyro bias estimate from the accelerometer (Mahony-style
+    /// integral term)
+    /// 
+    /// The error is the rotation between the gravity direction measured by
+    /// the accelerometer and the one the gyro-propagated orientation
+    /// predicts. A persistent error means the gyro is biased; integrating
+    /// it into `gyro_drift_compensation` cancels the bias at the next
+    /// integration. The component about gravity is unobservable here, so
+    /// a bias around the vertical axis is not learned. Skipped when the
+    /// accelerometer gave no correction, and slowed by the GPS
+    /// acceleration gate like the correction itself.
+    fn estimate_gyro_bias(&mut self, predicted: Quaternion, accel: &Vec3, dt: f64) {
+        let Some(gain) = self.gyro_bias_gain else {
+            return;
+        };
+        if !self.accel_correction_applied || !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        let measured = accel.normalize();
+        let expected = predicted.inverse().rotate(Vec3::new(0.0, 0.0, 1.0));
+        let error = Vec3::new(
+            measured.y * expected.z - measured.z * expected.y,
+            measured.z * expected.x - measured.x * expected.z,
+            measured.x * expected.y - measured.y * expected.x,
         );
-        
-        // Quaternion multiplication for integration
-        let q = self.orientation;
-        let new_q = Quaternion::new(
-            q.w - dq.x * q.x - dq.y * q.y - dq.z * q.z,
-            q.x + dq.x * q.w + dq.z * q.y - dq.y * q.z,
-            q.y + dq.y * q.w - dq.z * q.x + dq.x * q.z,
-            q.z + dq.z * q.w + dq.y * q.x - dq.x * q.y,
+        if !error.is_finite() {
+            return;
+        }
+        let step = gain * self.accel_trust() * dt;
+        let bias = self.gyro_drift_compensation;
+        self.gyro_drift_compensation = Vec3::new(
+            (bias.x - step * error.x).clamp(-MAX_GYRO_BIAS, MAX_GYRO_BIAS),
+            (bias.y - step * error.y).clamp(-MAX_GYRO_BIAS, MAX_GYRO_BIAS),
+            (bias.z - step * error.z).clamp(-MAX_GYRO_BIAS, MAX_GYRO_BIAS),
         );
-        
-        // Normalize to prevent accumulation of numerical errors
-        self.normalize_quaternion(new_q)
     }
 
     /// Calculate orientation from accelerometer (assumes gravity is dominant force)
-    fn orientation_from_accelerometer(&self, accel: &Vec3) -> Quaternion {
+    /// 
+    /// Returns `None` when the reading can't provide a gravity reference
+    /// (zero magnitude or non-finite components).
+    fn orientation_from_accelerometer(&self, accel: &Vec3) -> Option<Quaternion> {
+        let magnitude = accel.magnitude();
+        if !magnitude.is_finite() || magnitude < 1e-6 {
+            return None;
+        }
+        
         // Normalize acceleration vector
         let norm_accel = accel.normalize();
         
@@ -166,66 +689,203 @@ impl ComplementaryFilter {
         let (_, _, current_yaw) = self.orientation.to_euler();
         
         // Convert Euler angles to quaternion
-        self.euler_to_quaternion(roll, pitch, current_yaw)
+        Some(Quaternion::from_euler(roll, pitch, current_yaw))
     }
 
     /// Fuse gyroscope and accelerometer orientations using complementary filter
     fn fuse_orientations(&self, gyro_q: Quaternion, accel_q: Quaternion) -> Quaternion {
         // Complementary filter: orientation = alpha * gyro + (1 - alpha) * accel
         // For quaternions, we use spherical linear interpolation (SLERP)
-        self.slerp(accel_q, gyro_q, self.alpha)
+        accel_q.slerp(gyro_q, self.effective_alpha())
+    }
+
+    /// Gyro share of the fused orientation once the accelerometer
+    /// correction is scaled by `accel_trust`
+    fn effective_alpha(&self) -> f64 {
+        1.0 - (1.0 - self.alpha) * self.accel_trust()
+    }
+
+    /// Snapshot of the filter's decisions on the latest update
+    pub fn diagnostics(&self) -> ComplementaryDiagnostics {
+        let accel_trust = self.accel_trust();
+        ComplementaryDiagnostics {
+            alpha: self.alpha,
+            effective_alpha: self.effective_alpha(),
+            gyro_drift_compensation: self.gyro_drift_compensation,
+            simulated_gyro_bias: None,
+            accel_trust,
+            accel_gate_rejected: accel_trust < 1.0,
+            slerp_weight: self.accel_correction_applied.then(|| self.effective_alpha()),
+            norm_deviation: self.norm_deviation,
+            max_norm_deviation: self.max_norm_deviation,
+        }
+    }
+
+    /// Scale factor (0.0 - 1.0) for the accelerometer correction
+    /// 
+    /// During strong GPS-observed acceleration the accelerometer measures
+    /// mostly linear acceleration rather than gravity, so its correction is
+    /// scaled down in proportion to how far the gate is exceeded.
+    pub fn accel_trust(&self) -> f64 {
+        match self.gps_accel_gate {
+            Some(gate) if self.gps_motion.acceleration() > gate => gate / self.gps_motion.acceleration(),
+            _ => 1.0,
+        }
     }
 
-    /// Spherical Linear Interpolation between two quaternions
-    fn slerp(&self, q1: Quaternion, q2: Quaternion, t: f64) -> Quaternion {
-        // Calculate dot product
-        let mut dot = q1.w * q2.w + q1.x * q2.x + q1.y * q2.y + q1.z * q2.z;
+    /// Nudge yaw toward GPS course-over-ground
+    /// 
+    /// GPS heading is only meaningful while the vehicle is moving; below
+    /// `gps_yaw_min_speed` it is dominated by position noise, so the current
+    /// orientation is returned unchanged.
+    fn correct_yaw_from_gps(&self, gps: &GpsData) -> Quaternion {
+        if !gps.speed.is_finite() || gps.speed <= self.gps_yaw_min_speed || !gps.heading.is_finite() {
+            return self.orientation;
+        }
         
-        // Ensure we take the shorter path
-        let q2_adjusted = if dot < 0.0 {
-            dot = -dot;
-            Quaternion::new(-q2.w, -q2.x, -q2.y, -q2.z)
-        } else {
-            q2
-        };
+        let (roll, pitch, yaw) = self.orientation.to_euler();
+        let course = gps.heading.to_radians();
         
-        // If quaternions are very close, use linear interpolation
-        if dot > 0.9995 {
-            let result = Quaternion::new(
-                q1.w + t * (q2_adjusted.w - q1.w),
-                q1.x + t * (q2_adjusted.x - q1.x),
-                q1.y + t * (q2_adjusted.y - q1.y),
-                q1.z + t * (q2_adjusted.z - q1.z),
-            );
-            return self.normalize_quaternion(result);
-        }
+        // Wrap the error to [-π, π] so we always turn the short way round
+        let yaw_error = (course - yaw + PI).rem_euclid(2.0 * PI) - PI;
         
-        // Calculate interpolation coefficients
-        let theta = dot.acos();
-        let sin_theta = theta.sin();
-        let w1 = ((1.0 - t) * theta).sin() / sin_theta;
-        let w2 = (t * theta).sin() / sin_theta;
+        // Same trust split as the accelerometer correction
+        let corrected_yaw = yaw + (1.0 - self.alpha) * yaw_error;
         
-        Quaternion::new(
-            w1 * q1.w + w2 * q2_adjusted.w,
-            w1 * q1.x + w2 * q2_adjusted.x,
-            w1 * q1.y + w2 * q2_adjusted.y,
-            w1 * q1.z + w2 * q2_adjusted.z,
-        )
+        Quaternion::from_euler(roll, pitch, corrected_yaw)
     }
 
     /// Update position estimate using GPS
     fn update_position(&mut self, gps: &GpsData, _dt: f64) {
-        // Simple low-pass filter for position (could be more sophisticated)
-        let gps_weight = if gps.hdop < 3.0 { 0.3 } else { 0.1 };
+        if !gps_position_is_finite(gps) {
+            return;
+        }
+        
+        // Low-pass filter for position, trusting better and fresher fixes more
+        let gps_weight = self.position_weights.weight(gps.hdop) * self.gps_freshness();
         
         self.position.0 = self.position.0 * (1.0 - gps_weight) + gps.latitude * gps_weight;
         self.position.1 = self.position.1 * (1.0 - gps_weight) + gps.longitude * gps_weight;
         self.position.2 = self.position.2 * (1.0 - gps_weight) + gps.altitude * gps_weight;
+        
+        self.dead_reckoning = false;
+        self.position_uncertainty = gps.hdop * GPS_RANGE_ERROR_M;
+    }
+
+    /// Update position estimate with the per-axis Kalman filter
+    /// 
+    /// Predicts from IMU acceleration rotated into the local frame with
+    /// gravity removed, then corrects with the GPS fix. Measurement variance
+    /// scales with HDOP so poor fixes pull the estimate less, and grows with
+    /// the age of a stale fix. Without a fix
+    /// only the prediction runs and the covariance grows.
+    fn update_position_kalman(&mut self, imu: &ImuData, gps: &GpsData, has_fix: bool, dt: f64) {
+        if !self.initialized || !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        
+        let origin = self.origin;
+        let freshness = self.gps_freshness();
+        let start = geodetic_to_enu(origin, self.position);
+        let kalman = self.kalman.get_or_insert_with(|| {
+            let variance = (GPS_RANGE_ERROR_M * GPS_RANGE_ERROR_M).max(self.position_uncertainty.powi(2));
+            [start.x, start.y, start.z].map(|p| AxisKalman::new(p, variance, KALMAN_ACCEL_NOISE))
+        });
+        
+        // Body acceleration in the (north, east, up) frame, minus gravity
+        let accel = if imu.acceleration.is_finite() {
+            self.orientation.rotate(imu.acceleration)
+        } else {
+            Vec3::new(0.0, 0.0, GRAVITY)
+        };
+        let accel_enu = [accel.y, accel.x, accel.z - GRAVITY];
+        for (axis, a) in kalman.iter_mut().zip(accel_enu) {
+            axis.predict(a, dt);
+        }
+        
+        if has_fix {
+            let measured = geodetic_to_enu(origin, (gps.latitude, gps.longitude, gps.altitude));
+            let horizontal = (gps.hdop * GPS_RANGE_ERROR_M / freshness).powi(2);
+            let vertical = horizontal * GPS_VERTICAL_ERROR_FACTOR.powi(2);
+            kalman[0].correct(measured.x, horizontal);
+            kalman[1].correct(measured.y, horizontal);
+            kalman[2].correct(measured.z, vertical);
+        }
+        
+        let local = Vec3::new(kalman[0].position(), kalman[1].position(), kalman[2].position());
+        if local.is_finite() {
+            self.position = enu_to_geodetic(origin, local);
+        } else {
+            // Diverged; restart from the last good estimate on the next update
+            self.kalman = None;
+            return;
+        }
+        
+        self.dead_reckoning = !has_fix;
+        self.position_uncertainty = kalman[0].position_variance().max(kalman[1].position_variance()).sqrt();
+    }
+
+    /// Feed the drift watchdog this update's acceleration and any new fix
+    /// 
+    /// Without a fix (or position) the watchdog starts over, so the first
+    /// fix after an outage may legitimately correct the dead-reckoned
+    /// position without tripping it.
+    fn update_drift_watchdog(&mut self, imu: &ImuData, gps: &GpsData, has_fix: bool, dt: f64) {
+        let Some(watchdog) = self.drift_watchdog.as_mut() else {
+            return;
+        };
+        if !has_fix || !self.initialized {
+            watchdog.reset();
+            self.last_watchdog_fix = None;
+            return;
+        }
+        
+        // Body acceleration in the (north, east, up) frame to east/north/up, minus gravity
+        if imu.acceleration.is_finite() {
+            let accel = self.orientation.rotate(imu.acceleration);
+            watchdog.predict(Vec3::new(accel.y, accel.x, accel.z - GRAVITY), dt);
+        }
+        
+        // Every update carries the latest fix; check each fix once
+        if self.last_watchdog_fix == Some(gps.timestamp) {
+            return;
+        }
+        self.last_watchdog_fix = Some(gps.timestamp);
+        let position = geodetic_to_enu(self.origin, (gps.latitude, gps.longitude, gps.altitude));
+        let heading = gps.heading.to_radians();
+        let velocity = Vec3::new(gps.speed * heading.sin(), gps.speed * heading.cos(), 0.0);
+        watchdog.check(position, velocity);
+    }
+
+    /// Propagate position from the last velocity estimate while GPS has no fix
+    /// 
+    /// Bad fixes during an outage are ignored entirely rather than blended
+    /// in, and the reported position uncertainty grows with outage length.
+    fn dead_reckon(&mut self, dt: f64) {
+        if !self.initialized || !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        
+        // Velocity is (north, east, vertical)
+        let north = self.velocity.x * dt;
+        let east = self.velocity.y * dt;
+        let meters_per_degree_lon = METERS_PER_DEGREE * self.position.0.to_radians().cos();
+        
+        self.position.0 += north / METERS_PER_DEGREE;
+        if meters_per_degree_lon.abs() > f64::EPSILON {
+            self.position.1 += east / meters_per_degree_lon;
+        }
+        
+        self.dead_reckoning = true;
+        self.position_uncertainty += DEAD_RECKONING_DRIFT_MPS * dt;
     }
 
     /// Update velocity estimate
     fn update_velocity(&mut self, imu: &ImuData, gps: &GpsData, dt: f64) {
+        if !(gps.speed.is_finite() && gps.heading.is_finite() && imu.acceleration.is_finite() && dt.is_finite()) {
+            return;
+        }
+        
         // Use GPS speed and heading as primary velocity source
         let gps_heading_rad = gps.heading.to_radians();
         
@@ -235,35 +895,95 @@ impl ComplementaryFilter {
             0.0, // Simplified - could integrate vertical from IMU
         );
         
-        // Blend GPS velocity with IMU acceleration integration
-        let accel_contribution = Vec3::new(
-            imu.acceleration.x * dt,
-            imu.acceleration.y * dt,
-            imu.acceleration.z * dt,
+        // Integrate body acceleration in the (north, east, up) frame, minus
+        // gravity (the orientation-derived gravity vector, removed after
+        // rotating), then relax it toward GPS so a constant bias settles at
+        // bias × window instead of growing without bound
+        let accel = self.orientation.rotate(imu.acceleration);
+        let accel = Vec3::new(accel.x, accel.y, accel.z - GRAVITY);
+        let integrated = self.imu_velocity.unwrap_or(gps_velocity);
+        let integrated = Vec3::new(
+            integrated.x + accel.x * dt,
+            integrated.y + accel.y * dt,
+            integrated.z + accel.z * dt,
         );
+        let integrated = gps_velocity.lerp(integrated, (-dt.max(0.0) / self.velocity_window).exp());
+        self.imu_velocity = Some(integrated);
         
-        // Weighted fusion (favor GPS for velocity)
-        self.velocity = Vec3::new(
-            gps_velocity.x * 0.9 + accel_contribution.x * 0.1,
-            gps_velocity.y * 0.9 + accel_contribution.y * 0.1,
-            gps_velocity.z * 0.5 + accel_contribution.z * 0.5,
-        );
+        // Weighted fusion (favor GPS horizontally, split evenly vertically)
+        let horizontal = integrated.lerp(gps_velocity, 0.9);
+        let vertical = integrated.lerp(gps_velocity, 0.5);
+        self.velocity = Vec3::new(horizontal.x, horizontal.y, vertical.z);
+    }
+
+    /// Limit the change from `previous` to the configured rotation rate
+    /// 
+    /// A larger step is cut back along the shortest arc to the largest
+    /// rotation allowed over `dt`, keeping its direction.
+    fn limit_rotation(&self, previous: Quaternion, dt: f64) -> Quaternion {
+        let Some(rate) = self.max_rotation_rate else {
+            return self.orientation;
+        };
+        
+        let step = previous.angle_to(&self.orientation);
+        let allowed = rate.to_radians() * dt;
+        if !step.is_finite() || !allowed.is_finite() || step <= allowed {
+            return self.orientation;
+        }
+        
+        let limited = previous.slerp(self.orientation, allowed / step);
+        if limited.is_finite() { limited } else { previous }
+    }
+
+    /// Exponential moving average of the reported orientation
+    /// 
+    /// Each frame moves the smoothed orientation `1 - factor` of the way
+    /// toward the current estimate along the shortest arc.
+    fn smooth_output_orientation(&mut self) -> Quaternion {
+        let (Some(factor), Some(previous)) = (self.orientation_smoothing, self.smoothed_orientation) else {
+            self.smoothed_orientation = Some(self.orientation);
+            return self.orientation;
+        };
+        
+        let smoothed = previous.slerp(self.orientation, 1.0 - factor);
+        let smoothed = if smoothed.is_finite() { smoothed } else { self.orientation };
+        self.smoothed_orientation = Some(smoothed);
+        smoothed
     }
 
     /// Calculate fusion confidence based on sensor quality
     fn calculate_confidence(&self, imu: &ImuData, gps: &GpsData) -> f64 {
         // Confidence degrades with high noise and poor GPS
+        let weights = self.confidence_weights;
         let imu_confidence = 1.0 - imu.noise_level.min(1.0);
-        let gps_confidence = if gps.hdop < 2.0 {
+        let gps_confidence = if self.dead_reckoning {
+            0.0
+        } else if gps.hdop < weights.hdop_good {
             1.0
-        } else if gps.hdop < 5.0 {
+        } else if gps.hdop < weights.hdop_fair {
             0.7
         } else {
             0.3
-        };
+        } * self.gps_freshness();
         
-        // Combined confidence (weighted average)
-        0.6 * imu_confidence + 0.4 * gps_confidence
+        // Combined confidence (weighted average), cut while GPS and the
+        // IMU disagree about where we are
+        let confidence = weights.imu * imu_confidence + weights.gps * gps_confidence;
+        if self.position_divergence() {
+            confidence * DIVERGENCE_CONFIDENCE_FACTOR
+        } else {
+            confidence
+        }
+    }
+
+    /// How far a fix of the current age is trusted: fully up to the
+    /// timeout, then in proportion to timeout / age
+    fn gps_freshness(&self) -> f64 {
+        if self.is_gps_stale() {
+            self.gps_timeout / self.gps_fix_age
+        } else {
+            1.0
+        }
     }
 
     /// Calculate overall system health
@@ -272,40 +992,1141 @@ impl ComplementaryFilter {
         (imu.health + gps.health) / 2.0
     }
 
-    /// Convert Euler angles to quaternion
-    fn euler_to_quaternion(&self, roll: f64, pitch: f64, yaw: f64) -> Quaternion {
-        let cr = (roll / 2.0).cos();
-        let sr = (roll / 2.0).sin();
-        let cp = (pitch / 2.0).cos();
-        let sp = (pitch / 2.0).sin();
-        let cy = (yaw / 2.0).cos();
-        let sy = (yaw / 2.0).sin();
-        
-        Quaternion::new(
-            cr * cp * cy + sr * sp * sy,
-            sr * cp * cy - cr * sp * sy,
-            cr * sp * cy + sr * cp * sy,
-            cr * cp * sy - sr * sp * cy,
-        )
-    }
-
-    /// Normalize quaternion to unit length
-    fn normalize_quaternion(&self, q: Quaternion) -> Quaternion {
-        let norm = (q.w * q.w + q.x * q.x + q.y * q.y + q.z * q.z).sqrt();
-        if norm > 1e-6 {
-            Quaternion::new(q.w / norm, q.x / norm, q.y / norm, q.z / norm)
-        } else {
-            Quaternion::identity()
+    /// Forget the time of the last update
+    /// 
+    /// The next update uses the nominal time step instead of the wall-clock
+    /// gap, e.g. after the simulation was paused.
+    pub fn reset_timing(&mut self) {
+        self.last_update = None;
+    }
+
+    /// Snap position to a GPS fix and make it the new local origin
+    /// 
+    /// Discards the current position estimate (and Kalman state) instead of
+    /// blending toward the fix, e.g. after a bad fix or moving the simulated
+    /// origin. Without a usable fix, the next valid fix re-seeds position.
+    /// Returns whether the position was recentered immediately.
+    pub fn recenter(&mut self, gps: &GpsData) -> bool {
+        self.kalman = None;
+        self.imu_velocity = None;
+        if let Some(watchdog) = self.drift_watchdog.as_mut() {
+            watchdog.reset();
         }
+        if !gps_has_fix(gps) {
+            self.initialized = false;
+            return false;
+        }
+        
+        self.position = (gps.latitude, gps.longitude, gps.altitude);
+        self.origin = self.position;
+        self.initialized = true;
+        self.dead_reckoning = false;
+        self.position_uncertainty = gps.hdop * GPS_RANGE_ERROR_M;
+        true
     }
 
-    /// Get current filter alpha value
-    pub fn alpha(&self) -> f64 {
-        self.alpha
+    /// Start from a position estimate carried over from another filter
+    /// 
+    /// Keeps the local origin so `local_position` doesn't jump when filters
+    /// are switched at runtime. Ignored unless `position` is finite.
+    pub fn seed_position(&mut self, position: (f64, f64, f64), origin: (f64, f64, f64), uncertainty: f64) {
+        if !(position.0.is_finite() && position.1.is_finite() && position.2.is_finite()) {
+            return;
+        }
+        self.position = position;
+        self.origin = origin;
+        self.initialized = true;
+        self.position_uncertainty = finite_or(uncertainty, GPS_RANGE_ERROR_M).max(0.0);
     }
 
-    /// Update filter alpha value
-    pub fn set_alpha(&mut self, alpha: f64) {
-        self.alpha = alpha.clamp(0.0, 1.0);
+    /// Flag frames as reconverging until the orientation blend has settled
+    /// 
+    /// Lasts a few filter time constants: `alpha / (1 - alpha)` updates each.
+    pub fn start_reconverging(&mut self) {
+        self.reconverge_updates = convergence_updates(self.alpha);
+    }
+
+    /// Check whether frames are still flagged as reconverging
+    pub fn is_reconverging(&self) -> bool {
+        self.reconverge_updates > 0
+    }
+
+    /// Get the position estimate (`None` before the first fix)
+    pub fn position(&self) -> Option<(f64, f64, f64)> {
+        self.initialized.then_some(self.position)
+    }
+
+    /// Get the local origin that `local_position` is relative to
+    pub fn origin(&self) -> (f64, f64, f64) {
+        self.origin
+    }
+
+    /// Make the current orientation the zero reference (like a tare)
+    /// 
+    /// Reported orientation becomes the rotation from this reference, so it
+    /// reads as identity right after zeroing. Works at any attitude, near
+    /// gimbal lock included, since it is applied to the quaternion. Only
+    /// the output changes; fusion keeps its absolute estimate.
+    pub fn zero_orientation(&mut self) {
+        self.orientation_reference = Some(self.smoothed_orientation.unwrap_or(self.orientation));
+    }
+
+    /// Report absolute orientation again
+    pub fn clear_orientation_reference(&mut self) {
+        self.orientation_reference = None;
+    }
+
+    /// Get the zero reference orientation, if one is set
+    pub fn orientation_reference(&self) -> Option<Quaternion> {
+        self.orientation_reference
+    }
+
+    /// Check whether position is currently dead-reckoned
+    pub fn is_dead_reckoning(&self) -> bool {
+        self.dead_reckoning
+    }
+
+    /// Check whether the drift watchdog has GPS diverging from the IMU track
+    pub fn position_divergence(&self) -> bool {
+        self.drift_watchdog.as_ref().is_some_and(DriftWatchdog::is_faulted)
+    }
+
+    /// Get the drift watchdog settings, if enabled
+    pub fn drift_watchdog(&self) -> Option<&DriftWatchdogConfig> {
+        self.drift_watchdog.as_ref().map(DriftWatchdog::config)
+    }
+
+    /// Flag GPS positions diverging from IMU dead reckoning (off when
+    /// `None`; an invalid configuration is ignored)
+    pub fn set_drift_watchdog(&mut self, config: Option<DriftWatchdogConfig>) {
+        self.drift_watchdog = config.filter(DriftWatchdogConfig::is_valid).map(DriftWatchdog::new);
+        self.last_watchdog_fix = None;
+    }
+
+    /// Check whether gyro and GPS turn rates disagreed on the last update
+    pub fn has_consistency_fault(&self) -> bool {
+        self.consistency_fault
+    }
+
+    /// Get current horizontal position uncertainty (1σ, meters)
+    pub fn position_uncertainty(&self) -> f64 {
+        self.position_uncertainty
+    }
+
+    /// Get the GPS weights of the low-pass position update
+    pub fn position_weights(&self) -> PositionWeights {
+        self.position_weights
     }
-}
\ No newline at end of file
+
+    /// Set how strongly fixes pull the low-pass position estimate by
+    /// HDOP; invalid settings are ignored
+    pub fn set_position_weights(&mut self, weights: PositionWeights) {
+        if weights.is_valid() {
+            self.position_weights = weights;
+        }
+    }
+
+    /// Get the confidence weights (normalized to sum to 1)
+    pub fn confidence_weights(&self) -> ConfidenceWeights {
+        self.confidence_weights
+    }
+
+    /// Set how IMU and GPS quality are weighted into confidence, and the
+    /// HDOP thresholds for a good and a fair fix; invalid settings are
+    /// ignored
+    pub fn set_confidence_weights(&mut self, weights: ConfidenceWeights) {
+        if weights.is_valid() {
+            self.confidence_weights = weights.normalized();
+        }
+    }
+
+    /// Get the IMU velocity integration window (s)
+    pub fn velocity_window(&self) -> f64 {
+        self.velocity_window
+    }
+
+    /// Set the time constant (s) over which IMU-integrated velocity decays
+    /// toward GPS velocity; longer windows follow the IMU through brief
+    /// manoeuvres but let an accelerometer bias pull velocity further off
+    pub fn set_velocity_window(&mut self, secs: f64) {
+        if secs.is_finite() && secs > 0.0 {
+            self.velocity_window = secs;
+        }
+    }
+
+    /// Note how long ago (s) the GPS fix passed to the next updates
+    /// arrived; the same fix is passed on every update until a new one
+    /// comes in, so only the caller knows its age
+    pub fn set_gps_fix_age(&mut self, secs: f64) {
+        if secs.is_finite() && secs >= 0.0 {
+            self.gps_fix_age = secs;
+        }
+    }
+
+    /// Check whether the GPS fix is older than the timeout
+    pub fn is_gps_stale(&self) -> bool {
+        self.gps_fix_age > self.gps_timeout
+    }
+
+    /// Get the age (s) beyond which a GPS fix is stale
+    pub fn gps_timeout(&self) -> f64 {
+        self.gps_timeout
+    }
+
+    /// Set the age (s) beyond which a GPS fix is stale; stale fixes pull
+    /// position and count toward confidence less the older they get
+    pub fn set_gps_timeout(&mut self, secs: f64) {
+        if secs.is_finite() && secs > 0.0 {
+            self.gps_timeout = secs;
+        }
+    }
+
+    /// Get the position fusion strategy
+    pub fn position_strategy(&self) -> PositionStrategy {
+        self.position_strategy
+    }
+
+    /// Select the position fusion strategy
+    /// 
+    /// Switching to the Kalman filter starts it from the current estimate.
+    pub fn set_position_strategy(&mut self, strategy: PositionStrategy) {
+        self.position_strategy = strategy;
+        self.kalman = None;
+    }
+
+    /// Get the output orientation smoothing factor, if enabled
+    pub fn orientation_smoothing(&self) -> Option<f64> {
+        self.orientation_smoothing
+    }
+
+    /// Smooth the reported orientation for display (0 = none, toward 1 =
+    /// heavier smoothing and more lag); the fusion estimate is unaffected
+    pub fn set_orientation_smoothing(&mut self, factor: Option<f64>) {
+        self.orientation_smoothing = factor.filter(|f| f.is_finite()).map(|f| f.clamp(0.0, 0.99));
+    }
+
+    /// Get the number of gyro integration steps per update
+    pub fn integration_substeps(&self) -> u32 {
+        self.integration_substeps
+    }
+
+    /// Split each update's gyro integration into `substeps` equal steps
+    /// (1 = a single step over the whole interval; 0 is treated as 1)
+    pub fn set_integration_substeps(&mut self, substeps: u32) {
+        self.integration_substeps = substeps.max(1);
+    }
+
+    /// Get the gyro deadband (rad/s; 0 = off)
+    pub fn gyro_deadband(&self) -> f64 {
+        self.gyro_deadband
+    }
+
+    /// Zero bias-corrected gyro components below `deadband` (rad/s) before
+    /// integration, clamped to 0-`MAX_GYRO_DEADBAND`
+    /// 
+    /// Suppresses yaw drift from noise while stationary, but rotation
+    /// slower than the deadband is lost too, so keep it just above the
+    /// gyro noise level.
+    pub fn set_gyro_deadband(&mut self, deadband: f64) {
+        if deadband.is_finite() {
+            self.gyro_deadband = deadband.clamp(0.0, MAX_GYRO_DEADBAND);
+        }
+    }
+
+    /// Get the gyro bias estimator gain (1/s²), if enabled
+    pub fn gyro_bias_gain(&self) -> Option<f64> {
+        self.gyro_bias_gain
+    }
+
+    /// Estimate the gyro bias online with integral gain `gain` (1/s²) and
+    /// subtract it before integration; `None` (or a non-positive gain)
+    /// stops estimating and keeps the current estimate
+    /// 
+    /// Higher gains converge faster but let accelerometer disturbances
+    /// (vibration, sustained linear acceleration) into the estimate.
+    /// Around 0.01-0.1 suits the default alpha.
+    pub fn set_gyro_bias_gain(&mut self, gain: Option<f64>) {
+        self.gyro_bias_gain = gain.filter(|g| g.is_finite() && *g > 0.0);
+    }
+
+    /// Get the gyro bias currently estimated and subtracted (rad/s; zero
+    /// before any estimation)
+    pub fn estimated_gyro_bias(&self) -> Vec3 {
+        self.gyro_drift_compensation
+    }
+
+    /// Get the orientation change rate limit (deg/s), if enabled
+    pub fn max_rotation_rate(&self) -> Option<f64> {
+        self.max_rotation_rate
+    }
+
+    /// Cap the orientation change per update at `rate` deg/s times the
+    /// update interval; `None` (or a non-positive rate) removes the cap
+    /// 
+    /// Guards against single corrupt gyro samples. Set it well above the
+    /// fastest real rotation, or genuine motion will lag behind.
+    pub fn set_max_rotation_rate(&mut self, rate: Option<f64>) {
+        self.max_rotation_rate = rate.filter(|r| r.is_finite() && *r > 0.0);
+    }
+
+    /// Get the accelerometer bias subtracted from readings (m/s²)
+    pub fn accel_bias(&self) -> Vec3 {
+        self.accel_bias
+    }
+
+    /// Subtract `bias` (m/s², per axis) from accelerometer readings before
+    /// fusion; ignored unless every axis is finite and within
+    /// ±`MAX_ACCEL_BIAS`
+    pub fn set_accel_bias(&mut self, bias: Vec3) {
+        let axes = [bias.x, bias.y, bias.z];
+        if axes.iter().all(|b| b.is_finite() && b.abs() <= MAX_ACCEL_BIAS) {
+            self.accel_bias = bias;
+        }
+    }
+
+    /// Whether a stationary accelerometer calibration is in progress
+    pub fn accel_calibrating(&self) -> bool {
+        self.accel_calibration.is_some()
+    }
+
+    /// Estimate the accelerometer bias from the next `samples` readings
+    /// (at least 1), replacing the current bias when done
+    /// 
+    /// The sensor must rest level and still meanwhile: the bias is the
+    /// average reading minus gravity on +z. Readings are fused with the
+    /// old bias until the estimate is in. A result beyond
+    /// `MAX_ACCEL_BIAS` on any axis is discarded.
+    pub fn start_accel_calibration(&mut self, samples: u32) {
+        self.accel_calibration = Some(AccelCalibration {
+            sum: Vec3::zero(),
+            samples: 0,
+            target: samples.max(1),
+        });
+    }
+
+    /// Check whether the orientation estimate has settled since start-up
+    /// (or since the filter was switched in)
+    pub fn orientation_settled(&self) -> bool {
+        self.settling_updates == 0 && self.reconverge_updates == 0
+    }
+
+    /// Get current filter alpha value
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    /// Update filter alpha value
+    pub fn set_alpha(&mut self, alpha: f64) {
+        self.alpha = alpha.clamp(0.0, 1.0);
+    }
+
+    /// Get minimum GPS speed for course-based yaw correction (m/s)
+    pub fn gps_yaw_min_speed(&self) -> f64 {
+        self.gps_yaw_min_speed
+    }
+
+    /// Update minimum GPS speed for course-based yaw correction (m/s)
+    pub fn set_gps_yaw_min_speed(&mut self, speed: f64) {
+        self.gps_yaw_min_speed = speed.max(0.0);
+    }
+
+    /// Get GPS-derived acceleration gate (m/s²), if enabled
+    pub fn gps_accel_gate(&self) -> Option<f64> {
+        self.gps_accel_gate
+    }
+
+    /// Enable or disable down-weighting the accelerometer during GPS-observed
+    /// acceleration above `gate` (m/s²)
+    pub fn set_gps_accel_gate(&mut self, gate: Option<f64>) {
+        self.gps_accel_gate = gate.filter(|g| g.is_finite() && *g > 0.0);
+    }
+}
+
+impl FusionFilter for ComplementaryFilter {
+    fn kind(&self) -> FilterKind {
+        FilterKind::Complementary
+    }
+
+    fn update(&mut self, imu: ImuData, gps: GpsData) -> FusedSensorData {
+        ComplementaryFilter::update(self, imu, gps)
+    }
+
+    fn update_with_dt(&mut self, imu: ImuData, gps: GpsData, dt: f64) -> FusedSensorData {
+        ComplementaryFilter::update_with_dt(self, imu, gps, dt)
+    }
+
+    fn reset_timing(&mut self) {
+        ComplementaryFilter::reset_timing(self)
+    }
+
+    fn orientation(&self) -> Quaternion {
+        self.orientation
+    }
+
+    fn orientation_settled(&self) -> bool {
+        ComplementaryFilter::orientation_settled(self)
+    }
+
+    fn position(&self) -> Option<(f64, f64, f64)> {
+        ComplementaryFilter::position(self)
+    }
+
+    fn origin(&self) -> (f64, f64, f64) {
+        ComplementaryFilter::origin(self)
+    }
+
+    fn position_uncertainty(&self) -> f64 {
+        ComplementaryFilter::position_uncertainty(self)
+    }
+
+    fn seed_position(&mut self, position: (f64, f64, f64), origin: (f64, f64, f64), uncertainty: f64) {
+        ComplementaryFilter::seed_position(self, position, origin, uncertainty)
+    }
+
+    fn recenter(&mut self, gps: &GpsData) -> bool {
+        ComplementaryFilter::recenter(self, gps)
+    }
+
+    fn start_reconverging(&mut self) {
+        ComplementaryFilter::start_reconverging(self)
+    }
+
+    fn zero_orientation(&mut self) {
+        ComplementaryFilter::zero_orientation(self)
+    }
+
+    fn clear_orientation_reference(&mut self) {
+        ComplementaryFilter::clear_orientation_reference(self)
+    }
+
+    fn set_gps_fix_age(&mut self, secs: f64) {
+        ComplementaryFilter::set_gps_fix_age(self, secs)
+    }
+
+    fn is_gps_stale(&self) -> bool {
+        ComplementaryFilter::is_gps_stale(self)
+    }
+
+    fn accel_bias(&self) -> Vec3 {
+        ComplementaryFilter::accel_bias(self)
+    }
+
+    fn set_accel_bias(&mut self, bias: Vec3) {
+        ComplementaryFilter::set_accel_bias(self, bias)
+    }
+
+    fn diagnostics(&self) -> FilterDiagnostics {
+        FilterDiagnostics::Complementary(ComplementaryFilter::diagnostics(self))
+    }
+
+    fn start_accel_calibration(&mut self, samples: u32) -> bool {
+        ComplementaryFilter::start_accel_calibration(self, samples);
+        true
+    }
+
+    fn accel_calibrating(&self) -> bool {
+        ComplementaryFilter::accel_calibrating(self)
+    }
+
+    fn position_divergence(&self) -> bool {
+        ComplementaryFilter::position_divergence(self)
+    }
+}
+
+/// Check that a GPS fix has a finite latitude, longitude, and altitude
+fn gps_position_is_finite(gps: &GpsData) -> bool {
+    gps.latitude.is_finite() && gps.longitude.is_finite() && gps.altitude.is_finite()
+}
+
+/// Check that a GPS reading is a usable 3D fix
+/// 
+/// Too few satellites or too high an HDOP means the reported position is
+/// unreliable (tunnels, urban canyons) and should not be blended in.
+pub fn gps_has_fix(gps: &GpsData) -> bool {
+    gps_position_is_finite(gps)
+        && gps.satellites >= MIN_FIX_SATELLITES
+        && gps.hdop.is_finite()
+        && gps.hdop <= MAX_FIX_HDOP
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fusion::gps_motion::GPS_ACCEL_HOLD_SECS;
+    use crate::fusion::output::{FrameOutput, DEFAULT_GIMBAL_LOCK_MARGIN_DEG};
+    use pretty_assertions::assert_eq;
+
+    /// Level and at rest: gravity only, no rotation
+    fn level_imu() -> ImuData {
+        ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), Vec3::zero())
+    }
+
+    fn gps_moving(speed: f64, heading: f64) -> GpsData {
+        let mut gps = GpsData::new(37.7749, -122.4194, 10.0);
+        gps.speed = speed;
+        gps.heading = heading;
+        gps
+    }
+
+    /// Nominal time step the filter falls back to after `reset_timing`
+    const DT: f64 = 0.02;
+
+    /// One update of exactly `DT` seconds, whatever the wall clock says
+    fn update_nominal(filter: &mut ComplementaryFilter, imu: ImuData, gps: GpsData) -> FusedSensorData {
+        filter.reset_timing();
+        filter.update(imu, gps)
+    }
+
+    /// Feed the same readings for `steps` updates, returning the last frame
+    fn run(filter: &mut ComplementaryFilter, imu: &ImuData, gps: &GpsData, steps: usize) -> FusedSensorData {
+        let mut frame = filter.update(imu.clone(), gps.clone());
+        for _ in 1..steps {
+            frame = filter.update(imu.clone(), gps.clone());
+        }
+        frame
+    }
+
+    /// [`run`] with updates of exactly `DT` seconds
+    fn run_nominal(filter: &mut ComplementaryFilter, imu: &ImuData, gps: &GpsData, steps: usize) -> FusedSensorData {
+        let mut frame = update_nominal(filter, imu.clone(), gps.clone());
+        for _ in 1..steps {
+            frame = update_nominal(filter, imu.clone(), gps.clone());
+        }
+        frame
+    }
+
+    #[test]
+    fn test_yaw_tracks_gps_course_when_moving() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        let frame = run(&mut filter, &level_imu(), &gps_moving(5.0, 90.0), 500);
+
+        let (roll, pitch, yaw) = frame.euler_degrees;
+        assert!((yaw - 90.0).abs() < 0.5, "yaw {yaw} should follow the 90° course");
+        assert!(roll.abs() < 0.5 && pitch.abs() < 0.5);
+    }
+
+    #[test]
+    fn test_yaw_ignores_gps_course_below_min_speed() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        assert_eq!(filter.gps_yaw_min_speed(), DEFAULT_GPS_YAW_MIN_SPEED);
+
+        let speed = DEFAULT_GPS_YAW_MIN_SPEED * 0.5;
+        let frame = run(&mut filter, &level_imu(), &gps_moving(speed, 90.0), 500);
+        assert!(frame.euler_degrees.2.abs() < 0.5);
+
+        // Lowering the threshold lets the same course through
+        filter.set_gps_yaw_min_speed(speed * 0.5);
+        let frame = run(&mut filter, &level_imu(), &gps_moving(speed, 90.0), 500);
+        assert!((frame.euler_degrees.2 - 90.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_yaw_turns_the_short_way_across_north() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        run(&mut filter, &level_imu(), &gps_moving(5.0, 10.0), 500);
+
+        // 10° -> 350° is a 20° turn, not 340°
+        let frame = filter.update(level_imu(), gps_moving(5.0, 350.0));
+        let yaw = frame.euler_degrees.2.rem_euclid(360.0);
+        assert!(!(10.0..=349.0).contains(&yaw), "yaw {yaw} went the long way round");
+    }
+
+    #[test]
+    fn test_faulty_inputs_produce_frames_that_round_trip() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        run(&mut filter, &level_imu(), &gps_moving(5.0, 45.0), 50);
+
+        let nan = Vec3::new(f64::NAN, 0.0, GRAVITY);
+        let infinite = Vec3::new(0.0, f64::INFINITY, 0.0);
+        let mut bad_gps = gps_moving(f64::NAN, f64::INFINITY);
+        bad_gps.hdop = f64::NAN;
+        let inputs = [
+            (ImuData::new(nan, Vec3::zero()), gps_moving(5.0, 45.0)),
+            (ImuData::new(Vec3::zero(), infinite), gps_moving(5.0, 45.0)),
+            (ImuData::new(Vec3::zero(), Vec3::zero()), bad_gps.clone()),
+            (ImuData::new(nan, infinite), bad_gps),
+        ];
+        for (imu, gps) in inputs {
+            let frame = filter.update(imu, gps);
+            let json = serde_json::to_value(&frame).unwrap();
+            let nulls: Vec<_> = json.as_object().unwrap().iter().filter(|(_, v)| v.is_null()).map(|(k, _)| k).collect();
+            assert_eq!(nulls, ["anomaly_score"], "non-finite fields written as null");
+            let decoded: FusedSensorData = serde_json::from_value(json).unwrap();
+            assert!(decoded.orientation.is_finite());
+            assert_eq!(decoded.euler_degrees, frame.euler_degrees);
+        }
+
+        // Recovers once readings are sane again
+        let frame = run(&mut filter, &level_imu(), &gps_moving(5.0, 45.0), 500);
+        assert!((frame.euler_degrees.2 - 45.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_sharp_gps_speed_change_reduces_accel_trust() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        filter.set_gps_accel_gate(Some(2.0));
+        run(&mut filter, &level_imu(), &gps_moving(5.0, 0.0), 50);
+        assert_eq!(filter.accel_trust(), 1.0);
+
+        // 5 m/s gained over the last second: 5 m/s² against a 2 m/s² gate
+        let start = std::time::Instant::now();
+        filter.gps_motion = GpsMotion::new();
+        filter.gps_motion.observe(&gps_moving(5.0, 0.0), start);
+        let fix = start + std::time::Duration::from_secs(1);
+        filter.gps_motion.observe(&gps_moving(10.0, 0.0), fix);
+        assert!((filter.accel_trust() - 0.4).abs() < 0.01, "trust {}", filter.accel_trust());
+
+        // Trust returns once the fix stops changing
+        let later = fix + std::time::Duration::from_secs_f64(GPS_ACCEL_HOLD_SECS + 0.1);
+        filter.gps_motion.observe(&gps_moving(10.0, 0.0), later);
+        assert_eq!(filter.accel_trust(), 1.0);
+    }
+
+    #[test]
+    fn test_accel_trust_ungated_by_default() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        run(&mut filter, &level_imu(), &gps_moving(5.0, 0.0), 50);
+        let start = std::time::Instant::now();
+        filter.gps_motion = GpsMotion::new();
+        filter.gps_motion.observe(&gps_moving(5.0, 0.0), start);
+        filter.gps_motion.observe(&gps_moving(25.0, 0.0), start + std::time::Duration::from_secs(1));
+        assert_eq!(filter.accel_trust(), 1.0);
+    }
+
+    #[test]
+    fn test_signal_loss_dead_reckons_smoothly() {
+        use crate::sensors::gps::{GpsFaultType, GpsSimulator};
+
+        // Heading north at 10 m/s, one fix per update
+        let start = gps_moving(10.0, 0.0);
+        let fix_at = |t: f64| {
+            let mut gps = start.clone();
+            gps.latitude += 10.0 * t / METERS_PER_DEGREE;
+            gps
+        };
+        let mut filter = ComplementaryFilter::new(0.98);
+        let mut t = 0.0;
+        for _ in 0..500 {
+            t += DT;
+            update_nominal(&mut filter, level_imu(), fix_at(t));
+        }
+
+        // What the receiver reports after losing the signal, far off track
+        let mut receiver = GpsSimulator::new();
+        receiver.inject_fault(GpsFaultType::SignalLoss);
+        receiver.update();
+        let mut lost = receiver.get_latest();
+        lost.latitude = start.latitude + 0.01;
+        lost.longitude = start.longitude - 0.01;
+
+        let mut last = update_nominal(&mut filter, level_imu(), fix_at(t + DT));
+        let uncertainty = last.position_uncertainty;
+        for _ in 0..100 {
+            let frame = update_nominal(&mut filter, level_imu(), lost.clone());
+            assert!(frame.dead_reckoning);
+            let step = geodetic_to_enu(last.position, frame.position);
+            assert!((step.y - last.velocity.x * DT).abs() < 0.02, "moved {step:?} in one step");
+            assert!(step.x.abs() < 0.01);
+            last = frame;
+        }
+        assert!(last.position_uncertainty > uncertainty);
+
+        // Picks GPS back up when the fix returns
+        let frame = update_nominal(&mut filter, level_imu(), fix_at(t + 102.0 * DT));
+        assert!(!frame.dead_reckoning);
+    }
+
+    #[test]
+    fn test_accel_bias_does_not_make_velocity_drift() {
+        // Level and still, with a 0.5 m/s² vertical accelerometer bias
+        let biased = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY + 0.5), Vec3::zero());
+        let still = gps_moving(0.0, 0.0);
+        let mut filter = ComplementaryFilter::new(0.98);
+
+        // Settles near bias × window (halved by the vertical GPS blend)...
+        let settled = run_nominal(&mut filter, &biased, &still, 500).velocity.z;
+        assert!(settled > 0.0 && settled <= 0.5 * DEFAULT_VELOCITY_WINDOW_SECS, "settled at {settled} m/s");
+
+        // ...and stays there instead of integrating without bound
+        let later = run_nominal(&mut filter, &biased, &still, 5000).velocity.z;
+        assert!((later - settled).abs() < 1e-3, "drifted from {settled} to {later} m/s");
+
+        // A shorter window holds it closer to GPS
+        filter.set_velocity_window(0.2);
+        let short = run_nominal(&mut filter, &biased, &still, 500).velocity.z;
+        assert!(short < settled / 2.0, "{short} m/s with a 0.2 s window");
+    }
+
+    #[test]
+    fn test_gravity_is_removed_before_integrating_vertical_velocity() {
+        // Level and still at 10 Hz: integrating raw z would add ~0.1 × 9.81
+        // m/s every step
+        let mut filter = ComplementaryFilter::new(0.98);
+        let still = gps_moving(0.0, 0.0);
+        for _ in 0..100 {
+            let frame = filter.update_with_dt(level_imu(), still.clone(), 0.1);
+            assert!(frame.velocity.z.abs() < 1e-9, "vertical velocity {} m/s", frame.velocity.z);
+        }
+
+        // An accelerometer spike kicks velocity once, then it decays back
+        // instead of leaving gravity leaking in through a tilted estimate
+        let spike = ImuData::new(Vec3::new(12.0, -8.0, GRAVITY + 15.0), Vec3::zero());
+        let kicked = filter.update_with_dt(spike, still.clone(), 0.1).velocity.z;
+        assert!(kicked > 0.1, "spike barely registered: {kicked} m/s");
+        let mut frame = filter.update_with_dt(level_imu(), still.clone(), 0.1);
+        for _ in 0..100 {
+            frame = filter.update_with_dt(level_imu(), still.clone(), 0.1);
+        }
+        assert!(frame.velocity.z.abs() < 0.01, "vertical velocity {} m/s after the spike", frame.velocity.z);
+    }
+
+    #[test]
+    fn test_position_weight_falls_continuously_with_hdop() {
+        let weights = PositionWeights::default();
+        let sweep: Vec<f64> = (50..=2000).map(|i| weights.weight(i as f64 * 0.01)).collect();
+        assert_eq!(sweep[0], 0.3);
+        assert!(sweep.windows(2).all(|pair| pair[1] <= pair[0] && pair[0] - pair[1] < 0.003), "weight steps");
+
+        // Fixes under the old HDOP 3 threshold keep its 0.3 weight
+        assert_eq!(weights.weight(2.0), 0.3);
+        assert_eq!(weights.weight(3.0), 0.3);
+
+        // No cliff at the old HDOP 3 threshold, and poor fixes fade out
+        assert!((weights.weight(2.99) - weights.weight(3.01)).abs() < 2e-3);
+        assert!(weights.weight(100.0) < 0.01);
+        assert_eq!(weights.weight(f64::NAN), 0.0);
+
+        let floored = PositionWeights { min_weight: 0.05, ..weights };
+        assert_eq!(floored.weight(100.0), 0.05);
+        assert!(!PositionWeights { min_weight: 0.5, ..weights }.is_valid());
+
+        // A poor fix moves the estimate less than a good one
+        let step = |hdop: f64| {
+            let mut filter = ComplementaryFilter::new(0.98);
+            let mut gps = gps_moving(0.0, 0.0);
+            update_nominal(&mut filter, level_imu(), gps.clone());
+            gps.latitude += 1e-4;
+            gps.hdop = hdop;
+            update_nominal(&mut filter, level_imu(), gps).position.0 - 37.7749
+        };
+        assert!(step(8.0) < step(1.0) / 2.0);
+    }
+
+    #[test]
+    fn test_held_gps_fix_loses_influence_past_the_timeout() {
+        // Position step toward a held fix that moved, and the frame, when
+        // the fix is `age` seconds old
+        let step = |age: f64| {
+            let mut filter = ComplementaryFilter::new(0.98);
+            let mut gps = gps_moving(0.0, 0.0);
+            update_nominal(&mut filter, level_imu(), gps.clone());
+            gps.latitude += 1e-4;
+            filter.set_gps_fix_age(age);
+            let frame = update_nominal(&mut filter, level_imu(), gps);
+            (frame.position.0 - 37.7749, frame)
+        };
+
+        // A fix held between regular 1 Hz updates is not stale
+        let (fresh, frame) = step(0.98);
+        assert!(!frame.stale_gps);
+        assert_eq!(step(DEFAULT_GPS_TIMEOUT_SECS).0, fresh);
+
+        // Past the timeout the held fix pulls less, and less the older it is
+        let (stale, frame) = step(2.0 * DEFAULT_GPS_TIMEOUT_SECS);
+        assert!(frame.stale_gps && frame.status_flags.contains(StatusFlags::STALE_GPS));
+        assert!((stale - fresh / 2.0).abs() < 1e-9, "stale step {stale} vs fresh {fresh}");
+        assert!(step(4.0 * DEFAULT_GPS_TIMEOUT_SECS).0 < stale);
+        assert!(frame.confidence < step(0.98).1.confidence);
+    }
+
+    #[test]
+    fn test_confidence_weights_are_normalized_and_configurable() {
+        let mut noisy = level_imu();
+        noisy.noise_level = 0.3;
+        let mut poor_fix = gps_moving(0.0, 0.0);
+        poor_fix.hdop = 4.0;
+
+        // Default: 0.6 × 0.7 + 0.4 × 0.7 (fair fix)
+        let mut filter = ComplementaryFilter::new(0.98);
+        let frame = update_nominal(&mut filter, noisy.clone(), poor_fix.clone());
+        assert!((frame.confidence - 0.7).abs() < 1e-9, "confidence {}", frame.confidence);
+
+        // IMU only: confidence follows the noise level whatever the fix
+        filter.set_confidence_weights(ConfidenceWeights { imu: 1.0, gps: 0.0, ..ConfidenceWeights::default() });
+        for hdop in [1.0, 4.0, 9.0] {
+            poor_fix.hdop = hdop;
+            let frame = update_nominal(&mut filter, noisy.clone(), poor_fix.clone());
+            assert!((frame.confidence - 0.7).abs() < 1e-9, "confidence {} at HDOP {hdop}", frame.confidence);
+        }
+
+        // Weights that don't sum to 1 are scaled; a stricter good-fix threshold
+        filter.set_confidence_weights(ConfidenceWeights { imu: 1.0, gps: 3.0, hdop_good: 0.8, hdop_fair: 5.0 });
+        assert!((filter.confidence_weights().gps - 0.75).abs() < 1e-12);
+        poor_fix.hdop = 1.0;
+        let frame = update_nominal(&mut filter, noisy.clone(), poor_fix.clone());
+        assert!((frame.confidence - 0.7).abs() < 1e-9, "confidence {}", frame.confidence);
+
+        // Invalid settings keep the previous ones
+        filter.set_confidence_weights(ConfidenceWeights { imu: 0.0, gps: 0.0, ..ConfidenceWeights::default() });
+        assert!((filter.confidence_weights().gps - 0.75).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_recenter_snaps_position_to_the_fix() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        run(&mut filter, &level_imu(), &gps_moving(0.0, 0.0), 100);
+
+        // A fix 1 km north only pulls the low-pass estimate part way
+        let mut moved = gps_moving(0.0, 0.0);
+        moved.latitude += 1000.0 / METERS_PER_DEGREE;
+        let frame = update_nominal(&mut filter, level_imu(), moved.clone());
+        assert!((moved.latitude - frame.position.0) * METERS_PER_DEGREE > 100.0);
+
+        assert!(filter.recenter(&moved));
+        let frame = update_nominal(&mut filter, level_imu(), moved.clone());
+        let offset = geodetic_to_enu((moved.latitude, moved.longitude, moved.altitude), frame.position);
+        assert!(offset.magnitude() < 0.1, "{offset:?} from the fix after recentering");
+        assert!(frame.local_position.magnitude() < 0.1, "local origin not reset: {:?}", frame.local_position);
+    }
+
+    #[test]
+    fn test_health_override_propagates_to_system_health() {
+        use crate::sensors::{gps::GpsSimulator, imu::ImuSimulator};
+
+        let mut imu = ImuSimulator::new();
+        let mut gps = GpsSimulator::new();
+        imu.set_health_override(Some(0.2));
+        gps.set_health_override(Some(0.2));
+        let mut filter = ComplementaryFilter::new(0.98);
+        let frame = update_nominal(&mut filter, imu.read(), gps.get_latest());
+        assert!((frame.system_health - 0.2).abs() < 1e-12, "system health {}", frame.system_health);
+
+        // Cleared, the IMU reports its computed health again
+        imu.set_health_override(None);
+        let frame = update_nominal(&mut filter, imu.read(), gps.get_latest());
+        assert!(frame.system_health > 0.2 + 1e-6, "system health {}", frame.system_health);
+    }
+
+    /// Mean squared frame-to-frame roll change (deg²) under gyro jitter
+    fn roll_jitter(smoothing: Option<f64>) -> f64 {
+        let mut filter = ComplementaryFilter::new(0.98);
+        filter.set_orientation_smoothing(smoothing);
+        let gps = gps_moving(0.0, 0.0);
+        let mut last = run(&mut filter, &level_imu(), &gps, 50).euler_degrees.0;
+        let mut sum = 0.0;
+        for step in 0..200 {
+            let sign = if step % 2 == 0 { 1.0 } else { -1.0 };
+            let imu = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), Vec3::new(sign * 2.0, 0.0, 0.0));
+            let roll = update_nominal(&mut filter, imu, gps.clone()).euler_degrees.0;
+            sum += (roll - last).powi(2);
+            last = roll;
+        }
+        sum / 200.0
+    }
+
+    #[test]
+    fn test_orientation_smoothing_reduces_jitter() {
+        let raw = roll_jitter(None);
+        let smoothed = roll_jitter(Some(0.8));
+        assert!(raw > 1.0, "raw jitter {raw:.3} deg²");
+        assert!(smoothed < raw / 4.0, "smoothed jitter {smoothed:.3} vs raw {raw:.3} deg²");
+    }
+
+    #[test]
+    fn test_gyro_spike_during_steady_motion_flags_inconsistency() {
+        // Fixed steps throughout: the GPS course rate is timed on the
+        // filter's clock, which `update_with_dt` advances by `DT`
+        let mut filter = ComplementaryFilter::new(0.98);
+        for _ in 0..10 {
+            filter.update_with_dt(level_imu(), gps_moving(5.0, 90.0), DT);
+        }
+
+        // A new fix on the same course: the GPS turn rate is zero
+        let frame = filter.update_with_dt(level_imu(), gps_moving(5.5, 90.0), DT);
+        assert!(!frame.sensor_consistency_fault);
+
+        // The gyro suddenly reports a ~115°/s yaw rate
+        let spike = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), Vec3::new(0.0, 0.0, 2.0));
+        let frame = filter.update_with_dt(spike.clone(), gps_moving(5.5, 90.0), DT);
+        assert!(frame.sensor_consistency_fault);
+        assert!(frame.status_flags.contains(StatusFlags::SENSOR_CONSISTENCY_FAULT));
+
+        // Too slow for the GPS course to mean anything: no check
+        let slow = gps_moving(DEFAULT_GPS_YAW_MIN_SPEED * 0.5, 90.0);
+        let frame = filter.update_with_dt(spike, slow, DT);
+        assert!(!frame.sensor_consistency_fault);
+    }
+
+    #[test]
+    fn test_pitching_through_vertical_raises_gimbal_lock_warning() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        let gps = gps_moving(0.0, 0.0);
+        // No usable gravity reference, so the gyro alone drives orientation
+        let pitch_up = ImuData::new(Vec3::zero(), Vec3::new(0.0, 0.5, 0.0));
+
+        let output = FrameOutput::default();
+        let mut previous = update_nominal(&mut filter, pitch_up.clone(), gps.clone());
+        output.apply(&mut previous, &filter);
+        assert!(!previous.gimbal_lock_warning);
+        let mut warned = false;
+        // 0.01 rad per update: 170 updates carry pitch ~98° from level
+        for _ in 0..170 {
+            let mut frame = update_nominal(&mut filter, pitch_up.clone(), gps.clone());
+            output.apply(&mut frame, &filter);
+            warned |= frame.gimbal_lock_warning;
+            assert_eq!(frame.gimbal_lock_warning, 90.0 - frame.euler_degrees.1.abs() <= DEFAULT_GIMBAL_LOCK_MARGIN_DEG);
+            let step = previous.orientation.angle_to(&frame.orientation);
+            assert!(step < 0.02, "quaternion jumped {step} rad at pitch {:.1}°", frame.euler_degrees.1);
+            previous = frame;
+        }
+        assert!(warned, "no warning while pitching through 90°");
+        assert!(!previous.gimbal_lock_warning, "still warned at pitch {:.1}°", previous.euler_degrees.1);
+    }
+
+    /// Orientation error (rad) after turning at 2 rad/s for 1 s from 10 Hz
+    /// gyro readings alone
+    fn low_rate_turn_error(substeps: u32) -> f64 {
+        let mut filter = ComplementaryFilter::new(0.98);
+        filter.set_integration_substeps(substeps);
+        let turn = Vec3::new(0.0, 0.0, 2.0);
+        for _ in 0..10 {
+            filter.orientation = filter.integrate_gyroscope(&turn, 0.1).0;
+        }
+        // A 2 rad yaw about the vertical axis
+        filter.orientation.angle_to(&Quaternion::new(1.0_f64.cos(), 0.0, 0.0, 1.0_f64.sin()))
+    }
+
+    #[test]
+    fn test_norm_deviation_is_measurable_and_bounded_at_high_rotation_rates() {
+        let level = level_imu();
+        let mut filter = ComplementaryFilter::new(0.98);
+        update_nominal(&mut filter, level.clone(), gps_moving(0.0, 0.0));
+        assert_eq!(filter.diagnostics().norm_deviation, 0.0);
+
+        // 10 rad/s over 20 ms: |(1, ωdt/2)| - 1 ≈ (ωdt/2)² / 2
+        let spinning = ImuData::new(level.acceleration, Vec3::new(0.0, 0.0, 10.0));
+        update_nominal(&mut filter, spinning.clone(), gps_moving(0.0, 0.0));
+        let expected = (1.0 + 0.1_f64.powi(2)).sqrt() - 1.0;
+        let diagnostics = filter.diagnostics();
+        assert!((diagnostics.norm_deviation - expected).abs() < 1e-12, "{}", diagnostics.norm_deviation);
+        assert!(diagnostics.norm_deviation > 1e-3 && diagnostics.norm_deviation < 1e-2);
+
+        // Substeps shrink it; the maximum remembers the worst update
+        filter.set_integration_substeps(10);
+        update_nominal(&mut filter, spinning, gps_moving(0.0, 0.0));
+        let substepped = filter.diagnostics();
+        assert!(substepped.norm_deviation < expected / 50.0, "{}", substepped.norm_deviation);
+        assert_eq!(substepped.max_norm_deviation, diagnostics.norm_deviation);
+    }
+
+    #[test]
+    fn test_integration_substeps_reduce_low_rate_error() {
+        let single = low_rate_turn_error(1);
+        let substepped = low_rate_turn_error(10);
+        assert!(single > 1e-3, "single-step error {single:.2e} rad");
+        assert!(substepped < single / 10.0, "10 substeps {substepped:.2e} rad vs 1 substep {single:.2e} rad");
+        // Zero substeps is treated as one
+        assert_eq!(low_rate_turn_error(0), single);
+    }
+
+    /// Yaw (deg) after 60 s at rest with a noisy gyro (small bias plus a
+    /// fixed pseudo-random spread)
+    fn stationary_yaw_drift(deadband: f64) -> f64 {
+        let mut filter = ComplementaryFilter::new(0.98);
+        filter.set_gyro_deadband(deadband);
+        let gps = gps_moving(0.0, 0.0);
+        let mut frame = None;
+        for step in 0..3000 {
+            let noise = 0.002 * ((step as f64 * 1.7).sin() + 0.5);
+            let imu = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), Vec3::new(0.0, 0.0, noise));
+            frame = Some(update_nominal(&mut filter, imu, gps.clone()));
+        }
+        frame.unwrap().euler_degrees.2.abs()
+    }
+
+    #[test]
+    fn test_gyro_deadband_reduces_stationary_yaw_drift() {
+        let raw = stationary_yaw_drift(0.0);
+        let deadbanded = stationary_yaw_drift(0.005);
+        assert!(raw > 2.0, "yaw drift {raw:.2}° without a deadband");
+        assert!(deadbanded < raw / 3.0, "{deadbanded:.2}° with a deadband vs {raw:.2}° without");
+
+        // Genuine rotation well above the deadband still integrates
+        let mut filter = ComplementaryFilter::new(0.98);
+        filter.set_gyro_deadband(0.005);
+        let turning = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), Vec3::new(0.0, 0.0, 0.1));
+        let yaw = run_nominal(&mut filter, &turning, &gps_moving(0.0, 0.0), 50).euler_degrees.2;
+        assert!((yaw - 0.1_f64.to_degrees()).abs() < 0.5, "yaw {yaw:.2}° after 1 s at 0.1 rad/s");
+    }
+
+    /// Orientation change (degrees) from one 5 rad/s gyro spike on a settled filter
+    fn spike_step(max_rotation_rate: Option<f64>) -> f64 {
+        let mut filter = ComplementaryFilter::new(0.98);
+        filter.set_max_rotation_rate(max_rotation_rate);
+        let gps = gps_moving(0.0, 0.0);
+        let before = run_nominal(&mut filter, &level_imu(), &gps, 100).orientation;
+
+        let spike = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), Vec3::new(5.0, 0.0, 0.0));
+        let after = update_nominal(&mut filter, spike, gps).orientation;
+        before.angle_to(&after).to_degrees()
+    }
+
+    #[test]
+    fn test_rotation_rate_limit_bounds_a_gyro_spike() {
+        let unclamped = spike_step(None);
+        assert!(unclamped > 5.0, "spike only turned {unclamped}°");
+
+        // 90°/s allows 1.8° in one 20 ms update
+        let clamped = spike_step(Some(90.0));
+        assert!((clamped - 90.0 * DT).abs() < 1e-6, "clamped step was {clamped}°");
+
+        // A limit above the spike's own rate leaves it alone
+        assert!((spike_step(Some(1000.0)) - unclamped).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gyro_bias_estimate_converges_to_the_true_bias() {
+        let gps = gps_moving(0.0, 0.0);
+        // Still and tilted, so the bias on every axis shows up as a rotation
+        // the accelerometer contradicts (except about gravity)
+        let true_bias = Vec3::new(0.02, -0.015, 0.0);
+        let attitude = Quaternion::from_euler(0.2, -0.1, 0.0);
+        let imu = ImuData::new(attitude.inverse().rotate(Vec3::new(0.0, 0.0, GRAVITY)), true_bias);
+        let error = |frame: &FusedSensorData| {
+            let estimate = frame.estimated_gyro_bias;
+            Vec3::new(estimate.x - true_bias.x, estimate.y - true_bias.y, estimate.z - true_bias.z).magnitude()
+        };
+
+        // Nothing is estimated until enabled
+        let mut filter = ComplementaryFilter::new(0.98);
+        let frame = run_nominal(&mut filter, &imu, &gps, 500);
+        assert_eq!(frame.estimated_gyro_bias.to_array(), [0.0; 3]);
+
+        filter.set_gyro_bias_gain(Some(0.1));
+        let frames: Vec<FusedSensorData> = (0..6).map(|_| run_nominal(&mut filter, &imu, &gps, 500)).collect();
+        let errors: Vec<f64> = frames.iter().map(error).collect();
+        assert!(errors.windows(2).all(|pair| pair[1] < pair[0]), "error not shrinking: {errors:?}");
+        assert!(errors[5] < 1e-3, "estimate still {:.4} rad/s off", errors[5]);
+        assert_eq!(filter.estimated_gyro_bias().to_array(), frames[5].estimated_gyro_bias.to_array());
+    }
+
+    #[test]
+    fn test_stationary_calibration_estimates_and_removes_accel_bias() {
+        let bias = Vec3::new(0.3, -0.2, 0.15);
+        let biased = |step: usize| {
+            let noise = 0.01 * (step as f64 * 1.3).sin();
+            ImuData::new(Vec3::new(bias.x + noise, bias.y - noise, GRAVITY + bias.z + noise), Vec3::zero())
+        };
+        let gps = gps_moving(0.0, 0.0);
+
+        // Uncalibrated, the bias reads as a tilt
+        let mut uncalibrated = ComplementaryFilter::new(0.98);
+        let mut frame = update_nominal(&mut uncalibrated, biased(0), gps.clone());
+        for step in 1..500 {
+            frame = update_nominal(&mut uncalibrated, biased(step), gps.clone());
+        }
+        assert!(frame.euler_degrees.1.abs() > 1.0, "bias produced no tilt: {:?}", frame.euler_degrees);
+
+        let mut filter = ComplementaryFilter::new(0.98);
+        filter.start_accel_calibration(100);
+        for step in 0..100 {
+            assert!(filter.accel_calibrating());
+            update_nominal(&mut filter, biased(step), gps.clone());
+        }
+        assert!(!filter.accel_calibrating());
+        let estimate = filter.accel_bias();
+        for (got, want) in estimate.to_array().iter().zip(bias.to_array()) {
+            assert!((got - want).abs() < 0.005, "estimated {estimate:?}, injected {bias:?}");
+        }
+
+        for step in 100..600 {
+            frame = update_nominal(&mut filter, biased(step), gps.clone());
+        }
+        let (roll, pitch, _) = frame.euler_degrees;
+        assert!(roll.abs() < 0.2 && pitch.abs() < 0.2, "tilt {roll}°, {pitch}° after calibration");
+        assert_eq!(frame.raw_acceleration.to_array(), biased(599).acceleration.to_array(), "frames report the raw reading");
+    }
+
+    #[test]
+    fn test_zeroing_at_a_tilt_reports_identity() {
+        let gps = gps_moving(0.0, 0.0);
+        // Gravity as seen rolled 30° and pitched 89° (near gimbal lock)
+        for (roll, pitch) in [(30f64, 0f64), (0.0, 89.0)] {
+            let attitude = Quaternion::from_euler(roll.to_radians(), pitch.to_radians(), 0.0);
+            let tilted = ImuData::new(attitude.inverse().rotate(Vec3::new(0.0, 0.0, GRAVITY)), Vec3::zero());
+            let mut filter = ComplementaryFilter::new(0.98);
+            let absolute = run_nominal(&mut filter, &tilted, &gps, 500);
+            assert!(absolute.orientation.angle_to(&Quaternion::identity()).to_degrees() > 25.0);
+
+            filter.zero_orientation();
+            let zeroed = update_nominal(&mut filter, tilted.clone(), gps.clone());
+            let off = zeroed.orientation.angle_to(&Quaternion::identity()).to_degrees();
+            assert!(off < 0.5, "{off}° from identity after zeroing at ({roll}°, {pitch}°)");
+
+            filter.clear_orientation_reference();
+            let restored = update_nominal(&mut filter, tilted.clone(), gps.clone());
+            assert!(restored.orientation.angle_to(&absolute.orientation).to_degrees() < 0.5);
+        }
+    }
+
+    #[test]
+    fn test_gps_jump_trips_the_drift_watchdog() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        filter.set_drift_watchdog(Some(DriftWatchdogConfig {
+            threshold_m: 20.0,
+            persistence: std::time::Duration::from_secs(1),
+            window: std::time::Duration::from_secs(10),
+        }));
+        let mut gps = gps_moving(0.0, 0.0);
+
+        // One new fix per second (50 updates), each with its own timestamp
+        let run_fixes = |filter: &mut ComplementaryFilter, gps: &mut GpsData, seconds: usize| {
+            let mut frames = Vec::new();
+            for _ in 0..seconds {
+                gps.timestamp += chrono::Duration::seconds(1);
+                frames.push(run_nominal(filter, &level_imu(), gps, 50));
+            }
+            frames
+        };
+        let steady = run_fixes(&mut filter, &mut gps, 5);
+        assert!(steady.iter().all(|frame| !frame.status_flags.contains(StatusFlags::POSITION_DIVERGENCE)));
+        let confidence = steady.last().unwrap().confidence;
+
+        // ~200 m north while the IMU says we haven't moved
+        gps.latitude += 0.0018;
+        let jumped = run_fixes(&mut filter, &mut gps, 3);
+        assert!(filter.position_divergence(), "watchdog never tripped");
+        let frame = jumped.last().unwrap();
+        assert!(frame.status_flags.contains(StatusFlags::POSITION_DIVERGENCE));
+        assert!(frame.confidence < confidence * 0.75, "confidence {confidence} -> {}", frame.confidence);
+    }
+
+    /// Mean distance (m) the position estimate trails a vehicle heading
+    /// north at 10 m/s with a fix every update, after it has settled
+    fn ramp_lag(strategy: PositionStrategy) -> f64 {
+        let start = gps_moving(10.0, 0.0);
+        let mut filter = ComplementaryFilter::new(0.98);
+        filter.set_position_strategy(strategy);
+
+        let mut lag = 0.0;
+        for step in 1..=1500 {
+            let mut fix = start.clone();
+            fix.latitude += 10.0 * step as f64 * DT / METERS_PER_DEGREE;
+            let frame = update_nominal(&mut filter, level_imu(), fix.clone());
+            if step > 1000 {
+                lag += (fix.latitude - frame.position.0) * METERS_PER_DEGREE / 500.0;
+            }
+        }
+        lag
+    }
+
+    #[test]
+    fn test_kalman_tracks_a_ramp_with_less_lag() {
+        let low_pass = ramp_lag(PositionStrategy::LowPass);
+        let kalman = ramp_lag(PositionStrategy::Kalman);
+        assert!(low_pass > 0.3, "low-pass lag {low_pass:.2} m");
+        assert!(kalman.abs() < low_pass / 4.0, "Kalman lag {kalman:.2} m vs low-pass {low_pass:.2} m");
+    }
+}
+
@@ -0,0 +1,48 @@
+//! Fusion Filter Diagnostics
+//! 
+//! Snapshots of a fusion filter's internal decisions (how much it trusted
+//! each sensor on the latest update), published as periodic `filter_diag`
+//! messages for tuning. Which internals exist depends on the filter, so
+//! each filter type has its own variant, tagged with its name.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::complementary::ComplementaryDiagnostics;
+use super::ekf::EkfDiagnostics;
+use crate::models::Vec3;
+
+/// Internals of the fusion filter's latest update
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "filter", rename_all = "snake_case")]
+pub enum FilterDiagnostics {
+    /// Complementary filter (gyro/accel blend)
+    Complementary(ComplementaryDiagnostics),
+
+    /// Extended Kalman filter
+    Ekf(EkfDiagnostics),
+}
+
+impl FilterDiagnostics {
+    /// Note the simulated IMU's true gyro bias, to check the filter's
+    /// estimate against
+    pub fn set_simulated_gyro_bias(&mut self, bias: Vec3) {
+        match self {
+            FilterDiagnostics::Complementary(internals) => internals.simulated_gyro_bias = Some(bias),
+            FilterDiagnostics::Ekf(internals) => internals.simulated_gyro_bias = Some(bias),
+        }
+    }
+}
+
+/// Filter diagnostics message
+/// 
+/// Serializes as a `{"type": "filter_diag", ...}` message.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "type", rename = "filter_diag")]
+pub struct FilterDiagReport {
+    /// Timestamp of the frame the diagnostics were taken with
+    pub timestamp: DateTime<Utc>,
+
+    /// Filter-specific internals
+    pub diagnostics: FilterDiagnostics,
+}
@@ -0,0 +1,799 @@
+//! Extended Kalman Filter for Sensor Fusion
+//!
+//! An error-state extended Kalman filter on orientation and gyro bias,
+//! with the per-axis Kalman position filter the complementary filter can
+//! also use.
+//!
+//! - State: orientation quaternion (body to reference frame) and gyro
+//!   bias; the covariance tracks a three-angle attitude error in the
+//!   reference frame plus the bias error (6×6)
+//! - Predict: integrate the bias-corrected gyro; attitude uncertainty grows
+//!   with gyro noise and with the bias uncertainty
+//! - Correct: accelerometer against gravity, trusted less the further the
+//!   reading's magnitude is from 1 g, and yaw against GPS course when
+//!   moving fast enough
+//! - Position: constant-velocity Kalman filter per local axis, predicted
+//!   from rotated acceleration and corrected by GPS with HDOP-scaled
+//!   variance; dead-reckoned while GPS has no fix
+//!
+//! Unlike the complementary filter there is no fixed gyro/accel split:
+//! each correction is weighted by the covariances, and the gyro bias is
+//! learned as part of the state.
+
+use crate::models::{AltitudeDatum, FusedSensorData, GpsData, ImuData, Quaternion, StatusFlags, Vec3, enu_to_geodetic, finite_or, geodetic_to_enu};
+use serde::Serialize;
+use std::f64::consts::PI;
+
+use super::complementary::{gps_has_fix, DEFAULT_GPS_TIMEOUT_SECS, DEFAULT_GPS_YAW_MIN_SPEED, MAX_ACCEL_BIAS, MAX_GYRO_BIAS};
+use super::diagnostics::FilterDiagnostics;
+use super::filter::{FilterKind, FusionFilter};
+use super::gps_motion::GpsMotion;
+use super::kernels;
+use super::position::AxisKalman;
+
+/// Standard gravity (m/s²)
+const GRAVITY: f64 = 9.81;
+
+/// Gyro noise per reading (rad/s, 1σ)
+const GYRO_NOISE: f64 = 0.01;
+
+/// Gyro bias random walk (rad/s per √s)
+const GYRO_BIAS_WALK: f64 = 1e-3;
+
+/// Accelerometer noise at exactly 1 g (m/s², 1σ): sensor noise plus the
+/// linear acceleration of ordinary motion
+const ACCEL_NOISE: f64 = 0.5;
+
+/// Extra accelerometer noise per m/s² the reading's magnitude is off 1 g
+const ACCEL_DEVIATION_NOISE: f64 = 2.0;
+
+/// GPS course noise at 1 m/s (rad, 1σ); shrinks in proportion to speed
+const COURSE_NOISE_AT_1_MPS: f64 = 0.5;
+
+/// Attitude error (1σ, rad, ~30°) of a fresh filter
+const INITIAL_ATTITUDE_SIGMA: f64 = 0.5;
+
+/// Gyro bias error (1σ, rad/s) of a fresh filter
+const INITIAL_BIAS_SIGMA: f64 = 0.02;
+
+/// Tilt error (1σ, rad, ~2°) below which a reconverging filter has settled
+const SETTLED_TILT_SIGMA: f64 = 0.035;
+
+/// Longest reconvergence flag (updates), should the tilt never settle
+const MAX_RECONVERGE_UPDATES: u32 = 500;
+
+/// Per-unit-HDOP GPS position error (m)
+const GPS_RANGE_ERROR_M: f64 = 2.5;
+
+/// Vertical GPS error relative to horizontal
+const GPS_VERTICAL_ERROR_FACTOR: f64 = 1.5;
+
+/// Position filter process noise: unmodelled acceleration (m/s²)
+const POSITION_ACCEL_NOISE: f64 = 1.0;
+
+/// Tilt error (rad, ~6°) at which orientation confidence halves
+const CONFIDENCE_TILT_SIGMA: f64 = 0.1;
+
+/// Horizontal position error (m) at which position confidence halves
+const CONFIDENCE_POSITION_SIGMA: f64 = 10.0;
+
+/// Dense row-major matrix
+type Mat<const R: usize, const C: usize> = [[f64; C]; R];
+
+/// Error-state size: attitude (3) and gyro bias (3)
+const N: usize = 6;
+
+/// Extended Kalman filter internals on the latest update
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EkfDiagnostics {
+    /// Estimated gyro bias (rad/s)
+    pub gyro_bias: Vec3,
+
+    /// True gyro bias of the simulated IMU (rad/s), to check `gyro_bias`
+    /// against; filled in by the caller, and absent with real sensors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulated_gyro_bias: Option<Vec3>,
+
+    /// Roll/pitch error (1σ, degrees)
+    pub tilt_sigma_deg: f64,
+
+    /// Yaw error (1σ, degrees)
+    pub yaw_sigma_deg: f64,
+
+    /// Accelerometer noise the correction assumed (m/s², 1σ; unset when
+    /// the reading was unusable and no correction ran)
+    pub accel_noise: Option<f64>,
+
+    /// Distance between the measured and predicted gravity (m/s²)
+    pub accel_innovation: f64,
+
+    /// GPS course corrected yaw on the latest update
+    pub yaw_corrected: bool,
+}
+
+/// Extended Kalman filter for IMU and GPS sensor fusion
+pub struct EkfFilter {
+    /// Orientation estimate (body to reference frame)
+    orientation: Quaternion,
+
+    /// Estimated gyro bias (rad/s)
+    gyro_bias: Vec3,
+
+    /// Error covariance: attitude (reference frame, rad²) then bias
+    covariance: Mat<N, N>,
+
+    /// Position filters (east, north, up) around `origin`, started from
+    /// `position` on the next update when unset
+    kalman: Option<[AxisKalman; 3]>,
+
+    /// Position has been set, from a fix or carried over
+    initialized: bool,
+
+    /// Current position estimate (lat, lon, alt)
+    position: (f64, f64, f64),
+
+    /// Local ENU origin (lat, lon, alt), set from the first valid GPS fix
+    origin: (f64, f64, f64),
+
+    /// Horizontal position uncertainty (1σ, m)
+    position_uncertainty: f64,
+
+    /// Position is being propagated because GPS has no fix
+    dead_reckoning: bool,
+
+    /// Per-axis accelerometer bias subtracted from readings (m/s²)
+    accel_bias: Vec3,
+
+    /// Minimum GPS ground speed (m/s) before course-over-ground is trusted for yaw
+    gps_yaw_min_speed: f64,
+
+    /// Age (s) beyond which a GPS fix is stale
+    gps_timeout: f64,
+
+    /// Age (s) of the GPS fix passed to the updates
+    gps_fix_age: f64,
+
+    /// Updates left before a reconverging filter is reported settled anyway
+    reconverge_updates: u32,
+
+    /// Gyro integration steps per update
+    integration_substeps: u32,
+
+    /// Acceleration and course rate derived from recent GPS fixes
+    gps_motion: GpsMotion,
+
+    /// Gyro yaw rate disagrees with the GPS course rate on the latest update
+    consistency_fault: bool,
+
+    /// Zero reference the reported orientation is relative to
+    orientation_reference: Option<Quaternion>,
+
+    /// Time of last wall-clock update
+    last_update: Option<std::time::Instant>,
+
+    /// Accelerometer noise assumed on the latest update
+    accel_noise: Option<f64>,
+
+    /// Accelerometer innovation magnitude on the latest update
+    accel_innovation: f64,
+
+    /// GPS course corrected yaw on the latest update
+    yaw_corrected: bool,
+}
+
+impl EkfFilter {
+    /// Create a filter, level and at rest, with a wide initial covariance
+    pub fn new() -> Self {
+        let mut covariance = [[0.0; N]; N];
+        for (i, row) in covariance.iter_mut().enumerate() {
+            let sigma = if i < 3 { INITIAL_ATTITUDE_SIGMA } else { INITIAL_BIAS_SIGMA };
+            row[i] = sigma * sigma;
+        }
+        Self {
+            orientation: Quaternion::identity(),
+            gyro_bias: Vec3::zero(),
+            covariance,
+            kalman: None,
+            initialized: false,
+            position: (0.0, 0.0, 0.0),
+            origin: (0.0, 0.0, 0.0),
+            position_uncertainty: 0.0,
+            dead_reckoning: false,
+            accel_bias: Vec3::zero(),
+            gps_yaw_min_speed: DEFAULT_GPS_YAW_MIN_SPEED,
+            gps_timeout: DEFAULT_GPS_TIMEOUT_SECS,
+            gps_fix_age: 0.0,
+            reconverge_updates: 0,
+            integration_substeps: 1,
+            gps_motion: GpsMotion::new(),
+            consistency_fault: false,
+            orientation_reference: None,
+            last_update: None,
+            accel_noise: None,
+            accel_innovation: 0.0,
+            yaw_corrected: false,
+        }
+    }
+
+    /// Update filter with new sensor measurements, timed by the wall clock
+    pub fn update(&mut self, imu: ImuData, gps: GpsData) -> FusedSensorData {
+        let now = std::time::Instant::now();
+        let dt = match self.last_update {
+            Some(last) => now.duration_since(last).as_secs_f64(),
+            None => 0.02, // Default 50Hz = 0.02 seconds
+        };
+        self.last_update = Some(now);
+        self.fuse(imu, gps, dt, now)
+    }
+
+    /// Update with an explicit time step instead of the wall clock
+    ///
+    /// The filter's clock advances by `dt` per call, so the same readings
+    /// always give the same estimate however fast they are fed. A
+    /// non-finite or negative `dt` counts as zero.
+    pub fn update_with_dt(&mut self, imu: ImuData, gps: GpsData, dt: f64) -> FusedSensorData {
+        let dt = if dt.is_finite() { dt.max(0.0) } else { 0.0 };
+        let now = match self.last_update {
+            Some(last) => std::time::Duration::try_from_secs_f64(dt)
+                .ok()
+                .and_then(|step| last.checked_add(step))
+                .unwrap_or(last),
+            None => std::time::Instant::now(),
+        };
+        self.last_update = Some(now);
+        self.fuse(imu, gps, dt, now)
+    }
+
+    /// One predict/correct cycle of `dt` seconds ending at `now`
+    fn fuse(&mut self, imu: ImuData, gps: GpsData, dt: f64, now: std::time::Instant) -> FusedSensorData {
+        let raw_acceleration = imu.acceleration;
+        let accel = Vec3::new(
+            raw_acceleration.x - self.accel_bias.x,
+            raw_acceleration.y - self.accel_bias.y,
+            raw_acceleration.z - self.accel_bias.z,
+        );
+        let has_fix = gps_has_fix(&gps);
+        self.gps_motion.observe(&gps, now);
+
+        self.predict(imu.gyroscope, dt);
+        let mut correction = [0.0; N];
+        self.correct_tilt(accel, &mut correction);
+        self.yaw_corrected = has_fix && self.correct_yaw(&gps, &mut correction);
+        self.apply_correction(&correction);
+        self.update_position(accel, &gps, has_fix, dt);
+        self.consistency_fault = has_fix && self.gps_motion.turn_rates_disagree(self.orientation, imu.gyroscope, &gps, self.gps_yaw_min_speed);
+
+        if self.reconverge_updates > 0 {
+            self.reconverge_updates = if self.tilt_sigma() < SETTLED_TILT_SIGMA { 0 } else { self.reconverge_updates - 1 };
+        }
+
+        let orientation = match self.orientation_reference {
+            Some(reference) => reference.inverse() * self.orientation,
+            None => self.orientation,
+        };
+        let (roll, pitch, yaw) = orientation.to_euler();
+        let euler_degrees = (roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees());
+
+        let mut status = StatusFlags::empty();
+        status.set(StatusFlags::DEAD_RECKONING, self.dead_reckoning);
+        status.set(StatusFlags::GPS_FIX_VALID, has_fix);
+        status.set(StatusFlags::RECONVERGING, self.reconverge_updates > 0);
+        status.set(StatusFlags::SENSOR_CONSISTENCY_FAULT, self.consistency_fault);
+        status.set(StatusFlags::STALE_GPS, self.is_gps_stale());
+
+        let mut fused = FusedSensorData {
+            timestamp: chrono::Utc::now(),
+            orientation,
+            euler_degrees,
+            position: self.position,
+            altitude_datum: AltitudeDatum::Msl,
+            local_position: geodetic_to_enu(self.origin, self.position),
+            grid_cell: None,
+            velocity: self.velocity(),
+            raw_acceleration: raw_acceleration.finite_or_zero(),
+            raw_gyroscope: imu.gyroscope.finite_or_zero(),
+            world_angular_velocity: None,
+            estimated_gyro_bias: self.gyro_bias,
+            gps_speed: finite_or(gps.speed, 0.0),
+            gps_heading: finite_or(gps.heading, 0.0),
+            confidence: finite_or(self.confidence(), 0.0),
+            system_health: finite_or((imu.health + gps.health) / 2.0, 0.0),
+            position_uncertainty: finite_or(self.position_uncertainty(), 0.0),
+            // Flag booleans are filled in from `status` below
+            status_flags: StatusFlags::empty(),
+            dead_reckoning: false,
+            sensor_consistency_fault: false,
+            gimbal_lock_warning: false,
+            gps_fix_valid: false,
+            stale_anomaly: false,
+            position_divergence: false,
+            reconverging: false,
+            stale_gps: false,
+            unsettled_world_accel: false,
+            anomaly_score: None, // Set by ML service
+            inputs: None,
+        };
+        fused.set_status_flags(status);
+        fused
+    }
+
+    /// Propagate orientation with the bias-corrected gyro and grow the
+    /// covariance: P = F P Fᵀ + Q
+    fn predict(&mut self, gyro: Vec3, dt: f64) {
+        if !gyro.is_finite() || dt <= 0.0 {
+            return;
+        }
+        let rate = Vec3::new(gyro.x - self.gyro_bias.x, gyro.y - self.gyro_bias.y, gyro.z - self.gyro_bias.z);
+        self.orientation = kernels::integrate_gyro_steps(self.orientation, rate, dt, self.integration_substeps).0;
+
+        // The attitude error (reference frame) picks up the bias error
+        // rotated out of the body frame: dθ/dt = -R δb
+        let r = rotation_matrix(self.orientation);
+        let mut f = identity::<N>();
+        for i in 0..3 {
+            for j in 0..3 {
+                f[i][j + 3] = -r[i][j] * dt;
+            }
+        }
+        let mut covariance = mul(&mul(&f, &self.covariance), &transpose(&f));
+        let attitude_noise = (GYRO_NOISE * dt).powi(2);
+        let bias_noise = GYRO_BIAS_WALK * GYRO_BIAS_WALK * dt;
+        for (i, row) in covariance.iter_mut().enumerate() {
+            row[i] += if i < 3 { attitude_noise } else { bias_noise };
+        }
+        self.covariance = covariance;
+    }
+
+    /// Fold the accelerometer's view of gravity into `correction`
+    ///
+    /// The reading is predicted as gravity rotated into the body frame;
+    /// its noise grows with the distance of its magnitude from 1 g, so
+    /// strong linear acceleration barely moves the estimate.
+    fn correct_tilt(&mut self, accel: Vec3, correction: &mut [f64; N]) {
+        self.accel_noise = None;
+        self.accel_innovation = 0.0;
+        if !accel.is_finite() || accel.magnitude() < f64::EPSILON {
+            return;
+        }
+
+        let gravity = Vec3::new(0.0, 0.0, GRAVITY);
+        let predicted = self.orientation.inverse().rotate(gravity);
+        let innovation = [accel.x - predicted.x, accel.y - predicted.y, accel.z - predicted.z];
+        self.accel_innovation = innovation.iter().map(|v| v * v).sum::<f64>().sqrt();
+        let noise = ACCEL_NOISE + ACCEL_DEVIATION_NOISE * (accel.magnitude() - GRAVITY).abs();
+        self.accel_noise = Some(noise);
+
+        // H = [Rᵀ [g]×, 0]: a small reference-frame rotation δθ moves the
+        // predicted reading by Rᵀ (g × δθ)
+        let r_t = transpose(&rotation_matrix(self.orientation));
+        let g_cross = skew(gravity);
+        let h = mul(&r_t, &g_cross);
+        for (row, value) in h.iter().zip(innovation) {
+            let mut h_row = [0.0; N];
+            h_row[..3].copy_from_slice(row);
+            self.correct_scalar(h_row, value, noise * noise, correction);
+        }
+    }
+
+    /// Fold GPS course-over-ground into the yaw part of `correction`,
+    /// returning whether it was used
+    ///
+    /// Course is only meaningful while moving; its noise shrinks with speed.
+    fn correct_yaw(&mut self, gps: &GpsData, correction: &mut [f64; N]) -> bool {
+        if !gps.speed.is_finite() || gps.speed <= self.gps_yaw_min_speed || !gps.heading.is_finite() {
+            return false;
+        }
+        let (_, _, yaw) = self.orientation.to_euler();
+        // Wrap the error to [-π, π] so we always turn the short way round
+        let innovation = (gps.heading.to_radians() - yaw + PI).rem_euclid(2.0 * PI) - PI;
+        let noise = COURSE_NOISE_AT_1_MPS / gps.speed;
+        self.correct_scalar([0.0, 0.0, 1.0, 0.0, 0.0, 0.0], innovation, noise * noise, correction);
+        true
+    }
+
+    /// One scalar Kalman update of the error state, accumulated into
+    /// `correction` (sequential updates are exact for independent noise)
+    fn correct_scalar(&mut self, h: [f64; N], innovation: f64, variance: f64, correction: &mut [f64; N]) {
+        // Innovation against the corrections already accumulated
+        let innovation = innovation - (0..N).map(|i| h[i] * correction[i]).sum::<f64>();
+        let ph: [f64; N] = std::array::from_fn(|i| (0..N).map(|j| self.covariance[i][j] * h[j]).sum());
+        let s = (0..N).map(|i| h[i] * ph[i]).sum::<f64>() + variance;
+        if !(s.is_finite() && s > 0.0 && innovation.is_finite()) {
+            return;
+        }
+        let gain = ph.map(|p| p / s);
+        for (c, k) in correction.iter_mut().zip(gain) {
+            *c += k * innovation;
+        }
+        // P = P - K (H P), with H P = (P H)ᵀ for symmetric P
+        let covariance = self.covariance;
+        self.covariance = std::array::from_fn(|i| std::array::from_fn(|j| covariance[i][j] - gain[i] * ph[j]));
+    }
+
+    /// Apply the accumulated error-state correction to the nominal state
+    fn apply_correction(&mut self, correction: &[f64; N]) {
+        let rotation = rotation_vector(Vec3::new(correction[0], correction[1], correction[2]));
+        self.orientation = kernels::normalize(rotation * self.orientation);
+        self.gyro_bias = Vec3::new(
+            (self.gyro_bias.x + correction[3]).clamp(-MAX_GYRO_BIAS, MAX_GYRO_BIAS),
+            (self.gyro_bias.y + correction[4]).clamp(-MAX_GYRO_BIAS, MAX_GYRO_BIAS),
+            (self.gyro_bias.z + correction[5]).clamp(-MAX_GYRO_BIAS, MAX_GYRO_BIAS),
+        );
+        // Keep the covariance symmetric against rounding
+        let covariance = self.covariance;
+        self.covariance = std::array::from_fn(|i| std::array::from_fn(|j| (covariance[i][j] + covariance[j][i]) / 2.0));
+    }
+
+    /// Predict position from rotated acceleration, then correct with GPS
+    fn update_position(&mut self, accel: Vec3, gps: &GpsData, has_fix: bool, dt: f64) {
+        if !self.initialized {
+            if !has_fix {
+                return;
+            }
+            self.position = (gps.latitude, gps.longitude, gps.altitude);
+            self.origin = self.position;
+            self.position_uncertainty = gps.hdop * GPS_RANGE_ERROR_M;
+            self.initialized = true;
+        }
+        let origin = self.origin;
+        let freshness = self.gps_freshness();
+        if self.kalman.is_none() {
+            let start = geodetic_to_enu(origin, self.position);
+            let variance = self.position_uncertainty.max(GPS_RANGE_ERROR_M).powi(2);
+            self.kalman = Some([start.x, start.y, start.z].map(|p| AxisKalman::new(p, variance, POSITION_ACCEL_NOISE)));
+        }
+        let Some(kalman) = self.kalman.as_mut() else {
+            return;
+        };
+
+        if accel.is_finite() && dt > 0.0 {
+            // Body acceleration in the (north, east, up) frame, minus gravity
+            let world = self.orientation.rotate(accel);
+            let accel_enu = [world.y, world.x, world.z - GRAVITY];
+            for (axis, a) in kalman.iter_mut().zip(accel_enu) {
+                axis.predict(a, dt);
+            }
+        }
+        if has_fix {
+            let measured = geodetic_to_enu(origin, (gps.latitude, gps.longitude, gps.altitude));
+            let horizontal = (gps.hdop * GPS_RANGE_ERROR_M / freshness).powi(2);
+            let vertical = horizontal * GPS_VERTICAL_ERROR_FACTOR.powi(2);
+            kalman[0].correct(measured.x, horizontal);
+            kalman[1].correct(measured.y, horizontal);
+            kalman[2].correct(measured.z, vertical);
+        }
+
+        let local = Vec3::new(kalman[0].position(), kalman[1].position(), kalman[2].position());
+        if local.is_finite() {
+            self.position = enu_to_geodetic(origin, local);
+            self.position_uncertainty = kalman[0].position_variance().max(kalman[1].position_variance()).sqrt();
+        } else {
+            // Diverged; restart from the last good estimate on the next update
+            self.kalman = None;
+        }
+        self.dead_reckoning = !has_fix;
+    }
+
+    /// Velocity (north, east, up) from the position filter (m/s)
+    fn velocity(&self) -> Vec3 {
+        match &self.kalman {
+            Some(kalman) => Vec3::new(kalman[1].velocity(), kalman[0].velocity(), kalman[2].velocity()).finite_or_zero(),
+            None => Vec3::zero(),
+        }
+    }
+
+    /// Confidence from the covariances: half orientation (tilt error),
+    /// half position (horizontal error, none before the first fix)
+    fn confidence(&self) -> f64 {
+        let tilt = 1.0 / (1.0 + (self.tilt_sigma() / CONFIDENCE_TILT_SIGMA).powi(2));
+        let position = if self.initialized {
+            1.0 / (1.0 + (self.position_uncertainty / CONFIDENCE_POSITION_SIGMA).powi(2))
+        } else {
+            0.0
+        };
+        0.5 * tilt + 0.5 * position
+    }
+
+    /// How far a fix of the current age is trusted: fully up to the
+    /// timeout, then in proportion to timeout / age
+    fn gps_freshness(&self) -> f64 {
+        if self.is_gps_stale() {
+            self.gps_timeout / self.gps_fix_age
+        } else {
+            1.0
+        }
+    }
+
+    /// Roll/pitch error (1σ, rad)
+    fn tilt_sigma(&self) -> f64 {
+        (self.covariance[0][0] + self.covariance[1][1]).max(0.0).sqrt()
+    }
+
+    /// Get the estimated gyro bias (rad/s)
+    pub fn gyro_bias(&self) -> Vec3 {
+        self.gyro_bias
+    }
+
+    /// Get the orientation estimate
+    pub fn orientation(&self) -> Quaternion {
+        self.orientation
+    }
+
+    /// Check whether frames are still flagged as reconverging
+    pub fn is_reconverging(&self) -> bool {
+        self.reconverge_updates > 0
+    }
+
+    /// Set the minimum GPS ground speed (m/s) for course-over-ground yaw
+    /// correction
+    pub fn set_gps_yaw_min_speed(&mut self, speed: f64) {
+        if speed.is_finite() && speed >= 0.0 {
+            self.gps_yaw_min_speed = speed;
+        }
+    }
+
+    /// Set the age (s) beyond which a GPS fix is stale; stale fixes pull
+    /// position less the older they get
+    pub fn set_gps_timeout(&mut self, secs: f64) {
+        if secs.is_finite() && secs > 0.0 {
+            self.gps_timeout = secs;
+        }
+    }
+
+    /// Split each update's gyro integration into `substeps` equal steps
+    /// (1 = a single step over the whole interval; 0 is treated as 1)
+    pub fn set_integration_substeps(&mut self, substeps: u32) {
+        self.integration_substeps = substeps.max(1);
+    }
+
+    /// Check whether the gyro and GPS turn rates disagreed on the latest
+    /// update
+    pub fn has_consistency_fault(&self) -> bool {
+        self.consistency_fault
+    }
+
+    /// Snapshot of the filter's state on the latest update
+    pub fn diagnostics(&self) -> EkfDiagnostics {
+        EkfDiagnostics {
+            gyro_bias: self.gyro_bias,
+            simulated_gyro_bias: None,
+            tilt_sigma_deg: self.tilt_sigma().to_degrees(),
+            yaw_sigma_deg: self.covariance[2][2].max(0.0).sqrt().to_degrees(),
+            accel_noise: self.accel_noise,
+            accel_innovation: self.accel_innovation,
+            yaw_corrected: self.yaw_corrected,
+        }
+    }
+}
+
+impl Default for EkfFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FusionFilter for EkfFilter {
+    fn kind(&self) -> FilterKind {
+        FilterKind::Ekf
+    }
+
+    fn update(&mut self, imu: ImuData, gps: GpsData) -> FusedSensorData {
+        EkfFilter::update(self, imu, gps)
+    }
+
+    fn update_with_dt(&mut self, imu: ImuData, gps: GpsData, dt: f64) -> FusedSensorData {
+        EkfFilter::update_with_dt(self, imu, gps, dt)
+    }
+
+    fn reset_timing(&mut self) {
+        self.last_update = None;
+    }
+
+    fn orientation(&self) -> Quaternion {
+        self.orientation
+    }
+
+    fn orientation_settled(&self) -> bool {
+        self.reconverge_updates == 0 && self.tilt_sigma() < SETTLED_TILT_SIGMA
+    }
+
+    fn position(&self) -> Option<(f64, f64, f64)> {
+        self.initialized.then_some(self.position)
+    }
+
+    fn origin(&self) -> (f64, f64, f64) {
+        self.origin
+    }
+
+    fn position_uncertainty(&self) -> f64 {
+        self.position_uncertainty
+    }
+
+    /// Keeps the local origin, so `local_position` doesn't jump when
+    /// filters are switched at runtime. Ignored unless `position` is finite.
+    fn seed_position(&mut self, position: (f64, f64, f64), origin: (f64, f64, f64), uncertainty: f64) {
+        if !(position.0.is_finite() && position.1.is_finite() && position.2.is_finite()) {
+            return;
+        }
+        self.position = position;
+        self.origin = origin;
+        self.position_uncertainty = finite_or(uncertainty, GPS_RANGE_ERROR_M).max(0.0);
+        self.initialized = true;
+        self.kalman = None;
+    }
+
+    /// Without a usable fix, the next valid fix re-seeds position.
+    fn recenter(&mut self, gps: &GpsData) -> bool {
+        self.kalman = None;
+        if !gps_has_fix(gps) {
+            self.initialized = false;
+            return false;
+        }
+        self.position = (gps.latitude, gps.longitude, gps.altitude);
+        self.origin = self.position;
+        self.initialized = true;
+        self.position_uncertainty = gps.hdop * GPS_RANGE_ERROR_M;
+        self.dead_reckoning = false;
+        true
+    }
+
+    /// Flagged until the tilt error drops below ~2°.
+    fn start_reconverging(&mut self) {
+        self.reconverge_updates = MAX_RECONVERGE_UPDATES;
+    }
+
+    fn zero_orientation(&mut self) {
+        self.orientation_reference = Some(self.orientation);
+    }
+
+    fn clear_orientation_reference(&mut self) {
+        self.orientation_reference = None;
+    }
+
+    fn set_gps_fix_age(&mut self, secs: f64) {
+        if secs.is_finite() && secs >= 0.0 {
+            self.gps_fix_age = secs;
+        }
+    }
+
+    fn is_gps_stale(&self) -> bool {
+        self.gps_fix_age > self.gps_timeout
+    }
+
+    fn accel_bias(&self) -> Vec3 {
+        self.accel_bias
+    }
+
+    /// Ignored unless every axis is finite and within ±`MAX_ACCEL_BIAS`.
+    fn set_accel_bias(&mut self, bias: Vec3) {
+        let axes = [bias.x, bias.y, bias.z];
+        if axes.iter().all(|b| b.is_finite() && b.abs() <= MAX_ACCEL_BIAS) {
+            self.accel_bias = bias;
+        }
+    }
+
+    fn diagnostics(&self) -> FilterDiagnostics {
+        FilterDiagnostics::Ekf(EkfFilter::diagnostics(self))
+    }
+}
+
+/// Rotation matrix of a unit quaternion (body to reference frame)
+fn rotation_matrix(q: Quaternion) -> Mat<3, 3> {
+    let columns = [Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0)].map(|axis| q.rotate(axis));
+    std::array::from_fn(|i| std::array::from_fn(|j| columns[j].to_array()[i]))
+}
+
+/// Cross-product matrix: `skew(a) · b = a × b`
+fn skew(v: Vec3) -> Mat<3, 3> {
+    [[0.0, -v.z, v.y], [v.z, 0.0, -v.x], [-v.y, v.x, 0.0]]
+}
+
+/// Quaternion rotating by `v` radians about `v`'s direction
+fn rotation_vector(v: Vec3) -> Quaternion {
+    let angle = v.magnitude();
+    if !angle.is_finite() || angle < f64::EPSILON {
+        return Quaternion::identity();
+    }
+    let (s, c) = (angle / 2.0).sin_cos();
+    Quaternion::new(c, s * v.x / angle, s * v.y / angle, s * v.z / angle)
+}
+
+fn identity<const S: usize>() -> Mat<S, S> {
+    std::array::from_fn(|i| std::array::from_fn(|j| if i == j { 1.0 } else { 0.0 }))
+}
+
+fn transpose<const R: usize, const C: usize>(a: &Mat<R, C>) -> Mat<C, R> {
+    std::array::from_fn(|i| std::array::from_fn(|j| a[j][i]))
+}
+
+fn mul<const R: usize, const K: usize, const C: usize>(a: &Mat<R, K>, b: &Mat<K, C>) -> Mat<R, C> {
+    std::array::from_fn(|i| std::array::from_fn(|j| (0..K).map(|k| a[i][k] * b[k][j]).sum()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nominal time step (s)
+    const DT: f64 = 0.02;
+
+    fn gps_fix() -> GpsData {
+        GpsData::new(37.7749, -122.4194, 10.0)
+    }
+
+    /// Accelerometer reading of a sensor at rest at `roll` (rad)
+    fn resting_at_roll(roll: f64) -> Vec3 {
+        Quaternion::from_euler(roll, 0.0, 0.0).inverse().rotate(Vec3::new(0.0, 0.0, GRAVITY))
+    }
+
+    #[test]
+    fn test_tilt_converges_to_gravity() {
+        let mut filter = EkfFilter::new();
+        let roll = 30f64.to_radians();
+        let imu = ImuData::new(resting_at_roll(roll), Vec3::zero());
+        let mut frame = filter.update_with_dt(imu.clone(), gps_fix(), DT);
+        for _ in 0..200 {
+            frame = filter.update_with_dt(imu.clone(), gps_fix(), DT);
+        }
+        assert!((frame.euler_degrees.0 - 30.0).abs() < 0.5, "roll {:.2}°", frame.euler_degrees.0);
+        assert!(frame.euler_degrees.1.abs() < 0.5, "pitch {:.2}°", frame.euler_degrees.1);
+        assert!(filter.diagnostics().tilt_sigma_deg < 1.0);
+    }
+
+    #[test]
+    fn test_learns_a_constant_gyro_bias() {
+        // Level and still, with the gyro reading a constant offset on x
+        let mut filter = EkfFilter::new();
+        let bias = Vec3::new(0.02, -0.01, 0.0);
+        let imu = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), bias);
+        for _ in 0..3000 {
+            filter.update_with_dt(imu.clone(), gps_fix(), DT);
+        }
+        let estimate = filter.gyro_bias();
+        assert!((estimate.x - bias.x).abs() < 2e-3, "bias estimate {estimate:?}");
+        assert!((estimate.y - bias.y).abs() < 2e-3, "bias estimate {estimate:?}");
+        let (roll, pitch, _) = filter.orientation().to_euler();
+        assert!(roll.abs() < 0.01 && pitch.abs() < 0.01, "tilt drifted to ({roll:.3}, {pitch:.3})");
+    }
+
+    #[test]
+    fn test_reconverging_clears_once_tilt_settles() {
+        let mut filter = EkfFilter::new();
+        filter.seed_position((37.7749, -122.4194, 10.0), (37.7748, -122.4194, 10.0), 3.0);
+        filter.start_reconverging();
+        let imu = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), Vec3::zero());
+
+        let first = filter.update_with_dt(imu.clone(), gps_fix(), DT);
+        assert!(first.reconverging && first.status_flags.contains(StatusFlags::RECONVERGING));
+        // Seeded position and origin carried over: the fix sits ~11 m
+        // north of the origin
+        assert!((first.local_position.y - 0.0001 * crate::models::METERS_PER_DEGREE).abs() < 5.0);
+
+        let settled = (0..50).map(|_| filter.update_with_dt(imu.clone(), gps_fix(), DT)).position(|frame| !frame.reconverging);
+        assert!(settled.is_some(), "still reconverging after 50 updates");
+    }
+
+    #[test]
+    fn test_gyro_spike_during_steady_motion_flags_inconsistency() {
+        // The GPS course rate is timed on the filter's clock, which
+        // `update_with_dt` advances by `DT`
+        let mut filter = EkfFilter::new();
+        let level = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), Vec3::zero());
+        let mut gps = gps_fix();
+        gps.speed = 5.0;
+        gps.heading = 90.0;
+        for _ in 0..10 {
+            filter.update_with_dt(level.clone(), gps.clone(), DT);
+        }
+
+        // A new fix on the same course: the GPS turn rate is zero
+        gps.speed = 5.5;
+        assert!(!filter.update_with_dt(level, gps.clone(), DT).sensor_consistency_fault);
+
+        // The gyro suddenly reports a ~115°/s yaw rate
+        let spike = ImuData::new(Vec3::new(0.0, 0.0, GRAVITY), Vec3::new(0.0, 0.0, 2.0));
+        let frame = filter.update_with_dt(spike, gps, DT);
+        assert!(frame.sensor_consistency_fault && frame.status_flags.contains(StatusFlags::SENSOR_CONSISTENCY_FAULT));
+        assert!(filter.has_consistency_fault());
+    }
+}
@@ -0,0 +1,164 @@
+//! Fusion Filter Selection
+//!
+//! The fusion filters the sensor loop can run, behind the `FusionFilter`
+//! trait, so clients can switch between them at runtime (the `set_filter`
+//! command) and compare them on the same data. `FilterKind` names each
+//! implementation:
+//! - `complementary`: gyro/accel blend with GPS position (`ComplementaryFilter`)
+//! - `ekf`: error-state extended Kalman filter on orientation and gyro
+//!   bias, with a Kalman position filter (`EkfFilter`)
+
+use serde::Serialize;
+
+use super::complementary::ComplementaryFilter;
+use super::diagnostics::FilterDiagnostics;
+use super::ekf::EkfFilter;
+use crate::models::{FusedSensorData, GpsData, ImuData, Quaternion, Vec3};
+
+/// A fusion filter the sensor loop can run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterKind {
+    /// Complementary filter (gyro/accel blend)
+    #[default]
+    Complementary,
+
+    /// Extended Kalman filter (orientation and gyro bias)
+    Ekf,
+}
+
+impl FilterKind {
+    /// Every selectable filter
+    pub const ALL: [FilterKind; 2] = [FilterKind::Complementary, FilterKind::Ekf];
+
+    /// Name used in commands and diagnostics
+    pub fn name(self) -> &'static str {
+        match self {
+            FilterKind::Complementary => "complementary",
+            FilterKind::Ekf => "ekf",
+        }
+    }
+
+    /// Look a filter up by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Comma-separated names of every selectable filter
+    pub fn available() -> String {
+        Self::ALL.map(FilterKind::name).join(", ")
+    }
+
+    /// Create this filter with default settings
+    ///
+    /// `alpha` tunes the complementary filter; the EKF has no equivalent
+    /// and ignores it.
+    pub fn build(self, alpha: f64) -> Box<dyn FusionFilter> {
+        match self {
+            FilterKind::Complementary => Box::new(ComplementaryFilter::new(alpha)),
+            FilterKind::Ekf => Box::new(EkfFilter::new()),
+        }
+    }
+}
+
+/// A fusion filter: turns IMU and GPS readings into fused frames
+///
+/// Covers what the sensor loop does with a running filter, so filters can
+/// be swapped behind a `Box<dyn FusionFilter>`. Tuning is filter-specific
+/// and happens on the concrete type before boxing. Features a filter
+/// lacks have defaults that report them as unsupported.
+pub trait FusionFilter: Send {
+    /// Which filter this is
+    fn kind(&self) -> FilterKind;
+
+    /// Fuse one pair of readings, timed by the wall clock
+    fn update(&mut self, imu: ImuData, gps: GpsData) -> FusedSensorData;
+
+    /// Fuse one pair of readings with an explicit time step (s)
+    fn update_with_dt(&mut self, imu: ImuData, gps: GpsData, dt: f64) -> FusedSensorData;
+
+    /// Forget the time of the last update, so the next one uses the
+    /// nominal time step
+    fn reset_timing(&mut self);
+
+    /// Orientation estimate (body to world), before any display smoothing
+    /// or zeroing of the reported orientation
+    fn orientation(&self) -> Quaternion;
+
+    /// Whether the orientation estimate has settled since start-up (or
+    /// since the filter was switched in)
+    fn orientation_settled(&self) -> bool;
+
+    /// Position estimate (`None` before the first fix)
+    fn position(&self) -> Option<(f64, f64, f64)>;
+
+    /// Local origin that `local_position` is relative to
+    fn origin(&self) -> (f64, f64, f64);
+
+    /// Horizontal position uncertainty (1σ, meters)
+    fn position_uncertainty(&self) -> f64;
+
+    /// Start from a position estimate carried over from another filter
+    fn seed_position(&mut self, position: (f64, f64, f64), origin: (f64, f64, f64), uncertainty: f64);
+
+    /// Snap position to a GPS fix and make it the new local origin,
+    /// returning whether that happened now (`false` without a fix)
+    fn recenter(&mut self, gps: &GpsData) -> bool;
+
+    /// Flag frames as reconverging until the estimate has settled
+    fn start_reconverging(&mut self);
+
+    /// Make the current orientation the zero reference
+    fn zero_orientation(&mut self);
+
+    /// Report absolute orientation again
+    fn clear_orientation_reference(&mut self);
+
+    /// Note how long ago (s) the GPS fix passed to the next updates arrived
+    fn set_gps_fix_age(&mut self, secs: f64);
+
+    /// Check whether the GPS fix is older than the timeout
+    fn is_gps_stale(&self) -> bool;
+
+    /// Accelerometer bias subtracted from readings (m/s²)
+    fn accel_bias(&self) -> Vec3;
+
+    /// Subtract `bias` (m/s², per axis) from accelerometer readings
+    fn set_accel_bias(&mut self, bias: Vec3);
+
+    /// Internals of the latest update
+    fn diagnostics(&self) -> FilterDiagnostics;
+
+    /// Estimate the accelerometer bias from the next `samples` readings,
+    /// returning whether the filter supports it (default: unsupported)
+    fn start_accel_calibration(&mut self, _samples: u32) -> bool {
+        false
+    }
+
+    /// Whether an accelerometer calibration is in progress (default: never)
+    fn accel_calibrating(&self) -> bool {
+        false
+    }
+
+    /// Whether GPS positions diverge from IMU dead reckoning (default:
+    /// not checked)
+    fn position_divergence(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_round_trip_and_unknown_filters_are_rejected() {
+        for kind in FilterKind::ALL {
+            assert_eq!(FilterKind::from_name(kind.name()), Some(kind));
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.name());
+            assert_eq!(kind.build(0.98).kind(), kind);
+        }
+        assert_eq!(FilterKind::from_name("ukf"), None);
+        assert_eq!(FilterKind::available(), "complementary, ekf");
+    }
+}
@@ -0,0 +1,98 @@
+//! GPS Motion Cues
+//!
+//! Horizontal acceleration and course rate derived from successive GPS
+//! speed and course readings. They are independent of the IMU, so the
+//! filters use them to gate the accelerometer during hard manoeuvres and to
+//! cross-check the gyro yaw rate.
+
+use crate::models::{GpsData, Quaternion, Vec3};
+
+/// How long (s) a GPS-derived acceleration is held without a new fix
+pub const GPS_ACCEL_HOLD_SECS: f64 = 2.0;
+
+/// Gyro yaw rate and GPS course rate differing by more than this (deg/s)
+/// flags a sensor consistency fault
+pub const TURN_RATE_FAULT_DEG_S: f64 = 30.0;
+
+/// Acceleration and course rate from the latest distinct GPS readings
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpsMotion {
+    /// Last distinct GPS speed/heading and when it was seen
+    last: Option<(f64, f64, std::time::Instant)>,
+
+    /// Horizontal acceleration derived from speed and course changes (m/s²)
+    acceleration: f64,
+
+    /// Course change rate (deg/s)
+    turn_rate: Option<f64>,
+}
+
+impl GpsMotion {
+    /// Create a tracker that has seen no GPS reading yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold in the GPS reading current at `now`
+    ///
+    /// GPS fixes arrive far slower than IMU updates, so the derivatives are
+    /// only recomputed when speed or heading actually changes, and are
+    /// dropped if no new fix arrives for a while.
+    pub fn observe(&mut self, gps: &GpsData, now: std::time::Instant) {
+        if !(gps.speed.is_finite() && gps.heading.is_finite()) {
+            return;
+        }
+
+        match self.last {
+            Some((speed, heading, seen)) if speed != gps.speed || heading != gps.heading => {
+                let dt = now.duration_since(seen).as_secs_f64();
+                if dt > 1e-3 {
+                    // Along-track from speed change, cross-track from course change
+                    let along = (gps.speed - speed) / dt;
+                    let turn = (gps.heading - heading + 180.0).rem_euclid(360.0) - 180.0;
+                    let cross = gps.speed * turn.to_radians() / dt;
+                    self.acceleration = along.hypot(cross);
+                    self.turn_rate = Some(turn / dt);
+                    self.last = Some((gps.speed, gps.heading, now));
+                }
+            }
+            Some((_, _, seen)) => {
+                if now.duration_since(seen).as_secs_f64() > GPS_ACCEL_HOLD_SECS {
+                    self.acceleration = 0.0;
+                    self.turn_rate = None;
+                }
+            }
+            None => {
+                self.last = Some((gps.speed, gps.heading, now));
+            }
+        }
+    }
+
+    /// Horizontal acceleration seen by GPS (m/s², 0.0 when unknown)
+    pub fn acceleration(&self) -> f64 {
+        self.acceleration
+    }
+
+    /// Course change rate seen by GPS (deg/s), if recent
+    pub fn turn_rate(&self) -> Option<f64> {
+        self.turn_rate
+    }
+
+    /// Coordinated-turn check: the gyro yaw rate, rotated into the world
+    /// frame by `orientation`, should track how fast the GPS course changes
+    ///
+    /// Skipped at or below `min_speed` (course is noise) and when no
+    /// recent course change is known.
+    pub fn turn_rates_disagree(&self, orientation: Quaternion, gyro: Vec3, gps: &GpsData, min_speed: f64) -> bool {
+        let Some(gps_rate) = self.turn_rate else {
+            return false;
+        };
+        if !gps.speed.is_finite() || gps.speed <= min_speed || !gyro.is_finite() {
+            return false;
+        }
+
+        // Yaw rate about the vertical axis of the reference frame
+        let gyro_rate = orientation.rotate(gyro).z.to_degrees();
+        (gyro_rate - gps_rate).abs() > TURN_RATE_FAULT_DEG_S
+    }
+}
@@ -0,0 +1,114 @@
+//! Local Grid Mapping
+//!
+//! Maps the local east/north position onto square grid cells for
+//! map-style dashboards and spatial indexing:
+//! - Cell `(0, 0)` has its south-west corner at the fusion origin
+//! - Indices grow east (`x`) and north (`y`) and go negative beyond it
+//! - Optional hysteresis keeps the current cell until the position is
+//!   clearly past its edge, so noise near a boundary doesn't flicker
+
+use crate::models::{GridCell, Vec3};
+
+/// Maps local positions to grid cells, with boundary hysteresis
+#[derive(Debug, Clone)]
+pub struct GridMapper {
+    /// Cell edge length in meters
+    cell_size: f64,
+
+    /// Distance past a cell edge (m) before the cell changes
+    hysteresis: f64,
+
+    /// Cell reported for the previous position
+    current: Option<GridCell>,
+}
+
+impl GridMapper {
+    /// Create a mapper with `cell_size` meter cells
+    ///
+    /// `hysteresis` is clamped to at most half a cell. Non-finite or
+    /// non-positive sizes fall back to 1 m.
+    pub fn new(cell_size: f64, hysteresis: f64) -> Self {
+        let cell_size = if cell_size.is_finite() && cell_size > 0.0 { cell_size } else { 1.0 };
+        let hysteresis = if hysteresis.is_finite() { hysteresis.clamp(0.0, cell_size / 2.0) } else { 0.0 };
+        Self {
+            cell_size,
+            hysteresis,
+            current: None,
+        }
+    }
+
+    /// Cell edge length in meters
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    /// Distance past a cell edge before the cell changes, in meters
+    pub fn hysteresis(&self) -> f64 {
+        self.hysteresis
+    }
+
+    /// Cell containing `local` (east/north of the fusion origin)
+    ///
+    /// Each axis keeps its previous index while the position stays within
+    /// `hysteresis` of that cell; otherwise the containing cell is taken.
+    pub fn map(&mut self, local: Vec3) -> GridCell {
+        let cell = GridCell {
+            x: self.axis(local.x, self.current.map(|c| c.x)),
+            y: self.axis(local.y, self.current.map(|c| c.y)),
+        };
+        self.current = Some(cell);
+        cell
+    }
+
+    /// Forget the previous cell (e.g. after the origin moved)
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+
+    /// Index along one axis, sticking to `previous` within the hysteresis band
+    fn axis(&self, meters: f64, previous: Option<i64>) -> i64 {
+        if let Some(index) = previous {
+            let low = index as f64 * self.cell_size - self.hysteresis;
+            let high = (index + 1) as f64 * self.cell_size + self.hysteresis;
+            if (low..high).contains(&meters) {
+                return index;
+            }
+        }
+        // Saturates far outside the i64 range; local positions never get there
+        (meters / self.cell_size).floor() as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Cells reported while walking east along `xs`, 1 m north of the origin
+    fn walk_east(mapper: &mut GridMapper, xs: &[f64]) -> Vec<GridCell> {
+        xs.iter().map(|&x| mapper.map(Vec3::new(x, 1.0, 0.0))).collect()
+    }
+
+    #[test]
+    fn test_crossing_a_boundary_changes_cell() {
+        let mut mapper = GridMapper::new(10.0, 0.0);
+        let cells = walk_east(&mut mapper, &[-0.5, 4.0, 9.9, 10.1, 25.0]);
+        let xs: Vec<i64> = cells.iter().map(|c| c.x).collect();
+        assert_eq!(xs, vec![-1, 0, 0, 1, 2]);
+        assert!(cells.iter().all(|c| c.y == 0));
+    }
+
+    #[test]
+    fn test_hysteresis_holds_cell_through_boundary_noise() {
+        // Jitter of ±0.3 m around the x = 10 m edge, then a clear crossing
+        let path = [9.5, 10.2, 9.8, 10.3, 9.7, 10.25, 11.0, 10.3, 9.9];
+
+        let mut plain = GridMapper::new(10.0, 0.0);
+        let flicker: Vec<i64> = walk_east(&mut plain, &path).iter().map(|c| c.x).collect();
+        assert_eq!(flicker, vec![0, 1, 0, 1, 0, 1, 1, 1, 0]);
+
+        let mut sticky = GridMapper::new(10.0, 0.5);
+        let held: Vec<i64> = walk_east(&mut sticky, &path).iter().map(|c| c.x).collect();
+        assert_eq!(held, vec![0, 0, 0, 0, 0, 0, 1, 1, 1]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Health Event Log
+//!
+//! Records when sensor and system health degrade or recover, so dashboard
+//! anomalies can be correlated with sensor events after the fact:
+//! - Per-source hysteresis (degrade below one threshold, recover above a
+//!   higher one) so values hovering near a threshold don't flap
+//! - Bounded in-memory log shared between the fusion loop and the server
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Where a health reading comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthSource {
+    /// IMU sensor health
+    Imu,
+
+    /// GPS receiver health
+    Gps,
+
+    /// Overall fused system health
+    System,
+}
+
+/// Direction of a health transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthTransition {
+    /// Health fell below the degraded threshold
+    Degraded,
+
+    /// Health rose back above the recovered threshold
+    Recovered,
+}
+
+/// A recorded health transition
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthEvent {
+    /// When the transition was observed
+    pub timestamp: DateTime<Utc>,
+
+    /// Which health reading crossed the threshold
+    pub source: HealthSource,
+
+    /// Whether health degraded or recovered
+    pub transition: HealthTransition,
+
+    /// Health value that triggered the transition (0.0 - 1.0)
+    pub health: f64,
+}
+
+/// Hysteresis thresholds for health transitions
+#[derive(Debug, Clone, Copy)]
+pub struct HealthThresholds {
+    /// Health below this marks a source degraded
+    pub degraded_below: f64,
+
+    /// Health above this marks a degraded source recovered
+    pub recovered_above: f64,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            degraded_below: 0.5,
+            recovered_above: 0.8,
+        }
+    }
+}
+
+/// Tracks IMU, GPS, and system health and emits transitions
+pub struct HealthMonitor {
+    /// Hysteresis thresholds
+    thresholds: HealthThresholds,
+
+    /// Degraded state per source (IMU, GPS, system)
+    degraded: [bool; 3],
+}
+
+impl HealthMonitor {
+    /// Create a monitor; every source starts out healthy
+    pub fn new(thresholds: HealthThresholds) -> Self {
+        Self {
+            thresholds,
+            degraded: [false; 3],
+        }
+    }
+
+    /// Feed the latest health readings and collect any transitions
+    pub fn observe(&mut self, imu: f64, gps: f64, system: f64) -> Vec<HealthEvent> {
+        let now = Utc::now();
+        let readings = [
+            (HealthSource::Imu, imu),
+            (HealthSource::Gps, gps),
+            (HealthSource::System, system),
+        ];
+
+        let mut events = Vec::new();
+        for ((source, health), degraded) in readings.into_iter().zip(self.degraded.iter_mut()) {
+            if !health.is_finite() {
+                continue;
+            }
+
+            let transition = if !*degraded && health < self.thresholds.degraded_below {
+                HealthTransition::Degraded
+            } else if *degraded && health > self.thresholds.recovered_above {
+                HealthTransition::Recovered
+            } else {
+                continue;
+            };
+
+            *degraded = transition == HealthTransition::Degraded;
+            events.push(HealthEvent {
+                timestamp: now,
+                source,
+                transition,
+                health,
+            });
+        }
+        events
+    }
+}
+
+impl Default for HealthMonitor {
+    fn default() -> Self {
+        Self::new(HealthThresholds::default())
+    }
+}
+
+/// Bounded log of recent health events, oldest first
+pub struct HealthLog {
+    /// Recorded events
+    events: Mutex<VecDeque<HealthEvent>>,
+
+    /// Maximum number of events kept
+    capacity: usize,
+}
+
+impl HealthLog {
+    /// Create a log that keeps up to `capacity` events
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Append an event, evicting the oldest when full
+    pub fn push(&self, event: HealthEvent) {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    /// Copy of all recorded events, oldest first
+    pub fn events(&self) -> Vec<HealthEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
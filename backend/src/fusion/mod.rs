@@ -3,7 +3,28 @@
 //! Implements various sensor fusion techniques to combine noisy sensor
 //! measurements into accurate state estimates.
 
+pub mod anomaly;
 pub mod complementary;
+pub mod diagnostics;
+pub mod ekf;
+pub mod filter;
+pub mod gps_motion;
+pub mod grid;
+pub mod health;
+pub mod kernels;
+pub mod output;
+pub mod position;
+pub mod watchdog;
 
 // Re-export commonly used types
-pub use complementary::ComplementaryFilter;
\ No newline at end of file
+pub use anomaly::{AnomalyDetector, DetectorSet, ThresholdDetector, ZScoreDetector};
+pub use complementary::{ComplementaryDiagnostics, ComplementaryFilter, ConfidenceWeights, PositionWeights};
+pub use diagnostics::{FilterDiagReport, FilterDiagnostics};
+pub use ekf::{EkfDiagnostics, EkfFilter};
+pub use filter::{FilterKind, FusionFilter};
+pub use gps_motion::GpsMotion;
+pub use grid::GridMapper;
+pub use output::{AccelFrame, ConfidenceBounds, FrameOutput};
+pub use position::PositionStrategy;
+pub use health::{HealthLog, HealthMonitor};
+pub use watchdog::{DriftWatchdog, DriftWatchdogConfig};
\ No newline at end of file
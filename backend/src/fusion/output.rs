@@ -0,0 +1,258 @@
+//! Fused Frame Output Stages
+//!
+//! Adjustments to a fused frame that don't depend on how the estimate was
+//! made, so the sensor loop applies them to whichever filter is running:
+//! - Velocity capped at the platform's physical top speed
+//! - Confidence kept within configured bounds
+//! - Gimbal lock warning within a configurable margin of ±90° pitch
+//! - Optional world-frame angular velocity (debugging)
+//! - Acceleration optionally rotated into the world frame, flagged while
+//!   the orientation it was rotated by is still settling
+
+use crate::models::{FusedSensorData, StatusFlags};
+use serde::Serialize;
+
+use super::filter::FusionFilter;
+
+/// Default margin from ±90° pitch within which Euler angles are flagged (deg)
+pub const DEFAULT_GIMBAL_LOCK_MARGIN_DEG: f64 = 2.0;
+
+/// Range the reported `confidence` is clamped to
+/// 
+/// Applied last, after every other adjustment, so that consumers never see
+/// a certainty (or total doubt) no real sensor setup justifies.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ConfidenceBounds {
+    /// Lowest confidence reported
+    pub floor: f64,
+
+    /// Highest confidence reported
+    pub ceiling: f64,
+}
+
+impl Default for ConfidenceBounds {
+    fn default() -> Self {
+        Self {
+            floor: 0.0,
+            ceiling: 1.0,
+        }
+    }
+}
+
+impl ConfidenceBounds {
+    /// Check that 0 <= floor <= ceiling <= 1
+    pub fn is_valid(&self) -> bool {
+        (0.0..=1.0).contains(&self.ceiling) && (0.0..=self.ceiling).contains(&self.floor)
+    }
+
+    /// Clamp a confidence into the bounds
+    pub fn clamp(&self, confidence: f64) -> f64 {
+        confidence.clamp(self.floor, self.ceiling)
+    }
+}
+
+/// Frame `raw_acceleration` is reported in
+///
+/// Gravity stays in the reading either way; only its axes change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccelFrame {
+    /// Sensor axes, as measured
+    #[default]
+    Body,
+
+    /// Rotated into the world (navigation) frame by the orientation
+    /// estimate, so gravity lies on the vertical axis however the device
+    /// is tilted
+    World,
+}
+
+/// Filter-independent output settings
+#[derive(Debug, Clone, Copy)]
+pub struct FrameOutput {
+    /// Physical top speed (m/s) the reported velocity is clamped to
+    pub max_speed: Option<f64>,
+
+    /// Range the reported confidence is clamped to
+    pub confidence_bounds: ConfidenceBounds,
+
+    /// Pitch margin from ±90° (degrees) within which Euler angles are
+    /// flagged unreliable
+    pub gimbal_lock_margin_deg: f64,
+
+    /// Add the gyro reading rotated into the world frame
+    pub world_angular_velocity: bool,
+
+    /// Frame `raw_acceleration` is reported in
+    pub accel_frame: AccelFrame,
+}
+
+impl Default for FrameOutput {
+    fn default() -> Self {
+        Self {
+            max_speed: None,
+            confidence_bounds: ConfidenceBounds::default(),
+            gimbal_lock_margin_deg: DEFAULT_GIMBAL_LOCK_MARGIN_DEG,
+            world_angular_velocity: false,
+            accel_frame: AccelFrame::default(),
+        }
+    }
+}
+
+impl FrameOutput {
+    /// Apply the output stages to a frame `filter` just produced
+    ///
+    /// Rotations use the filter's own orientation estimate, before any
+    /// display smoothing or zeroing of the reported one.
+    pub fn apply(&self, frame: &mut FusedSensorData, filter: &dyn FusionFilter) {
+        // Never report faster than the platform can physically move
+        if let Some(max) = self.max_speed {
+            frame.velocity = frame.velocity.clamp_magnitude(max);
+        }
+        frame.confidence = self.confidence_bounds.clamp(frame.confidence);
+        frame.world_angular_velocity = self.world_angular_velocity.then(|| filter.orientation().rotate(frame.raw_gyroscope));
+        let world_accel = self.accel_frame == AccelFrame::World;
+        if world_accel {
+            frame.raw_acceleration = filter.orientation().rotate(frame.raw_acceleration);
+        }
+        frame.set_status_flag(StatusFlags::UNSETTLED_WORLD_ACCEL, world_accel && !filter.orientation_settled());
+        frame.set_status_flag(
+            StatusFlags::GIMBAL_LOCK_WARNING,
+            90.0 - frame.euler_degrees.1.abs() <= self.gimbal_lock_margin_deg,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fusion::{ComplementaryFilter, FilterKind};
+    use crate::models::{GpsData, ImuData, Quaternion, Vec3};
+
+    const DT: f64 = 0.02;
+
+    fn gps_fix() -> GpsData {
+        GpsData::new(37.7749, -122.4194, 10.0)
+    }
+
+    #[test]
+    fn test_level_yaw_rotation_stays_on_world_z() {
+        let output = FrameOutput { world_angular_velocity: true, ..FrameOutput::default() };
+        let turning = ImuData::new(Vec3::new(0.0, 0.0, 9.81), Vec3::new(0.0, 0.0, 0.5));
+        for kind in FilterKind::ALL {
+            let mut filter = kind.build(0.98);
+            let mut frame = filter.update_with_dt(turning.clone(), gps_fix(), DT);
+            for _ in 0..25 {
+                frame = filter.update_with_dt(turning.clone(), gps_fix(), DT);
+            }
+            output.apply(&mut frame, filter.as_ref());
+
+            // Half a second in, the heading has moved but the axis hasn't
+            assert!(frame.euler_degrees.2.abs() > 10.0, "{}: yaw {:.1}°", kind.name(), frame.euler_degrees.2);
+            let world = frame.world_angular_velocity.expect("world angular velocity enabled");
+            assert!(world.x.abs() < 1e-3 && world.y.abs() < 1e-3, "{}: {world:?} off the world z axis", kind.name());
+            assert!((world.z - 0.5).abs() < 1e-3, "{}: {world:?}", kind.name());
+
+            FrameOutput::default().apply(&mut frame, filter.as_ref());
+            assert!(frame.world_angular_velocity.is_none());
+        }
+    }
+
+    #[test]
+    fn test_velocity_is_capped_for_every_filter() {
+        // Heading north at 30 m/s against a 20 m/s cap
+        let output = FrameOutput { max_speed: Some(20.0), ..FrameOutput::default() };
+        let level = ImuData::new(Vec3::new(0.0, 0.0, 9.81), Vec3::zero());
+        for kind in FilterKind::ALL {
+            let mut filter = kind.build(0.98);
+            let mut gps = gps_fix();
+            gps.speed = 30.0;
+            let mut frame = filter.update_with_dt(level.clone(), gps.clone(), DT);
+            for _ in 0..200 {
+                gps.latitude += 30.0 * DT / crate::models::METERS_PER_DEGREE;
+                frame = filter.update_with_dt(level.clone(), gps.clone(), DT);
+            }
+            let uncapped = frame.velocity;
+            assert!(uncapped.magnitude() > 25.0, "{}: {uncapped:?}", kind.name());
+            output.apply(&mut frame, filter.as_ref());
+            assert!((frame.velocity.magnitude() - 20.0).abs() < 1e-9, "{}: {:?}", kind.name(), frame.velocity);
+            assert!((frame.velocity.x / frame.velocity.magnitude() - uncapped.x / uncapped.magnitude()).abs() < 1e-9, "{}: direction changed", kind.name());
+        }
+    }
+
+    #[test]
+    fn test_confidence_is_clamped_for_every_filter() {
+        let level = ImuData::new(Vec3::new(0.0, 0.0, 9.81), Vec3::zero());
+        for kind in FilterKind::ALL {
+            let mut filter = kind.build(0.98);
+            let mut frame = filter.update_with_dt(level.clone(), gps_fix(), DT);
+            for _ in 0..100 {
+                frame = filter.update_with_dt(level.clone(), gps_fix(), DT);
+            }
+            let raw = frame.confidence;
+            assert!(raw > 0.3 && raw < 0.99, "{}: confidence {raw}", kind.name());
+
+            // Cut to the ceiling, raised to the floor
+            let mut capped = frame.clone();
+            FrameOutput { confidence_bounds: ConfidenceBounds { floor: 0.1, ceiling: 0.3 }, ..FrameOutput::default() }.apply(&mut capped, filter.as_ref());
+            assert_eq!(capped.confidence, 0.3, "{}", kind.name());
+            let mut floored = frame.clone();
+            FrameOutput { confidence_bounds: ConfidenceBounds { floor: 0.99, ceiling: 1.0 }, ..FrameOutput::default() }.apply(&mut floored, filter.as_ref());
+            assert_eq!(floored.confidence, 0.99, "{}", kind.name());
+
+            FrameOutput::default().apply(&mut frame, filter.as_ref());
+            assert_eq!(frame.confidence, raw, "{}", kind.name());
+        }
+    }
+
+    #[test]
+    fn test_world_frame_acceleration_puts_gravity_on_the_vertical() {
+        // Gravity as seen rolled 30° and pitched -20°
+        let attitude = Quaternion::from_euler(30f64.to_radians(), (-20f64).to_radians(), 0.0);
+        let tilted = ImuData::new(attitude.inverse().rotate(Vec3::new(0.0, 0.0, 9.81)), Vec3::zero());
+        let world = FrameOutput { accel_frame: AccelFrame::World, ..FrameOutput::default() };
+        for kind in FilterKind::ALL {
+            // Body frame: the reading as measured, gravity spread over the axes
+            let mut filter = kind.build(0.98);
+            let mut body = filter.update_with_dt(tilted.clone(), gps_fix(), DT);
+            FrameOutput::default().apply(&mut body, filter.as_ref());
+            assert_eq!(body.raw_acceleration.to_array(), tilted.acceleration.to_array());
+            assert!(!body.unsettled_world_accel);
+
+            // World frame: flagged until the orientation has settled...
+            let mut filter = kind.build(0.98);
+            let mut first = filter.update_with_dt(tilted.clone(), gps_fix(), DT);
+            world.apply(&mut first, filter.as_ref());
+            assert!(first.unsettled_world_accel && first.status_flags.contains(StatusFlags::UNSETTLED_WORLD_ACCEL), "{}", kind.name());
+
+            // ...then gravity lies along the world vertical axis
+            let mut settled = first;
+            for _ in 0..500 {
+                settled = filter.update_with_dt(tilted.clone(), gps_fix(), DT);
+            }
+            world.apply(&mut settled, filter.as_ref());
+            assert!(!settled.unsettled_world_accel, "{}", kind.name());
+            let accel = settled.raw_acceleration;
+            assert!(accel.x.abs() < 0.05 && accel.y.abs() < 0.05, "{}: horizontal gravity leak {accel:?}", kind.name());
+            assert!((accel.z - 9.81).abs() < 0.05, "{}: vertical {accel:?}", kind.name());
+
+            // A switched-in filter has to settle again
+            let mut switched = kind.build(0.98);
+            switched.start_reconverging();
+            let mut frame = switched.update_with_dt(tilted.clone(), gps_fix(), DT);
+            world.apply(&mut frame, switched.as_ref());
+            assert!(frame.unsettled_world_accel, "{}", kind.name());
+        }
+    }
+
+    #[test]
+    fn test_gimbal_lock_margin_applies_to_the_reported_pitch() {
+        let mut filter = ComplementaryFilter::new(0.98);
+        let mut frame = filter.update_with_dt(ImuData::new(Vec3::new(0.0, 0.0, 9.81), Vec3::zero()), gps_fix(), DT);
+        frame.euler_degrees.1 = 85.0;
+        FrameOutput::default().apply(&mut frame, &filter);
+        assert!(!frame.gimbal_lock_warning);
+        FrameOutput { gimbal_lock_margin_deg: 10.0, ..FrameOutput::default() }.apply(&mut frame, &filter);
+        assert!(frame.gimbal_lock_warning && frame.status_flags.contains(StatusFlags::GIMBAL_LOCK_WARNING));
+    }
+}
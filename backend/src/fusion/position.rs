@@ -0,0 +1,107 @@
+//! Position Fusion Strategies
+//!
+//! How GPS fixes are folded into the position estimate:
+//! - Low-pass: fixed-weight blend toward each fix (simple, but lags motion)
+//! - Kalman: per-axis constant-velocity Kalman filter, predicted from
+//!   IMU acceleration and corrected by GPS with HDOP-scaled variance
+
+use serde::Serialize;
+
+/// Position fusion strategy used by the complementary filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionStrategy {
+    /// Blend toward each GPS fix with an HDOP-dependent weight
+    #[default]
+    LowPass,
+
+    /// Two-state (position, velocity) Kalman filter per axis
+    Kalman,
+}
+
+/// Two-state (position, velocity) Kalman filter for a single axis
+#[derive(Debug, Clone)]
+pub struct AxisKalman {
+    /// Estimated position (m)
+    position: f64,
+
+    /// Estimated velocity (m/s)
+    velocity: f64,
+
+    /// State covariance [[pp, pv], [vp, vv]]
+    covariance: [[f64; 2]; 2],
+
+    /// Process noise: standard deviation of unmodelled acceleration (m/s²)
+    accel_noise: f64,
+}
+
+impl AxisKalman {
+    /// Create a filter at rest at `position` with the given variance (m²)
+    pub fn new(position: f64, position_variance: f64, accel_noise: f64) -> Self {
+        Self {
+            position,
+            velocity: 0.0,
+            // Velocity starts unknown; a few fixes pin it down
+            covariance: [[position_variance, 0.0], [0.0, 100.0]],
+            accel_noise,
+        }
+    }
+
+    /// Propagate the state by `dt` seconds under measured acceleration
+    pub fn predict(&mut self, accel: f64, dt: f64) {
+        let accel = if accel.is_finite() { accel } else { 0.0 };
+
+        self.position += self.velocity * dt + 0.5 * accel * dt * dt;
+        self.velocity += accel * dt;
+
+        // P = F P Fᵀ + Q with F = [[1, dt], [0, 1]]
+        let [[pp, pv], [vp, vv]] = self.covariance;
+        let pp = pp + dt * (pv + vp) + dt * dt * vv;
+        let pv = pv + dt * vv;
+        let vp = vp + dt * vv;
+
+        // Q for white acceleration noise
+        let q = self.accel_noise * self.accel_noise;
+        let dt2 = dt * dt;
+        self.covariance = [
+            [pp + q * dt2 * dt2 / 4.0, pv + q * dt2 * dt / 2.0],
+            [vp + q * dt2 * dt / 2.0, vv + q * dt2],
+        ];
+    }
+
+    /// Correct the state with a position measurement of variance `variance` (m²)
+    pub fn correct(&mut self, measured: f64, variance: f64) {
+        if !measured.is_finite() || !variance.is_finite() || variance <= 0.0 {
+            return;
+        }
+
+        let [[pp, pv], [vp, vv]] = self.covariance;
+        let innovation = measured - self.position;
+        let s = pp + variance;
+        let (k_p, k_v) = (pp / s, vp / s);
+
+        self.position += k_p * innovation;
+        self.velocity += k_v * innovation;
+
+        // P = (I - K H) P
+        self.covariance = [
+            [(1.0 - k_p) * pp, (1.0 - k_p) * pv],
+            [vp - k_v * pp, vv - k_v * pv],
+        ];
+    }
+
+    /// Estimated position (m)
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    /// Estimated velocity (m/s)
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// Position variance (m²)
+    pub fn position_variance(&self) -> f64 {
+        self.covariance[0][0]
+    }
+}
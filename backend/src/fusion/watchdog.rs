@@ -0,0 +1,193 @@
+//! Position Drift Watchdog
+//!
+//! Cross-checks two independent position sources: the GPS track and a
+//! track dead-reckoned from the IMU alone (the GPS velocity at an anchor
+//! fix, then double-integrated world-frame acceleration). A GPS jump or
+//! spoofed fix moves one but not the other.
+//!
+//! IMU dead reckoning drifts, so the check is deliberately loose:
+//! - Divergence within `threshold` is normal, and the IMU track is
+//!   re-anchored at a fix every `window` while the two agree, which bounds
+//!   how far it can drift
+//! - Divergence beyond `threshold` only raises the fault once it has
+//!   lasted `persistence`, so a single noisy fix doesn't
+//! - A fault that outlasts another `window` is accepted as the new truth
+//!   (the IMU track is no use after that long) and the watchdog re-arms
+//!
+//! Only horizontal position is compared; GPS altitude is too noisy.
+
+use crate::models::{duration_secs, Vec3};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Settings for the position drift watchdog
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DriftWatchdogConfig {
+    /// Horizontal divergence (m) tolerated between GPS and the IMU track
+    pub threshold_m: f64,
+
+    /// How long the divergence must last before the fault is raised
+    #[serde(rename = "persistence_secs", serialize_with = "duration_secs::serialize")]
+    pub persistence: Duration,
+
+    /// Age at which an agreeing IMU track is re-anchored, and how long a
+    /// raised fault is held before the GPS track is accepted
+    #[serde(rename = "window_secs", serialize_with = "duration_secs::serialize")]
+    pub window: Duration,
+}
+
+impl Default for DriftWatchdogConfig {
+    fn default() -> Self {
+        Self {
+            threshold_m: 50.0,
+            persistence: Duration::from_secs(3),
+            window: Duration::from_secs(10),
+        }
+    }
+}
+
+impl DriftWatchdogConfig {
+    /// Check that the threshold is positive and finite and the window is
+    /// non-zero
+    pub fn is_valid(&self) -> bool {
+        self.threshold_m.is_finite() && self.threshold_m > 0.0 && !self.window.is_zero()
+    }
+}
+
+/// IMU-only track started from a GPS fix
+#[derive(Debug, Clone, Copy)]
+struct ImuTrack {
+    /// Predicted position (east, north, up; m)
+    position: Vec3,
+
+    /// Predicted velocity (east, north, up; m/s)
+    velocity: Vec3,
+
+    /// Time since the anchor fix (s)
+    age: f64,
+}
+
+/// Watches for GPS positions diverging from the IMU-predicted track
+#[derive(Debug, Clone)]
+pub struct DriftWatchdog {
+    config: DriftWatchdogConfig,
+
+    /// Current IMU track (none until the first fix)
+    track: Option<ImuTrack>,
+
+    /// How long the divergence has exceeded the threshold (s)
+    exceeded_for: Option<f64>,
+
+    /// Horizontal divergence at the last fix (m)
+    divergence: Option<f64>,
+
+    /// The fault is raised
+    fault: bool,
+}
+
+impl DriftWatchdog {
+    /// Create a watchdog with no track yet
+    pub fn new(config: DriftWatchdogConfig) -> Self {
+        Self {
+            config,
+            track: None,
+            exceeded_for: None,
+            divergence: None,
+            fault: false,
+        }
+    }
+
+    /// Watchdog settings
+    pub fn config(&self) -> &DriftWatchdogConfig {
+        &self.config
+    }
+
+    /// Advance the IMU track by `dt` seconds of world-frame acceleration
+    /// (east, north, up; m/s², gravity removed)
+    pub fn predict(&mut self, accel: Vec3, dt: f64) {
+        let Some(track) = self.track.as_mut() else {
+            return;
+        };
+        if !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        let accel = accel.finite_or_zero();
+
+        track.position = Vec3::new(
+            track.position.x + track.velocity.x * dt + 0.5 * accel.x * dt * dt,
+            track.position.y + track.velocity.y * dt + 0.5 * accel.y * dt * dt,
+            track.position.z + track.velocity.z * dt + 0.5 * accel.z * dt * dt,
+        );
+        track.velocity = Vec3::new(
+            track.velocity.x + accel.x * dt,
+            track.velocity.y + accel.y * dt,
+            track.velocity.z + accel.z * dt,
+        );
+        track.age += dt;
+        if let Some(exceeded) = self.exceeded_for.as_mut() {
+            *exceeded += dt;
+        }
+    }
+
+    /// Compare a new GPS fix (position and velocity, east/north/up) against
+    /// the IMU track and return whether the fault is raised
+    pub fn check(&mut self, position: Vec3, velocity: Vec3) -> bool {
+        if !(position.is_finite() && velocity.is_finite()) {
+            return self.fault;
+        }
+        let Some(track) = self.track else {
+            self.anchor(position, velocity);
+            return self.fault;
+        };
+
+        let divergence = (position.x - track.position.x).hypot(position.y - track.position.y);
+        self.divergence = Some(divergence);
+        let window = self.config.window.as_secs_f64();
+
+        if divergence <= self.config.threshold_m {
+            self.exceeded_for = None;
+            self.fault = false;
+            if track.age >= window {
+                self.anchor(position, velocity);
+            }
+            return self.fault;
+        }
+
+        let exceeded = *self.exceeded_for.get_or_insert(0.0);
+        let persistence = self.config.persistence.as_secs_f64();
+        if exceeded >= persistence + window {
+            // Diverged for too long to trust the IMU track; start over
+            self.anchor(position, velocity);
+            self.exceeded_for = None;
+            self.fault = false;
+        } else if exceeded >= persistence {
+            self.fault = true;
+        }
+        self.fault
+    }
+
+    /// Whether GPS and the IMU track currently disagree
+    pub fn is_faulted(&self) -> bool {
+        self.fault
+    }
+
+    /// Horizontal divergence at the last fix (m)
+    pub fn divergence(&self) -> Option<f64> {
+        self.divergence
+    }
+
+    /// Drop the track and clear the fault, e.g. after losing the GPS fix;
+    /// the next fix starts a new track
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    /// Start a new IMU track at a GPS fix
+    fn anchor(&mut self, position: Vec3, velocity: Vec3) {
+        self.track = Some(ImuTrack {
+            position,
+            velocity,
+            age: 0.0,
+        });
+    }
+}
@@ -0,0 +1,17 @@
+//! Real-Time Sensor Fusion Library
+//!
+//! Sensor simulators, fusion algorithms, and the WebSocket streaming server
+//! used by the telemetry backend binary. Exposed as a library so the
+//! individual components can be reused and composed independently.
+
+pub mod error;
+pub mod models;
+pub mod sensors;
+pub mod fusion;
+pub mod analysis;
+pub mod sinks;
+pub mod websocket;
+pub mod bench;
+pub mod scenario;
+
+pub use error::SensorFusionError;
@@ -3,140 +3,1061 @@
 //! High-performance telemetry system that simulates sensors, performs fusion,
 //! and streams data via WebSocket to ML services and frontend clients.
 
-use anyhow::Result;
+use anyhow::{Result, Context, bail};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::{info, error, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-mod models;
-mod sensors;
-mod fusion;
-mod websocket;
-
-use models::FusedSensorData;
-use sensors::{imu::ImuSimulator, gps::GpsSimulator};
-use fusion::complementary::ComplementaryFilter;
-use websocket::server::WebSocketServer;
+use sensor_fusion_backend::SensorFusionError;
+use sensor_fusion_backend::scenario::Scenario;
+use sensor_fusion_backend::models::{AltitudeReference, FusedSensorData, SensorInputs, StatusFlags, TimestampFormat, GpsData, ImuData, Vec3};
+use sensor_fusion_backend::sensors::{ImuConfig, ImuSimulator, GpsSimulator, GpsScheduler, GpsTiming, HealthSmoothing, ReplaySource, ChaosConfig, ChaosSchedule, ExternalLimits, ExternalSample, SensorInput, DelayQueue, FaultTimers, SensorLatency, SimRngConfig, imu::FaultType, gps::GpsFaultType};
+use sensor_fusion_backend::sensors::chaos::{ChaosAction, ChaosFault};
+use sensor_fusion_backend::sensors::rng::{GPS_STREAM, GPS_TIMING_STREAM, IMU_STREAM};
+use sensor_fusion_backend::fusion::{AccelFrame, DetectorSet, ThresholdDetector, ZScoreDetector, ComplementaryFilter, EkfFilter, FusionFilter, ConfidenceBounds, ConfidenceWeights, DriftWatchdogConfig, PositionWeights, FilterDiagReport, FrameOutput, FilterKind, GridMapper, PositionStrategy, HealthLog, HealthMonitor};
+use sensor_fusion_backend::fusion::complementary::{MAX_ACCEL_BIAS, MAX_GYRO_DEADBAND};
+use sensor_fusion_backend::fusion::health::HealthTransition;
+use sensor_fusion_backend::fusion::output::DEFAULT_GIMBAL_LOCK_MARGIN_DEG;
+use sensor_fusion_backend::fusion::complementary::{DEFAULT_GPS_TIMEOUT_SECS, DEFAULT_GPS_YAW_MIN_SPEED, DEFAULT_VELOCITY_WINDOW_SECS};
+use sensor_fusion_backend::analysis::{FieldStats, FieldStatsConfig, SpectrumAnalyzer, SpectrumConfig};
+use sensor_fusion_backend::sinks::{BroadcastSink, JsonlSink, MqttConfig, MqttSink, SinkSet};
+use sensor_fusion_backend::websocket::{ConnectionStats, ControlCommand, DashboardServer, FrameIds, MetadataConfig, WebSocketServer, OriginPolicy, OutputPrecision};
 
 /// Application configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 struct Config {
     /// WebSocket server port
     ws_port: u16,
+    /// Serve WebSocket clients on this Unix domain socket instead of the
+    /// TCP port, for processes on the same host (Unix only)
+    ws_unix_socket: Option<PathBuf>,
+    /// Serve the embedded debug dashboard page on this HTTP port (off when unset)
+    dashboard_port: Option<u16>,
     /// IMU update frequency in Hz
     imu_frequency: u32,
     /// GPS update frequency in Hz
     gps_frequency: u32,
+    /// Global cap on frames broadcast per second (every fused frame when unset)
+    broadcast_rate_hz: Option<u32>,
+    /// IMU simulator settings (oversampling, mounting orientation, g-sensitivity, accel/gyro output rates)
+    imu: ImuConfig,
+    /// Fusion filter to start with (switchable at runtime with `set_filter`)
+    filter: FilterKind,
     /// Fusion filter alpha parameter (0.0 - 1.0)
     filter_alpha: f64,
+    /// Minimum GPS speed (m/s) before GPS course corrects yaw
+    gps_yaw_min_speed: f64,
+    /// GPS-derived acceleration (m/s²) above which accel trust is reduced
+    gps_accel_gate: Option<f64>,
+    /// Physical top speed (m/s); faster fused velocities are clamped
+    max_speed: Option<f64>,
+    /// Time constant (s) over which IMU-integrated velocity decays toward GPS velocity
+    velocity_window_secs: f64,
+    /// Age (s) beyond which a GPS fix is stale and counts less toward
+    /// position and confidence; must exceed the GPS update interval
+    gps_timeout_secs: f64,
+    /// IMU/GPS weights (normalized) and HDOP thresholds behind frame confidence
+    confidence_weights: ConfidenceWeights,
+    /// Floor and ceiling the reported confidence is clamped to, after every
+    /// other adjustment (e.g. 0.05/0.99 to never claim certainty)
+    confidence_bounds: ConfidenceBounds,
+    /// GPS weight of the low-pass position update, falling off continuously with HDOP
+    position_weights: PositionWeights,
+    /// Display smoothing of the reported orientation in [0, 1) (off when unset)
+    orientation_smoothing: Option<f64>,
+    /// Gyro integration steps per fusion update (more help accuracy at low IMU rates)
+    integration_substeps: u32,
+    /// Add the gyro reading rotated into the world frame to frames (debugging)
+    world_angular_velocity: bool,
+    /// Frame of `raw_acceleration` in frames: as measured (`body`) or
+    /// rotated into the navigation frame (`world`); gravity stays in either
+    accel_frame: AccelFrame,
+    /// Gyro rates below this (rad/s) are zeroed to stop stationary drift (0 = off)
+    gyro_deadband: f64,
+    /// Integral gain (1/s²) of the online gyro bias estimator fed back into integration (off when unset)
+    gyro_bias_gain: Option<f64>,
+    /// Largest orientation change per second (deg/s) before a step is cut back (off when unset)
+    max_rotation_rate_dps: Option<f64>,
+    /// Accelerometer bias (m/s², per axis) subtracted before fusion, e.g. from an earlier calibration
+    accel_bias: Vec3,
+    /// IMU readings averaged by a `calibrate_accel` command (sensor level and still meanwhile)
+    accel_calibration_samples: u32,
+    /// Flag GPS positions diverging from IMU dead reckoning, e.g. spoofing (off when unset)
+    position_watchdog: Option<DriftWatchdogConfig>,
+    /// Pitch margin from ±90° (degrees) within which Euler angles are flagged unreliable
+    gimbal_lock_margin_deg: f64,
+    /// Score anomalies in-process while no ML service is posting scores
+    builtin_anomaly_detector: bool,
+    /// Also flag IMU magnitudes beyond fixed limits as anomalous (off when unset)
+    threshold_detector: Option<ThresholdDetector>,
+    /// Datum for reported altitude (MSL, ellipsoidal, or above ground)
+    altitude: AltitudeReference,
+    /// How GPS fixes are fused into position (low-pass blend or Kalman)
+    position_strategy: PositionStrategy,
+    /// Edge length (m) of the local grid cells added to frames (no cells when unset)
+    grid_cell_size: Option<f64>,
+    /// Distance (m) past a cell edge before the reported grid cell changes
+    grid_hysteresis: f64,
+    /// Directory for daily-rotated log files (stderr only when unset)
+    log_dir: Option<PathBuf>,
+    /// Interval between fused telemetry summary log lines in seconds
+    telemetry_summary_secs: u64,
+    /// Keep re-broadcasting the last fused frame while the simulation is paused
+    broadcast_while_paused: bool,
+    /// Round floats in outgoing frames (full precision when unset)
+    output_precision: Option<OutputPrecision>,
+    /// Browser origins allowed to open WebSocket connections (any when empty)
+    allowed_origins: Vec<String>,
+    /// Accept WebSocket handshakes without an `Origin` header (non-browser clients)
+    allow_missing_origin: bool,
+    /// Append a CRC-32 integrity checksum to every streamed frame
+    frame_checksums: bool,
+    /// Ping every WebSocket connection at this interval in seconds (disabled when unset)
+    ws_keepalive_secs: Option<u64>,
+    /// Default timestamp format of streamed frames (clients may switch)
+    timestamp_format: TimestampFormat,
+    /// Names of the world, body, IMU and GPS frames in the coordinate metadata
+    frame_ids: FrameIds,
+    /// Repeat the coordinate metadata in every frame, not only in the connection message
+    metadata_every_frame: bool,
+    /// Frames kept for the HTTP long-poll fallback (disabled when unset)
+    long_poll_history: Option<usize>,
+    /// Maximum time a long-poll request waits for new frames in seconds
+    long_poll_timeout_secs: u64,
+    /// Record published frames to this JSON Lines file (replayable with `replay_file`)
+    record_file: Option<PathBuf>,
+    /// Also print published frames to stdout as JSON Lines
+    print_frames: bool,
+    /// Shut down after this many seconds and print a run summary (0 = run forever)
+    duration_secs: u64,
+    /// Run this scenario file offline, print its frames as JSON Lines, and exit
+    scenario_file: Option<PathBuf>,
+    /// Publish frames to an MQTT broker (disabled when unset)
+    mqtt: Option<MqttConfig>,
+    /// Periodic accelerometer vibration spectrum (disabled when unset)
+    spectrum: Option<SpectrumConfig>,
+    /// Periodic rolling min/max/mean/stddev of key fields (disabled when unset)
+    field_stats: Option<FieldStatsConfig>,
+    /// Push fusion filter internals (effective alpha, accel gating) at this interval in seconds (disabled when unset)
+    filter_diag_secs: Option<u64>,
+    /// Replay a JSON Lines recording instead of simulating sensors
+    replay_file: Option<PathBuf>,
+    /// Replay speed multiplier (1.0 = real time, 0.0 = paused)
+    playback_speed: f64,
+    /// Tokio worker threads (`TOKIO_WORKER_THREADS` or one per core when unset)
+    worker_threads: Option<usize>,
+    /// Run everything on a single-threaded runtime (single-core deployments)
+    current_thread_runtime: bool,
+    /// Start with chaos mode (randomized fault injection) enabled
+    chaos_enabled: bool,
+    /// Timing bounds for chaos mode faults
+    chaos: ChaosConfig,
+    /// Seed for reproducible chaos runs (random when unset)
+    chaos_seed: Option<u64>,
+    /// Compound faults: a command name mapped to the faults it injects
+    /// together, e.g. a power glitch upsetting IMU and GPS at once
+    compound_faults: BTreeMap<String, Vec<String>>,
+    /// Random number generator of the IMU/GPS simulators (`small` for speed, a seed for reproducible runs)
+    sensor_rng: SimRngConfig,
+    /// Delay before simulated readings reach the fusion loop (readings keep
+    /// their measurement timestamps; IMU delays round up to whole ticks)
+    sensor_latency: SensorLatency,
+    /// Seconds the simulators take to reach nominal output after start
+    /// (GPS satellites acquired, gyro bias settled; 0 = nominal at once)
+    sensor_warm_up_secs: u64,
+    /// Jitter and dropped fixes of simulated GPS updates (regular by default)
+    gps_timing: GpsTiming,
+    /// Debounce and hysteresis of simulated GPS health, so a brief satellite dip doesn't tank it
+    gps_health_smoothing: HealthSmoothing,
+    /// Number of health transitions kept for `health_log` requests
+    health_log_capacity: usize,
+    /// Number of client commands kept for `command_history` requests
+    command_history_capacity: usize,
+    /// Where IMU/GPS readings come from (simulators, pushed, or both)
+    sensor_input: SensorInput,
+    /// Bounds pushed readings must stay within (raise for extreme maneuvers)
+    external_limits: ExternalLimits,
+    /// Token sensor sources must present before pushing readings
+    #[serde(serialize_with = "redact")]
+    sensor_source_token: Option<String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             ws_port: 8080,
+            ws_unix_socket: None,
+            dashboard_port: None,
             imu_frequency: 50,  // 50 Hz for IMU
             gps_frequency: 1,   // 1 Hz for GPS
+            broadcast_rate_hz: None,
+            imu: ImuConfig::default(),
+            filter: FilterKind::Complementary,
             filter_alpha: 0.98, // Complementary filter parameter
+            gps_yaw_min_speed: DEFAULT_GPS_YAW_MIN_SPEED, // Below this, GPS heading is noise
+            gps_accel_gate: None,
+            max_speed: None,
+            velocity_window_secs: DEFAULT_VELOCITY_WINDOW_SECS,
+            gps_timeout_secs: DEFAULT_GPS_TIMEOUT_SECS,
+            confidence_weights: ConfidenceWeights::default(),
+            confidence_bounds: ConfidenceBounds::default(),
+            position_weights: PositionWeights::default(),
+            orientation_smoothing: None,
+            integration_substeps: 1,
+            world_angular_velocity: false,
+            accel_frame: AccelFrame::Body,
+            gyro_deadband: 0.0,
+            gyro_bias_gain: None,
+            max_rotation_rate_dps: None,
+            accel_bias: Vec3::zero(),
+            accel_calibration_samples: 100, // 2 s at 50 Hz
+            position_watchdog: None,
+            gimbal_lock_margin_deg: DEFAULT_GIMBAL_LOCK_MARGIN_DEG,
+            builtin_anomaly_detector: true,
+            threshold_detector: None,
+            altitude: AltitudeReference::default(),
+            position_strategy: PositionStrategy::LowPass,
+            grid_cell_size: None,
+            grid_hysteresis: 0.0,
+            log_dir: None,
+            telemetry_summary_secs: 10,
+            broadcast_while_paused: true,
+            output_precision: None,
+            allowed_origins: Vec::new(),
+            allow_missing_origin: true,
+            frame_checksums: false,
+            ws_keepalive_secs: None,
+            timestamp_format: TimestampFormat::Rfc3339,
+            frame_ids: FrameIds::default(),
+            metadata_every_frame: false,
+            long_poll_history: None,
+            long_poll_timeout_secs: 10,
+            record_file: None,
+            print_frames: false,
+            duration_secs: 0,
+            scenario_file: None,
+            mqtt: None,
+            spectrum: None,
+            field_stats: None,
+            filter_diag_secs: None,
+            replay_file: None,
+            playback_speed: 1.0,
+            worker_threads: None,
+            current_thread_runtime: false,
+            chaos_enabled: false,
+            chaos: ChaosConfig::default(),
+            chaos_seed: None,
+            compound_faults: BTreeMap::from([(
+                "power_glitch".to_string(),
+                vec!["high_noise".to_string(), "gps_poor_accuracy".to_string()],
+            )]),
+            sensor_rng: SimRngConfig::default(),
+            sensor_latency: SensorLatency::default(),
+            sensor_warm_up_secs: 0,
+            gps_timing: GpsTiming::default(),
+            gps_health_smoothing: HealthSmoothing::default(),
+            health_log_capacity: 100,
+            command_history_capacity: 50,
+            sensor_input: SensorInput::Simulated,
+            external_limits: ExternalLimits::default(),
+            sensor_source_token: None,
         }
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize structured logging
+impl Config {
+    /// Reject values the pipeline can't run with
+    fn validate(&self) -> Result<(), SensorFusionError> {
+        let invalid = |msg: String| Err(SensorFusionError::Config(msg));
+
+        // Tick intervals are whole milliseconds
+        if !(1..=1000).contains(&self.imu_frequency) {
+            return invalid(format!("imu_frequency must be 1-1000 Hz, got {}", self.imu_frequency));
+        }
+        if !(1..=1000).contains(&self.gps_frequency) {
+            return invalid(format!("gps_frequency must be 1-1000 Hz, got {}", self.gps_frequency));
+        }
+        if self.filter_diag_secs == Some(0) {
+            return invalid("filter_diag_secs must be at least 1".to_string());
+        }
+        if self.ws_keepalive_secs == Some(0) {
+            return invalid("ws_keepalive_secs must be at least 1".to_string());
+        }
+        if self.broadcast_rate_hz == Some(0) {
+            return invalid("broadcast_rate_hz must be at least 1".to_string());
+        }
+        if let Err(e) = self.imu.validate() {
+            return invalid(e.to_string());
+        }
+        if let Some(Err(e)) = self.mqtt.as_ref().map(MqttConfig::validate) {
+            return invalid(e.to_string());
+        }
+        if self.spectrum.as_ref().is_some_and(|s| !s.is_valid()) {
+            return invalid("spectrum window must be a power of two from 8 to 4096 with a non-zero interval".to_string());
+        }
+        if self.field_stats.as_ref().is_some_and(|s| s.window.is_zero() || s.interval.is_zero()) {
+            return invalid("field_stats window and interval must be non-zero".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.filter_alpha) {
+            return invalid(format!("filter_alpha must be between 0 and 1, got {}", self.filter_alpha));
+        }
+        if !(self.gps_yaw_min_speed.is_finite() && self.gps_yaw_min_speed >= 0.0) {
+            return invalid(format!("gps_yaw_min_speed must be >= 0, got {}", self.gps_yaw_min_speed));
+        }
+        if let Some(gate) = self.gps_accel_gate {
+            if !(gate.is_finite() && gate > 0.0) {
+                return invalid(format!("gps_accel_gate must be > 0, got {}", gate));
+            }
+        }
+        if let Some(max) = self.max_speed {
+            if !(max.is_finite() && max >= 0.0) {
+                return invalid(format!("max_speed must be >= 0, got {}", max));
+            }
+        }
+        if !(self.velocity_window_secs.is_finite() && self.velocity_window_secs > 0.0) {
+            return invalid(format!("velocity_window_secs must be > 0, got {}", self.velocity_window_secs));
+        }
+        if !self.confidence_weights.is_valid() {
+            return invalid(format!(
+                "confidence_weights need non-negative weights with a positive sum and 0 < hdop_good <= hdop_fair, got {:?}",
+                self.confidence_weights
+            ));
+        }
+        if !self.confidence_bounds.is_valid() {
+            return invalid(format!(
+                "confidence_bounds need 0 <= floor <= ceiling <= 1, got {:?}",
+                self.confidence_bounds
+            ));
+        }
+        if !self.position_weights.is_valid() {
+            return invalid(format!(
+                "position_weights need 0 <= min_weight <= max_weight <= 1 and reference_hdop > 0, got {:?}",
+                self.position_weights
+            ));
+        }
+        if let Some(factor) = self.orientation_smoothing {
+            if !(0.0..1.0).contains(&factor) {
+                return invalid(format!("orientation_smoothing must be in [0, 1), got {}", factor));
+            }
+        }
+        if !(1..=MAX_INTEGRATION_SUBSTEPS).contains(&self.integration_substeps) {
+            return invalid(format!(
+                "integration_substeps must be 1-{}, got {}",
+                MAX_INTEGRATION_SUBSTEPS, self.integration_substeps
+            ));
+        }
+        if !(0.0..=MAX_GYRO_DEADBAND).contains(&self.gyro_deadband) {
+            return invalid(format!("gyro_deadband must be 0-{} rad/s, got {}", MAX_GYRO_DEADBAND, self.gyro_deadband));
+        }
+        if let Some(gain) = self.gyro_bias_gain {
+            if !gain.is_finite() || gain <= 0.0 {
+                return invalid(format!("gyro_bias_gain must be > 0, got {}", gain));
+            }
+        }
+        if let Some(rate) = self.max_rotation_rate_dps {
+            if !rate.is_finite() || rate <= 0.0 {
+                return invalid(format!("max_rotation_rate_dps must be > 0, got {}", rate));
+            }
+        }
+        if ![self.accel_bias.x, self.accel_bias.y, self.accel_bias.z]
+            .iter()
+            .all(|b| b.is_finite() && b.abs() <= MAX_ACCEL_BIAS)
+        {
+            return invalid(format!("accel_bias must be within ±{} m/s² per axis, got {:?}", MAX_ACCEL_BIAS, self.accel_bias));
+        }
+        if self.accel_calibration_samples == 0 {
+            return invalid("accel_calibration_samples must be at least 1".to_string());
+        }
+        if self.position_watchdog.is_some_and(|w| !w.is_valid()) {
+            return invalid(format!("position_watchdog needs threshold_m > 0 and a non-zero window, got {:?}", self.position_watchdog));
+        }
+        if !(0.0..=90.0).contains(&self.gimbal_lock_margin_deg) {
+            return invalid(format!("gimbal_lock_margin_deg must be 0-90, got {}", self.gimbal_lock_margin_deg));
+        }
+        if self.filter == FilterKind::Ekf {
+            let unsupported = self.ekf_unsupported_settings();
+            if !unsupported.is_empty() {
+                return invalid(format!(
+                    "the ekf filter can't honor {} (unset them or use the complementary filter)",
+                    unsupported.join(", ")
+                ));
+            }
+        }
+        if let Some(size) = self.grid_cell_size {
+            if !(size.is_finite() && size > 0.0) {
+                return invalid(format!("grid_cell_size must be > 0, got {}", size));
+            }
+            if !(0.0..=size / 2.0).contains(&self.grid_hysteresis) {
+                return invalid(format!("grid_hysteresis must be 0-{} m (half a cell), got {}", size / 2.0, self.grid_hysteresis));
+            }
+        }
+        if !(self.altitude.geoid_separation.is_finite() && self.altitude.ground_elevation.is_finite()) {
+            return invalid("altitude geoid_separation and ground_elevation must be finite".to_string());
+        }
+        if !(self.playback_speed.is_finite() && self.playback_speed >= 0.0) {
+            return invalid(format!("playback_speed must be >= 0, got {}", self.playback_speed));
+        }
+        if self.sensor_latency.imu > MAX_SENSOR_LATENCY || self.sensor_latency.gps > MAX_SENSOR_LATENCY {
+            return invalid(format!("sensor_latency must be at most {:?}, got {:?}", MAX_SENSOR_LATENCY, self.sensor_latency));
+        }
+        if self.threshold_detector.is_some_and(|t| !t.is_valid()) {
+            return invalid(format!("threshold_detector limits must be positive, got {:?}", self.threshold_detector));
+        }
+        if self.sensor_warm_up_secs > MAX_SENSOR_WARM_UP_SECS {
+            return invalid(format!("sensor_warm_up_secs must be at most {}, got {}", MAX_SENSOR_WARM_UP_SECS, self.sensor_warm_up_secs));
+        }
+        for (name, faults) in &self.compound_faults {
+            if LoopCommand::from_name(name).is_some() {
+                return invalid(format!("compound fault {:?} would shadow the built-in command", name));
+            }
+            if faults.is_empty() {
+                return invalid(format!("compound fault {:?} needs at least one fault", name));
+            }
+            if let Some(unknown) = faults.iter().find(|f| ChaosFault::from_name(f).is_none()) {
+                return invalid(format!(
+                    "compound fault {:?} has unknown fault {:?} (available: {})",
+                    name, unknown, ChaosFault::available()
+                ));
+            }
+        }
+        let gps_interval = std::time::Duration::from_millis(1000 / self.gps_frequency as u64);
+        if !self.gps_health_smoothing.is_valid() {
+            return invalid(format!("gps_health_smoothing needs hysteresis 0-1, got {:?}", self.gps_health_smoothing));
+        }
+        // Regular updates must never count as stale, even when late
+        let max_gap = (gps_interval + self.gps_timing.jitter).as_secs_f64();
+        if !(self.gps_timeout_secs.is_finite() && self.gps_timeout_secs > max_gap) {
+            return invalid(format!(
+                "gps_timeout_secs must exceed the GPS update interval plus jitter ({} s), got {}",
+                max_gap, self.gps_timeout_secs
+            ));
+        }
+        if !self.gps_timing.is_valid(gps_interval) {
+            return invalid(format!(
+                "gps_timing needs drop_probability 0-1 and jitter under the {:?} GPS interval, got {:?}",
+                gps_interval, self.gps_timing
+            ));
+        }
+        if cfg!(not(unix)) && self.ws_unix_socket.is_some() {
+            return invalid("ws_unix_socket needs a Unix platform".to_string());
+        }
+        if let Some(port) = self.dashboard_port {
+            if port == self.ws_port {
+                return invalid(format!("dashboard_port must differ from ws_port, both are {}", port));
+            }
+            // Browsers can't open WebSocket connections over a Unix socket
+            if self.ws_unix_socket.is_some() {
+                return invalid("dashboard_port needs the WebSocket server on TCP, not ws_unix_socket".to_string());
+            }
+        }
+        let frame_ids = &self.frame_ids;
+        for (role, id) in [("world", &frame_ids.world), ("body", &frame_ids.body), ("imu", &frame_ids.imu), ("gps", &frame_ids.gps)] {
+            if id.is_empty() || id.chars().any(char::is_whitespace) {
+                return invalid(format!("frame_ids.{} must be a non-empty name without whitespace, got {:?}", role, id));
+            }
+        }
+        if self.worker_threads == Some(0) {
+            return invalid("worker_threads must be at least 1".to_string());
+        }
+        if !self.external_limits.is_valid() {
+            return invalid(format!("external_limits must be finite positive bounds with min_altitude < max_altitude, got {:?}", self.external_limits));
+        }
+        if self.sensor_input.accepts_external()
+            && self.sensor_source_token.as_deref().is_none_or(str::is_empty)
+        {
+            return invalid("external sensor input requires a sensor_source_token".to_string());
+        }
+        Ok(())
+    }
+
+    /// Complementary filter settings changed from their defaults, which
+    /// the EKF has no counterpart for
+    fn ekf_unsupported_settings(&self) -> Vec<&'static str> {
+        let defaults = Config::default();
+        [
+            ("gps_accel_gate", self.gps_accel_gate.is_some()),
+            ("velocity_window_secs", self.velocity_window_secs != defaults.velocity_window_secs),
+            ("confidence_weights", self.confidence_weights != defaults.confidence_weights),
+            ("position_weights", self.position_weights != defaults.position_weights),
+            ("orientation_smoothing", self.orientation_smoothing.is_some()),
+            ("gyro_deadband", self.gyro_deadband != defaults.gyro_deadband),
+            ("gyro_bias_gain", self.gyro_bias_gain.is_some()),
+            ("max_rotation_rate_dps", self.max_rotation_rate_dps.is_some()),
+            ("position_watchdog", self.position_watchdog.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect()
+    }
+}
+
+impl Config {
+    /// Apply command-line flags on top of the configuration
+    /// 
+    /// `--stdout-jsonl` prints every published frame to stdout as a JSON
+    /// line (logs stay on stderr). `--duration-secs N` (or
+    /// `--duration-secs=N`) shuts down after N seconds, flushing the
+    /// outputs, and prints a run summary to stderr. `--scenario FILE`
+    /// runs a scenario file instead of the live pipeline. `--ws-port N`
+    /// overrides `ws_port` (0 lets the OS pick a free port).
+    fn apply_args(&mut self, args: impl IntoIterator<Item = String>) -> Result<(), SensorFusionError> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            match flag {
+                "--stdout-jsonl" if inline_value.is_none() => self.print_frames = true,
+                "--duration-secs" => {
+                    let value = inline_value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| SensorFusionError::Config("--duration-secs needs a value".to_string()))?;
+                    self.duration_secs = value.trim().parse().map_err(|_| {
+                        SensorFusionError::Config(format!("--duration-secs must be a whole number of seconds, got {:?}", value))
+                    })?;
+                }
+                "--ws-port" => {
+                    let value = inline_value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| SensorFusionError::Config("--ws-port needs a value".to_string()))?;
+                    self.ws_port = value.trim().parse().map_err(|_| {
+                        SensorFusionError::Config(format!("--ws-port must be a port number, got {:?}", value))
+                    })?;
+                }
+                "--scenario" => {
+                    let path = inline_value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| SensorFusionError::Config("--scenario needs a file".to_string()))?;
+                    self.scenario_file = Some(PathBuf::from(path));
+                }
+                _ => {
+                    return Err(SensorFusionError::Config(format!(
+                        "unknown argument {:?} (supported: --stdout-jsonl, --duration-secs N, --ws-port N, --scenario FILE)",
+                        arg
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Origins allowed to open WebSocket connections: the configured ones
+/// plus the embedded dashboard's, when an allowlist is in force
+fn allowed_origins(config: &Config) -> Vec<String> {
+    let mut origins = config.allowed_origins.clone();
+    if let (Some(port), false) = (config.dashboard_port, origins.is_empty()) {
+        origins.extend([format!("http://127.0.0.1:{}", port), format!("http://localhost:{}", port)]);
+    }
+    origins
+}
+
+/// Serialize a secret as `"<redacted>"` (or `null` when unset)
+fn redact<S: Serializer>(secret: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    secret.as_ref().map(|_| "<redacted>").serialize(serializer)
+}
+
+/// Environment variable overriding the worker thread count
+const WORKER_THREADS_ENV: &str = "TOKIO_WORKER_THREADS";
+
+/// Build the Tokio runtime described by the configuration
+fn build_runtime(config: &Config) -> Result<tokio::runtime::Runtime> {
+    let mut builder = if config.current_thread_runtime {
+        tokio::runtime::Builder::new_current_thread()
+    } else {
+        let worker_threads = match config.worker_threads {
+            Some(threads) => Some(threads),
+            None => match std::env::var(WORKER_THREADS_ENV) {
+                Ok(value) => Some(value.trim().parse::<usize>().with_context(|| {
+                    format!("{} must be a positive integer, got {:?}", WORKER_THREADS_ENV, value)
+                })?),
+                Err(_) => None,
+            },
+        };
+
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        match worker_threads {
+            Some(0) => bail!("worker_threads must be at least 1"),
+            Some(threads) => {
+                builder.worker_threads(threads);
+            }
+            None => {}
+        }
+        builder
+    };
+
+    builder
+        .enable_all()
+        .build()
+        .context("Failed to build Tokio runtime")
+}
+
+fn main() -> Result<()> {
+    // Load configuration
+    let mut config = Config::default();
+    config.apply_args(std::env::args().skip(1))?;
+    config.validate()?;
+
+    if let Some(path) = &config.scenario_file {
+        return run_scenario(path);
+    }
+
+    let runtime = build_runtime(&config)?;
+    runtime.block_on(run(config))
+}
+
+/// Run a scenario file and print its frames to stdout as JSON Lines
+fn run_scenario(path: &std::path::Path) -> Result<()> {
+    let scenario = Scenario::from_file(path).with_context(|| format!("Failed to load scenario {}", path.display()))?;
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    for frame in scenario.run()? {
+        serde_json::to_writer(&mut out, &frame)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Log layer writing plain-text lines to a daily rotated file in `dir`
+///
+/// Lines are written on a background thread; the returned guard flushes
+/// them when dropped.
+fn log_file_layer<S>(dir: &std::path::Path) -> Result<(impl tracing_subscriber::Layer<S>, tracing_appender::non_blocking::WorkerGuard)>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+    let appender = tracing_appender::rolling::daily(dir, "sensor-fusion.log");
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    let layer = tracing_subscriber::fmt::layer()
+        .with_target(false)
+        .with_ansi(false)
+        .with_writer(writer);
+    Ok((layer, guard))
+}
+
+/// Start logging, the sensor pipeline and the server, then wait for them
+async fn run(config: Config) -> Result<()> {
+    // Optional rolling file sink; the guard must live until shutdown so
+    // buffered lines are flushed
+    let (file_layer, _log_guard) = match &config.log_dir {
+        Some(dir) => {
+            let (layer, guard) = log_file_layer(dir)?;
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    // Initialize structured logging (console logs on stderr, leaving
+    // stdout to the frame printer)
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "sensor_fusion_backend=info,tower_http=info".into()),
         )
-        .with(tracing_subscriber::fmt::layer().with_target(false))
+        .with(tracing_subscriber::fmt::layer().with_target(false).with_writer(std::io::stderr))
+        .with(file_layer)
         .init();
 
     info!("🚀 Starting Real-Time Sensor Fusion Backend");
-
-    // Load configuration
-    let config = Config::default();
-    info!("📋 Configuration: {:?}", config);
+    let mut logged_config = config.clone();
+    if logged_config.sensor_source_token.is_some() {
+        logged_config.sensor_source_token = Some("<redacted>".to_string());
+    }
+    info!("📋 Configuration: {:?}", logged_config);
+    match tokio::runtime::Handle::current().runtime_flavor() {
+        tokio::runtime::RuntimeFlavor::CurrentThread => info!("🧵 Running on a single-threaded runtime"),
+        _ => info!("🧵 Running on a multi-threaded runtime"),
+    }
+    if let Some(dir) = &config.log_dir {
+        info!("📝 Logging to daily rotated files in {}", dir.display());
+    }
 
     // Create broadcast channel for sensor data distribution
-    // Buffer size of 100 allows consumers to lag slightly without blocking producers
-    let (tx, _rx) = broadcast::channel::<FusedSensorData>(100);
+    // Buffer size of 100 allows consumers to lag slightly without blocking producers.
+    // No receiver is kept here, so sends only reach actual subscribers.
+    let (tx, _) = broadcast::channel::<FusedSensorData>(100);
     let tx = Arc::new(tx);
 
+    // Watch channel holding only the latest frame, for clients that never
+    // want to fall behind
+    let (latest_tx, latest_rx) = tokio::sync::watch::channel(FusedSensorData::default());
+
+    // Turns true to stop the fusion loop and server at the end of a run
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
     // Create command channel for fault injection
-    let (cmd_tx, cmd_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let (cmd_tx, cmd_rx) = tokio::sync::mpsc::unbounded_channel::<ControlCommand>();
     let cmd_tx = Arc::new(cmd_tx);
 
     // Create shared state for anomaly scores from ML service
     let anomaly_score = Arc::new(tokio::sync::RwLock::new(None::<f64>));
 
+    // Record of health degradations and recoveries, queryable by clients
+    let health_log = Arc::new(HealthLog::new(config.health_log_capacity));
+
+    // Counters for the end-of-run summary
+    let run_stats = Arc::new(RunStats::default());
+    let connection_stats = Arc::new(ConnectionStats::new());
+    let run_started = tokio::time::Instant::now();
+
+    // Pushed readings from authenticated sensor sources, if accepted
+    let (external_tx, external_rx) = if config.sensor_input.accepts_external() {
+        let (tx, rx) = tokio::sync::mpsc::channel::<ExternalSample>(256);
+        (Some(tx), Some(rx))
+    } else {
+        (None, None)
+    };
+
     // Clone config for later use
     let config_clone = config.clone();
     let anomaly_score_read = anomaly_score.clone();
+    let health_log_write = health_log.clone();
+    let run_stats_write = run_stats.clone();
+
+    // Server-pushed messages (analysis results) for streaming clients
+    let (notices_tx, _notices_rx) = broadcast::channel::<Arc<str>>(16);
+    if let Some(spectrum) = &config.spectrum {
+        info!("🎵 Vibration spectrum: {}-sample window every {:?}", spectrum.window, spectrum.interval);
+        tokio::spawn(run_spectrum_loop(tx.subscribe(), notices_tx.clone(), spectrum.clone()));
+    }
+    if let Some(field_stats) = &config.field_stats {
+        info!("📈 Field statistics: {:?} window every {:?}", field_stats.window, field_stats.interval);
+        tokio::spawn(run_field_stats_loop(tx.subscribe(), notices_tx.clone(), field_stats.clone()));
+    }
+    // Filter internals, published by the fusion loop beside its frames
+    let (diagnostics_tx, diagnostics_rx) = tokio::sync::watch::channel(None);
+    if let Some(secs) = config.filter_diag_secs {
+        info!("🔬 Filter diagnostics every {}s", secs);
+        tokio::spawn(run_filter_diag_loop(diagnostics_rx, notices_tx.clone(), std::time::Duration::from_secs(secs)));
+    }
 
-    // Spawn sensor simulation task with command receiver and anomaly score state
-    let sensor_tx = tx.clone();
-    let sensor_handle = tokio::spawn(async move {
-        if let Err(e) = run_sensor_fusion_loop(sensor_tx, config_clone, cmd_rx, anomaly_score_read).await {
-            error!("❌ Sensor fusion loop error: {}", e);
+    // Outputs every published frame is fanned out to
+    let mut sinks = SinkSet::new();
+    sinks.push(Box::new(BroadcastSink::new(tx.clone(), latest_tx).with_connection_stats(connection_stats.clone())));
+    if let Some(path) = &config.record_file {
+        let recorder = JsonlSink::create(path)
+            .await
+            .with_context(|| format!("Failed to create recording {}", path.display()))?;
+        info!("⏺️  Recording frames to {}", path.display());
+        sinks.push(Box::new(recorder));
+    }
+    if config.print_frames {
+        sinks.push(Box::new(JsonlSink::stdout()));
+    }
+    if let Some(mqtt) = &config.mqtt {
+        info!("📤 Publishing frames to MQTT topic {} on {} ({:?})", mqtt.topic, mqtt.broker, mqtt.qos);
+        sinks.push(Box::new(MqttSink::spawn(mqtt.clone())?));
+    }
+
+    // Configuration served to get_config requests; the fusion loop keeps
+    // it current as settings change at runtime
+    let served_config = serde_json::to_value(&config)
+        .map_err(|e| warn!("⚠️  Configuration can't be served to get_config requests: {}", e))
+        .ok();
+    let (config_tx, config_rx) = tokio::sync::watch::channel(served_config.clone().unwrap_or_default());
+
+    // Spawn sensor simulation (or replay) task with command receiver and anomaly score state
+    let loop_shutdown = shutdown_rx.clone();
+    let loop_clients = connection_stats.clone();
+    let mut sensor_handle = match &config.replay_file {
+        Some(path) => {
+            let mut replay = ReplaySource::from_jsonl(path)
+                .with_context(|| format!("Failed to load recording {}", path.display()))?;
+            replay.set_playback_speed(config.playback_speed)?;
+            info!("📼 Replaying {} frames from {} at {}x", replay.len(), path.display(), config.playback_speed);
+            
+            tokio::spawn(async move {
+                if let Err(e) = run_replay_loop(sinks, replay, cmd_rx, anomaly_score_read, run_stats_write, loop_shutdown).await {
+                    error!("❌ Replay loop error: {}", e);
+                }
+            })
         }
-    });
+        None => tokio::spawn(async move {
+            if let Err(e) = run_sensor_fusion_loop(sinks, config_clone, config_tx, loop_clients, diagnostics_tx, cmd_rx, anomaly_score_read, health_log_write, external_rx, run_stats_write, loop_shutdown).await {
+                error!("❌ Sensor fusion loop error: {}", e);
+            }
+        }),
+    };
 
     // Start WebSocket server with command channel and anomaly score state
-    let ws_server = WebSocketServer::new(config.ws_port, tx.clone(), cmd_tx.clone(), anomaly_score.clone());
-    let server_handle = tokio::spawn(async move {
+    let mut ws_server = WebSocketServer::new(config.ws_port, tx.clone(), latest_rx, cmd_tx.clone(), anomaly_score.clone())
+        .with_health_log(health_log)
+        .with_connection_stats(connection_stats.clone())
+        .with_command_history(config.command_history_capacity);
+    let ekf_unsupported = config.ekf_unsupported_settings();
+    if !ekf_unsupported.is_empty() {
+        ws_server = ws_server.with_unavailable_filter(FilterKind::Ekf, format!("it can't honor {}", ekf_unsupported.join(", ")));
+    }
+    if served_config.is_some() {
+        ws_server = ws_server.with_config(config_rx);
+    }
+    if let (Some(samples), Some(token)) = (external_tx, &config.sensor_source_token) {
+        info!("🛰️  Accepting pushed sensor readings ({:?})", config.sensor_input);
+        ws_server = ws_server
+            .with_sensor_source(token.clone(), samples)
+            .with_external_limits(config.external_limits);
+    }
+    if let Some(precision) = config.output_precision {
+        ws_server = ws_server.with_output_precision(precision);
+    }
+    ws_server = ws_server
+        .with_frame_checksums(config.frame_checksums)
+        .with_timestamp_format(config.timestamp_format)
+        .with_metadata(MetadataConfig {
+            frame_ids: config.frame_ids.clone(),
+            accel_frame: config.accel_frame,
+            every_frame: config.metadata_every_frame,
+        })
+        .with_notices(notices_tx)
+        .with_origin_policy(OriginPolicy::new(allowed_origins(&config), config.allow_missing_origin))
+        .with_shutdown(shutdown_rx.clone());
+    if let Some(secs) = config.ws_keepalive_secs {
+        ws_server = ws_server.with_keepalive(std::time::Duration::from_secs(secs));
+    }
+    #[cfg(unix)]
+    if let Some(path) = &config.ws_unix_socket {
+        ws_server = ws_server.with_unix_socket(path);
+    }
+    if let Some(capacity) = config.long_poll_history {
+        let timeout = std::time::Duration::from_secs(config.long_poll_timeout_secs);
+        ws_server = ws_server.with_long_poll(capacity, timeout);
+    }
+    let mut server_handle = tokio::spawn(async move {
         if let Err(e) = ws_server.run().await {
             error!("❌ WebSocket server error: {}", e);
         }
     });
+    if let Some(port) = config.dashboard_port {
+        // The page connects from the browser, which can't reach a socket file
+        let ws_url = match &config.ws_unix_socket {
+            Some(path) => bail!("dashboard_port can't be served with ws_unix_socket ({}); the page needs the WebSocket server on TCP", path.display()),
+            None => format!("ws://127.0.0.1:{}/stream", config.ws_port),
+        };
+        let dashboard = DashboardServer::new(port, &ws_url).with_shutdown(shutdown_rx);
+        tokio::spawn(async move {
+            if let Err(e) = dashboard.run().await {
+                error!("❌ Dashboard server error: {}", e);
+            }
+        });
+    }
 
     info!("✅ All systems operational");
-    info!("🌐 WebSocket server listening on ws://127.0.0.1:{}", config.ws_port);
+    match &config.ws_unix_socket {
+        Some(path) => info!("🌐 WebSocket server listening on unix:{}", path.display()),
+        None => info!("🌐 WebSocket server listening on ws://127.0.0.1:{}", config.ws_port),
+    }
     info!("📡 Streaming sensor data at {} Hz (IMU) and {} Hz (GPS)", 
           config.imu_frequency, config.gps_frequency);
 
     // Wait for tasks to complete (they shouldn't unless there's an error)
+    // or for the configured run duration to pass
+    let run_duration = std::time::Duration::from_secs(config.duration_secs);
     tokio::select! {
-        result = sensor_handle => {
+        result = &mut sensor_handle => {
             if let Err(e) = result {
                 error!("Sensor task panicked: {}", e);
             }
         }
-        result = server_handle => {
+        result = &mut server_handle => {
             if let Err(e) = result {
                 error!("Server task panicked: {}", e);
             }
         }
+        _ = tokio::time::sleep(run_duration), if !run_duration.is_zero() => {
+            info!("⏱️  Run duration of {:?} reached", run_duration);
+        }
     }
 
+    // Stop the fusion loop (which flushes the sinks) and the server (which
+    // closes its connections), and wait for both
     info!("🛑 Shutting down gracefully");
+    let _ = shutdown_tx.send(true);
+    let stopped = tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+        for (task, handle) in [("Sensor", sensor_handle), ("Server", server_handle)] {
+            if handle.is_finished() {
+                continue;
+            }
+            if let Err(e) = handle.await {
+                error!("{} task panicked: {}", task, e);
+            }
+        }
+    })
+    .await;
+    if stopped.is_err() {
+        warn!("⚠️  Tasks still running after {:?}; exiting anyway", SHUTDOWN_TIMEOUT);
+    }
+
+    // Printed whatever the log level, for scripted runs
+    eprintln!(
+        "📋 Run summary: {:.1}s, {} frames published, peak {} clients ({} connections), {} faults injected, {} health degradations",
+        run_started.elapsed().as_secs_f64(),
+        run_stats.frames.load(Ordering::Relaxed),
+        connection_stats.peak(),
+        connection_stats.total(),
+        run_stats.faults_injected.load(Ordering::Relaxed),
+        run_stats.health_degradations.load(Ordering::Relaxed),
+    );
     Ok(())
 }
 
+/// Longest wait at shutdown for the fusion loop and server to finish
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Counters reported in the end-of-run summary
+#[derive(Debug, Default)]
+struct RunStats {
+    /// Frames fused, whether or not the broadcast rate cap published them
+    fused: AtomicU64,
+    /// Frames published to the sinks
+    frames: AtomicU64,
+    /// Faults injected by command or chaos mode
+    faults_injected: AtomicU64,
+    /// Sensor health degradations
+    health_degradations: AtomicU64,
+}
+
 /// Main sensor fusion loop
 /// 
 /// This function orchestrates sensor simulation, data fusion, command handling, and broadcasting.
+/// It runs until `shutdown` turns true, then closes the sinks. Settings
+/// changed at runtime are written back to `served_config`. Raw readings
+/// are attached to frames only while `clients` counts a client taking
+/// combined frames, or for the built-in anomaly detectors. With
+/// `filter_diag_secs` set, each update's filter internals are published
+/// on `diagnostics`.
+#[allow(clippy::too_many_arguments)]
 async fn run_sensor_fusion_loop(
-    tx: Arc<broadcast::Sender<FusedSensorData>>,
+    mut sinks: SinkSet,
     config: Config,
-    mut cmd_rx: tokio::sync::mpsc::UnboundedReceiver<String>,
+    served_config: tokio::sync::watch::Sender<serde_json::Value>,
+    clients: Arc<ConnectionStats>,
+    diagnostics: tokio::sync::watch::Sender<Option<FilterDiagReport>>,
+    mut cmd_rx: tokio::sync::mpsc::UnboundedReceiver<ControlCommand>,
     anomaly_score: Arc<tokio::sync::RwLock<Option<f64>>>,
+    health_log: Arc<HealthLog>,
+    mut external_rx: Option<tokio::sync::mpsc::Receiver<ExternalSample>>,
+    stats: Arc<RunStats>,
+    mut shutdown: tokio::sync::watch::Receiver<bool>,
 ) -> Result<()> {
     info!("🔧 Initializing sensor simulators and fusion engine");
 
     // Initialize sensor simulators
-    let mut imu = ImuSimulator::new();
+    let mut imu = ImuSimulator::with_config(config.imu.clone())?;
     let mut gps = GpsSimulator::new();
-    let mut filter = ComplementaryFilter::new(config.filter_alpha);
+    imu.set_read_rate(config.imu_frequency);
+    imu.set_rng(config.sensor_rng.build(IMU_STREAM));
+    gps.set_rng(config.sensor_rng.build(GPS_STREAM));
+    let warm_up = std::time::Duration::from_secs(config.sensor_warm_up_secs);
+    imu.set_warm_up(warm_up);
+    gps.set_warm_up(warm_up);
+    gps.set_health_smoothing(config.gps_health_smoothing);
+    let mut filter = build_filter(config.filter, &config);
+    let output = frame_output(&config);
 
     // Calculate time intervals
     let imu_interval = std::time::Duration::from_millis(1000 / config.imu_frequency as u64);
     let gps_interval = std::time::Duration::from_millis(1000 / config.gps_frequency as u64);
 
     let mut imu_ticker = tokio::time::interval(imu_interval);
-    let mut gps_ticker = tokio::time::interval(gps_interval);
+    
+    // Fuse at IMU rate but only publish every Nth frame (capped at IMU rate)
+    let broadcast_every = match config.broadcast_rate_hz {
+        Some(rate) if rate < config.imu_frequency => {
+            let every = (config.imu_frequency as f64 / rate as f64).round().max(1.0) as u64;
+            info!("📡 Broadcasting every {} fused frames (~{:.1} Hz)", every, config.imu_frequency as f64 / every as f64);
+            every
+        }
+        Some(rate) => {
+            if rate > config.imu_frequency {
+                warn!("📡 broadcast_rate_hz {} exceeds IMU rate; broadcasting every frame", rate);
+            }
+            1
+        }
+        None => 1,
+    };
+    let mut fused_count: u64 = 0;
+    // GPS updates, irregular when jitter or dropped fixes are configured
+    let mut gps_schedule = GpsScheduler::new(gps_interval, config.gps_timing);
+    gps_schedule.set_rng(config.sensor_rng.build(GPS_TIMING_STREAM));
+    let gps_timer = tokio::time::sleep(std::time::Duration::ZERO);
+    tokio::pin!(gps_timer);
+
+    // Telemetry summary accumulators (rate, mean confidence, mean health)
+    let summary_interval = std::time::Duration::from_secs(config.telemetry_summary_secs.max(1));
+    let mut summary_ticker = tokio::time::interval(summary_interval);
+    summary_ticker.tick().await; // First tick completes immediately
+    let mut summary_frames: u64 = 0;
+    let mut summary_confidence = 0.0;
+    let mut summary_health = 0.0;
+
+    // Health transition tracking (hysteresis avoids flapping)
+    let mut health_monitor = HealthMonitor::default();
+
+    // Simulation pause state; while paused the simulators are not advanced
+    let mut paused = false;
+    let mut last_frame: Option<FusedSensorData> = None;
+
+    // Anomaly scoring: the in-process detectors, plus the latest ML service
+    // score and when it arrived
+    let mut detectors = DetectorSet::new();
+    if config.builtin_anomaly_detector {
+        detectors.push(ZScoreDetector::new());
+    }
+    if let Some(thresholds) = config.threshold_detector {
+        detectors.push(thresholds);
+    }
+    let mut builtin_score: Option<f64> = None;
+    let mut external_score: Option<(f64, tokio::time::Instant)> = None;
+    let mut ml_scores_live = false;
+
+    // Local grid cells, when configured
+    let mut grid = config.grid_cell_size.map(|size| GridMapper::new(size, config.grid_hysteresis));
+
+    // Simulated readings in flight, and the newest GPS fix to have arrived
+    let mut imu_delay = DelayQueue::new(config.sensor_latency.imu);
+    let mut gps_delay = DelayQueue::new(config.sensor_latency.gps);
+    let mut delayed_gps: Option<GpsData> = None;
+    if !(imu_delay.latency().is_zero() && gps_delay.latency().is_zero()) {
+        info!("⏱️  Simulated sensor latency: IMU {:?}, GPS {:?}", imu_delay.latency(), gps_delay.latency());
+    }
+
+    // Whether a new GPS fix arrived since the last published frame (raw
+    // readings in combined frames flag a repeated fix as stale)
+    let mut gps_fresh = true;
+    // When the latest GPS fix reached the filter (after any simulated
+    // latency), so it can tell a held fix from a stale one
+    let mut gps_fix_at = tokio::time::Instant::now();
+
+    // Latest pushed readings and when they arrived; an IMU reading is
+    // consumed by the tick that fuses it
+    let mut external_imu: Option<ImuData> = None;
+    let mut external_gps: Option<(GpsData, tokio::time::Instant)> = None;
+    let mut last_external_imu: Option<tokio::time::Instant> = None;
+    let mut source_live = false;
+
+    // Chaos mode: randomized fault injection, toggled by command
+    let mut chaos = ChaosSchedule::new(config.chaos.clone(), config.chaos_seed);
+    let mut chaos_enabled = config.chaos_enabled;
+    let mut chaos_event = chaos.next_event();
+    let chaos_timer = tokio::time::sleep(chaos_event.delay);
+    tokio::pin!(chaos_timer);
+    if chaos_enabled {
+        info!("🐒 Chaos mode enabled (seed: {:?})", config.chaos_seed);
+    }
+
+    // Injected faults that clear themselves, each on its own timer
+    let mut fault_timers = FaultTimers::new();
+    let fault_timer = tokio::time::sleep(std::time::Duration::ZERO);
+    tokio::pin!(fault_timer);
 
     info!("✅ Fusion engine initialized with alpha = {}", config.filter_alpha);
 
@@ -144,52 +1065,1603 @@ async fn run_sensor_fusion_loop(
         tokio::select! {
             // High-frequency IMU updates
             _ = imu_ticker.tick() => {
-                let imu_data = imu.read();
-                let gps_data = gps.get_latest();
+                let mut fused_data = if paused {
+                    // Frozen: repeat the last frame with a fresh timestamp
+                    match (&last_frame, config.broadcast_while_paused) {
+                        (Some(frame), true) => {
+                            let mut frame = frame.clone();
+                            frame.timestamp = chrono::Utc::now();
+                            frame
+                        }
+                        _ => continue,
+                    }
+                } else {
+                    let now = tokio::time::Instant::now();
+                    let live = last_external_imu
+                        .is_some_and(|t| now.duration_since(t) < EXTERNAL_SOURCE_TIMEOUT);
+                    if live != source_live && config.sensor_input.accepts_external() {
+                        source_live = live;
+                        if live {
+                            info!("🛰️  External sensor source live");
+                        } else {
+                            warn!("🛰️  External sensor source lost");
+                        }
+                    }
+                    
+                    // A delayed fix is new when it arrives, not when it was taken
+                    if receive_gps(&mut gps_delay, &mut delayed_gps) {
+                        gps_fresh = true;
+                        gps_fix_at = now;
+                    }
+                    
+                    let (imu_data, gps_data) = match (config.sensor_input, external_imu.take()) {
+                        (SensorInput::Simulated, _) => match simulated_imu(&mut imu, &mut imu_delay) {
+                            Some(imu_data) => (imu_data, simulated_gps(&mut gps, &gps_schedule, &gps_delay, &delayed_gps)),
+                            // First reading still in flight
+                            None => continue,
+                        },
+                        (_, Some(imu_data)) => {
+                            let gps_data = match &external_gps {
+                                Some((gps_data, at)) if now.duration_since(*at) < EXTERNAL_GPS_TIMEOUT => gps_data.clone(),
+                                // No recent pushed fix: simulator or dead reckoning
+                                _ if config.sensor_input == SensorInput::ExternalWithFallback => {
+                                    simulated_gps(&mut gps, &gps_schedule, &gps_delay, &delayed_gps)
+                                }
+                                _ => no_fix(),
+                            };
+                            (imu_data, gps_data)
+                        }
+                        // Waiting for the next pushed reading
+                        (SensorInput::External, None) => continue,
+                        (SensorInput::ExternalWithFallback, None) if live => continue,
+                        (SensorInput::ExternalWithFallback, None) => match simulated_imu(&mut imu, &mut imu_delay) {
+                            Some(imu_data) => (imu_data, simulated_gps(&mut gps, &gps_schedule, &gps_delay, &delayed_gps)),
+                            None => continue,
+                        },
+                    };
+                    let (imu_health, gps_health) = (imu_data.health, gps_data.health);
+                    let inputs = (clients.combined() > 0 || !detectors.is_empty()).then(|| {
+                        Arc::new(SensorInputs {
+                            imu: imu_data.clone(),
+                            gps: gps_data.clone(),
+                            gps_stale: !gps_fresh,
+                        })
+                    });
+                    
+                    // Perform sensor fusion
+                    let calibrating = filter.accel_calibrating();
+                    let diverged = filter.position_divergence();
+                    let stale = filter.is_gps_stale();
+                    filter.set_gps_fix_age(now.duration_since(gps_fix_at).as_secs_f64());
+                    let mut fused = filter.update(imu_data, gps_data);
+                    output.apply(&mut fused, filter.as_ref());
+                    match (stale, filter.is_gps_stale()) {
+                        (false, true) => warn!("🛰️  No GPS fix for {:.1} s; GPS is stale", config.gps_timeout_secs),
+                        (true, false) => info!("🛰️  GPS fixes arriving again"),
+                        _ => {}
+                    }
+                    if calibrating && !filter.accel_calibrating() {
+                        let bias = filter.accel_bias();
+                        info!("📏 Accelerometer bias now ({:.3}, {:.3}, {:.3}) m/s²", bias.x, bias.y, bias.z);
+                        update_served_config(&served_config, "accel_bias", bias);
+                    }
+                    match (diverged, filter.position_divergence()) {
+                        (false, true) => warn!("🛰️  GPS position diverges from IMU dead reckoning"),
+                        (true, false) => info!("🛰️  GPS position consistent with IMU again"),
+                        _ => {}
+                    }
+                    config.altitude.apply(&mut fused);
+                    if let Some(grid) = grid.as_mut() {
+                        fused.grid_cell = Some(grid.map(fused.local_position));
+                    }
+                    builtin_score = inputs.as_ref().and_then(|inputs| detectors.score(&fused, &inputs.imu, &inputs.gps));
+                    fused.inputs = inputs;
+                    if config.filter_diag_secs.is_some() {
+                        let mut internals = filter.diagnostics();
+                        if config.sensor_input == SensorInput::Simulated {
+                            internals.set_simulated_gyro_bias(imu.gyro_bias());
+                        }
+                        diagnostics.send_replace(Some(FilterDiagReport {
+                            timestamp: fused.timestamp,
+                            diagnostics: internals,
+                        }));
+                    }
+                    
+                    // Log health degradations and recoveries
+                    for event in health_monitor.observe(imu_health, gps_health, fused.system_health) {
+                        match event.transition {
+                            HealthTransition::Degraded => {
+                                warn!("🩺 {:?} health degraded to {:.2}", event.source, event.health);
+                                stats.health_degradations.fetch_add(1, Ordering::Relaxed);
+                            }
+                            HealthTransition::Recovered => info!("🩺 {:?} health recovered to {:.2}", event.source, event.health),
+                        }
+                        health_log.push(event);
+                    }
+                    fused
+                };
+                
+                // Pick up a score posted by the ML service since the last tick
+                if let Ok(mut posted) = anomaly_score.try_write() {
+                    if let Some(score) = posted.take() {
+                        external_score = Some((score, tokio::time::Instant::now()));
+                    }
+                }
                 
-                // Perform sensor fusion
-                let mut fused_data = filter.update(imu_data, gps_data);
+                // A recent ML score counts as one more detector; without
+                // in-process detectors the last one is held indefinitely
+                let ml_score = external_score
+                    .filter(|(_, at)| detectors.is_empty() || at.elapsed() < EXTERNAL_SCORE_TIMEOUT)
+                    .map(|(score, _)| score);
+                if !detectors.is_empty() && ml_score.is_some() != ml_scores_live {
+                    ml_scores_live = ml_score.is_some();
+                    if ml_scores_live {
+                        info!("🧠 ML service scores received; combining with built-in anomaly detectors");
+                    } else {
+                        warn!("🧠 No recent ML service score; using built-in anomaly detectors alone");
+                    }
+                }
+                fused_data.anomaly_score = match (ml_score, builtin_score) {
+                    (Some(ml), Some(builtin)) => Some(ml.max(builtin)),
+                    (ml, builtin) => ml.or(builtin),
+                };
+                let ml_fresh = external_score.is_some_and(|(_, at)| at.elapsed() < EXTERNAL_SCORE_TIMEOUT);
+                fused_data.set_status_flag(StatusFlags::STALE_ANOMALY, !ml_fresh);
                 
-                // Add latest anomaly score from ML service
-                if let Ok(score) = anomaly_score.try_read() {
-                    fused_data.anomaly_score = *score;
+                summary_frames += 1;
+                summary_confidence += fused_data.confidence;
+                summary_health += fused_data.system_health;
+                
+                last_frame = Some(fused_data.clone());
+                
+                fused_count += 1;
+                stats.fused.fetch_add(1, Ordering::Relaxed);
+                if !fused_count.is_multiple_of(broadcast_every) {
+                    continue;
                 }
                 
-                // Broadcast to all connected clients (non-blocking)
-                let _ = tx.send(fused_data);
+                // Fan out to clients, recorders, and other sinks
+                sinks.send(&fused_data);
+                stats.frames.fetch_add(1, Ordering::Relaxed);
+                gps_fresh = false;
+            }
+            
+            // Periodic fused telemetry summary
+            _ = summary_ticker.tick() => {
+                if summary_frames > 0 {
+                    let n = summary_frames as f64;
+                    info!(
+                        "📊 Telemetry: {:.1} Hz, confidence {:.3}, health {:.3}",
+                        n / summary_interval.as_secs_f64(),
+                        summary_confidence / n,
+                        summary_health / n,
+                    );
+                }
+                summary_frames = 0;
+                summary_confidence = 0.0;
+                summary_health = 0.0;
             }
             
             // Low-frequency GPS updates
-            _ = gps_ticker.tick() => {
-                gps.update();
+            _ = &mut gps_timer => {
+                let next = gps_timer.deadline() + gps_schedule.next_delay();
+                gps_timer.as_mut().reset(next);
+                if !paused && gps_schedule.deliver() {
+                    gps.update();
+                    if gps_delay.latency().is_zero() {
+                        gps_fresh = true;
+                        gps_fix_at = tokio::time::Instant::now();
+                    } else {
+                        gps_delay.push(gps.get_latest(), std::time::Instant::now());
+                    }
+                }
+            }
+            
+            // Readings pushed by an external sensor source
+            Some(sample) = recv_external(&mut external_rx) => {
+                match sample {
+                    ExternalSample::Imu(imu_data) => {
+                        external_imu = Some(imu_data);
+                        last_external_imu = Some(tokio::time::Instant::now());
+                    }
+                    ExternalSample::Gps(gps_data) => {
+                        external_gps = Some((gps_data, tokio::time::Instant::now()));
+                        gps_fresh = true;
+                        gps_fix_at = tokio::time::Instant::now();
+                    }
+                }
+            }
+            
+            // Randomized chaos fault injection/reset
+            _ = &mut chaos_timer, if chaos_enabled && !paused => {
+                if let ChaosAction::Inject(_) = chaos_event.action {
+                    stats.faults_injected.fetch_add(1, Ordering::Relaxed);
+                }
+                apply_chaos_action(chaos_event.action, &mut imu, &mut gps, &fault_timers);
+                chaos_event = chaos.next_event();
+                chaos_timer.as_mut().reset(tokio::time::Instant::now() + chaos_event.delay);
+            }
+            
+            // Timed faults whose duration has run out
+            _ = &mut fault_timer, if !fault_timers.is_empty() => {
+                for fault in fault_timers.expire(std::time::Instant::now()) {
+                    info!("⏲️  Fault duration elapsed, clearing {:?}", fault);
+                    match fault {
+                        ChaosFault::Imu(fault) => imu.clear_fault(fault),
+                        ChaosFault::Gps(fault) => gps.clear_fault(fault),
+                    }
+                }
+                if let Some(deadline) = fault_timers.next_deadline() {
+                    fault_timer.as_mut().reset(tokio::time::Instant::from_std(deadline));
+                }
             }
             
             // Handle fault injection commands from WebSocket clients
             Some(cmd) = cmd_rx.recv() => {
-                info!("⚡ Received command: {}", cmd);
-                match cmd.as_str() {
-                    "accel_spike" => {
+                info!("⚡ Received command: {}", cmd.action);
+                let now = std::time::Instant::now();
+                match LoopCommand::from_name(&cmd.action) {
+                    Some(LoopCommand::AccelSpike) => {
                         info!("💥 Injecting accelerometer spike!");
-                        imu.inject_fault(crate::sensors::imu::FaultType::AccelSpike);
+                        inject_fault(ChaosFault::Imu(FaultType::AccelSpike), cmd.duration.is_some(), &mut imu, &mut gps);
+                        fault_timers.inject(ChaosFault::Imu(FaultType::AccelSpike), cmd.duration, now);
+                        stats.faults_injected.fetch_add(1, Ordering::Relaxed);
                     }
-                    "gyro_spike" => {
+                    Some(LoopCommand::GyroSpike) => {
                         info!("💥 Injecting gyroscope spike!");
-                        imu.inject_fault(crate::sensors::imu::FaultType::GyroSpike);
+                        inject_fault(ChaosFault::Imu(FaultType::GyroSpike), cmd.duration.is_some(), &mut imu, &mut gps);
+                        fault_timers.inject(ChaosFault::Imu(FaultType::GyroSpike), cmd.duration, now);
+                        stats.faults_injected.fetch_add(1, Ordering::Relaxed);
                     }
-                    "high_noise" => {
+                    Some(LoopCommand::HighNoise) => {
                         info!("💥 Injecting high noise!");
-                        imu.inject_fault(crate::sensors::imu::FaultType::HighNoise);
+                        inject_fault(ChaosFault::Imu(FaultType::HighNoise), cmd.duration.is_some(), &mut imu, &mut gps);
+                        fault_timers.inject(ChaosFault::Imu(FaultType::HighNoise), cmd.duration, now);
+                        stats.faults_injected.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Some(LoopCommand::GpsSignalLoss) => {
+                        info!("💥 Injecting GPS signal loss!");
+                        gps.inject_fault(GpsFaultType::SignalLoss);
+                        fault_timers.inject(ChaosFault::Gps(GpsFaultType::SignalLoss), cmd.duration, now);
+                        stats.faults_injected.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Some(LoopCommand::Pause) => {
+                        info!("⏸️  Pausing simulation");
+                        paused = true;
+                    }
+                    Some(LoopCommand::Resume) => {
+                        info!("▶️  Resuming simulation");
+                        paused = false;
+                        filter.reset_timing();
+                        // Readings taken before the pause are stale
+                        imu_delay.clear();
+                        // ...but the held fix didn't age while time stood still
+                        gps_fix_at = tokio::time::Instant::now();
+                    }
+                    Some(LoopCommand::Recenter) => {
+                        let fix = match &external_gps {
+                            Some((gps_data, at)) if at.elapsed() < EXTERNAL_GPS_TIMEOUT => gps_data.clone(),
+                            _ => simulated_gps(&mut gps, &gps_schedule, &gps_delay, &delayed_gps),
+                        };
+                        if filter.recenter(&fix) {
+                            if let Some(grid) = grid.as_mut() {
+                                grid.reset();
+                            }
+                            info!("🎯 Position recentered on GPS fix ({:.6}, {:.6})", fix.latitude, fix.longitude);
+                        } else {
+                            info!("🎯 No GPS fix; position will re-seed from the next fix");
+                        }
+                    }
+                    Some(LoopCommand::ZeroOrientation) => {
+                        info!("📐 Orientation zeroed at the current attitude");
+                        filter.zero_orientation();
+                    }
+                    Some(LoopCommand::ClearReference) => {
+                        info!("📐 Reporting absolute orientation");
+                        filter.clear_orientation_reference();
+                    }
+                    Some(LoopCommand::SetFilter) => {
+                        let kind = cmd.filter.unwrap_or(config.filter);
+                        let unsupported = if kind == FilterKind::Ekf { config.ekf_unsupported_settings() } else { Vec::new() };
+                        if unsupported.is_empty() {
+                            // Start clean, keeping position (and its local origin)
+                            // and the accelerometer bias calibrated so far
+                            let mut next = build_filter(kind, &config);
+                            if let Some(position) = filter.position() {
+                                next.seed_position(position, filter.origin(), filter.position_uncertainty());
+                            }
+                            next.set_accel_bias(filter.accel_bias());
+                            next.start_reconverging();
+                            filter = next;
+                            update_served_config(&served_config, "filter", kind);
+                            info!("🔀 Fusion filter switched to {}; reconverging", kind.name());
+                        } else {
+                            warn!("🔀 Not switching to the {} filter: it can't honor {}", kind.name(), unsupported.join(", "));
+                        }
                     }
-                    "reset" => {
+                    Some(LoopCommand::CalibrateAccel) => {
+                        if filter.start_accel_calibration(config.accel_calibration_samples) {
+                            info!("📏 Calibrating accelerometer bias over {} readings", config.accel_calibration_samples);
+                        } else {
+                            warn!("📏 The {} filter can't calibrate the accelerometer", filter.kind().name());
+                        }
+                    }
+                    Some(LoopCommand::ChaosOn) if !chaos_enabled => {
+                        info!("🐒 Chaos mode enabled");
+                        chaos_enabled = true;
+                        chaos_timer.as_mut().reset(tokio::time::Instant::now() + chaos_event.delay);
+                    }
+                    Some(LoopCommand::ChaosOff) if chaos_enabled => {
+                        info!("🐒 Chaos mode disabled");
+                        chaos_enabled = false;
+                        // Don't leave a chaos fault active
+                        if let ChaosAction::Reset(_) = chaos_event.action {
+                            apply_chaos_action(chaos_event.action, &mut imu, &mut gps, &fault_timers);
+                            chaos_event = chaos.next_event();
+                        }
+                    }
+                    Some(LoopCommand::ChaosOn | LoopCommand::ChaosOff) => {}
+                    Some(LoopCommand::Reset) => {
                         info!("✅ Resetting all faults");
                         imu.reset_faults();
                         gps.reset_faults();
+                        fault_timers.clear();
+                    }
+                    None if config.compound_faults.contains_key(&cmd.action) => {
+                        let action = cmd.action.as_str();
+                        info!("💥 Injecting compound fault {}!", action);
+                        // Names were checked when the config was validated
+                        for fault in config.compound_faults[action].iter().filter_map(|name| ChaosFault::from_name(name)) {
+                            inject_fault(fault, cmd.duration.is_some(), &mut imu, &mut gps);
+                            fault_timers.inject(fault, cmd.duration, now);
+                        }
+                        stats.faults_injected.fetch_add(1, Ordering::Relaxed);
+                    }
+                    None => {
+                        warn!("❓ Unknown command: {}", cmd.action);
+                    }
+                }
+                if let Some(deadline) = fault_timers.next_deadline() {
+                    fault_timer.as_mut().reset(tokio::time::Instant::from_std(deadline));
+                }
+            }
+            
+            _ = shutdown_requested(&mut shutdown) => break,
+        }
+    }
+    
+    info!("🛑 Fusion loop stopped; flushing outputs");
+    sinks.close().await;
+    Ok(())
+}
+
+/// Record a setting changed at runtime in the configuration served to
+/// `get_config` requests
+fn update_served_config(served_config: &tokio::sync::watch::Sender<serde_json::Value>, key: &str, value: impl Serialize) {
+    let Ok(value) = serde_json::to_value(value) else {
+        return;
+    };
+    served_config.send_if_modified(|served| match served.as_object_mut() {
+        Some(fields) => fields.insert(key.to_string(), value.clone()).as_ref() != Some(&value),
+        None => false,
+    });
+}
+
+/// Wait until `shutdown` turns true (forever if its sender is gone)
+async fn shutdown_requested(shutdown: &mut tokio::sync::watch::Receiver<bool>) {
+    if shutdown.wait_for(|stop| *stop).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Pushed IMU readings older than this mean the sensor source is gone
+const EXTERNAL_SOURCE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Pushed GPS fixes older than this are no longer used
+const EXTERNAL_GPS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Most gyro integration substeps accepted per update
+const MAX_INTEGRATION_SUBSTEPS: u32 = 1000;
+
+/// Longest simulated sensor latency accepted
+const MAX_SENSOR_LATENCY: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Longest simulated sensor warm-up accepted (seconds)
+const MAX_SENSOR_WARM_UP_SECS: u64 = 600;
+
+/// A command the fusion loop handles itself
+/// 
+/// Compound faults can't reuse these names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoopCommand {
+    AccelSpike,
+    GyroSpike,
+    HighNoise,
+    GpsSignalLoss,
+    Pause,
+    Resume,
+    Recenter,
+    ZeroOrientation,
+    ClearReference,
+    SetFilter,
+    CalibrateAccel,
+    ChaosOn,
+    ChaosOff,
+    Reset,
+}
+
+impl LoopCommand {
+    /// Name the command is sent by
+    fn name(self) -> &'static str {
+        match self {
+            LoopCommand::AccelSpike => "accel_spike",
+            LoopCommand::GyroSpike => "gyro_spike",
+            LoopCommand::HighNoise => "high_noise",
+            LoopCommand::GpsSignalLoss => "gps_signal_loss",
+            LoopCommand::Pause => "pause",
+            LoopCommand::Resume => "resume",
+            LoopCommand::Recenter => "recenter",
+            LoopCommand::ZeroOrientation => "zero_orientation",
+            LoopCommand::ClearReference => "clear_reference",
+            LoopCommand::SetFilter => "set_filter",
+            LoopCommand::CalibrateAccel => "calibrate_accel",
+            LoopCommand::ChaosOn => "chaos_on",
+            LoopCommand::ChaosOff => "chaos_off",
+            LoopCommand::Reset => "reset",
+        }
+    }
+
+    /// Look a command up by name
+    fn from_name(name: &str) -> Option<Self> {
+        LOOP_COMMANDS.into_iter().find(|command| command.name() == name)
+    }
+}
+
+/// Every command the fusion loop handles
+const LOOP_COMMANDS: [LoopCommand; 14] = [
+    LoopCommand::AccelSpike,
+    LoopCommand::GyroSpike,
+    LoopCommand::HighNoise,
+    LoopCommand::GpsSignalLoss,
+    LoopCommand::Pause,
+    LoopCommand::Resume,
+    LoopCommand::Recenter,
+    LoopCommand::ZeroOrientation,
+    LoopCommand::ClearReference,
+    LoopCommand::SetFilter,
+    LoopCommand::CalibrateAccel,
+    LoopCommand::ChaosOn,
+    LoopCommand::ChaosOff,
+    LoopCommand::Reset,
+];
+
+/// ML service scores older than this give way to the built-in detector
+const EXTERNAL_SCORE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Receive the next pushed reading, or wait forever when not accepting any
+async fn recv_external(
+    external_rx: &mut Option<tokio::sync::mpsc::Receiver<ExternalSample>>,
+) -> Option<ExternalSample> {
+    match external_rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Create the fusion filter `kind`, configured from `config`
+/// 
+/// Most tuning settings belong to the complementary filter; the EKF takes
+/// the GPS yaw speed, GPS timeout, integration substeps and accelerometer
+/// bias (see `Config::ekf_unsupported_settings`). The frame output
+/// settings apply to either, through [`frame_output`].
+fn build_filter(kind: FilterKind, config: &Config) -> Box<dyn FusionFilter> {
+    let mut filter: Box<dyn FusionFilter> = match kind {
+        FilterKind::Complementary => {
+            let mut filter = ComplementaryFilter::new(config.filter_alpha);
+            filter.set_gps_yaw_min_speed(config.gps_yaw_min_speed);
+            filter.set_gps_accel_gate(config.gps_accel_gate);
+            filter.set_velocity_window(config.velocity_window_secs);
+            filter.set_gps_timeout(config.gps_timeout_secs);
+            filter.set_confidence_weights(config.confidence_weights);
+            filter.set_position_weights(config.position_weights);
+            filter.set_position_strategy(config.position_strategy);
+            filter.set_orientation_smoothing(config.orientation_smoothing);
+            filter.set_integration_substeps(config.integration_substeps);
+            filter.set_gyro_deadband(config.gyro_deadband);
+            filter.set_gyro_bias_gain(config.gyro_bias_gain);
+            filter.set_max_rotation_rate(config.max_rotation_rate_dps);
+            filter.set_drift_watchdog(config.position_watchdog);
+            Box::new(filter)
+        }
+        FilterKind::Ekf => {
+            let mut filter = EkfFilter::new();
+            filter.set_gps_yaw_min_speed(config.gps_yaw_min_speed);
+            filter.set_gps_timeout(config.gps_timeout_secs);
+            filter.set_integration_substeps(config.integration_substeps);
+            Box::new(filter)
+        }
+    };
+    filter.set_accel_bias(config.accel_bias);
+    filter
+}
+
+/// Output stages applied to every fused frame, whichever filter made it
+fn frame_output(config: &Config) -> FrameOutput {
+    FrameOutput {
+        max_speed: config.max_speed,
+        confidence_bounds: config.confidence_bounds,
+        gimbal_lock_margin_deg: config.gimbal_lock_margin_deg,
+        world_angular_velocity: config.world_angular_velocity,
+        accel_frame: config.accel_frame,
+    }
+}
+
+/// GPS reading without a fix, so the filter dead-reckons
+fn no_fix() -> GpsData {
+    GpsData {
+        satellites: 0,
+        health: 0.0,
+        ..GpsData::new(0.0, 0.0, 0.0)
+    }
+}
+
+/// Read the simulated IMU through its latency queue (`None` while the
+/// first reading is still in flight)
+/// 
+/// One reading is released per tick so none are skipped; a tick landing
+/// just before a reading is due settles the delay one tick longer.
+fn simulated_imu(imu: &mut ImuSimulator, delay: &mut DelayQueue<ImuData>) -> Option<ImuData> {
+    let now = std::time::Instant::now();
+    delay.push(imu.read(), now);
+    delay.pop_ready(now)
+}
+
+/// Take the newest delayed GPS fix whose latency has elapsed into
+/// `arrived`, returning whether one did
+fn receive_gps(delay: &mut DelayQueue<GpsData>, arrived: &mut Option<GpsData>) -> bool {
+    match delay.latest_ready(std::time::Instant::now()) {
+        Some(fix) => {
+            *arrived = Some(fix);
+            true
+        }
+        None => false,
+    }
+}
+
+/// The simulated GPS fix the fusion loop sees: a fresh reading, or with
+/// latency configured, the newest delayed fix to have arrived (no fix once
+/// too many updates in a row were dropped)
+fn simulated_gps(
+    gps: &mut GpsSimulator,
+    schedule: &GpsScheduler,
+    delay: &DelayQueue<GpsData>,
+    arrived: &Option<GpsData>,
+) -> GpsData {
+    if schedule.fix_lost() {
+        return no_fix();
+    }
+    if delay.latency().is_zero() {
+        return gps.get_latest();
+    }
+    arrived.clone().unwrap_or_else(no_fix)
+}
+
+/// Inject `fault` into whichever simulator it belongs to
+/// 
+/// `held` keeps an IMU spike in every reading until it is cleared, for a
+/// fault given a duration; otherwise the spike shows in one reading.
+fn inject_fault(fault: ChaosFault, held: bool, imu: &mut ImuSimulator, gps: &mut GpsSimulator) {
+    match fault {
+        ChaosFault::Imu(fault) if held => imu.inject_held_fault(fault),
+        ChaosFault::Imu(fault) => imu.inject_fault(fault),
+        ChaosFault::Gps(fault) => gps.inject_fault(fault),
+    }
+}
+
+/// Perform a chaos mode inject or reset on the simulators
+/// 
+/// A reset clears only the fault chaos mode injected, leaving faults
+/// injected by command in effect. A fault with a running timer is left
+/// for the timer to clear.
+fn apply_chaos_action(action: ChaosAction, imu: &mut ImuSimulator, gps: &mut GpsSimulator, timers: &FaultTimers) {
+    match action {
+        ChaosAction::Reset(fault) if timers.contains(fault) => {
+            info!("🐒 Chaos: leaving timed {:?} to clear on schedule", fault);
+        }
+        ChaosAction::Inject(ChaosFault::Imu(fault)) => {
+            info!("🐒 Chaos: injecting IMU {:?}", fault);
+            // Held until chaos mode resets it
+            imu.inject_held_fault(fault);
+        }
+        ChaosAction::Inject(ChaosFault::Gps(fault)) => {
+            info!("🐒 Chaos: injecting GPS {:?}", fault);
+            gps.inject_fault(fault);
+        }
+        ChaosAction::Reset(ChaosFault::Imu(fault)) => {
+            info!("🐒 Chaos: clearing IMU {:?}", fault);
+            imu.clear_fault(fault);
+        }
+        ChaosAction::Reset(ChaosFault::Gps(fault)) => {
+            info!("🐒 Chaos: clearing GPS {:?}", fault);
+            gps.clear_fault(fault);
+        }
+    }
+}
+
+/// Vibration spectrum loop
+/// 
+/// Buffers the acceleration magnitude of published frames and pushes a
+/// `spectrum` message to clients every interval once the window is full.
+async fn run_spectrum_loop(
+    mut frames: broadcast::Receiver<FusedSensorData>,
+    notices: broadcast::Sender<Arc<str>>,
+    config: SpectrumConfig,
+) {
+    let mut analyzer = SpectrumAnalyzer::new(config.window);
+    let mut ticker = tokio::time::interval(config.interval);
+    ticker.tick().await; // First tick completes immediately
+    
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok(frame) => analyzer.push(frame.timestamp, frame.raw_acceleration.magnitude()),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    // A gap would smear the spectrum; start a fresh window
+                    warn!("🎵 Spectrum analysis lagged, skipped {} frames", skipped);
+                    analyzer.clear();
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            
+            _ = ticker.tick() => {
+                let Some(spectrum) = analyzer.spectrum() else {
+                    continue;
+                };
+                match serde_json::to_string(&spectrum) {
+                    Ok(json) => {
+                        let _ = notices.send(json.into());
                     }
-                    _ => {
-                        warn!("❓ Unknown command: {}", cmd);
+                    Err(e) => warn!("🎵 Failed to serialize spectrum: {}", e),
+                }
+            }
+        }
+    }
+}
+
+/// Field statistics loop
+/// 
+/// Tracks published frames over the configured window and pushes a
+/// `field_stats` message to clients every interval.
+async fn run_field_stats_loop(
+    mut frames: broadcast::Receiver<FusedSensorData>,
+    notices: broadcast::Sender<Arc<str>>,
+    config: FieldStatsConfig,
+) {
+    let mut stats = FieldStats::new(config.window);
+    let mut ticker = tokio::time::interval(config.interval);
+    ticker.tick().await; // First tick completes immediately
+    
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok(frame) => stats.push(&frame),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("📈 Field statistics lagged, skipped {} frames", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            
+            _ = ticker.tick() => {
+                let Some(report) = stats.report() else {
+                    continue;
+                };
+                match serde_json::to_string(&report) {
+                    Ok(json) => {
+                        let _ = notices.send(json.into());
                     }
+                    Err(e) => warn!("📈 Failed to serialize field statistics: {}", e),
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Filter diagnostics loop
+/// 
+/// Pushes the latest diagnostics the fusion loop published as a
+/// `filter_diag` message to clients every interval, unless none arrived
+/// since the last one. The replay loop publishes none, so nothing is sent
+/// during replay.
+async fn run_filter_diag_loop(
+    mut diagnostics: tokio::sync::watch::Receiver<Option<FilterDiagReport>>,
+    notices: broadcast::Sender<Arc<str>>,
+    interval: std::time::Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await; // First tick completes immediately
+    
+    loop {
+        ticker.tick().await;
+        match diagnostics.has_changed() {
+            Ok(true) => {}
+            Ok(false) => continue,
+            // Fusion loop gone
+            Err(_) => return,
+        }
+        let Some(report) = *diagnostics.borrow_and_update() else {
+            continue;
+        };
+        match serde_json::to_string(&report) {
+            Ok(json) => {
+                let _ = notices.send(json.into());
+            }
+            Err(e) => warn!("🔬 Failed to serialize filter diagnostics: {}", e),
+        }
+    }
+}
+
+/// Replay loop
+/// 
+/// Broadcasts recorded frames paced by their recorded timestamps, scaled by
+/// the replay source's playback speed. Ends when the recording is exhausted
+/// or `shutdown` turns true, closing the sinks.
+async fn run_replay_loop(
+    mut sinks: SinkSet,
+    mut replay: ReplaySource,
+    mut cmd_rx: tokio::sync::mpsc::UnboundedReceiver<ControlCommand>,
+    anomaly_score: Arc<tokio::sync::RwLock<Option<f64>>>,
+    stats: Arc<RunStats>,
+    mut shutdown: tokio::sync::watch::Receiver<bool>,
+) -> Result<()> {
+    // Speed to restore when a paused replay is resumed
+    let mut resume_speed = if replay.playback_speed() > 0.0 { replay.playback_speed() } else { 1.0 };
+
+    loop {
+        tokio::select! {
+            frame = replay.next() => {
+                let Some(mut fused_data) = frame else {
+                    info!("📼 Replay finished");
+                    break;
+                };
+                
+                // Live ML scores still apply to replayed data
+                if let Ok(score) = anomaly_score.try_read() {
+                    if score.is_some() {
+                        fused_data.anomaly_score = *score;
+                    }
+                }
+                
+                sinks.send(&fused_data);
+                stats.frames.fetch_add(1, Ordering::Relaxed);
+            }
+            
+            // Only simulation control applies to a replay
+            Some(cmd) = cmd_rx.recv() => {
+                match LoopCommand::from_name(&cmd.action) {
+                    Some(LoopCommand::Pause) => {
+                        info!("⏸️  Pausing replay");
+                        if replay.playback_speed() > 0.0 {
+                            resume_speed = replay.playback_speed();
+                        }
+                        replay.set_playback_speed(0.0)?;
+                    }
+                    Some(LoopCommand::Resume) => {
+                        info!("▶️  Resuming replay at {}x", resume_speed);
+                        replay.set_playback_speed(resume_speed)?;
+                    }
+                    Some(_) => {
+                        warn!("❓ Command not available during replay: {}", cmd.action);
+                    }
+                    None => {
+                        warn!("❓ Unknown command: {}", cmd.action);
+                    }
+                }
+            }
+            
+            _ = shutdown_requested(&mut shutdown) => break,
+        }
+    }
+    
+    sinks.close().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// A fusion loop running on simulated sensors, publishing to a channel
+    struct LoopHarness {
+        frames: broadcast::Receiver<FusedSensorData>,
+        commands: tokio::sync::mpsc::UnboundedSender<ControlCommand>,
+        stats: Arc<RunStats>,
+        health_log: Arc<HealthLog>,
+        /// Client counts the loop reads; take a combined count to get raw
+        /// readings on frames without the built-in detectors
+        clients: Arc<ConnectionStats>,
+        /// Filter diagnostics the loop publishes
+        diagnostics: tokio::sync::watch::Receiver<Option<FilterDiagReport>>,
+        /// Configuration the loop serves to get_config requests
+        config: tokio::sync::watch::Receiver<serde_json::Value>,
+        shutdown: tokio::sync::watch::Sender<bool>,
+        task: tokio::task::JoinHandle<Result<()>>,
+    }
+
+    impl LoopHarness {
+        fn spawn(config: Config) -> Self {
+            Self::spawn_with_external(config, None)
+        }
+
+        /// Spawn a loop fed by a pushed-sample channel, returning its sender
+        fn spawn_external(config: Config) -> (Self, tokio::sync::mpsc::Sender<ExternalSample>) {
+            let (samples, external_rx) = tokio::sync::mpsc::channel(256);
+            (Self::spawn_with_external(config, Some(external_rx)), samples)
+        }
+
+        fn spawn_with_external(config: Config, external_rx: Option<tokio::sync::mpsc::Receiver<ExternalSample>>) -> Self {
+            let (tx, frames) = broadcast::channel(1024);
+            let (latest_tx, _) = tokio::sync::watch::channel(FusedSensorData::default());
+            let mut sinks = SinkSet::new();
+            sinks.push(Box::new(BroadcastSink::new(Arc::new(tx), latest_tx)));
+            let (commands, cmd_rx) = tokio::sync::mpsc::unbounded_channel();
+            let stats = Arc::new(RunStats::default());
+            let health_log = Arc::new(HealthLog::new(config.health_log_capacity));
+            let (shutdown, shutdown_rx) = tokio::sync::watch::channel(false);
+            let (config_tx, served_config) = tokio::sync::watch::channel(serde_json::to_value(&config).unwrap());
+            let clients = Arc::new(ConnectionStats::new());
+            let (diagnostics_tx, diagnostics) = tokio::sync::watch::channel(None);
+            let task = tokio::spawn(run_sensor_fusion_loop(
+                sinks,
+                config,
+                config_tx,
+                clients.clone(),
+                diagnostics_tx,
+                cmd_rx,
+                Arc::new(tokio::sync::RwLock::new(None)),
+                health_log.clone(),
+                external_rx,
+                stats.clone(),
+                shutdown_rx,
+            ));
+            Self { frames, commands, stats, health_log, clients, diagnostics, config: served_config, shutdown, task }
+        }
+
+        fn send(&self, command: ControlCommand) {
+            self.commands.send(command).unwrap();
+        }
+
+        async fn next_frame(&mut self) -> FusedSensorData {
+            tokio::time::timeout(std::time::Duration::from_secs(2), self.frames.recv())
+                .await
+                .expect("no frame within 2s")
+                .unwrap()
+        }
+
+        /// Frames published over the next `millis`, after discarding any
+        /// already queued
+        async fn frames_for(&mut self, millis: u64) -> Vec<FusedSensorData> {
+            self.frames = self.frames.resubscribe();
+            tokio::time::sleep(std::time::Duration::from_millis(millis)).await;
+            let mut frames = Vec::new();
+            while let Ok(frame) = self.frames.try_recv() {
+                frames.push(frame);
+            }
+            frames
+        }
+    }
+
+    impl Drop for LoopHarness {
+        fn drop(&mut self) {
+            self.task.abort();
+        }
+    }
+
+    /// Fresh scratch directory for one test
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sensor-fusion-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_log_file_receives_lines() {
+        let dir = scratch_dir("log-file");
+        let (layer, guard) = log_file_layer(&dir).unwrap();
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            info!("📊 Telemetry: 50.0 Hz, confidence 0.900, health 1.000");
+        });
+        drop(guard);
+
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(files.len(), 1);
+        let name = files[0].file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("sensor-fusion.log."), "unexpected log file {name}");
+        let contents = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(contents.contains("Telemetry: 50.0 Hz"), "log file missing line: {contents:?}");
+        assert!(!contents.contains('\x1b'), "log file contains ANSI escapes");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pause_freezes_orientation_until_resume() {
+        let mut harness = LoopHarness::spawn(Config::default());
+        harness.next_frame().await;
+
+        harness.send(ControlCommand::new("pause"));
+        // Let the command land before sampling
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let paused = harness.frames_for(300).await;
+        assert!(paused.len() >= 5, "paused frames still broadcast by default");
+        assert!(
+            paused.windows(2).all(|pair| pair[0].euler_degrees == pair[1].euler_degrees && pair[0].position == pair[1].position),
+            "orientation evolved while paused"
+        );
+        assert!(paused.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp));
+
+        harness.send(ControlCommand::new("resume"));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let resumed = harness.frames_for(300).await;
+        assert!(
+            resumed.windows(2).any(|pair| pair[0].euler_degrees != pair[1].euler_degrees),
+            "orientation didn't evolve after resume"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pause_without_broadcast_publishes_nothing() {
+        let config = Config {
+            broadcast_while_paused: false,
+            ..Config::default()
+        };
+        let mut harness = LoopHarness::spawn(config);
+        harness.next_frame().await;
+
+        harness.send(ControlCommand::new("pause"));
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let published = harness.stats.frames.load(Ordering::Relaxed);
+        assert!(harness.frames_for(300).await.is_empty());
+        assert_eq!(harness.stats.frames.load(Ordering::Relaxed), published);
+
+        harness.send(ControlCommand::new("resume"));
+        harness.next_frame().await;
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_rate_cap_publishes_every_nth_fused_frame() {
+        let config = Config {
+            imu_frequency: 50,
+            broadcast_rate_hz: Some(10),
+            ..Config::default()
+        };
+        let mut harness = LoopHarness::spawn(config);
+        for _ in 0..5 {
+            harness.next_frame().await;
+        }
+
+        // Stop the loop so the counters are final, then collect what the
+        // subscription (open since before the first frame) still holds
+        harness.shutdown.send(true).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(2), &mut harness.task)
+            .await
+            .expect("loop still running after shutdown")
+            .unwrap()
+            .unwrap();
+        let mut received = 5;
+        while harness.frames.try_recv().is_ok() {
+            received += 1;
+        }
+
+        // Every 5th fused frame (50 Hz / 10 Hz) is published, and only those
+        let fused = harness.stats.fused.load(Ordering::Relaxed);
+        let published = harness.stats.frames.load(Ordering::Relaxed);
+        assert_eq!(published, fused / 5, "fused {fused} frames but published {published}");
+        assert_eq!(received, published);
+    }
+
+    #[tokio::test]
+    async fn test_injected_fault_logs_health_degradation() {
+        use sensor_fusion_backend::fusion::health::HealthSource;
+
+        let mut harness = LoopHarness::spawn(Config::default());
+        harness.next_frame().await;
+        assert!(harness.health_log.events().is_empty());
+
+        let injected_at = chrono::Utc::now();
+        harness.send(ControlCommand::new("gps_signal_loss"));
+        let mut degraded = None;
+        for _ in 0..200 {
+            harness.next_frame().await;
+            degraded = harness.health_log.events().into_iter().find(|e| e.source == HealthSource::Gps);
+            if degraded.is_some() {
+                break;
+            }
+        }
+
+        let event = degraded.expect("GPS signal loss never logged a degradation");
+        assert_eq!(event.transition, HealthTransition::Degraded);
+        assert!(event.health < 0.5);
+        assert!(event.timestamp >= injected_at);
+        assert!(harness.stats.health_degradations.load(Ordering::Relaxed) >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_compound_fault_degrades_imu_and_gps_together() {
+        // RMS change of acceleration between the last frames: sensor noise
+        // dominates the smooth simulated motion
+        fn accel_jitter(frames: &[FusedSensorData]) -> f64 {
+            let accel: Vec<f64> = frames.iter().rev().take(25).map(|f| f.inputs.as_ref().unwrap().imu.acceleration.x).collect();
+            let sum: f64 = accel.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum();
+            (sum / (accel.len() - 1) as f64).sqrt()
+        }
+        fn last_hdop(frames: &[FusedSensorData]) -> f64 {
+            frames.last().unwrap().inputs.as_ref().unwrap().gps.hdop
+        }
+
+        let mut harness = LoopHarness::spawn(Config::default());
+        harness.next_frame().await;
+
+        harness.send(ControlCommand::new("power_glitch"));
+        let faulted = harness.frames_for(1500).await;
+        assert!(accel_jitter(&faulted) > 0.3, "IMU noise not raised: {}", accel_jitter(&faulted));
+        assert!(last_hdop(&faulted) > 5.0, "GPS accuracy not degraded: {}", last_hdop(&faulted));
+        assert_eq!(harness.stats.faults_injected.load(Ordering::Relaxed), 1);
+
+        // One reset clears every component
+        harness.send(ControlCommand::new("reset"));
+        let recovered = harness.frames_for(1500).await;
+        assert!(accel_jitter(&recovered) < 0.3, "IMU noise still raised: {}", accel_jitter(&recovered));
+        assert!(last_hdop(&recovered) < 5.0, "GPS accuracy still degraded: {}", last_hdop(&recovered));
+    }
+
+    #[test]
+    fn test_compound_faults_must_name_known_faults() {
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+
+        config.compound_faults.insert("brownout".to_string(), vec!["gps_jamming".to_string()]);
+        assert!(config.validate().is_err());
+
+        config.compound_faults.insert("brownout".to_string(), Vec::new());
+        assert!(config.validate().is_err());
+
+        config.compound_faults.clear();
+        config.compound_faults.insert("reset".to_string(), vec!["high_noise".to_string()]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_loop_commands_are_found_by_their_own_names() {
+        let mut names = std::collections::HashSet::new();
+        for command in LOOP_COMMANDS {
+            assert_eq!(LoopCommand::from_name(command.name()), Some(command));
+            assert!(names.insert(command.name()), "{} listed twice", command.name());
+
+            // None can be taken over by a compound fault
+            let mut config = Config::default();
+            config.compound_faults.insert(command.name().to_string(), vec!["high_noise".to_string()]);
+            assert!(config.validate().is_err(), "compound fault shadows {}", command.name());
+        }
+        assert_eq!(LoopCommand::from_name("power_glitch"), None);
+    }
+
+    #[test]
+    fn test_confidence_bounds_need_floor_below_ceiling() {
+        let mut config = Config { confidence_bounds: ConfidenceBounds { floor: 0.05, ceiling: 0.99 }, ..Config::default() };
+        assert!(config.validate().is_ok());
+
+        config.confidence_bounds = ConfidenceBounds { floor: 0.9, ceiling: 0.1 };
+        assert!(matches!(config.validate(), Err(SensorFusionError::Config(message)) if message.contains("confidence_bounds")));
+
+        config.confidence_bounds = ConfidenceBounds { floor: 0.0, ceiling: 1.5 };
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_the_loop() {
+        let mut harness = LoopHarness::spawn(Config::default());
+        harness.next_frame().await;
+
+        harness.shutdown.send(true).unwrap();
+        let result = tokio::time::timeout(std::time::Duration::from_secs(2), &mut harness.task)
+            .await
+            .expect("loop still running after shutdown");
+        assert!(result.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_accel_spike_raises_builtin_anomaly_score() {
+        // Seeded, so the spike drawn is the same every run, and fast so the
+        // rolling statistics settle quickly
+        let config = Config {
+            imu_frequency: 200,
+            sensor_rng: SimRngConfig { seed: Some(3), ..SimRngConfig::default() },
+            ..Config::default()
+        };
+        let mut harness = LoopHarness::spawn(config);
+
+        // Let the detector settle on nominal readings (no ML service posts
+        // scores)
+        let mut frame = None;
+        for _ in 0..250 {
+            frame = Some(harness.next_frame().await);
+        }
+        let baseline = frame.and_then(|frame| frame.anomaly_score).expect("no built-in score after warm-up");
+        assert!(baseline < 0.1, "score {baseline:.2} before the spike");
+
+        harness.send(ControlCommand::new("accel_spike"));
+        let mut peak = 0.0_f64;
+        for _ in 0..25 {
+            peak = peak.max(harness.next_frame().await.anomaly_score.unwrap());
+        }
+        assert!(peak > 0.9, "score peaked at {peak:.2} after an accel spike");
+        assert_eq!(harness.stats.faults_injected.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pushed_imu_reading_reaches_raw_acceleration() {
+        let config = Config {
+            sensor_input: SensorInput::External,
+            sensor_source_token: Some("secret".to_string()),
+            ..Config::default()
+        };
+        let (mut harness, samples) = LoopHarness::spawn_external(config);
+
+        let pushed = Vec3::new(1.25, -0.5, 9.5);
+        samples.send(ExternalSample::Imu(ImuData::new(pushed, Vec3::zero()))).await.unwrap();
+        let frame = harness.next_frame().await;
+        let raw = frame.raw_acceleration;
+        assert_eq!((raw.x, raw.y, raw.z), (pushed.x, pushed.y, pushed.z));
+    }
+
+    #[tokio::test]
+    async fn test_combined_frames_nest_time_aligned_readings() {
+        use sensor_fusion_backend::websocket::client::{ClientEncoder, ClientSettings, FrameFormat};
+
+        let mut harness = LoopHarness::spawn(Config::default());
+        let _combined = harness.clients.take_combined();
+        let frames = harness.frames_for(1500).await;
+        let mut encoder = ClientEncoder::new(None);
+        encoder.set_settings(ClientSettings { frame_format: FrameFormat::Combined, ..ClientSettings::default() });
+
+        let mut stale = Vec::new();
+        for frame in &frames {
+            let json = encoder.encode(frame, std::time::Instant::now()).unwrap().unwrap();
+            let combined: serde_json::Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(combined["type"], "combined");
+            let [imu, gps, fused] = ["imu", "gps", "fused"].map(|key| &combined[key]);
+            assert!(imu.is_object() && gps.is_object() && fused.is_object(), "{json}");
+
+            // The IMU reading is from this tick: stamped within one 20 ms
+            // sample period before fusion, and the GPS fix no later
+            assert_eq!(combined["timestamp"], fused["timestamp"]);
+            let time = |value: &serde_json::Value| value.as_str().unwrap().parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+            let imu_lag = time(&fused["timestamp"]) - time(&imu["timestamp"]);
+            assert!((0..20).contains(&imu_lag.num_milliseconds()), "IMU reading {imu_lag} before fusion: {json}");
+            assert!(time(&gps["timestamp"]) <= time(&fused["timestamp"]), "GPS fix from the future: {json}");
+            stale.push(combined["gps_stale"].as_bool().unwrap());
+        }
+        // 1 Hz GPS under 50 Hz IMU: mostly repeats of the latest fix, flagged
+        assert!(stale.contains(&false), "no fresh GPS fix in {} frames", stale.len());
+        assert!(stale.iter().filter(|&&s| s).count() > stale.len() / 2);
+    }
+
+    #[tokio::test]
+    async fn test_filter_diag_reports_configured_alpha() {
+        let config = Config {
+            filter_alpha: 0.9,
+            filter_diag_secs: Some(1),
+            ..Config::default()
+        };
+        let mut harness = LoopHarness::spawn(config);
+        let (notices_tx, mut notices) = broadcast::channel(16);
+        let diag = tokio::spawn(run_filter_diag_loop(harness.diagnostics.clone(), notices_tx, std::time::Duration::from_millis(100)));
+
+        let notice = tokio::time::timeout(std::time::Duration::from_secs(2), notices.recv()).await.unwrap().unwrap();
+        diag.abort();
+        let report: serde_json::Value = serde_json::from_str(&notice).unwrap();
+        assert_eq!(report["type"], "filter_diag");
+        assert_eq!(report["diagnostics"]["filter"], "complementary");
+        assert_eq!(report["diagnostics"]["alpha"], 0.9);
+        assert!(report["diagnostics"]["effective_alpha"].as_f64().is_some_and(|alpha| alpha >= 0.9));
+        assert!(report["diagnostics"]["simulated_gyro_bias"]["x"].is_number());
+
+        // Diagnostics are only published when they are enabled
+        harness.next_frame().await;
+        assert!(harness.diagnostics.borrow().is_some());
+        let mut plain = LoopHarness::spawn(Config::default());
+        plain.next_frame().await;
+        assert!(plain.diagnostics.borrow().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_set_filter_keeps_streaming_and_flags_reconvergence() {
+        let config = Config {
+            filter_alpha: 0.9,
+            filter_diag_secs: Some(1),
+            ..Config::default()
+        };
+        let mut harness = LoopHarness::spawn(config);
+        // Let position settle on the first fixes
+        let before = harness.frames_for(300).await;
+        let mut before = before.last().expect("no frames before switching").clone();
+        assert!(!before.reconverging);
+        let report = harness.diagnostics.borrow().expect("no diagnostics before switching");
+        assert_eq!(serde_json::to_value(report.diagnostics).unwrap()["filter"], "complementary");
+
+        for kind in [FilterKind::Ekf, FilterKind::Complementary] {
+            harness.send(ControlCommand::set_filter(kind));
+            let after = harness.frames_for(1000).await;
+            let first = after.iter().position(|frame| frame.reconverging).expect("switch never flagged");
+            assert!(after[first].status_flags.contains(StatusFlags::RECONVERGING));
+            assert!(!after.last().unwrap().reconverging, "{} still reconverging after 1 s", kind.name());
+
+            // Position carried over rather than re-seeded, diagnostics keep
+            // coming from the new filter
+            let jump = (after[first].local_position.x - before.local_position.x).hypot(after[first].local_position.y - before.local_position.y);
+            assert!(jump < 5.0, "position jumped {jump} m on the switch to {}", kind.name());
+            let report = harness.diagnostics.borrow().expect("diagnostics stopped");
+            assert!(report.timestamp >= after.last().unwrap().timestamp, "diagnostics stopped");
+            assert_eq!(serde_json::to_value(report.diagnostics).unwrap()["filter"], kind.name());
+            assert_eq!(harness.config.borrow()["filter"], kind.name());
+            before = after.last().unwrap().clone();
+        }
+    }
+
+    #[test]
+    fn test_ekf_rejects_settings_it_cant_honor() {
+        // Output settings apply to either filter
+        let mut config = Config {
+            filter: FilterKind::Ekf,
+            max_speed: Some(20.0),
+            gimbal_lock_margin_deg: 5.0,
+            world_angular_velocity: true,
+            integration_substeps: 4,
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.gyro_deadband = 0.01;
+        config.position_watchdog = Some(DriftWatchdogConfig::default());
+        let Err(SensorFusionError::Config(message)) = config.validate() else { panic!("ekf accepted gyro_deadband") };
+        assert!(message.contains("gyro_deadband, position_watchdog"), "{message}");
+
+        config.filter = FilterKind::Complementary;
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_set_filter_refuses_ekf_with_settings_it_cant_honor() {
+        let config = Config { gyro_deadband: 0.01, ..Config::default() };
+        let mut harness = LoopHarness::spawn(config);
+        harness.next_frame().await;
+        harness.send(ControlCommand::set_filter(FilterKind::Ekf));
+        let frames = harness.frames_for(300).await;
+        assert!(frames.iter().all(|frame| !frame.reconverging), "switched anyway");
+        assert_eq!(harness.config.borrow()["filter"], "complementary");
+    }
+
+    #[tokio::test]
+    async fn test_ekf_frames_get_the_output_stages() {
+        let config = Config {
+            filter: FilterKind::Ekf,
+            max_speed: Some(0.0),
+            world_angular_velocity: true,
+            ..Config::default()
+        };
+        let mut harness = LoopHarness::spawn(config);
+        for frame in harness.frames_for(200).await {
+            assert_eq!(frame.velocity.magnitude(), 0.0);
+            assert!(frame.world_angular_velocity.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_confidence_bounds_hold_after_switching_to_the_ekf() {
+        // A zero-width range pins every frame's confidence
+        let config = Config { confidence_bounds: ConfidenceBounds { floor: 0.42, ceiling: 0.42 }, ..Config::default() };
+        let mut harness = LoopHarness::spawn(config);
+        assert!(harness.frames_for(200).await.iter().all(|frame| frame.confidence == 0.42));
+
+        harness.send(ControlCommand::set_filter(FilterKind::Ekf));
+        let frames = harness.frames_for(500).await;
+        let switched = frames.iter().position(|frame| frame.reconverging).expect("switch never flagged");
+        assert_eq!(harness.config.borrow()["filter"], "ekf");
+        for frame in &frames[switched..] {
+            assert_eq!(frame.confidence, 0.42);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ekf_reports_world_frame_acceleration() {
+        // Simulated IMU mounted rolled 40°: gravity spread over body y and z
+        let config = Config {
+            filter: FilterKind::Ekf,
+            accel_frame: AccelFrame::World,
+            imu: ImuConfig {
+                mounting: sensor_fusion_backend::models::Quaternion::from_euler(40f64.to_radians(), 0.0, 0.0),
+                ..ImuConfig::default()
+            },
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+        let mut harness = LoopHarness::spawn(config);
+        let frames = harness.frames_for(2000).await;
+        let settled: Vec<_> = frames.iter().filter(|frame| !frame.unsettled_world_accel).collect();
+        assert!(settled.len() > frames.len() / 2, "{} of {} frames settled", settled.len(), frames.len());
+        let n = settled.len() as f64;
+        let mean = |axis: fn(&Vec3) -> f64| settled.iter().map(|frame| axis(&frame.raw_acceleration)).sum::<f64>() / n;
+        let mean = Vec3::new(mean(|a| a.x), mean(|a| a.y), mean(|a| a.z));
+        assert!(mean.x.abs() < 0.3 && mean.y.abs() < 0.3, "horizontal gravity leak {mean:?}");
+        assert!((mean.z - 9.81).abs() < 0.3, "vertical {mean:?}");
+    }
+
+    #[tokio::test]
+    async fn test_raw_readings_are_gathered_only_for_combined_clients() {
+        let config = Config { builtin_anomaly_detector: false, ..Config::default() };
+        let mut harness = LoopHarness::spawn(config);
+        assert!(harness.next_frame().await.inputs.is_none());
+
+        let combined = harness.clients.take_combined();
+        assert!(harness.frames_for(100).await.iter().all(|frame| frame.inputs.is_some()));
+
+        drop(combined);
+        assert!(harness.frames_for(100).await.iter().all(|frame| frame.inputs.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_delayed_gps_fix_is_fresh_when_it_arrives() {
+        let config = Config {
+            sensor_latency: SensorLatency { imu: std::time::Duration::ZERO, gps: std::time::Duration::from_millis(300) },
+            ..Config::default()
+        };
+        let mut harness = LoopHarness::spawn(config);
+        let _combined = harness.clients.take_combined();
+        let frames = harness.frames_for(2500).await;
+
+        // Every frame flagged fresh carries a fix the previous one didn't
+        let inputs: Vec<_> = frames.iter().map(|frame| frame.inputs.clone().unwrap()).collect();
+        let fresh: Vec<_> = inputs.windows(2).filter(|pair| !pair[1].gps_stale).collect();
+        assert!(!fresh.is_empty(), "no fresh fix in {} frames", frames.len());
+        for pair in fresh {
+            assert_ne!(pair[0].gps.timestamp, pair[1].gps.timestamp, "repeated fix flagged fresh");
+        }
+    }
+
+    #[test]
+    fn test_gps_latency_delivers_fixes_with_measurement_timestamps() {
+        let latency = std::time::Duration::from_millis(100);
+        let mut gps = GpsSimulator::new();
+        let schedule = GpsScheduler::new(std::time::Duration::from_millis(200), GpsTiming::default());
+        let mut delay = DelayQueue::new(latency);
+        let mut arrived = None;
+
+        gps.update();
+        let measured = gps.get_latest();
+        delay.push(measured.clone(), std::time::Instant::now());
+        assert!(!receive_gps(&mut delay, &mut arrived));
+        let seen = simulated_gps(&mut gps, &schedule, &delay, &arrived);
+        assert_eq!(seen.satellites, 0, "fix visible before its latency elapsed");
+
+        std::thread::sleep(latency + std::time::Duration::from_millis(20));
+        assert!(receive_gps(&mut delay, &mut arrived));
+        let seen = simulated_gps(&mut gps, &schedule, &delay, &arrived);
+        assert_eq!(seen.timestamp, measured.timestamp);
+        let age = chrono::Utc::now() - seen.timestamp;
+        assert!(age >= chrono::Duration::milliseconds(100), "fix only {age} old on arrival");
+    }
+
+    #[test]
+    fn test_chaos_reset_keeps_commanded_faults() {
+        let mut imu = ImuSimulator::new();
+        let mut gps = GpsSimulator::new();
+        gps.inject_fault(GpsFaultType::SignalLoss);
+
+        let chaos_fault = ChaosFault::Gps(GpsFaultType::PoorAccuracy);
+        let timers = FaultTimers::new();
+        apply_chaos_action(ChaosAction::Inject(chaos_fault), &mut imu, &mut gps, &timers);
+        apply_chaos_action(ChaosAction::Reset(chaos_fault), &mut imu, &mut gps, &timers);
+
+        assert!(gps.get_latest().satellites < 4, "chaos reset cleared the commanded signal loss");
+    }
+
+    #[test]
+    fn test_chaos_reset_leaves_timed_fault_to_its_timer() {
+        let mut imu = ImuSimulator::new();
+        let mut gps = GpsSimulator::new();
+        let fault = ChaosFault::Gps(GpsFaultType::SignalLoss);
+        let mut timers = FaultTimers::new();
+        gps.inject_fault(GpsFaultType::SignalLoss);
+        timers.inject(fault, Some(std::time::Duration::from_secs(30)), std::time::Instant::now());
+
+        // Chaos happens to inject and reset the same fault meanwhile
+        apply_chaos_action(ChaosAction::Inject(fault), &mut imu, &mut gps, &timers);
+        apply_chaos_action(ChaosAction::Reset(fault), &mut imu, &mut gps, &timers);
+        assert!(gps.get_latest().satellites < 4, "chaos reset cut the timed signal loss short");
+    }
+
+    #[tokio::test]
+    async fn test_gps_goes_stale_only_when_fixes_stop() {
+        // Regular 1 Hz fixes never reach the timeout
+        let config = Config { gps_timeout_secs: 1.5, ..Config::default() };
+        let mut harness = LoopHarness::spawn(config.clone());
+        let frames = harness.frames_for(2500).await;
+        assert!(frames.iter().all(|frame| !frame.stale_gps), "regular fixes flagged stale");
+
+        // Every update dropped: the startup fix is held, then goes stale
+        let config = Config { gps_timing: GpsTiming { drop_probability: 1.0, ..GpsTiming::default() }, ..config };
+        let mut harness = LoopHarness::spawn(config);
+        let frames = harness.frames_for(2500).await;
+        let first = frames.iter().position(|frame| frame.stale_gps).expect("held fix never flagged stale");
+        assert!(first > 0 && frames[first..].iter().all(|frame| frame.stale_gps));
+        assert!(frames[first].status_flags.contains(StatusFlags::STALE_GPS));
+    }
+
+    #[tokio::test]
+    async fn test_position_stays_bounded_with_most_gps_fixes_dropped() {
+        let config = Config {
+            gps_timing: GpsTiming { drop_probability: 0.9, ..GpsTiming::default() },
+            sensor_rng: SimRngConfig { seed: Some(11), ..SimRngConfig::default() },
+            ..Config::default()
+        };
+        let mut harness = LoopHarness::spawn(config);
+        let frames = harness.frames_for(5000).await;
+
+        let position = |frame: &FusedSensorData| (frame.local_position.x, frame.local_position.y);
+        for frame in &frames {
+            let (x, y) = position(frame);
+            assert!(x.hypot(y) < 50.0, "position ({x:.1}, {y:.1}) m ran away");
+            assert!(frame.position_uncertainty.is_finite());
+        }
+
+        // Past the fix-loss gap the filter dead-reckons from the last good
+        // fix instead of drifting off
+        assert!(frames.iter().any(|frame| frame.dead_reckoning), "no dead reckoning in {} frames", frames.len());
+        let mut anchor = None;
+        for frame in &frames {
+            if !frame.dead_reckoning {
+                anchor = None;
+                continue;
+            }
+            let (x0, y0) = *anchor.get_or_insert(position(frame));
+            let (x, y) = position(frame);
+            assert!((x - x0).hypot(y - y0) < 1.0, "dead reckoning drifted to ({x:.2}, {y:.2}) m from ({x0:.2}, {y0:.2})");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gps_fix_age_counts_from_delivery() {
+        // Fixes reach the filter 2 s after they're taken: none has arrived
+        // by the 1.5 s timeout, though the first was taken at startup
+        let config = Config {
+            gps_timeout_secs: 1.5,
+            sensor_latency: SensorLatency { imu: std::time::Duration::ZERO, gps: std::time::Duration::from_secs(2) },
+            ..Config::default()
+        };
+        let mut harness = LoopHarness::spawn(config);
+        let frames = harness.frames_for(3500).await;
+        assert!(frames.iter().any(|frame| frame.stale_gps), "fix in flight counted as delivered");
+
+        // Then 1 Hz deliveries keep it fresh
+        assert!(frames[frames.len() - 50..].iter().all(|frame| !frame.stale_gps), "delivered fixes flagged stale");
+    }
+
+    #[tokio::test]
+    async fn test_timed_fault_clears_after_its_duration() {
+        let mut harness = LoopHarness::spawn(Config::default());
+        let mut fixed = false;
+        for _ in 0..100 {
+            fixed = harness.next_frame().await.gps_fix_valid;
+            if fixed {
+                break;
+            }
+        }
+        assert!(fixed, "no GPS fix before the fault");
+
+        let duration = std::time::Duration::from_secs(1);
+        harness.send(ControlCommand::new("gps_signal_loss").with_duration(Some(duration)));
+        let during = harness.frames_for(700).await;
+        assert!(during.last().is_some_and(|frame| !frame.gps_fix_valid), "signal loss never took effect");
+
+        // Cleared with no reset command; the 1 Hz GPS picks the fix back up
+        let after = harness.frames_for(1500).await;
+        assert!(after.last().is_some_and(|frame| frame.gps_fix_valid), "fault still in effect after its duration");
+    }
+
+    #[test]
+    fn test_server_starts_on_current_thread_runtime() {
+        let config = Config {
+            current_thread_runtime: true,
+            ..Config::default()
+        };
+        let runtime = build_runtime(&config).unwrap();
+        runtime.block_on(async {
+            assert_eq!(tokio::runtime::Handle::current().runtime_flavor(), tokio::runtime::RuntimeFlavor::CurrentThread);
+            let mut harness = LoopHarness::spawn(config);
+            harness.next_frame().await;
+
+            let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+            let (tx, _) = broadcast::channel(16);
+            let (_latest_tx, latest_rx) = tokio::sync::watch::channel(FusedSensorData::default());
+            let (cmd_tx, _cmd_rx) = tokio::sync::mpsc::unbounded_channel();
+            let server = WebSocketServer::new(port, Arc::new(tx), latest_rx, Arc::new(cmd_tx), Arc::new(tokio::sync::RwLock::new(None)));
+            let server = tokio::spawn(server.run());
+            let url = format!("ws://127.0.0.1:{}/", port);
+            let mut connected = None;
+            for _ in 0..100 {
+                if let Ok((ws, _)) = tokio_tungstenite::connect_async(&url).await {
+                    connected = Some(ws);
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            let mut ws = connected.expect("server never accepted a connection");
+            let welcome = tokio::time::timeout(std::time::Duration::from_secs(2), futures_util::StreamExt::next(&mut ws))
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            assert!(welcome.to_text().unwrap().contains(r#""type":"connection""#));
+            server.abort();
+        });
+    }
+
+    #[tokio::test]
+    async fn test_served_config_is_redacted_and_follows_runtime_changes() {
+        let config = Config {
+            filter_alpha: 0.95,
+            sensor_input: SensorInput::External,
+            sensor_source_token: Some("secret".to_string()),
+            accel_calibration_samples: 10,
+            ..Config::default()
+        };
+        let (mut harness, samples) = LoopHarness::spawn_external(config);
+        {
+            let served = harness.config.borrow();
+            assert_eq!(served["ws_port"], 8080);
+            assert_eq!(served["filter_alpha"], 0.95);
+            assert_eq!(served["imu_frequency"], 50);
+            assert_eq!(served["filter"], "complementary");
+            assert_eq!(served["sensor_source_token"], "<redacted>");
+            assert!(!served.to_string().contains("secret"));
+            assert_eq!(served["accel_bias"], serde_json::json!({"x": 0.0, "y": 0.0, "z": 0.0}));
+        }
+
+        // A calibration finishing changes the bias in effect. The command
+        // may be picked up after the first samples, so keep them coming.
+        harness.send(ControlCommand::new("calibrate_accel"));
+        let biased = ImuData::new(Vec3::new(0.2, -0.1, 9.81), Vec3::zero());
+        for _ in 0..100 {
+            if harness.config.has_changed().unwrap() {
+                break;
+            }
+            samples.send(ExternalSample::Imu(biased.clone())).await.unwrap();
+            harness.next_frame().await;
+        }
+        assert!(harness.config.has_changed().unwrap(), "served config never updated");
+        assert_ne!(harness.config.borrow()["accel_bias"], serde_json::json!({"x": 0.0, "y": 0.0, "z": 0.0}));
+    }
+
+    #[test]
+    fn test_dashboard_on_a_unix_socket_rejected() {
+        let config = Config {
+            ws_unix_socket: Some(PathBuf::from("/tmp/fusion.sock")),
+            dashboard_port: Some(8081),
+            ..Config::default()
+        };
+        assert!(matches!(config.validate(), Err(SensorFusionError::Config(message)) if message.contains("ws_unix_socket")));
+    }
+
+    #[test]
+    fn test_zero_worker_threads_rejected() {
+        let config = Config {
+            worker_threads: Some(0),
+            ..Config::default()
+        };
+        assert!(matches!(config.validate(), Err(SensorFusionError::Config(message)) if message.contains("worker_threads")));
+        assert!(build_runtime(&config).is_err());
+    }
+}
+
@@ -5,6 +5,16 @@
 
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A slice had the wrong number of components for the target type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("expected {expected} components, got {actual}")]
+pub struct ComponentCountError {
+    pub expected: usize,
+    pub actual: usize,
+}
 
 /// 3D vector representation for acceleration, rotation, and position
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -25,20 +35,114 @@ impl Vec3 {
         Self::new(0.0, 0.0, 0.0)
     }
 
+    /// Create a vector from `[x, y, z]`
+    pub fn from_array([x, y, z]: [f64; 3]) -> Self {
+        Self::new(x, y, z)
+    }
+
+    /// Components as `[x, y, z]`
+    pub fn to_array(self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+
     /// Calculate magnitude of the vector
     pub fn magnitude(&self) -> f64 {
         (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
     }
 
+    /// Check that all components are finite (not NaN or infinite)
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
+    /// Replace non-finite components with zero
+    pub fn finite_or_zero(&self) -> Self {
+        Self::new(finite_or(self.x, 0.0), finite_or(self.y, 0.0), finite_or(self.z, 0.0))
+    }
+
     /// Normalize the vector to unit length
     pub fn normalize(&self) -> Self {
         let mag = self.magnitude();
-        if mag > 0.0 {
+        if mag > 0.0 && mag.is_finite() {
             Self::new(self.x / mag, self.y / mag, self.z / mag)
         } else {
             Self::zero()
         }
     }
+
+    /// Linear interpolation from `self` (t = 0) to `other` (t = 1)
+    pub fn lerp(self, other: Vec3, t: f64) -> Self {
+        Self::new(
+            self.x + t * (other.x - self.x),
+            self.y + t * (other.y - self.y),
+            self.z + t * (other.z - self.z),
+        )
+    }
+
+    /// Scale the vector down to at most `max` magnitude, keeping its direction
+    /// 
+    /// A `max` of zero (or a negative or NaN one) yields the zero vector.
+    pub fn clamp_magnitude(self, max: f64) -> Self {
+        if max.is_nan() || max <= 0.0 {
+            return Self::zero();
+        }
+        
+        let mag = self.magnitude();
+        if mag <= max {
+            self
+        } else if mag.is_finite() {
+            let scale = max / mag;
+            Self::new(self.x * scale, self.y * scale, self.z * scale)
+        } else {
+            Self::zero()
+        }
+    }
+}
+
+impl Default for Vec3 {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl From<[f64; 3]> for Vec3 {
+    fn from(components: [f64; 3]) -> Self {
+        Self::from_array(components)
+    }
+}
+
+impl TryFrom<&[f64]> for Vec3 {
+    type Error = ComponentCountError;
+
+    /// Build from a slice of exactly three components `[x, y, z]`
+    fn try_from(components: &[f64]) -> Result<Self, Self::Error> {
+        <[f64; 3]>::try_from(components)
+            .map(Self::from_array)
+            .map_err(|_| ComponentCountError { expected: 3, actual: components.len() })
+    }
+}
+
+/// Meters per degree of latitude (and of longitude at the equator)
+pub const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Convert a geodetic position to local east/north/up meters from an origin
+/// 
+/// Uses a flat-earth approximation around the origin, which is accurate
+/// to well under a meter within a few kilometers.
+pub fn geodetic_to_enu(origin: (f64, f64, f64), position: (f64, f64, f64)) -> Vec3 {
+    let north = (position.0 - origin.0) * METERS_PER_DEGREE;
+    let east = (position.1 - origin.1) * METERS_PER_DEGREE * origin.0.to_radians().cos();
+    let up = position.2 - origin.2;
+    Vec3::new(east, north, up)
+}
+
+/// Convert local east/north/up meters from an origin back to geodetic
+/// 
+/// Inverse of `geodetic_to_enu`, using the same flat-earth approximation.
+pub fn enu_to_geodetic(origin: (f64, f64, f64), local: Vec3) -> (f64, f64, f64) {
+    let latitude = origin.0 + local.y / METERS_PER_DEGREE;
+    let longitude = origin.1 + local.x / (METERS_PER_DEGREE * origin.0.to_radians().cos());
+    (latitude, longitude, origin.2 + local.z)
 }
 
 /// Quaternion representation for 3D orientation
@@ -63,8 +167,40 @@ impl Quaternion {
         Self::new(1.0, 0.0, 0.0, 0.0)
     }
 
+    /// Create a quaternion from `[w, x, y, z]` (scalar first)
+    pub fn from_array([w, x, y, z]: [f64; 4]) -> Self {
+        Self::new(w, x, y, z)
+    }
+
+    /// Components as `[w, x, y, z]` (scalar first)
+    pub fn to_array(self) -> [f64; 4] {
+        [self.w, self.x, self.y, self.z]
+    }
+
+    /// Check that all components are finite (not NaN or infinite)
+    pub fn is_finite(&self) -> bool {
+        self.w.is_finite() && self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
+    /// Create from Euler angles (roll, pitch, yaw) in radians
+    /// 
+    /// Rotations apply yaw first, then pitch, then roll (intrinsic Z-Y-X),
+    /// the inverse of `to_euler`.
+    pub fn from_euler(roll: f64, pitch: f64, yaw: f64) -> Self {
+        let (sr, cr) = (roll / 2.0).sin_cos();
+        let (sp, cp) = (pitch / 2.0).sin_cos();
+        let (sy, cy) = (yaw / 2.0).sin_cos();
+
+        Self::new(
+            cr * cp * cy + sr * sp * sy,
+            sr * cp * cy - cr * sp * sy,
+            cr * sp * cy + sr * cp * sy,
+            cr * cp * sy - sr * sp * cy,
+        )
+    }
+
     /// Convert to Euler angles (roll, pitch, yaw) in radians
-    pub fn to_euler(&self) -> (f64, f64, f64) {
+    pub fn to_euler(self) -> (f64, f64, f64) {
         // Roll (x-axis rotation)
         let sinr_cosp = 2.0 * (self.w * self.x + self.y * self.z);
         let cosr_cosp = 1.0 - 2.0 * (self.x * self.x + self.y * self.y);
@@ -85,6 +221,225 @@ impl Quaternion {
 
         (roll, pitch, yaw)
     }
+
+    /// Rotate a vector from the body frame into the reference frame
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        // v' = v + 2w(u × v) + 2u × (u × v), with u the vector part
+        let (w, x, y, z) = (self.w, self.x, self.y, self.z);
+        let tx = 2.0 * (y * v.z - z * v.y);
+        let ty = 2.0 * (z * v.x - x * v.z);
+        let tz = 2.0 * (x * v.y - y * v.x);
+        Vec3::new(
+            v.x + w * tx + (y * tz - z * ty),
+            v.y + w * ty + (z * tx - x * tz),
+            v.z + w * tz + (x * ty - y * tx),
+        )
+    }
+
+    /// Angle in radians (0 to π) of the rotation taking `self` to `other`
+    /// 
+    /// The geodesic distance `2·acos(|q₁·q₂|)`: `q` and `-q` describe the
+    /// same orientation, so they are 0 apart. Components are normalized
+    /// first, so slightly denormalized inputs don't push `acos` out of its
+    /// domain.
+    pub fn angle_to(&self, other: &Quaternion) -> f64 {
+        let dot = self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z;
+        let norms = (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+            * (other.w * other.w + other.x * other.x + other.y * other.y + other.z * other.z).sqrt();
+        2.0 * (dot / norms).abs().min(1.0).acos()
+    }
+
+    /// Spherical linear interpolation from `self` (t = 0) to `other` (t = 1)
+    /// 
+    /// Always takes the shorter arc, and falls back to normalized linear
+    /// interpolation when the two rotations are nearly identical.
+    pub fn slerp(self, other: Quaternion, t: f64) -> Quaternion {
+        // Ensure we take the shorter path
+        let mut dot = self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z;
+        let other = if dot < 0.0 {
+            dot = -dot;
+            Quaternion::new(-other.w, -other.x, -other.y, -other.z)
+        } else {
+            other
+        };
+
+        // If quaternions are very close, use linear interpolation
+        if dot > 0.9995 {
+            let result = Quaternion::new(
+                self.w + t * (other.w - self.w),
+                self.x + t * (other.x - self.x),
+                self.y + t * (other.y - self.y),
+                self.z + t * (other.z - self.z),
+            );
+            let norm = (result.w * result.w + result.x * result.x + result.y * result.y + result.z * result.z).sqrt();
+            return Quaternion::new(result.w / norm, result.x / norm, result.y / norm, result.z / norm);
+        }
+
+        // Calculate interpolation coefficients
+        let theta = dot.acos();
+        let sin_theta = theta.sin();
+        let w1 = ((1.0 - t) * theta).sin() / sin_theta;
+        let w2 = (t * theta).sin() / sin_theta;
+
+        Quaternion::new(
+            w1 * self.w + w2 * other.w,
+            w1 * self.x + w2 * other.x,
+            w1 * self.y + w2 * other.y,
+            w1 * self.z + w2 * other.z,
+        )
+    }
+
+    /// Re-express an aerospace orientation in the ROS convention
+    /// 
+    /// `self` rotates an FRD body (x forward, y right, z down) into an NED
+    /// world (x north, y east, z down), as the fusion filter computes it.
+    /// The result rotates an FLU body (x forward, y left, z up) into an ENU
+    /// world (x east, y north, z up), as in ROS REP 103:
+    /// `q_enu = q(NED→ENU) · q · q(FLU→FRD)`, which works out to
+    /// `((w + z), (x + y), (x − y), (w − z)) / √2`.
+    pub fn ned_to_enu(self) -> Quaternion {
+        let (w, x, y, z) = (self.w, self.x, self.y, self.z);
+        let s = std::f64::consts::FRAC_1_SQRT_2;
+        Quaternion::new(s * (w + z), s * (x + y), s * (x - y), s * (w - z))
+    }
+
+    /// Inverse rotation: the conjugate, scaled for non-unit quaternions
+    /// 
+    /// Falls back to identity for a zero or non-finite quaternion.
+    pub fn inverse(self) -> Quaternion {
+        let norm_sq = self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z;
+        if !(norm_sq.is_finite() && norm_sq > 0.0) {
+            return Self::identity();
+        }
+        Self::new(self.w / norm_sq, -self.x / norm_sq, -self.y / norm_sq, -self.z / norm_sq)
+    }
+}
+
+impl std::ops::Mul for Quaternion {
+    type Output = Quaternion;
+
+    /// Hamilton product: the rotation `rhs` followed by `self`
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        Quaternion::new(
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        )
+    }
+}
+
+impl From<[f64; 4]> for Quaternion {
+    fn from(components: [f64; 4]) -> Self {
+        Self::from_array(components)
+    }
+}
+
+impl TryFrom<&[f64]> for Quaternion {
+    type Error = ComponentCountError;
+
+    /// Build from a slice of exactly four components `[w, x, y, z]`
+    fn try_from(components: &[f64]) -> Result<Self, Self::Error> {
+        <[f64; 4]>::try_from(components)
+            .map(Self::from_array)
+            .map_err(|_| ComponentCountError { expected: 4, actual: components.len() })
+    }
+}
+
+/// How timestamps are written in outgoing JSON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    /// RFC 3339 string with nanoseconds, e.g. `"2024-12-07T10:30:00.123456789Z"`
+    #[default]
+    Rfc3339,
+    
+    /// Integer milliseconds since the Unix epoch, e.g. `1733567400123`
+    /// 
+    /// Anything finer than a millisecond is truncated.
+    EpochMillis,
+}
+
+/// Serde helpers for `DateTime<Utc>` fields
+/// 
+/// Timestamps are read from either an RFC 3339 string or integer epoch
+/// milliseconds, so data written in either [`TimestampFormat`] reads back.
+pub mod timestamp {
+    use chrono::{DateTime, Utc};
+    use serde::{de, Deserializer, Serializer};
+    use std::fmt;
+    
+    /// Serialize as integer milliseconds since the Unix epoch
+    pub fn serialize_millis<S: Serializer>(timestamp: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(timestamp.timestamp_millis())
+    }
+    
+    /// Deserialize from an RFC 3339 string or integer epoch milliseconds
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+    
+    struct TimestampVisitor;
+    
+    impl de::Visitor<'_> for TimestampVisitor {
+        type Value = DateTime<Utc>;
+        
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an RFC 3339 timestamp or integer epoch milliseconds")
+        }
+        
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            DateTime::parse_from_rfc3339(v)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(E::custom)
+        }
+        
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+            DateTime::from_timestamp_millis(v)
+                .ok_or_else(|| E::custom(format!("epoch milliseconds {v} out of range")))
+        }
+        
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            let millis = i64::try_from(v)
+                .map_err(|_| E::custom(format!("epoch milliseconds {v} out of range")))?;
+            self.visit_i64(millis)
+        }
+    }
+}
+
+/// Serde helper writing a `Duration` as fractional seconds
+pub mod duration_secs {
+    use serde::Serializer;
+    use std::time::Duration;
+    
+    /// Serialize as seconds, e.g. `0.25`
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+}
+
+/// A timestamp tagged with the format it is serialized in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WireTimestamp {
+    /// Written as an RFC 3339 string
+    Rfc3339(DateTime<Utc>),
+    
+    /// Written as integer epoch milliseconds
+    EpochMillis(
+        #[serde(serialize_with = "timestamp::serialize_millis", deserialize_with = "timestamp::deserialize")]
+        DateTime<Utc>,
+    ),
+}
+
+impl WireTimestamp {
+    /// Tag a timestamp with the format to write it in
+    pub fn new(timestamp: DateTime<Utc>, format: TimestampFormat) -> Self {
+        match format {
+            TimestampFormat::Rfc3339 => WireTimestamp::Rfc3339(timestamp),
+            TimestampFormat::EpochMillis => WireTimestamp::EpochMillis(timestamp),
+        }
+    }
 }
 
 /// Raw IMU (Inertial Measurement Unit) sensor data
@@ -93,6 +448,7 @@ impl Quaternion {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImuData {
     /// Timestamp of the measurement
+    #[serde(default = "Utc::now", deserialize_with = "timestamp::deserialize")]
     pub timestamp: DateTime<Utc>,
     
     /// Linear acceleration in m/s² (includes gravity)
@@ -102,9 +458,11 @@ pub struct ImuData {
     pub gyroscope: Vec3,
     
     /// Estimated noise level (0.0 = no noise, 1.0 = high noise)
+    #[serde(default)]
     pub noise_level: f64,
     
     /// Sensor health status (0.0 = failed, 1.0 = healthy)
+    #[serde(default = "full_health")]
     pub health: f64,
 }
 
@@ -125,6 +483,7 @@ impl ImuData {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GpsData {
     /// Timestamp of the measurement
+    #[serde(default = "Utc::now", deserialize_with = "timestamp::deserialize")]
     pub timestamp: DateTime<Utc>,
     
     /// Latitude in degrees
@@ -137,9 +496,11 @@ pub struct GpsData {
     pub altitude: f64,
     
     /// Ground speed in m/s
+    #[serde(default)]
     pub speed: f64,
     
     /// Heading in degrees (0-360, where 0 is North)
+    #[serde(default)]
     pub heading: f64,
     
     /// Horizontal dilution of precision (lower is better)
@@ -149,6 +510,7 @@ pub struct GpsData {
     pub satellites: u8,
     
     /// Sensor health status (0.0 = failed, 1.0 = healthy)
+    #[serde(default = "full_health")]
     pub health: f64,
 }
 
@@ -169,12 +531,84 @@ impl GpsData {
     }
 }
 
+/// Vertical reference of a reported altitude
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AltitudeDatum {
+    /// Meters above mean sea level (what GPS receivers report)
+    #[default]
+    Msl,
+    
+    /// Meters above the WGS84 ellipsoid
+    Ellipsoid,
+    
+    /// Meters above the configured ground elevation
+    Agl,
+}
+
+/// Converts mean-sea-level altitudes to a chosen datum
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct AltitudeReference {
+    /// Datum to report altitude in
+    pub datum: AltitudeDatum,
+    
+    /// Geoid height above the ellipsoid at the operating area (m), used for
+    /// `Ellipsoid`
+    pub geoid_separation: f64,
+    
+    /// Ground elevation above mean sea level (m), used for `Agl`
+    pub ground_elevation: f64,
+}
+
+impl AltitudeReference {
+    /// Convert an altitude above mean sea level to this datum
+    /// 
+    /// AGL is reported as-is when below the configured ground (negative).
+    pub fn convert(&self, msl: f64) -> f64 {
+        match self.datum {
+            AltitudeDatum::Msl => msl,
+            AltitudeDatum::Ellipsoid => msl + self.geoid_separation,
+            AltitudeDatum::Agl => msl - self.ground_elevation,
+        }
+    }
+    
+    /// Convert a fused frame's altitude (MSL) and tag it with the datum
+    pub fn apply(&self, frame: &mut FusedSensorData) {
+        frame.position.2 = self.convert(frame.position.2);
+        frame.altitude_datum = self.datum;
+    }
+}
+
+/// Square cell of the local grid (see [`crate::fusion::GridMapper`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GridCell {
+    /// Cells east of the fusion origin (negative to the west)
+    pub x: i64,
+    
+    /// Cells north of the fusion origin (negative to the south)
+    pub y: i64,
+}
+
+/// Version of the fused frame wire format, sent in the connection message
+/// 
+/// Bumped whenever a frame field is renamed, removed, or changes meaning
+/// or units. New optional fields don't bump it; clients ignore fields they
+/// don't know.
+pub const SCHEMA_VERSION: u32 = 1;
+
 /// Fused sensor data after processing through fusion algorithm
 /// 
 /// This is the primary data structure streamed to clients and ML services.
+/// 
+/// Every `f64` field produced by the fusion filter is guaranteed finite:
+/// serde_json writes NaN/infinity as `null`, which clients cannot read back
+/// into a number. Non-finite sensor inputs are rejected by the filter
+/// (the previous estimate is held) and raw readings, GPS speed/heading,
+/// confidence, and health are reported as `0.0` when they aren't finite.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FusedSensorData {
     /// Timestamp of the fused estimate
+    #[serde(deserialize_with = "timestamp::deserialize")]
     pub timestamp: DateTime<Utc>,
     
     /// Estimated orientation as quaternion
@@ -186,15 +620,40 @@ pub struct FusedSensorData {
     /// Estimated position (latitude, longitude, altitude)
     pub position: (f64, f64, f64),
     
+    /// Vertical reference of the altitude in `position`
+    #[serde(default)]
+    pub altitude_datum: AltitudeDatum,
+    
+    /// Estimated position in meters east/north/up of the fusion origin
+    /// (the first valid GPS fix)
+    #[serde(default)]
+    pub local_position: Vec3,
+    
+    /// Grid cell containing `local_position`; only present when a grid
+    /// cell size is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid_cell: Option<GridCell>,
+    
     /// Estimated linear velocity in m/s
     pub velocity: Vec3,
     
-    /// Raw accelerometer reading
+    /// Raw accelerometer reading, gravity included; in the body frame
+    /// unless the filter is set to report it in the world frame
     pub raw_acceleration: Vec3,
     
     /// Raw gyroscope reading
     pub raw_gyroscope: Vec3,
     
+    /// Raw gyroscope reading rotated into the world frame by the estimated
+    /// orientation (rad/s), for debugging; only present when enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub world_angular_velocity: Option<Vec3>,
+    
+    /// Gyroscope bias (rad/s) the filter currently estimates and subtracts
+    /// from `raw_gyroscope` before integrating; zero until estimated
+    #[serde(default)]
+    pub estimated_gyro_bias: Vec3,
+    
     /// GPS ground speed in m/s
     pub gps_speed: f64,
     
@@ -207,8 +666,83 @@ pub struct FusedSensorData {
     /// Overall system health (0.0 = critical, 1.0 = healthy)
     pub system_health: f64,
     
+    /// Health and fault flags packed into one word (see [`StatusFlags`])
+    /// 
+    /// The canonical source for the boolean flags below, which mirror it
+    /// for readability; set both through `set_status_flags`.
+    #[serde(default)]
+    pub status_flags: StatusFlags,
+    
+    /// True while position is dead-reckoned because GPS has no fix
+    #[serde(default)]
+    pub dead_reckoning: bool,
+    
+    /// Estimated horizontal position error in meters (1σ); grows during
+    /// dead-reckoning
+    #[serde(default)]
+    pub position_uncertainty: f64,
+    
+    /// True while the gyroscope yaw rate grossly disagrees with the GPS
+    /// course change rate (checked only while moving with a GPS fix)
+    #[serde(default)]
+    pub sensor_consistency_fault: bool,
+    
+    /// True while pitch is near ±90°, where roll and yaw in
+    /// `euler_degrees` are unreliable (the quaternion stays valid)
+    #[serde(default)]
+    pub gimbal_lock_warning: bool,
+    
+    /// True while the GPS fix is usable for positioning
+    #[serde(default)]
+    pub gps_fix_valid: bool,
+    
+    /// True while no recent ML service score backs `anomaly_score` (it is
+    /// held, from the built-in detector, or missing)
+    #[serde(default)]
+    pub stale_anomaly: bool,
+    
+    /// True while the drift watchdog sees the GPS position diverging from
+    /// IMU dead reckoning (a GPS jump or spoofing); confidence is reduced
+    #[serde(default)]
+    pub position_divergence: bool,
+    
+    /// True while a newly switched-in fusion filter is still converging;
+    /// its orientation and velocity may be off until it clears
+    #[serde(default)]
+    pub reconverging: bool,
+    
+    /// True while the GPS fix is older than the filter's timeout (no new
+    /// fix arrived); GPS then counts less toward position and confidence
+    #[serde(default)]
+    pub stale_gps: bool,
+    
+    /// True while `raw_acceleration` is reported in the world frame but the
+    /// orientation estimate rotating it hasn't settled yet (start-up or a
+    /// filter switch), so its axes may be off
+    #[serde(default)]
+    pub unsettled_world_accel: bool,
+    
     /// Anomaly score from ML service (if available)
     pub anomaly_score: Option<f64>,
+    
+    /// Raw readings this frame was fused from, for combined frames
+    /// 
+    /// Never serialized, so recorded and replayed frames don't have them.
+    #[serde(skip)]
+    pub inputs: Option<Arc<SensorInputs>>,
+}
+
+/// Raw readings a fused frame was computed from
+#[derive(Debug, Clone)]
+pub struct SensorInputs {
+    /// IMU reading fused in this tick
+    pub imu: ImuData,
+    
+    /// GPS fix fused in this tick (the latest one, even if not new)
+    pub gps: GpsData,
+    
+    /// No new GPS fix arrived since the previous published frame
+    pub gps_stale: bool,
 }
 
 impl FusedSensorData {
@@ -220,14 +754,131 @@ impl FusedSensorData {
             orientation: Quaternion::identity(),
             euler_degrees: euler,
             position: (0.0, 0.0, 0.0),
+            altitude_datum: AltitudeDatum::Msl,
+            local_position: Vec3::zero(),
+            grid_cell: None,
             velocity: Vec3::zero(),
             raw_acceleration: Vec3::zero(),
             raw_gyroscope: Vec3::zero(),
+            world_angular_velocity: None,
+            estimated_gyro_bias: Vec3::zero(),
             gps_speed: 0.0,
             gps_heading: 0.0,
             confidence: 1.0,
             system_health: 1.0,
+            status_flags: StatusFlags::empty(),
+            dead_reckoning: false,
+            position_uncertainty: 0.0,
+            sensor_consistency_fault: false,
+            gimbal_lock_warning: false,
+            gps_fix_valid: false,
+            stale_anomaly: false,
+            position_divergence: false,
+            reconverging: false,
+            stale_gps: false,
+            unsettled_world_accel: false,
             anomaly_score: None,
+            inputs: None,
+        }
+    }
+
+    /// Create a frame with the given orientation (radians, as for
+    /// `Quaternion::from_euler`), other fields as in `new`
+    /// 
+    /// The angles are normalized first, so both fields describe the same
+    /// rotation the same way: angles are wrapped to ±180° (a 370° yaw gives
+    /// the fields of a 10° yaw, quaternion sign included) and a pitch beyond
+    /// ±90° becomes its equivalent roll/pitch/yaw.
+    pub fn from_orientation(roll: f64, pitch: f64, yaw: f64) -> Self {
+        let (roll, pitch, yaw) = Quaternion::from_euler(roll, pitch, yaw).to_euler();
+        let orientation = Quaternion::from_euler(roll, pitch, yaw);
+        Self {
+            orientation,
+            euler_degrees: (roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees()),
+            ..Self::new()
+        }
+    }
+
+    /// Estimate the state at `timestamp` between `self` and a later frame
+    ///
+    /// Orientation is slerped (Euler angles recomputed from it); position,
+    /// velocity and the other continuous fields are lerped by the time
+    /// fraction, clamped to the two frames. Flags, grid cell and raw
+    /// readings come from whichever frame is nearer in time.
+    pub fn interpolate(&self, later: &FusedSensorData, timestamp: DateTime<Utc>) -> Self {
+        let span = (later.timestamp - self.timestamp).num_microseconds().unwrap_or(0);
+        let t = if span > 0 {
+            let offset = (timestamp - self.timestamp).num_microseconds().unwrap_or(0);
+            (offset as f64 / span as f64).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let lerp = |a: f64, b: f64| a + t * (b - a);
+        let orientation = self.orientation.slerp(later.orientation, t);
+        let (roll, pitch, yaw) = orientation.to_euler();
+        let nearer = if t < 0.5 { self } else { later };
+        Self {
+            timestamp,
+            orientation,
+            euler_degrees: (roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees()),
+            position: (
+                lerp(self.position.0, later.position.0),
+                lerp(self.position.1, later.position.1),
+                lerp(self.position.2, later.position.2),
+            ),
+            local_position: self.local_position.lerp(later.local_position, t),
+            velocity: self.velocity.lerp(later.velocity, t),
+            gps_speed: lerp(self.gps_speed, later.gps_speed),
+            confidence: lerp(self.confidence, later.confidence),
+            system_health: lerp(self.system_health, later.system_health),
+            position_uncertainty: lerp(self.position_uncertainty, later.position_uncertainty),
+            ..nearer.clone()
+        }
+    }
+
+    /// Check that every float in the frame is finite
+    /// 
+    /// serde_json writes NaN and infinity as `null`, so a frame failing
+    /// this doesn't read back as a `FusedSensorData`.
+    pub fn is_finite(&self) -> bool {
+        let (roll, pitch, yaw) = self.euler_degrees;
+        let (lat, lon, alt) = self.position;
+        self.orientation.is_finite()
+            && [roll, pitch, yaw, lat, lon, alt].iter().all(|v| v.is_finite())
+            && self.local_position.is_finite()
+            && self.velocity.is_finite()
+            && self.raw_acceleration.is_finite()
+            && self.raw_gyroscope.is_finite()
+            && self.world_angular_velocity.is_none_or(|w| w.is_finite())
+            && self.estimated_gyro_bias.is_finite()
+            && [self.gps_speed, self.gps_heading, self.confidence, self.system_health, self.position_uncertainty]
+                .iter()
+                .all(|v| v.is_finite())
+            && self.anomaly_score.is_none_or(f64::is_finite)
+    }
+
+    /// Copy of the frame with every non-finite float replaced: the
+    /// orientation by identity, a non-finite anomaly score by none, and
+    /// other values by 0
+    pub fn sanitized(&self) -> Self {
+        let finite3 = |(a, b, c): (f64, f64, f64)| (finite_or(a, 0.0), finite_or(b, 0.0), finite_or(c, 0.0));
+        Self {
+            orientation: if self.orientation.is_finite() { self.orientation } else { Quaternion::identity() },
+            euler_degrees: finite3(self.euler_degrees),
+            position: finite3(self.position),
+            local_position: self.local_position.finite_or_zero(),
+            velocity: self.velocity.finite_or_zero(),
+            raw_acceleration: self.raw_acceleration.finite_or_zero(),
+            raw_gyroscope: self.raw_gyroscope.finite_or_zero(),
+            world_angular_velocity: self.world_angular_velocity.map(|w| w.finite_or_zero()),
+            estimated_gyro_bias: self.estimated_gyro_bias.finite_or_zero(),
+            gps_speed: finite_or(self.gps_speed, 0.0),
+            gps_heading: finite_or(self.gps_heading, 0.0),
+            confidence: finite_or(self.confidence, 0.0),
+            system_health: finite_or(self.system_health, 0.0),
+            position_uncertainty: finite_or(self.position_uncertainty, 0.0),
+            anomaly_score: self.anomaly_score.filter(|score| score.is_finite()),
+            ..self.clone()
         }
     }
 
@@ -235,10 +886,612 @@ impl FusedSensorData {
     pub fn set_anomaly_score(&mut self, score: f64) {
         self.anomaly_score = Some(score.clamp(0.0, 1.0));
     }
+
+    /// Replace the status flags and update the matching booleans
+    pub fn set_status_flags(&mut self, flags: StatusFlags) {
+        self.status_flags = flags;
+        self.dead_reckoning = flags.contains(StatusFlags::DEAD_RECKONING);
+        self.sensor_consistency_fault = flags.contains(StatusFlags::SENSOR_CONSISTENCY_FAULT);
+        self.gimbal_lock_warning = flags.contains(StatusFlags::GIMBAL_LOCK_WARNING);
+        self.gps_fix_valid = flags.contains(StatusFlags::GPS_FIX_VALID);
+        self.stale_anomaly = flags.contains(StatusFlags::STALE_ANOMALY);
+        self.position_divergence = flags.contains(StatusFlags::POSITION_DIVERGENCE);
+        self.reconverging = flags.contains(StatusFlags::RECONVERGING);
+        self.stale_gps = flags.contains(StatusFlags::STALE_GPS);
+        self.unsettled_world_accel = flags.contains(StatusFlags::UNSETTLED_WORLD_ACCEL);
+    }
+
+    /// Set or clear one status flag and its boolean
+    pub fn set_status_flag(&mut self, flag: StatusFlags, on: bool) {
+        let mut flags = self.status_flags;
+        flags.set(flag, on);
+        self.set_status_flags(flags);
+    }
+}
+
+/// Health and fault flags of a fused frame as a bitfield
+/// 
+/// Serialized as a plain `u32`. Bit positions are fixed so binary
+/// consumers can rely on them:
+/// 
+/// | Bit | Value | Flag                       |
+/// |-----|-------|----------------------------|
+/// | 0   | 0x01  | `dead_reckoning`           |
+/// | 1   | 0x02  | `sensor_consistency_fault` |
+/// | 2   | 0x04  | `gimbal_lock_warning`      |
+/// | 3   | 0x08  | `gps_fix_valid`            |
+/// | 4   | 0x10  | `stale_anomaly`            |
+/// | 5   | 0x20  | `position_divergence`      |
+/// | 6   | 0x40  | `reconverging`             |
+/// | 7   | 0x80  | `stale_gps`                |
+/// | 8   | 0x100 | `unsettled_world_accel`    |
+/// 
+/// Higher bits are reserved and currently zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StatusFlags(u32);
+
+impl StatusFlags {
+    /// Position is dead-reckoned because GPS has no fix
+    pub const DEAD_RECKONING: Self = Self(1 << 0);
+
+    /// Gyro yaw rate disagrees with the GPS course change rate
+    pub const SENSOR_CONSISTENCY_FAULT: Self = Self(1 << 1);
+
+    /// Pitch is near ±90° and the Euler angles are unreliable
+    pub const GIMBAL_LOCK_WARNING: Self = Self(1 << 2);
+
+    /// The GPS fix is usable for positioning
+    pub const GPS_FIX_VALID: Self = Self(1 << 3);
+
+    /// No recent ML service score backs the anomaly score
+    pub const STALE_ANOMALY: Self = Self(1 << 4);
+
+    /// GPS position diverges from IMU dead reckoning (drift watchdog)
+    pub const POSITION_DIVERGENCE: Self = Self(1 << 5);
+
+    /// A newly switched-in fusion filter is still converging
+    pub const RECONVERGING: Self = Self(1 << 6);
+
+    /// The GPS fix is older than the filter's timeout
+    pub const STALE_GPS: Self = Self(1 << 7);
+
+    /// World-frame acceleration rotated by an orientation still settling
+    pub const UNSETTLED_WORLD_ACCEL: Self = Self(1 << 8);
+
+    /// No flags set
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Flags from their raw bits
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Raw bits
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Whether every bit of `flag` is set
+    pub const fn contains(self, flag: Self) -> bool {
+        self.0 & flag.0 == flag.0
+    }
+
+    /// Set or clear the bits of `flag`
+    pub fn set(&mut self, flag: Self, on: bool) {
+        if on {
+            self.0 |= flag.0;
+        } else {
+            self.0 &= !flag.0;
+        }
+    }
+}
+
+/// Serde default for sensor health fields omitted by external sources
+fn full_health() -> f64 {
+    1.0
+}
+
+/// Return `value` if it is finite, otherwise `fallback`
+pub fn finite_or(value: f64, fallback: f64) -> f64 {
+    if value.is_finite() {
+        value
+    } else {
+        fallback
+    }
 }
 
 impl Default for FusedSensorData {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+/// Single-precision vector for compact wire frames
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Vec3F32 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl From<Vec3> for Vec3F32 {
+    fn from(v: Vec3) -> Self {
+        Self {
+            x: v.x as f32,
+            y: v.y as f32,
+            z: v.z as f32,
+        }
+    }
+}
+
+/// Single-precision quaternion for compact wire frames
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct QuaternionF32 {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl From<Quaternion> for QuaternionF32 {
+    fn from(q: Quaternion) -> Self {
+        Self {
+            w: q.w as f32,
+            x: q.x as f32,
+            y: q.y as f32,
+            z: q.z as f32,
+        }
+    }
+}
+
+/// `FusedSensorData` with single-precision floats, for bandwidth-sensitive
+/// clients
+/// 
+/// Serializes to the same JSON shape, so it reads back into
+/// `FusedSensorData`. Latitude and longitude stay `f64`: an `f32` only
+/// resolves about 1 m of longitude, which shows up as visible jitter on a
+/// map. Either position representation, and either orientation
+/// representation, may be left out (`None`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusedSensorDataF32 {
+    /// Timestamp of the fused estimate
+    pub timestamp: WireTimestamp,
+    
+    /// Estimated orientation as quaternion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<QuaternionF32>,
+    
+    /// Euler angles in degrees (roll, pitch, yaw)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub euler_degrees: Option<(f32, f32, f32)>,
+    
+    /// Estimated position (latitude, longitude, altitude)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<(f64, f64, f32)>,
+    
+    /// Vertical reference of the altitude in `position`
+    pub altitude_datum: AltitudeDatum,
+    
+    /// Estimated position in meters east/north/up of the fusion origin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_position: Option<Vec3F32>,
+    
+    /// Grid cell containing `local_position`, when enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_cell: Option<GridCell>,
+    
+    /// Estimated linear velocity in m/s
+    pub velocity: Vec3F32,
+    
+    /// Raw accelerometer reading
+    pub raw_acceleration: Vec3F32,
+    
+    /// Raw gyroscope reading
+    pub raw_gyroscope: Vec3F32,
+    
+    /// Gyroscope reading in the world frame, when enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub world_angular_velocity: Option<Vec3F32>,
+    
+    /// Gyroscope bias the filter estimates (rad/s)
+    pub estimated_gyro_bias: Vec3F32,
+    
+    /// GPS ground speed in m/s
+    pub gps_speed: f32,
+    
+    /// GPS heading in degrees
+    pub gps_heading: f32,
+    
+    /// Fusion confidence level (0.0 = low, 1.0 = high)
+    pub confidence: f32,
+    
+    /// Overall system health (0.0 = critical, 1.0 = healthy)
+    pub system_health: f32,
+    
+    /// Health and fault flags (see [`StatusFlags`])
+    pub status_flags: StatusFlags,
+    
+    /// True while position is dead-reckoned because GPS has no fix
+    pub dead_reckoning: bool,
+    
+    /// Estimated horizontal position error in meters (1σ)
+    pub position_uncertainty: f32,
+    
+    /// Gyroscope yaw rate disagrees with the GPS course change rate
+    pub sensor_consistency_fault: bool,
+    
+    /// Pitch is near ±90° and the Euler angles are unreliable
+    pub gimbal_lock_warning: bool,
+    
+    /// The GPS fix is usable for positioning
+    pub gps_fix_valid: bool,
+    
+    /// No recent ML service score backs `anomaly_score`
+    pub stale_anomaly: bool,
+    
+    /// GPS position diverges from IMU dead reckoning
+    pub position_divergence: bool,
+    
+    /// A newly switched-in fusion filter is still converging
+    pub reconverging: bool,
+    
+    /// The GPS fix is older than the filter's timeout
+    pub stale_gps: bool,
+    
+    /// World-frame acceleration rotated by an orientation still settling
+    pub unsettled_world_accel: bool,
+    
+    /// Anomaly score from ML service (if available)
+    pub anomaly_score: Option<f32>,
+}
+
+impl From<&FusedSensorData> for FusedSensorDataF32 {
+    fn from(frame: &FusedSensorData) -> Self {
+        let (roll, pitch, yaw) = frame.euler_degrees;
+        let (lat, lon, alt) = frame.position;
+        Self {
+            timestamp: WireTimestamp::Rfc3339(frame.timestamp),
+            orientation: Some(frame.orientation.into()),
+            euler_degrees: Some((roll as f32, pitch as f32, yaw as f32)),
+            position: Some((lat, lon, alt as f32)),
+            altitude_datum: frame.altitude_datum,
+            local_position: Some(frame.local_position.into()),
+            grid_cell: frame.grid_cell,
+            velocity: frame.velocity.into(),
+            raw_acceleration: frame.raw_acceleration.into(),
+            raw_gyroscope: frame.raw_gyroscope.into(),
+            world_angular_velocity: frame.world_angular_velocity.map(Vec3F32::from),
+            estimated_gyro_bias: frame.estimated_gyro_bias.into(),
+            gps_speed: frame.gps_speed as f32,
+            gps_heading: frame.gps_heading as f32,
+            confidence: frame.confidence as f32,
+            system_health: frame.system_health as f32,
+            status_flags: frame.status_flags,
+            dead_reckoning: frame.dead_reckoning,
+            position_uncertainty: frame.position_uncertainty as f32,
+            sensor_consistency_fault: frame.sensor_consistency_fault,
+            gimbal_lock_warning: frame.gimbal_lock_warning,
+            gps_fix_valid: frame.gps_fix_valid,
+            stale_anomaly: frame.stale_anomaly,
+            position_divergence: frame.position_divergence,
+            reconverging: frame.reconverging,
+            stale_gps: frame.stale_gps,
+            unsettled_world_accel: frame.unsettled_world_accel,
+            anomaly_score: frame.anomaly_score.map(|score| score as f32),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// A frame with a non-finite value in every float field that has one
+    fn fault_laden_frame() -> FusedSensorData {
+        let bad = Vec3::new(f64::NAN, f64::INFINITY, f64::NEG_INFINITY);
+        FusedSensorData {
+            orientation: Quaternion::new(f64::NAN, 0.0, 0.0, 0.0),
+            euler_degrees: (f64::NAN, 0.0, f64::INFINITY),
+            position: (f64::NAN, 1.0, f64::INFINITY),
+            velocity: bad,
+            raw_acceleration: bad,
+            raw_gyroscope: bad,
+            world_angular_velocity: Some(bad),
+            estimated_gyro_bias: bad,
+            gps_speed: f64::NAN,
+            gps_heading: f64::INFINITY,
+            confidence: f64::NAN,
+            system_health: f64::NEG_INFINITY,
+            anomaly_score: Some(f64::NAN),
+            ..FusedSensorData::default()
+        }
+    }
+
+    fn components(v: Vec3) -> (f64, f64, f64) {
+        (v.x, v.y, v.z)
+    }
+
+    #[test]
+    fn test_normalize_degenerate_vectors_to_zero() {
+        assert_eq!(components(Vec3::zero().normalize()), (0.0, 0.0, 0.0));
+        assert_eq!(components(Vec3::new(f64::INFINITY, 0.0, 0.0).normalize()), (0.0, 0.0, 0.0));
+        assert_eq!(components(Vec3::new(f64::NAN, 1.0, 0.0).normalize()), (0.0, 0.0, 0.0));
+        assert_eq!(components(Vec3::new(0.0, 3.0, 4.0).normalize()), (0.0, 0.6, 0.8));
+    }
+
+    #[test]
+    fn test_non_finite_frame_does_not_round_trip() {
+        let json = serde_json::to_string(&fault_laden_frame()).unwrap();
+        assert!(serde_json::from_str::<FusedSensorData>(&json).is_err());
+    }
+
+    #[test]
+    fn test_sanitized_frame_round_trips() {
+        let frame = fault_laden_frame();
+        assert!(!frame.is_finite());
+        let sanitized = frame.sanitized();
+        assert!(sanitized.is_finite());
+        assert_eq!(sanitized.position.1, 1.0);
+        let json = serde_json::to_string(&sanitized).unwrap();
+        assert!(serde_json::from_str::<FusedSensorData>(&json).is_ok());
+    }
+
+    #[test]
+    fn test_f32_frame_reads_back_with_small_loss() {
+        let frame = FusedSensorData {
+            euler_degrees: (12.345678, -3.210987, 179.987654),
+            position: (39.739236912, -104.990251234, 1655.123456),
+            velocity: Vec3::new(3.1234567, -2.7654321, 0.01234567),
+            confidence: 0.87654321,
+            ..FusedSensorData::default()
+        };
+        let json = serde_json::to_string(&FusedSensorDataF32::from(&frame)).unwrap();
+        let decoded: FusedSensorData = serde_json::from_str(&json).unwrap();
+
+        // Latitude and longitude stay f64, so they come back exactly
+        assert_eq!((decoded.position.0, decoded.position.1), (frame.position.0, frame.position.1));
+        assert!((decoded.position.2 - frame.position.2).abs() < 1e-3);
+        let (roll, pitch, yaw) = decoded.euler_degrees;
+        assert!((roll - 12.345678).abs() < 1e-5 && (pitch + 3.210987).abs() < 1e-5 && (yaw - 179.987654).abs() < 1e-4);
+        let (got, want) = (decoded.velocity, frame.velocity);
+        for (got, want) in [(got.x, want.x), (got.y, want.y), (got.z, want.z)] {
+            assert!((got - want).abs() < 1e-6, "velocity {got} vs {want}");
+        }
+        assert!((decoded.confidence - frame.confidence).abs() < 1e-7);
+    }
+
+    /// Rotation by `yaw` radians about the vertical axis
+    fn yaw_rotation(yaw: f64) -> Quaternion {
+        Quaternion::new((yaw / 2.0).cos(), 0.0, 0.0, (yaw / 2.0).sin())
+    }
+
+    #[test]
+    fn test_agl_datum_subtracts_ground_elevation() {
+        let reference = AltitudeReference {
+            datum: AltitudeDatum::Agl,
+            geoid_separation: -17.0,
+            ground_elevation: 1600.0,
+        };
+        let mut frame = FusedSensorData {
+            position: (39.7392, -104.9903, 1655.0),
+            ..FusedSensorData::default()
+        };
+        reference.apply(&mut frame);
+        assert_eq!(frame.position, (39.7392, -104.9903, 55.0));
+        assert_eq!(frame.altitude_datum, AltitudeDatum::Agl);
+
+        // Below the configured ground, AGL goes negative rather than clamping
+        assert_eq!(reference.convert(1590.0), -10.0);
+        let ellipsoid = AltitudeReference { datum: AltitudeDatum::Ellipsoid, ..reference };
+        assert_eq!(ellipsoid.convert(1655.0), 1638.0);
+    }
+
+    #[test]
+    fn test_sensor_readings_accept_either_timestamp_format() {
+        let at = DateTime::from_timestamp_millis(1_733_567_400_123).unwrap();
+        for timestamp in [serde_json::json!(1_733_567_400_123_i64), serde_json::json!("2024-12-07T10:30:00.123Z")] {
+            let imu: ImuData = serde_json::from_value(serde_json::json!({
+                "timestamp": timestamp,
+                "acceleration": {"x": 0.0, "y": 0.0, "z": 9.81},
+                "gyroscope": {"x": 0.0, "y": 0.0, "z": 0.0},
+            }))
+            .unwrap();
+            assert_eq!(imu.timestamp, at);
+
+            let mut gps = serde_json::to_value(GpsData::new(39.7, -105.0, 1655.0)).unwrap();
+            gps["timestamp"] = timestamp;
+            assert_eq!(serde_json::from_value::<GpsData>(gps).unwrap().timestamp, at);
+        }
+    }
+
+    #[test]
+    fn test_array_and_slice_conversions_round_trip() {
+        let v = Vec3::from_array([1.5, -2.0, 9.81]);
+        assert_eq!((v.x, v.y, v.z), (1.5, -2.0, 9.81));
+        assert_eq!(v.to_array(), [1.5, -2.0, 9.81]);
+        let q = Quaternion::from_array([0.5, 0.5, -0.5, 0.5]);
+        assert_eq!((q.w, q.x, q.y, q.z), (0.5, 0.5, -0.5, 0.5));
+        assert_eq!(q.to_array(), [0.5, 0.5, -0.5, 0.5]);
+
+        let components = [0.5, 0.5, -0.5, 0.5];
+        assert_eq!(Vec3::try_from(&components[..3]).unwrap().to_array(), [0.5, 0.5, -0.5]);
+        assert_eq!(Quaternion::try_from(&components[..]).unwrap().to_array(), components);
+
+        let error = Vec3::try_from(&components[..]).unwrap_err();
+        assert_eq!((error.expected, error.actual), (3, 4));
+        let error = Quaternion::try_from(&components[..2]).unwrap_err();
+        assert_eq!((error.expected, error.actual), (4, 2));
+    }
+
+    #[test]
+    fn test_each_status_flag_sets_its_documented_bit() {
+        // Flag, its documented bit, and the boolean mirroring it
+        type Case = (StatusFlags, u32, fn(&FusedSensorData) -> bool);
+        let cases: [Case; 9] = [
+            (StatusFlags::DEAD_RECKONING, 0x01, |f| f.dead_reckoning),
+            (StatusFlags::SENSOR_CONSISTENCY_FAULT, 0x02, |f| f.sensor_consistency_fault),
+            (StatusFlags::GIMBAL_LOCK_WARNING, 0x04, |f| f.gimbal_lock_warning),
+            (StatusFlags::GPS_FIX_VALID, 0x08, |f| f.gps_fix_valid),
+            (StatusFlags::STALE_ANOMALY, 0x10, |f| f.stale_anomaly),
+            (StatusFlags::POSITION_DIVERGENCE, 0x20, |f| f.position_divergence),
+            (StatusFlags::RECONVERGING, 0x40, |f| f.reconverging),
+            (StatusFlags::STALE_GPS, 0x80, |f| f.stale_gps),
+            (StatusFlags::UNSETTLED_WORLD_ACCEL, 0x100, |f| f.unsettled_world_accel),
+        ];
+        for (flag, bit, boolean) in cases {
+            let mut frame = FusedSensorData::default();
+            frame.set_status_flag(flag, true);
+            assert_eq!(frame.status_flags.bits(), bit);
+            assert!(boolean(&frame), "{bit:#04x} set without its boolean");
+            let json = serde_json::to_value(&frame).unwrap();
+            assert_eq!(json["status_flags"], bit);
+
+            frame.set_status_flag(flag, false);
+            assert_eq!(frame.status_flags, StatusFlags::empty());
+            assert!(!boolean(&frame), "{bit:#04x} cleared but its boolean is still set");
+        }
+    }
+
+    #[test]
+    fn test_ned_to_enu_remaps_known_orientations() {
+        let close = |a: Quaternion, b: Quaternion| a.angle_to(&b) < 1e-9;
+
+        // Level and facing north is a 90° yaw in ENU (x points east)
+        let north = Quaternion::identity().ned_to_enu();
+        let s = std::f64::consts::FRAC_1_SQRT_2;
+        assert!(close(north, Quaternion::new(s, 0.0, 0.0, s)), "{north:?}");
+
+        // Level and facing east lines both frames up
+        let east = Quaternion::from_euler(0.0, 0.0, 90f64.to_radians()).ned_to_enu();
+        assert!(close(east, Quaternion::identity()), "{east:?}");
+
+        // Nose up is negative pitch about FLU's left-pointing y; right wing
+        // down is positive roll in both; compass heading becomes 90° − yaw
+        let ned = Quaternion::from_euler(20f64.to_radians(), 30f64.to_radians(), 30f64.to_radians());
+        let (roll, pitch, yaw) = ned.ned_to_enu().to_euler();
+        assert!((roll.to_degrees() - 20.0).abs() < 1e-9, "roll {}", roll.to_degrees());
+        assert!((pitch.to_degrees() + 30.0).abs() < 1e-9, "pitch {}", pitch.to_degrees());
+        assert!((yaw.to_degrees() - 60.0).abs() < 1e-9, "yaw {}", yaw.to_degrees());
+
+        // The same as rotating vectors through the frame changes by hand:
+        // FRD forward/right/down in NED vs FLU forward/left/up in ENU
+        let enu = ned.ned_to_enu();
+        for (frd, flu) in [
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0)),
+            (Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -1.0, 0.0)),
+            (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0)),
+        ] {
+            let n = ned.rotate(frd);
+            let e = enu.rotate(flu);
+            let expected = Vec3::new(n.y, n.x, -n.z);
+            assert!((e.x - expected.x).abs() < 1e-9 && (e.y - expected.y).abs() < 1e-9 && (e.z - expected.z).abs() < 1e-9, "{e:?} vs {expected:?}");
+        }
+    }
+
+    #[test]
+    fn test_from_orientation_wraps_370_degree_yaw() {
+        let wrapped = FusedSensorData::from_orientation(0.0, 0.0, 370f64.to_radians());
+        let direct = FusedSensorData::from_orientation(0.0, 0.0, 10f64.to_radians());
+
+        let (roll, pitch, yaw) = wrapped.euler_degrees;
+        assert!((yaw - 10.0).abs() < 1e-9, "yaw {yaw}");
+        assert!(roll.abs() < 1e-9 && pitch.abs() < 1e-9);
+        let q = |f: &FusedSensorData| [f.orientation.w, f.orientation.x, f.orientation.y, f.orientation.z];
+        for (a, b) in q(&wrapped).iter().zip(q(&direct)) {
+            assert!((a - b).abs() < 1e-12, "{:?} != {:?}", wrapped.orientation, direct.orientation);
+        }
+
+        // Both fields describe the same rotation
+        let from_euler = Quaternion::from_euler(roll.to_radians(), pitch.to_radians(), yaw.to_radians());
+        assert!(wrapped.orientation.angle_to(&from_euler) < 1e-9);
+        assert!(wrapped.orientation.w > 0.0, "quaternion sign not normalized");
+    }
+
+    #[test]
+    fn test_angle_to_90_degree_rotation_regardless_of_sign() {
+        let level = Quaternion::identity();
+        let turned = yaw_rotation(90f64.to_radians());
+        let negated = Quaternion::new(-turned.w, -turned.x, -turned.y, -turned.z);
+
+        assert!((level.angle_to(&turned) - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        assert!((level.angle_to(&negated) - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        assert!((turned.angle_to(&level) - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        assert_eq!(turned.angle_to(&turned), 0.0);
+        assert_eq!(turned.angle_to(&negated), 0.0);
+    }
+
+    #[test]
+    fn test_slerp_halfway_between_90_degrees_is_45() {
+        let level = Quaternion::identity();
+        let turned = yaw_rotation(90f64.to_radians());
+
+        let halfway = level.slerp(turned, 0.5);
+        let (roll, pitch, yaw) = halfway.to_euler();
+        assert!((yaw.to_degrees() - 45.0).abs() < 1e-9);
+        assert!(roll.abs() < 1e-12 && pitch.abs() < 1e-12);
+        assert!((level.angle_to(&halfway).to_degrees() - 45.0).abs() < 1e-9);
+        assert!((halfway.angle_to(&turned).to_degrees() - 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_slerp_about_a_tilted_axis_stays_on_the_arc() {
+        let a = Quaternion::new(0.8, 0.2, -0.1, 0.55);
+        let b = Quaternion::new(0.3, -0.4, 0.2, -0.843);
+        let norm = |q: Quaternion| (q.w * q.w + q.x * q.x + q.y * q.y + q.z * q.z).sqrt();
+        let (a, b) = (
+            Quaternion::new(a.w / norm(a), a.x / norm(a), a.y / norm(a), a.z / norm(a)),
+            Quaternion::new(b.w / norm(b), b.x / norm(b), b.y / norm(b), b.z / norm(b)),
+        );
+        let total = a.angle_to(&b);
+        for t in [0.1, 0.25, 0.5, 0.75, 0.9] {
+            let q = a.slerp(b, t);
+            assert!((a.angle_to(&q) - t * total).abs() < 1e-9);
+            assert!((q.angle_to(&b) - (1.0 - t) * total).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_slerp_takes_the_shorter_arc() {
+        let level = Quaternion::identity();
+        let turned = yaw_rotation(1.0);
+        let negated = Quaternion::new(-turned.w, -turned.x, -turned.y, -turned.z);
+        let halfway = yaw_rotation(0.5);
+        assert!(level.slerp(negated, 0.5).angle_to(&halfway) < 1e-9);
+        assert!(level.slerp(turned, 0.0).angle_to(&level) < 1e-9);
+        assert!(level.slerp(turned, 1.0).angle_to(&turned) < 1e-9);
+    }
+
+    #[test]
+    fn test_rotate_matches_conjugation() {
+        let q = Quaternion::from_euler(0.4, -1.1, 2.5);
+        let v = Vec3::new(9.81, -0.5, 2.0);
+        let r = q * Quaternion::new(0.0, v.x, v.y, v.z) * q.inverse();
+        let rotated = q.rotate(v);
+        assert!((rotated.x - r.x).abs() < 1e-12 && (rotated.y - r.y).abs() < 1e-12 && (rotated.z - r.z).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_clamp_magnitude_preserves_direction() {
+        let velocity = Vec3::new(30.0, -40.0, 0.0);
+        let clamped = velocity.clamp_magnitude(10.0);
+        assert!((clamped.magnitude() - 10.0).abs() < 1e-12);
+        assert_eq!(components(clamped.normalize()), components(velocity.normalize()));
+        assert_eq!(components(clamped), (6.0, -8.0, 0.0));
+
+        let slow = Vec3::new(1.0, 2.0, 2.0);
+        assert_eq!(components(slow.clamp_magnitude(10.0)), components(slow));
+        assert_eq!(components(velocity.clamp_magnitude(0.0)), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_lerp_endpoints_and_midpoint() {
+        let a = Vec3::new(0.0, 2.0, -4.0);
+        let b = Vec3::new(10.0, 4.0, 4.0);
+        assert_eq!(components(a.lerp(b, 0.0)), components(a));
+        assert_eq!(components(a.lerp(b, 1.0)), components(b));
+        assert_eq!(components(a.lerp(b, 0.5)), (5.0, 3.0, 0.0));
+    }
+}
@@ -0,0 +1,425 @@
+//! Reproducible Scenarios
+//!
+//! A scenario file (JSON) fully specifies an offline fusion run: simulator
+//! seed and RNG, motion profile (with its GPS waypoints), duration and
+//! rates, filter, and a fault schedule. Running it steps seeded simulators with an explicit time step
+//! and stamps every reading and frame with simulated time, so the same
+//! file always produces the same fused output stream, byte for byte. That
+//! makes a scenario a bug report or CI test case anyone can replay.
+//!
+//! Every name in a scenario (filter, motion profile, faults) is checked
+//! against what this build provides before anything runs, waypoints are
+//! checked against the motion profile, and unknown fields are rejected
+//! rather than silently ignored.
+
+use crate::fusion::{FilterKind, FrameOutput};
+use crate::models::FusedSensorData;
+use crate::sensors::chaos::ChaosFault;
+use crate::sensors::rng::{GPS_STREAM, IMU_STREAM};
+use crate::sensors::{GpsSimulator, ImuSimulator, RngBackend, SimRngConfig, WaypointRoute};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::path::Path;
+use thiserror::Error;
+
+/// Motion profiles the simulators can follow
+///
+/// `survey_circle` is the simulators' built-in motion: a ~111 m circle at
+/// ~5.6 m/s with a slowly oscillating altitude. `waypoints` flies the
+/// scenario's GPS waypoints in a loop at `waypoint_speed_mps`.
+pub const MOTION_PROFILES: [&str; 2] = ["survey_circle", "waypoints"];
+
+/// Fastest waypoint route accepted (m/s)
+pub const MAX_WAYPOINT_SPEED: f64 = 100.0;
+
+/// Longest scenario accepted (s)
+pub const MAX_SCENARIO_SECS: f64 = 3600.0;
+
+/// Errors produced while loading or validating a scenario
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    /// Scenario file could not be read
+    #[error("failed to read scenario: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Scenario file is not valid scenario JSON
+    #[error("invalid scenario: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    /// The filter named is not built into this backend
+    #[error("unknown filter {0:?} (available: {})", FilterKind::available())]
+    UnknownFilter(String),
+
+    /// The motion profile named does not exist
+    #[error("unknown motion profile {0:?} (available: {})", MOTION_PROFILES.join(", "))]
+    UnknownMotion(String),
+
+    /// A scheduled fault names no known fault
+    #[error("fault {index}: unknown fault {name:?} (available: {})", ChaosFault::available())]
+    UnknownFault { index: usize, name: String },
+
+    /// A setting is out of range
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// A fault injected at a point in the scenario
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduledFault {
+    /// Seconds into the scenario
+    pub at_secs: f64,
+
+    /// Fault name (as in the fault injection commands)
+    pub fault: String,
+
+    /// Seconds until the fault is cleared (stays until the end when unset)
+    #[serde(default)]
+    pub duration_secs: Option<f64>,
+}
+
+/// A point on the GPS route of the `waypoints` motion profile
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Waypoint {
+    /// Latitude (degrees)
+    pub latitude: f64,
+
+    /// Longitude (degrees)
+    pub longitude: f64,
+
+    /// Altitude (m)
+    pub altitude: f64,
+}
+
+/// A reproducible fusion run
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Free-form label for reports
+    #[serde(default)]
+    pub name: String,
+
+    /// Simulator seed
+    pub seed: u64,
+
+    /// Simulator RNG algorithm
+    #[serde(default)]
+    pub rng: RngBackend,
+
+    /// Simulated run length (s)
+    pub duration_secs: f64,
+
+    /// IMU rate (Hz); every IMU reading is one fused frame
+    #[serde(default = "default_imu_rate")]
+    pub imu_rate_hz: u32,
+
+    /// GPS fix rate (Hz)
+    #[serde(default = "default_gps_rate")]
+    pub gps_rate_hz: u32,
+
+    /// Fusion filter name
+    #[serde(default = "default_filter")]
+    pub filter: String,
+
+    /// Filter alpha parameter (0.0 - 1.0)
+    #[serde(default = "default_alpha")]
+    pub alpha: f64,
+
+    /// Motion profile name (see `MOTION_PROFILES`)
+    #[serde(default = "default_motion")]
+    pub motion: String,
+
+    /// GPS route of the `waypoints` motion profile, flown in a loop
+    #[serde(default)]
+    pub waypoints: Vec<Waypoint>,
+
+    /// Ground speed along the waypoints (m/s)
+    #[serde(default = "default_waypoint_speed")]
+    pub waypoint_speed_mps: f64,
+
+    /// Simulated time of the first reading
+    #[serde(default = "default_start")]
+    pub start: DateTime<Utc>,
+
+    /// Faults to inject, in any order
+    #[serde(default)]
+    pub faults: Vec<ScheduledFault>,
+}
+
+fn default_imu_rate() -> u32 {
+    50
+}
+
+fn default_gps_rate() -> u32 {
+    1
+}
+
+fn default_filter() -> String {
+    FilterKind::default().name().to_string()
+}
+
+fn default_alpha() -> f64 {
+    0.98
+}
+
+fn default_motion() -> String {
+    MOTION_PROFILES[0].to_string()
+}
+
+fn default_waypoint_speed() -> f64 {
+    5.0
+}
+
+fn default_start() -> DateTime<Utc> {
+    DateTime::from_timestamp(1_704_067_200, 0).unwrap_or_default() // 2024-01-01T00:00:00Z
+}
+
+/// A scheduled fault change, at an IMU tick
+#[derive(Debug, Clone, Copy)]
+struct FaultEvent {
+    tick: usize,
+    fault: ChaosFault,
+    inject: bool,
+    /// The fault has a duration, so an IMU spike holds until its clear
+    held: bool,
+}
+
+impl Scenario {
+    /// Parse and validate a scenario from JSON
+    pub fn from_json(json: &str) -> Result<Self, ScenarioError> {
+        let scenario: Self = serde_json::from_str(json)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Load and validate a scenario file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Check ranges, that every referenced filter, motion profile and
+    /// fault exists, and that waypoints come with the profile flying them
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        let invalid = |message: String| Err(ScenarioError::Invalid(message));
+        if !(self.duration_secs > 0.0 && self.duration_secs <= MAX_SCENARIO_SECS) {
+            return invalid(format!("duration_secs must be > 0 and at most {}, got {}", MAX_SCENARIO_SECS, self.duration_secs));
+        }
+        if !(1..=1000).contains(&self.imu_rate_hz) {
+            return invalid(format!("imu_rate_hz must be 1-1000 Hz, got {}", self.imu_rate_hz));
+        }
+        if !(1..=self.imu_rate_hz).contains(&self.gps_rate_hz) {
+            return invalid(format!("gps_rate_hz must be between 1 and imu_rate_hz, got {}", self.gps_rate_hz));
+        }
+        if !(0.0..=1.0).contains(&self.alpha) {
+            return invalid(format!("alpha must be between 0 and 1, got {}", self.alpha));
+        }
+        if FilterKind::from_name(&self.filter).is_none() {
+            return Err(ScenarioError::UnknownFilter(self.filter.clone()));
+        }
+        if !MOTION_PROFILES.contains(&self.motion.as_str()) {
+            return Err(ScenarioError::UnknownMotion(self.motion.clone()));
+        }
+        if self.motion == "waypoints" {
+            if self.waypoints.len() < 2 {
+                return invalid(format!("motion \"waypoints\" needs at least 2 waypoints, got {}", self.waypoints.len()));
+            }
+            for (index, waypoint) in self.waypoints.iter().enumerate() {
+                if !(-90.0..=90.0).contains(&waypoint.latitude) || !(-180.0..=180.0).contains(&waypoint.longitude) {
+                    return invalid(format!("waypoint {}: latitude/longitude out of range, got {}, {}", index, waypoint.latitude, waypoint.longitude));
+                }
+                if !waypoint.altitude.is_finite() {
+                    return invalid(format!("waypoint {}: altitude must be finite, got {}", index, waypoint.altitude));
+                }
+            }
+            if self.route().length() <= 0.0 {
+                return invalid("waypoints must not all be the same point".to_string());
+            }
+            if !(self.waypoint_speed_mps > 0.0 && self.waypoint_speed_mps <= MAX_WAYPOINT_SPEED) {
+                return invalid(format!("waypoint_speed_mps must be > 0 and at most {}, got {}", MAX_WAYPOINT_SPEED, self.waypoint_speed_mps));
+            }
+        } else if !self.waypoints.is_empty() {
+            return invalid(format!("waypoints are only flown by motion \"waypoints\", not {:?}", self.motion));
+        }
+        for (index, fault) in self.faults.iter().enumerate() {
+            if ChaosFault::from_name(&fault.fault).is_none() {
+                return Err(ScenarioError::UnknownFault { index, name: fault.fault.clone() });
+            }
+            if !(0.0..self.duration_secs).contains(&fault.at_secs) {
+                return invalid(format!("fault {}: at_secs must be within the scenario, got {}", index, fault.at_secs));
+            }
+            if let Some(duration) = fault.duration_secs {
+                if !(duration.is_finite() && duration > 0.0) {
+                    return invalid(format!("fault {}: duration_secs must be > 0, got {}", index, duration));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The waypoints as a simulator route
+    fn route(&self) -> WaypointRoute {
+        let waypoints = self.waypoints.iter().map(|w| (w.latitude, w.longitude, w.altitude)).collect();
+        WaypointRoute::new(waypoints, self.waypoint_speed_mps)
+    }
+
+    /// Number of fused frames the scenario produces
+    pub fn frames(&self) -> usize {
+        (self.duration_secs * self.imu_rate_hz as f64).round() as usize
+    }
+
+    /// Fault injections and clears in tick order (injections first)
+    fn fault_events(&self) -> Vec<FaultEvent> {
+        let rate = self.imu_rate_hz as f64;
+        let mut events: Vec<FaultEvent> = self
+            .faults
+            .iter()
+            .filter_map(|scheduled| Some((scheduled, ChaosFault::from_name(&scheduled.fault)?)))
+            .flat_map(|(scheduled, fault)| {
+                let held = scheduled.duration_secs.is_some();
+                let inject = FaultEvent { tick: (scheduled.at_secs * rate).round() as usize, fault, inject: true, held };
+                let clear = scheduled
+                    .duration_secs
+                    .map(|duration| FaultEvent { tick: ((scheduled.at_secs + duration) * rate).round() as usize, fault, inject: false, held });
+                std::iter::once(inject).chain(clear)
+            })
+            .collect();
+        events.sort_by_key(|event| (event.tick, !event.inject));
+        events
+    }
+
+    /// Run the scenario, returning every fused frame
+    ///
+    /// Follows the sensor loop: one fused frame per IMU reading, the GPS
+    /// advancing at its own rate. Readings and frames carry simulated
+    /// timestamps from `start`, so the output depends only on the file.
+    pub fn run(&self) -> Result<Vec<FusedSensorData>, ScenarioError> {
+        self.validate()?;
+        let rng = SimRngConfig { backend: self.rng, seed: Some(self.seed) };
+        let mut imu = ImuSimulator::new();
+        let mut gps = GpsSimulator::new();
+        imu.set_read_rate(self.imu_rate_hz);
+        imu.set_rng(rng.build(IMU_STREAM));
+        gps.set_rng(rng.build(GPS_STREAM));
+        if self.motion == "waypoints" {
+            gps.set_route(Some(self.route()), std::time::Duration::from_secs_f64(1.0 / self.gps_rate_hz as f64));
+        }
+        // Names were checked by `validate`
+        let mut filter = FilterKind::from_name(&self.filter).unwrap_or_default().build(self.alpha);
+        let output = FrameOutput::default();
+
+        let dt = 1.0 / self.imu_rate_hz as f64;
+        let ticks_per_fix = (self.imu_rate_hz / self.gps_rate_hz) as usize;
+        let at = |tick: usize| self.start + chrono::Duration::microseconds((tick as f64 * dt * 1e6).round() as i64);
+        let mut events = self.fault_events().into_iter().peekable();
+        let mut fix_time = self.start;
+        let mut frames = Vec::with_capacity(self.frames());
+        for tick in 0..self.frames() {
+            while let Some(event) = events.next_if(|event| event.tick <= tick) {
+                match (event.fault, event.inject) {
+                    (ChaosFault::Imu(fault), true) if event.held => imu.inject_held_fault(fault),
+                    (ChaosFault::Imu(fault), true) => imu.inject_fault(fault),
+                    (ChaosFault::Imu(fault), false) => imu.clear_fault(fault),
+                    (ChaosFault::Gps(fault), true) => gps.inject_fault(fault),
+                    (ChaosFault::Gps(fault), false) => gps.clear_fault(fault),
+                }
+            }
+            if tick % ticks_per_fix == 0 {
+                gps.update();
+                fix_time = at(tick);
+            }
+
+            let mut imu_data = imu.read();
+            imu_data.timestamp = at(tick);
+            let mut gps_data = gps.get_latest();
+            gps_data.timestamp = fix_time;
+            let mut frame = filter.update_with_dt(imu_data, gps_data, dt);
+            output.apply(&mut frame, filter.as_ref());
+            frame.timestamp = at(tick);
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"{
+        "name": "signal loss on the survey circle",
+        "seed": 7,
+        "duration_secs": 20,
+        "motion": "survey_circle",
+        "faults": [
+            { "at_secs": 12, "fault": "accel_spike" },
+            { "at_secs": 5, "fault": "gps_signal_loss", "duration_secs": 4 }
+        ]
+    }"#;
+
+    /// Scenario output as JSON lines
+    fn jsonl(scenario: &Scenario) -> Vec<String> {
+        scenario.run().unwrap().iter().map(|frame| serde_json::to_string(frame).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_running_a_scenario_twice_gives_identical_output() {
+        let scenario = Scenario::from_json(SCENARIO).unwrap();
+        let first = jsonl(&scenario);
+        assert_eq!(first.len(), 1000);
+        assert_eq!(first, jsonl(&scenario));
+
+        // The fault schedule is applied: no fix during the signal loss
+        let frames = scenario.run().unwrap();
+        assert!(!frames[200].dead_reckoning && frames[350].dead_reckoning && !frames[500].dead_reckoning);
+
+        let reseeded = Scenario { seed: 8, ..scenario };
+        assert_ne!(first, jsonl(&reseeded));
+    }
+
+    #[test]
+    fn test_unknown_references_are_rejected() {
+        let with = |field: &str, value: serde_json::Value| {
+            let mut json: serde_json::Value = serde_json::from_str(SCENARIO).unwrap();
+            json[field] = value;
+            Scenario::from_json(&json.to_string())
+        };
+        assert!(matches!(with("filter", "ukf".into()), Err(ScenarioError::UnknownFilter(name)) if name == "ukf"));
+        assert!(matches!(with("motion", "figure_eight".into()), Err(ScenarioError::UnknownMotion(_))));
+        let faults = serde_json::json!([{ "at_secs": 1, "fault": "gps_jam" }]);
+        assert!(matches!(with("faults", faults), Err(ScenarioError::UnknownFault { index: 0, .. })));
+        let late = serde_json::json!([{ "at_secs": 25, "fault": "high_noise" }]);
+        assert!(matches!(with("faults", late), Err(ScenarioError::Invalid(_))));
+        assert!(matches!(with("duration_secs", 0.into()), Err(ScenarioError::Invalid(_))));
+        let waypoints = serde_json::json!([{ "latitude": 39.7392, "longitude": -104.9903, "altitude": 1655 }]);
+        assert!(matches!(with("waypoints", waypoints), Err(ScenarioError::Invalid(_))));
+        let bogus = serde_json::json!([{ "latitude": 39.7392, "longitude": -104.9903, "alt": 1655 }]);
+        assert!(matches!(with("waypoints", bogus), Err(ScenarioError::Parse(_))));
+    }
+
+    #[test]
+    fn test_waypoints_are_validated_and_flown() {
+        let route = |waypoints: serde_json::Value| {
+            let mut json: serde_json::Value = serde_json::from_str(SCENARIO).unwrap();
+            json["motion"] = "waypoints".into();
+            json["waypoints"] = waypoints;
+            json["waypoint_speed_mps"] = 10.0.into();
+            Scenario::from_json(&json.to_string())
+        };
+        let at = |latitude: f64, longitude: f64| serde_json::json!({ "latitude": latitude, "longitude": longitude, "altitude": 1655 });
+        assert!(matches!(route(serde_json::json!([at(39.7392, -104.9903)])), Err(ScenarioError::Invalid(_))));
+        assert!(matches!(route(serde_json::json!([at(39.7392, -104.9903), at(39.7392, -104.9903)])), Err(ScenarioError::Invalid(_))));
+        assert!(matches!(route(serde_json::json!([at(39.7392, -104.9903), at(91.0, -104.9903)])), Err(ScenarioError::Invalid(_))));
+
+        // Out and back along a ~111 m north-south line: the fused position
+        // stays near the line instead of flying the survey circle
+        let scenario = route(serde_json::json!([at(39.7392, -104.9903), at(39.7402, -104.9903)])).unwrap();
+        let first = jsonl(&scenario);
+        assert_eq!(first, jsonl(&scenario));
+        let frames = scenario.run().unwrap();
+        let (lat, lon, _) = frames[500].position;
+        assert!(lat > 39.7390 && lat < 39.7404, "lat {lat}");
+        assert!((lon + 104.9903).abs() < 0.0002, "lon {lon}");
+        assert_ne!(first, jsonl(&Scenario::from_json(SCENARIO).unwrap()));
+    }
+}
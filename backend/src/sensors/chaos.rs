@@ -0,0 +1,209 @@
+//! Chaos Mode
+//!
+//! Randomized fault injection for soak testing. While enabled, a random
+//! IMU or GPS fault is injected after a random quiet interval and reset
+//! after a random duration, one fault at a time. With a seed, the sequence
+//! of events is fully reproducible.
+
+use super::gps::GpsFaultType;
+use super::imu::FaultType;
+use crate::models::duration_secs;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::time::Duration;
+
+/// Bounds for the randomized chaos timing
+#[derive(Debug, Clone, Serialize)]
+pub struct ChaosConfig {
+    /// Shortest quiet interval before the next fault
+    #[serde(rename = "min_interval_secs", serialize_with = "duration_secs::serialize")]
+    pub min_interval: Duration,
+
+    /// Longest quiet interval before the next fault
+    #[serde(rename = "max_interval_secs", serialize_with = "duration_secs::serialize")]
+    pub max_interval: Duration,
+
+    /// Shortest time a fault stays active
+    #[serde(rename = "min_duration_secs", serialize_with = "duration_secs::serialize")]
+    pub min_duration: Duration,
+
+    /// Longest time a fault stays active
+    #[serde(rename = "max_duration_secs", serialize_with = "duration_secs::serialize")]
+    pub max_duration: Duration,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_secs(5),
+            max_interval: Duration::from_secs(30),
+            min_duration: Duration::from_secs(1),
+            max_duration: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A fault chaos mode can inject
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaosFault {
+    /// IMU fault
+    Imu(FaultType),
+
+    /// GPS fault
+    Gps(GpsFaultType),
+}
+
+impl ChaosFault {
+    /// Name used in commands and scenario files
+    pub fn name(self) -> &'static str {
+        match self {
+            ChaosFault::Imu(FaultType::AccelSpike) => "accel_spike",
+            ChaosFault::Imu(FaultType::GyroSpike) => "gyro_spike",
+            ChaosFault::Imu(FaultType::HighNoise) => "high_noise",
+            ChaosFault::Gps(GpsFaultType::SignalLoss) => "gps_signal_loss",
+            ChaosFault::Gps(GpsFaultType::PoorAccuracy) => "gps_poor_accuracy",
+            ChaosFault::Gps(GpsFaultType::PositionJump) => "gps_position_jump",
+        }
+    }
+
+    /// Look a fault up by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        FAULTS.into_iter().find(|fault| fault.name() == name)
+    }
+
+    /// Comma-separated names of every fault
+    pub fn available() -> String {
+        FAULTS.map(ChaosFault::name).join(", ")
+    }
+}
+
+/// What to do when a chaos event fires
+#[derive(Debug, Clone, Copy)]
+pub enum ChaosAction {
+    /// Inject the fault
+    Inject(ChaosFault),
+
+    /// Clear the previously injected fault
+    Reset(ChaosFault),
+}
+
+/// A chaos action and how long to wait before performing it
+#[derive(Debug, Clone, Copy)]
+pub struct ChaosEvent {
+    /// Delay after the previous event
+    pub delay: Duration,
+
+    /// Action to perform once the delay has elapsed
+    pub action: ChaosAction,
+}
+
+/// Every fault chaos mode picks from
+const FAULTS: [ChaosFault; 6] = [
+    ChaosFault::Imu(FaultType::AccelSpike),
+    ChaosFault::Imu(FaultType::GyroSpike),
+    ChaosFault::Imu(FaultType::HighNoise),
+    ChaosFault::Gps(GpsFaultType::SignalLoss),
+    ChaosFault::Gps(GpsFaultType::PoorAccuracy),
+    ChaosFault::Gps(GpsFaultType::PositionJump),
+];
+
+/// Generator of randomized fault inject/reset events
+pub struct ChaosSchedule {
+    /// Timing bounds
+    config: ChaosConfig,
+
+    /// Source of randomness (seeded for reproducible runs)
+    rng: StdRng,
+
+    /// Fault injected by the last event, awaiting its reset event
+    injected: Option<ChaosFault>,
+}
+
+impl ChaosSchedule {
+    /// Create a schedule; the same seed always yields the same events
+    pub fn new(config: ChaosConfig, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            config,
+            rng,
+            injected: None,
+        }
+    }
+
+    /// Produce the next event
+    ///
+    /// Alternates between injecting a random fault after a quiet interval
+    /// and resetting it after a random duration.
+    pub fn next_event(&mut self) -> ChaosEvent {
+        match self.injected.take() {
+            Some(fault) => ChaosEvent {
+                delay: self.random_between(self.config.min_duration, self.config.max_duration),
+                action: ChaosAction::Reset(fault),
+            },
+            None => {
+                let delay = self.random_between(self.config.min_interval, self.config.max_interval);
+                let fault = FAULTS[self.rng.gen_range(0..FAULTS.len())];
+                self.injected = Some(fault);
+                ChaosEvent {
+                    delay,
+                    action: ChaosAction::Inject(fault),
+                }
+            }
+        }
+    }
+
+    /// Uniform random duration within bounds (tolerates swapped bounds)
+    fn random_between(&mut self, a: Duration, b: Duration) -> Duration {
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+        if low == high {
+            return low;
+        }
+        Duration::from_secs_f64(self.rng.gen_range(low.as_secs_f64()..=high.as_secs_f64()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn events(seed: u64, count: usize) -> Vec<String> {
+        let mut schedule = ChaosSchedule::new(ChaosConfig::default(), Some(seed));
+        (0..count)
+            .map(|_| {
+                let event = schedule.next_event();
+                format!("{:?} {:?}", event.delay, event.action)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fixed_seed_is_deterministic() {
+        assert_eq!(events(42, 20), events(42, 20));
+        assert_ne!(events(42, 20), events(43, 20));
+    }
+
+    #[test]
+    fn test_events_alternate_and_respect_bounds() {
+        let config = ChaosConfig::default();
+        let mut schedule = ChaosSchedule::new(config.clone(), Some(7));
+        for _ in 0..10 {
+            let inject = schedule.next_event();
+            let ChaosAction::Inject(fault) = inject.action else {
+                panic!("expected inject, got {:?}", inject.action);
+            };
+            assert!((config.min_interval..=config.max_interval).contains(&inject.delay));
+
+            let reset = schedule.next_event();
+            let ChaosAction::Reset(cleared) = reset.action else {
+                panic!("expected reset, got {:?}", reset.action);
+            };
+            assert_eq!(cleared, fault);
+            assert!((config.min_duration..=config.max_duration).contains(&reset.delay));
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! External Sensor Input
+//!
+//! Lets a hardware bridge push real IMU/GPS readings over WebSocket in
+//! place of (or alongside) the simulators:
+//! - Physical plausibility checks on every pushed reading, with
+//!   configurable bounds for vehicles that legitimately exceed the defaults
+//! - Per-source rate limiting so a misbehaving bridge can't flood the loop
+//! - Input selection and source-loss behaviour for the fusion loop
+
+use crate::models::{GpsData, ImuData};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Largest accepted acceleration magnitude (m/s², ~16 g)
+pub const MAX_ACCELERATION: f64 = 16.0 * 9.81;
+
+/// Largest accepted angular rate magnitude (rad/s, ~2000 °/s)
+pub const MAX_ANGULAR_RATE: f64 = 35.0;
+
+/// Bounds a pushed reading must stay within
+/// 
+/// Units are fixed: acceleration in m/s² (gravity included), angular rate
+/// in rad/s, altitude in meters above sea level, and speed in m/s.
+/// Latitude, longitude, heading, and the 0-1 health/noise fields always
+/// use their natural ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ExternalLimits {
+    /// Largest accepted acceleration magnitude (m/s²)
+    pub max_acceleration: f64,
+
+    /// Largest accepted angular rate magnitude (rad/s)
+    pub max_angular_rate: f64,
+
+    /// Lowest accepted GPS altitude (m)
+    pub min_altitude: f64,
+
+    /// Highest accepted GPS altitude (m)
+    pub max_altitude: f64,
+
+    /// Largest accepted GPS ground speed (m/s)
+    pub max_speed: f64,
+
+    /// Largest accepted HDOP
+    pub max_hdop: f64,
+}
+
+impl Default for ExternalLimits {
+    fn default() -> Self {
+        Self {
+            max_acceleration: MAX_ACCELERATION,
+            max_angular_rate: MAX_ANGULAR_RATE,
+            min_altitude: -1_000.0,
+            max_altitude: 100_000.0,
+            max_speed: 1_000.0,
+            max_hdop: 100.0,
+        }
+    }
+}
+
+impl ExternalLimits {
+    /// Check every bound is finite and leaves a non-empty range
+    pub fn is_valid(&self) -> bool {
+        let maxima = [self.max_acceleration, self.max_angular_rate, self.max_speed, self.max_hdop];
+        maxima.iter().all(|m| m.is_finite() && *m > 0.0)
+            && self.min_altitude.is_finite()
+            && self.max_altitude.is_finite()
+            && self.min_altitude < self.max_altitude
+    }
+}
+
+/// Minimum spacing between accepted IMU samples (1 kHz)
+pub const MIN_IMU_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Minimum spacing between accepted GPS samples (20 Hz)
+pub const MIN_GPS_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Where the fusion loop takes its sensor readings from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensorInput {
+    /// Built-in simulators only; pushed data is refused
+    #[default]
+    Simulated,
+
+    /// Pushed readings only; fusion stops while no source is sending
+    External,
+
+    /// Pushed readings when a source is sending, simulators otherwise
+    ExternalWithFallback,
+}
+
+impl SensorInput {
+    /// Check whether pushed readings are accepted at all
+    pub fn accepts_external(self) -> bool {
+        self != SensorInput::Simulated
+    }
+}
+
+/// A reading pushed by an external sensor source
+#[derive(Debug, Clone)]
+pub enum ExternalSample {
+    /// IMU reading
+    Imu(ImuData),
+
+    /// GPS reading
+    Gps(GpsData),
+}
+
+/// Reasons a pushed reading is rejected
+#[derive(Debug, Error)]
+pub enum ExternalDataError {
+    /// The message is not a valid reading
+    #[error("malformed reading: {0}")]
+    Malformed(#[from] serde_json::Error),
+
+    /// A field is NaN or infinite
+    #[error("{0} is not finite")]
+    NonFinite(&'static str),
+
+    /// A field is outside its physically plausible range
+    #[error("{field} out of range: {value} (allowed {min} to {max})")]
+    OutOfRange { field: &'static str, value: f64, min: f64, max: f64 },
+
+    /// The source is sending faster than allowed
+    #[error("sample rate limit exceeded")]
+    RateLimited,
+}
+
+/// Check a pushed IMU reading for physical plausibility
+pub fn validate_imu(imu: &ImuData, limits: &ExternalLimits) -> Result<(), ExternalDataError> {
+    if !imu.acceleration.is_finite() {
+        return Err(ExternalDataError::NonFinite("acceleration"));
+    }
+    if !imu.gyroscope.is_finite() {
+        return Err(ExternalDataError::NonFinite("gyroscope"));
+    }
+    check_range("acceleration", imu.acceleration.magnitude(), 0.0, limits.max_acceleration)?;
+    check_range("gyroscope", imu.gyroscope.magnitude(), 0.0, limits.max_angular_rate)?;
+    check_range("noise_level", imu.noise_level, 0.0, 1.0)?;
+    check_range("health", imu.health, 0.0, 1.0)
+}
+
+/// Check a pushed GPS reading for physical plausibility
+pub fn validate_gps(gps: &GpsData, limits: &ExternalLimits) -> Result<(), ExternalDataError> {
+    check_range("latitude", gps.latitude, -90.0, 90.0)?;
+    check_range("longitude", gps.longitude, -180.0, 180.0)?;
+    check_range("altitude", gps.altitude, limits.min_altitude, limits.max_altitude)?;
+    check_range("speed", gps.speed, 0.0, limits.max_speed)?;
+    check_range("heading", gps.heading, 0.0, 360.0)?;
+    check_range("hdop", gps.hdop, 0.0, limits.max_hdop)?;
+    check_range("health", gps.health, 0.0, 1.0)
+}
+
+/// Reject non-finite values and values outside `[min, max]`
+fn check_range(field: &'static str, value: f64, min: f64, max: f64) -> Result<(), ExternalDataError> {
+    if !value.is_finite() {
+        return Err(ExternalDataError::NonFinite(field));
+    }
+    if value < min || value > max {
+        return Err(ExternalDataError::OutOfRange { field, value, min, max });
+    }
+    Ok(())
+}
+
+/// Enforces a minimum spacing between accepted samples
+#[derive(Debug, Clone)]
+pub struct SampleRateLimiter {
+    /// Minimum time between accepted samples
+    min_interval: Duration,
+
+    /// When the last sample was accepted
+    last_accepted: Option<Instant>,
+
+    /// Samples rejected so far
+    rejected: u64,
+}
+
+impl SampleRateLimiter {
+    /// Create a limiter allowing one sample per `min_interval`
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            last_accepted: None,
+            rejected: 0,
+        }
+    }
+
+    /// Accept or reject a sample arriving at `now`
+    pub fn check(&mut self, now: Instant) -> Result<(), ExternalDataError> {
+        match self.last_accepted {
+            Some(last) if now.duration_since(last) < self.min_interval => {
+                self.rejected += 1;
+                Err(ExternalDataError::RateLimited)
+            }
+            _ => {
+                self.last_accepted = Some(now);
+                Ok(())
+            }
+        }
+    }
+
+    /// Number of samples rejected so far
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+}
@@ -0,0 +1,67 @@
+//! Timed Faults
+//!
+//! Deadlines for injected faults that clear themselves. Each fault has its
+//! own timer, so overlapping faults with different durations expire
+//! independently; re-injecting a fault restarts (or, without a duration,
+//! cancels) its timer.
+
+use super::chaos::ChaosFault;
+use std::time::{Duration, Instant};
+
+/// Deadlines of the faults due to clear automatically
+#[derive(Debug, Clone, Default)]
+pub struct FaultTimers {
+    /// Fault and when it clears, one entry per fault
+    pending: Vec<(ChaosFault, Instant)>,
+}
+
+impl FaultTimers {
+    /// Create an empty set of timers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note an injection of `fault`, clearing it after `duration` if given
+    ///
+    /// Replaces any earlier timer for the same fault: the latest injection
+    /// decides how long the fault lasts.
+    pub fn inject(&mut self, fault: ChaosFault, duration: Option<Duration>, now: Instant) {
+        self.pending.retain(|(f, _)| *f != fault);
+        if let Some(deadline) = duration.and_then(|d| now.checked_add(d)) {
+            self.pending.push((fault, deadline));
+        }
+    }
+
+    /// Drop every timer (all faults were reset)
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// When the next fault is due to clear
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.iter().map(|(_, deadline)| *deadline).min()
+    }
+
+    /// Remove and return the faults whose time is up, earliest first
+    pub fn expire(&mut self, now: Instant) -> Vec<ChaosFault> {
+        let mut expired: Vec<_> = self.pending.iter().copied().filter(|(_, deadline)| *deadline <= now).collect();
+        expired.sort_by_key(|(_, deadline)| *deadline);
+        self.pending.retain(|(_, deadline)| *deadline > now);
+        expired.into_iter().map(|(fault, _)| fault).collect()
+    }
+
+    /// Check whether `fault` is waiting on its timer to clear
+    pub fn contains(&self, fault: ChaosFault) -> bool {
+        self.pending.iter().any(|(f, _)| *f == fault)
+    }
+
+    /// Number of faults waiting to clear
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Check whether no fault is waiting to clear
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
@@ -6,11 +6,111 @@
 //! - Satellite visibility variations
 //! - Realistic accuracy degradation
 //! - Speed and heading calculations
+//! - Optional cold-start warm-up (satellites acquired over time)
+//! - Debounced health, so a one-update satellite dip doesn't tank it
+//! - Optional waypoint route replacing the built-in circular flight
 
+use super::rng::{RngBackend, SimRng};
+use super::warmup::WarmUp;
 use crate::models::{GpsData, Vec3};
 use rand::Rng;
 use rand_distr::{Distribution, Normal};
+use serde::Serialize;
 use std::f64::consts::PI;
+use std::time::Duration;
+
+/// Simulated time between updates (the motion model assumes 1 Hz)
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// HDOP reported at the start of a warm-up, before any geometry is known
+const COLD_START_HDOP: f64 = 20.0;
+
+/// Debounce and hysteresis of the reported GPS health
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HealthSmoothing {
+    /// Consecutive updates a drop in health must last before it is
+    /// reported (0 or 1 = at once); rises are reported at once
+    pub debounce_updates: u32,
+
+    /// Drops in health smaller than this are not reported (0.0 - 1.0)
+    pub hysteresis: f64,
+}
+
+impl HealthSmoothing {
+    /// Check that the hysteresis is within 0.0 - 1.0
+    pub fn is_valid(&self) -> bool {
+        (0.0..=1.0).contains(&self.hysteresis)
+    }
+}
+
+impl Default for HealthSmoothing {
+    fn default() -> Self {
+        Self { debounce_updates: 3, hysteresis: 0.05 }
+    }
+}
+
+/// Metres per degree of latitude (flat-earth approximation)
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Latitude, longitude and altitude (m)
+type Point = (f64, f64, f64);
+
+/// A closed path through waypoints, flown at constant speed
+///
+/// Legs are straight lines in a local flat-earth approximation, fine for
+/// routes of a few kilometres. After the last waypoint the route returns
+/// to the first and starts over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaypointRoute {
+    /// Waypoints (latitude, longitude, altitude in metres)
+    waypoints: Vec<Point>,
+
+    /// Ground speed in m/s
+    speed: f64,
+}
+
+impl WaypointRoute {
+    /// Create a route through `waypoints` (at least two, not all the same
+    /// point) flown at `speed` m/s
+    pub fn new(waypoints: Vec<(f64, f64, f64)>, speed: f64) -> Self {
+        Self { waypoints, speed }
+    }
+
+    /// Legs of the route: start, end, and horizontal (north, east) offset
+    /// in metres, the closing leg back to the first waypoint included
+    fn legs(&self) -> impl Iterator<Item = (Point, Point, f64, f64)> + '_ {
+        let count = self.waypoints.len();
+        (0..count).map(move |i| {
+            let (from, to) = (self.waypoints[i], self.waypoints[(i + 1) % count]);
+            let north = (to.0 - from.0) * METERS_PER_DEGREE;
+            let east = (to.1 - from.1) * METERS_PER_DEGREE * from.0.to_radians().cos();
+            (from, to, north, east)
+        })
+    }
+
+    /// Horizontal length of one lap (m)
+    pub fn length(&self) -> f64 {
+        self.legs().map(|(_, _, north, east)| north.hypot(east)).sum()
+    }
+
+    /// Position, heading (radians, 0 = North) and climb rate (m/s) after
+    /// `elapsed` seconds on the route
+    fn at(&self, elapsed: f64) -> (Point, f64, f64) {
+        let lap = self.length();
+        let mut distance = if lap > 0.0 { (self.speed * elapsed).rem_euclid(lap) } else { 0.0 };
+        for (from, to, north, east) in self.legs() {
+            let leg = north.hypot(east);
+            if leg > 0.0 && distance < leg {
+                let f = distance / leg;
+                let position = (from.0 + (to.0 - from.0) * f, from.1 + (to.1 - from.1) * f, from.2 + (to.2 - from.2) * f);
+                let climb = (to.2 - from.2) * self.speed / leg;
+                return (position, east.atan2(north), climb);
+            }
+            distance -= leg;
+        }
+        (self.waypoints[0], 0.0, 0.0)
+    }
+}
 
 /// GPS sensor simulator with realistic accuracy characteristics
 pub struct GpsSimulator {
@@ -41,15 +141,36 @@ pub struct GpsSimulator {
     /// Simulation update counter
     update_count: u64,
     
-    /// Random number generator (using thread-safe StdRng)
-    rng: rand::rngs::StdRng,
+    /// Reported health forced by the user, replacing the computed value
+    health_override: Option<f64>,
+    
+    /// Injected faults degrading the signal until cleared
+    signal_faults: Vec<GpsFaultType>,
+    
+    /// Debounce and hysteresis of the reported health
+    health_smoothing: HealthSmoothing,
+    
+    /// Health as last reported (computed afresh when unset)
+    reported_health: Option<f64>,
+    
+    /// Consecutive updates health has been below the reported value
+    health_dip_updates: u32,
+    
+    /// Cold-start period over which satellites are acquired
+    warm_up: WarmUp,
+    
+    /// Route flown instead of the circular flight, and the simulated time
+    /// between updates
+    route: Option<(WaypointRoute, Duration)>,
+    
+    /// Random number generator (StdRng unless configured otherwise)
+    rng: SimRng,
 }
 
 impl GpsSimulator {
     /// Create a new GPS simulator starting at a default location
     /// Starting position: Denver, Colorado area (example coordinates)
     pub fn new() -> Self {
-        use rand::SeedableRng;
         let start_position = (39.7392, -104.9903, 1655.0); // Lat, Lon, Alt (meters)
         
         Self {
@@ -62,7 +183,14 @@ impl GpsSimulator {
             position_noise_std: 2.5, // ~2.5 meter accuracy
             last_good_position: start_position,
             update_count: 0,
-            rng: rand::rngs::StdRng::from_entropy(),
+            health_override: None,
+            signal_faults: Vec::new(),
+            health_smoothing: HealthSmoothing::default(),
+            reported_health: None,
+            health_dip_updates: 0,
+            warm_up: WarmUp::default(),
+            route: None,
+            rng: SimRng::from_entropy(RngBackend::Std),
         }
     }
 
@@ -75,6 +203,7 @@ impl GpsSimulator {
         
         // Simulate satellite visibility and accuracy changes
         self.update_signal_quality();
+        self.update_reported_health();
         
         // Store good position for fallback
         if self.satellites >= 4 && self.hdop < 5.0 {
@@ -84,8 +213,10 @@ impl GpsSimulator {
 
     /// Get the latest GPS reading with noise
     pub fn get_latest(&mut self) -> GpsData {
+        let (satellites, hdop) = self.reported_signal();
+        
         // Add GPS position noise based on current HDOP
-        let noise_scale = self.hdop * self.position_noise_std;
+        let noise_scale = hdop * self.position_noise_std;
         let normal = Normal::new(0.0, noise_scale).unwrap();
         
         // Convert position noise to lat/lon offsets (simplified)
@@ -100,7 +231,10 @@ impl GpsSimulator {
         );
         
         // Calculate health based on satellite count and HDOP
-        let health = self.calculate_health();
+        let health = self
+            .health_override
+            .or(self.reported_health)
+            .unwrap_or_else(|| calculate_health(satellites, hdop));
         
         GpsData {
             timestamp: chrono::Utc::now(),
@@ -109,14 +243,22 @@ impl GpsSimulator {
             altitude: noisy_position.2,
             speed: self.speed,
             heading: self.heading.to_degrees().rem_euclid(360.0),
-            hdop: self.hdop,
-            satellites: self.satellites,
+            hdop,
+            satellites,
             health,
         }
     }
 
     /// Simulate realistic GPS movement patterns
     fn simulate_movement(&mut self) {
+        if let Some((route, interval)) = &self.route {
+            let (position, heading, climb) = route.at(interval.as_secs_f64() * self.update_count as f64);
+            self.position = position;
+            self.speed = route.speed;
+            self.heading = heading;
+            self.velocity = Vec3::new(self.speed * heading.cos(), self.speed * heading.sin(), climb);
+            return;
+        }
         let t = self.update_count as f64;
         
         // Simulate a circular flight pattern (like a drone doing surveillance)
@@ -153,7 +295,7 @@ impl GpsSimulator {
     /// Update GPS signal quality metrics (satellites and HDOP)
     fn update_signal_quality(&mut self) {
         // Simulate occasional GPS quality degradation
-        if self.update_count % 20 == 0 {
+        if self.update_count.is_multiple_of(20) {
             // Every 20 seconds, randomly adjust signal quality
             let quality_change: f64 = self.rng.gen_range(-0.3..0.3);
             self.hdop = (self.hdop + quality_change).clamp(0.8, 8.0);
@@ -174,27 +316,108 @@ impl GpsSimulator {
         }
     }
 
-    /// Calculate overall GPS health metric
-    fn calculate_health(&self) -> f64 {
-        // Health based on satellite count and HDOP
-        let sat_score = (self.satellites as f64 / 12.0).min(1.0);
-        let hdop_score = (3.0 / self.hdop).min(1.0);
-        
-        // Weighted average (satellites more important than HDOP)
-        0.6 * sat_score + 0.4 * hdop_score
+    /// Follow the computed health, holding off on brief drops
+    /// 
+    /// A drop is only reported once it has lasted `debounce_updates`
+    /// updates, after which further drops follow at once. Drops within the
+    /// hysteresis band are ignored; rises are always followed.
+    fn update_reported_health(&mut self) {
+        let (satellites, hdop) = self.reported_signal();
+        let health = calculate_health(satellites, hdop);
+        let smoothing = self.health_smoothing;
+        match self.reported_health {
+            Some(reported) if health < reported - smoothing.hysteresis => {
+                self.health_dip_updates += 1;
+                if self.health_dip_updates >= smoothing.debounce_updates {
+                    self.reported_health = Some(health);
+                }
+            }
+            Some(reported) if health < reported => {
+                self.health_dip_updates = 0;
+            }
+            _ => {
+                self.health_dip_updates = 0;
+                self.reported_health = Some(health);
+            }
+        }
+    }
+
+    /// Get the health debounce and hysteresis
+    pub fn health_smoothing(&self) -> HealthSmoothing {
+        self.health_smoothing
+    }
+
+    /// Set how long a drop in health must last before it is reported, and
+    /// the smallest drop reported (invalid settings are ignored)
+    pub fn set_health_smoothing(&mut self, smoothing: HealthSmoothing) {
+        if smoothing.is_valid() {
+            self.health_smoothing = smoothing;
+        }
+    }
+
+    /// Report health computed from the current signal, skipping the
+    /// debounce (an injected fault is a genuine change)
+    fn reset_reported_health(&mut self) {
+        self.reported_health = None;
+        self.health_dip_updates = 0;
+    }
+
+    /// Satellites and HDOP as reported, degraded while warming up
+    /// 
+    /// The satellite count ramps up from zero and HDOP down from
+    /// `COLD_START_HDOP` to the simulated signal, so there is no fix for
+    /// the first part of the warm-up.
+    fn reported_signal(&self) -> (u8, f64) {
+        let progress = self.warm_up.progress(UPDATE_INTERVAL.saturating_mul(self.update_count.try_into().unwrap_or(u32::MAX)));
+        let satellites = (self.satellites as f64 * progress).round() as u8;
+        let hdop = self.hdop.max(COLD_START_HDOP + (self.hdop - COLD_START_HDOP) * progress);
+        (satellites, hdop)
+    }
+
+    /// Start from a cold receiver that reaches nominal signal after
+    /// `duration` of updates (zero = nominal from the first fix)
+    pub fn set_warm_up(&mut self, duration: Duration) {
+        self.warm_up = WarmUp::new(duration);
+    }
+
+    /// Get the cold-start warm-up period
+    pub fn warm_up(&self) -> Duration {
+        self.warm_up.duration()
+    }
+
+    /// Fly `route` instead of the circular flight (`None` restores it),
+    /// with `update_interval` of simulated time between updates
+    pub fn set_route(&mut self, route: Option<WaypointRoute>, update_interval: Duration) {
+        self.route = route.map(|route| (route, update_interval));
+    }
+
+    /// Replace the random number generator, e.g. with a seeded one for a
+    /// reproducible run
+    pub fn set_rng(&mut self, rng: SimRng) {
+        self.rng = rng;
     }
 
     /// Inject a GPS fault for testing
     pub fn inject_fault(&mut self, fault_type: GpsFaultType) {
+        if fault_type != GpsFaultType::PositionJump && !self.signal_faults.contains(&fault_type) {
+            self.signal_faults.push(fault_type);
+        }
+        self.apply_fault(fault_type);
+    }
+
+    /// Apply a fault's effect on the simulated receiver
+    fn apply_fault(&mut self, fault_type: GpsFaultType) {
         match fault_type {
             GpsFaultType::SignalLoss => {
                 self.satellites = 2; // Below minimum for 3D fix
                 self.hdop = 15.0;
+                self.reset_reported_health();
             }
             GpsFaultType::PoorAccuracy => {
                 self.hdop = 8.0;
                 self.satellites = 4;
                 self.position_noise_std = 15.0; // 15 meter accuracy
+                self.reset_reported_health();
             }
             GpsFaultType::PositionJump => {
                 // Simulate sudden position error
@@ -206,9 +429,40 @@ impl GpsSimulator {
 
     /// Reset fault conditions to normal
     pub fn reset_faults(&mut self) {
+        self.signal_faults.clear();
         self.hdop = 1.2;
         self.satellites = 12;
         self.position_noise_std = 2.5;
+        self.reset_reported_health();
+    }
+
+    /// Clear a single fault, leaving any others in effect
+    /// 
+    /// Signal loss and poor accuracy degrade the same signal, so the signal
+    /// is restored and whichever of them remains is re-applied. A position
+    /// jump is one-shot and has nothing to undo.
+    pub fn clear_fault(&mut self, fault_type: GpsFaultType) {
+        if !self.signal_faults.contains(&fault_type) {
+            return;
+        }
+        let remaining: Vec<_> = self.signal_faults.iter().copied().filter(|f| *f != fault_type).collect();
+        self.reset_faults();
+        for fault in remaining {
+            self.inject_fault(fault);
+        }
+    }
+
+    /// Force the reported health to `health` (clamped to 0.0 - 1.0), or
+    /// return to computed health with `None`
+    /// 
+    /// Independent of fault injection; resetting faults keeps the override.
+    pub fn set_health_override(&mut self, health: Option<f64>) {
+        self.health_override = health.filter(|h| h.is_finite()).map(|h| h.clamp(0.0, 1.0));
+    }
+
+    /// Get the forced health value, if any
+    pub fn health_override(&self) -> Option<f64> {
+        self.health_override
     }
 
     /// Get current position without noise (for fusion algorithm ground truth)
@@ -222,6 +476,16 @@ impl GpsSimulator {
     }
 }
 
+/// Calculate overall GPS health metric
+fn calculate_health(satellites: u8, hdop: f64) -> f64 {
+    // Health based on satellite count and HDOP
+    let sat_score = (satellites as f64 / 12.0).min(1.0);
+    let hdop_score = (3.0 / hdop).min(1.0);
+    
+    // Weighted average (satellites more important than HDOP)
+    0.6 * sat_score + 0.4 * hdop_score
+}
+
 impl Default for GpsSimulator {
     fn default() -> Self {
         Self::new()
@@ -229,7 +493,7 @@ impl Default for GpsSimulator {
 }
 
 /// Types of GPS faults that can be injected for testing
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GpsFaultType {
     /// Insufficient satellites for 3D fix
     SignalLoss,
@@ -237,4 +501,103 @@ pub enum GpsFaultType {
     PoorAccuracy,
     /// Sudden position jump (multipath or error)
     PositionJump,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Reported satellite counts over the first `updates` one-second updates
+    fn satellites_over(warm_up: Duration, updates: usize) -> Vec<u8> {
+        let mut gps = GpsSimulator::new();
+        gps.set_warm_up(warm_up);
+        (0..updates)
+            .map(|_| {
+                gps.update();
+                gps.get_latest().satellites
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_warm_up_ramps_satellites_to_nominal() {
+        let counts = satellites_over(Duration::from_secs(10), 15);
+        assert!(counts[0] < 4, "fix available 1 s into a cold start: {counts:?}");
+        assert!(counts.windows(2).all(|pair| pair[0] <= pair[1]), "not ramping: {counts:?}");
+        assert_eq!(&counts[9..], &[12; 6], "not nominal after the warm-up: {counts:?}");
+    }
+
+    #[test]
+    fn test_zero_warm_up_is_nominal_immediately() {
+        assert_eq!(satellites_over(Duration::ZERO, 5), vec![12; 5]);
+    }
+
+    /// Reported health after one update with `satellites` in view
+    fn health_with(gps: &mut GpsSimulator, satellites: u8) -> f64 {
+        gps.satellites = satellites;
+        gps.update();
+        gps.get_latest().health
+    }
+
+    #[test]
+    fn test_one_update_satellite_dip_barely_moves_health() {
+        let mut gps = GpsSimulator::new();
+        let nominal = health_with(&mut gps, 12);
+        let dip = health_with(&mut gps, 5);
+        assert!((nominal - dip).abs() < 0.05, "health fell from {nominal} to {dip}");
+        assert_eq!(health_with(&mut gps, 12), nominal);
+    }
+
+    #[test]
+    fn test_sustained_satellite_drop_lowers_health() {
+        let mut gps = GpsSimulator::new();
+        let nominal = health_with(&mut gps, 12);
+        let healths: Vec<f64> = (0..4).map(|_| health_with(&mut gps, 5)).collect();
+        assert_eq!(healths[1], nominal);
+        assert!(healths[2] < nominal - 0.3, "still {healths:?} after three updates");
+        assert_eq!(healths[2], healths[3]);
+
+        // Recovery is reported at once
+        assert_eq!(health_with(&mut gps, 12), nominal);
+
+        // Without debounce the first update already shows the drop
+        gps.set_health_smoothing(HealthSmoothing { debounce_updates: 0, hysteresis: 0.0 });
+        assert!(health_with(&mut gps, 5) < nominal - 0.3);
+    }
+
+    #[test]
+    fn test_injected_signal_loss_skips_the_debounce() {
+        let mut gps = GpsSimulator::new();
+        gps.update();
+        gps.inject_fault(GpsFaultType::SignalLoss);
+        assert!(gps.get_latest().health < 0.3);
+        gps.update();
+        assert!(gps.get_latest().health < 0.3);
+    }
+
+    #[test]
+    fn test_route_is_flown_at_constant_speed_and_loops() {
+        // 111.32 m due north, 10 m up, then back
+        let start = (39.7392, -104.9903, 1655.0);
+        let route = WaypointRoute::new(vec![start, (39.7402, -104.9903, 1665.0)], 11.132);
+        assert!((route.length() - 222.64).abs() < 1e-6);
+        let mut gps = GpsSimulator::new();
+        gps.set_route(Some(route), UPDATE_INTERVAL);
+        let mut after = |updates: usize| {
+            (0..updates).for_each(|_| gps.update());
+            (gps.get_true_position(), gps.heading, gps.get_true_velocity())
+        };
+
+        let ((lat, lon, alt), heading, velocity) = after(5);
+        assert!((lat - 39.7397).abs() < 1e-9 && (lon - start.1).abs() < 1e-9 && (alt - 1660.0).abs() < 1e-6);
+        assert!(heading.abs() < 1e-9);
+        assert!((velocity.x - 11.132).abs() < 1e-9 && (velocity.z - 1.0).abs() < 1e-9);
+
+        let ((lat, _, _), heading, _) = after(10);
+        assert!((lat - 39.7397).abs() < 1e-9);
+        assert!((heading - PI).abs() < 1e-9);
+
+        let ((lat, _, alt), _, _) = after(5);
+        assert!((lat - start.0).abs() < 1e-9 && (alt - start.2).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,157 @@
+//! GPS Update Timing
+//!
+//! Real receivers don't deliver a fix exactly every interval: fixes arrive
+//! with some timing jitter, and now and then one is missed altogether
+//! (brief obstruction, receiver busy). `GpsScheduler` decides when the
+//! next GPS update is due and whether it delivers a fix.
+//!
+//! A run of missed fixes longer than `FIX_LOSS_GAP` counts as losing the
+//! fix, so the fusion filter falls back to dead reckoning instead of
+//! holding on to an ever older position.
+
+use super::rng::{RngBackend, SimRng};
+use crate::models::duration_secs;
+use rand::Rng;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Gap without a delivered fix after which the fix counts as lost
+pub const FIX_LOSS_GAP: Duration = Duration::from_secs(3);
+
+/// Irregularity of simulated GPS updates
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GpsTiming {
+    /// Largest deviation of an update from its nominal time (uniform ±)
+    #[serde(rename = "jitter_secs", serialize_with = "duration_secs::serialize")]
+    pub jitter: Duration,
+
+    /// Chance that an update delivers no fix (0.0 - 1.0)
+    pub drop_probability: f64,
+}
+
+impl GpsTiming {
+    /// Check that the drop probability is within 0.0 - 1.0 and the jitter
+    /// is shorter than `interval`, so updates stay in order
+    pub fn is_valid(&self, interval: Duration) -> bool {
+        (0.0..=1.0).contains(&self.drop_probability) && self.jitter < interval
+    }
+}
+
+/// Schedules GPS updates with jitter and dropped fixes
+#[derive(Debug, Clone)]
+pub struct GpsScheduler {
+    /// Nominal time between updates
+    interval: Duration,
+
+    /// Jitter and drop settings
+    timing: GpsTiming,
+
+    /// Consecutive updates that delivered no fix
+    missed: u32,
+
+    rng: SimRng,
+}
+
+impl GpsScheduler {
+    /// Create a scheduler for updates every `interval`
+    pub fn new(interval: Duration, timing: GpsTiming) -> Self {
+        Self {
+            interval,
+            timing,
+            missed: 0,
+            rng: SimRng::from_entropy(RngBackend::Std),
+        }
+    }
+
+    /// Replace the random number generator, e.g. with a seeded one for a
+    /// reproducible run
+    pub fn set_rng(&mut self, rng: SimRng) {
+        self.rng = rng;
+    }
+
+    /// Time from this update until the next one
+    pub fn next_delay(&mut self) -> Duration {
+        if self.timing.jitter.is_zero() {
+            return self.interval;
+        }
+        let jitter = self.timing.jitter.as_secs_f64();
+        let offset = self.rng.gen_range(-jitter..=jitter);
+        Duration::from_secs_f64((self.interval.as_secs_f64() + offset).max(0.0))
+    }
+
+    /// Decide whether the update now due delivers a fix
+    pub fn deliver(&mut self) -> bool {
+        let dropped = self.timing.drop_probability > 0.0 && self.rng.gen_bool(self.timing.drop_probability.min(1.0));
+        self.missed = if dropped { self.missed.saturating_add(1) } else { 0 };
+        !dropped
+    }
+
+    /// Time since the last delivered fix, in nominal update intervals
+    pub fn gap(&self) -> Duration {
+        self.interval.saturating_mul(self.missed)
+    }
+
+    /// Whether fixes have been missing for longer than `FIX_LOSS_GAP`
+    pub fn fix_lost(&self) -> bool {
+        self.gap() > FIX_LOSS_GAP
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn seeded(interval: Duration, timing: GpsTiming) -> GpsScheduler {
+        let mut schedule = GpsScheduler::new(interval, timing);
+        schedule.set_rng(SimRng::seeded(RngBackend::Std, 5));
+        schedule
+    }
+
+    #[test]
+    fn test_high_drop_probability_drops_most_fixes() {
+        let timing = GpsTiming { drop_probability: 0.9, ..GpsTiming::default() };
+        let mut schedule = seeded(Duration::from_millis(100), timing);
+        let mut delivered = 0;
+        for _ in 0..1000 {
+            if schedule.deliver() {
+                delivered += 1;
+                assert_eq!(schedule.gap(), Duration::ZERO, "a delivered fix ends the gap");
+            }
+        }
+        assert!((50..=150).contains(&delivered), "{delivered} of 1000 fixes delivered");
+    }
+
+    #[test]
+    fn test_fix_counts_as_lost_only_past_the_gap() {
+        let timing = GpsTiming { drop_probability: 1.0, ..GpsTiming::default() };
+        let mut schedule = seeded(Duration::from_secs(1), timing);
+        for _ in 0..3 {
+            assert!(!schedule.deliver());
+        }
+        assert_eq!(schedule.gap(), FIX_LOSS_GAP);
+        assert!(!schedule.fix_lost(), "lost at exactly the gap");
+        schedule.deliver();
+        assert!(schedule.fix_lost());
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        let interval = Duration::from_secs(1);
+        let timing = GpsTiming { jitter: Duration::from_millis(200), ..GpsTiming::default() };
+        let mut schedule = seeded(interval, timing);
+        let delays: Vec<Duration> = (0..200).map(|_| schedule.next_delay()).collect();
+        assert!(delays.iter().all(|d| (Duration::from_millis(800)..=Duration::from_millis(1200)).contains(d)));
+        assert!(delays.iter().any(|d| *d != interval), "no jitter applied");
+    }
+
+    #[test]
+    fn test_seeded_schedules_repeat() {
+        let timing = GpsTiming { jitter: Duration::from_millis(200), drop_probability: 0.3 };
+        let run = |mut schedule: GpsScheduler| -> Vec<(Duration, bool)> {
+            (0..50).map(|_| (schedule.next_delay(), schedule.deliver())).collect()
+        };
+        let interval = Duration::from_secs(1);
+        assert_eq!(run(seeded(interval, timing)), run(seeded(interval, timing)));
+    }
+}
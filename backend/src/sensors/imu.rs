@@ -5,11 +5,118 @@
 //! - Gyroscope with bias drift
 //! - Configurable noise profiles
 //! - Realistic sensor dynamics
+//! - Optional internal oversampling (averaging K sub-samples per reading)
+//! - Configurable mounting orientation relative to the vehicle body
+//! - Optional gyro g-sensitivity (acceleration leaking into rotation rate)
+//! - Optional warm-up with an elevated gyro bias that settles over time
+//! - Independent accelerometer and gyroscope output data rates
 
-use crate::models::{ImuData, Vec3};
+use super::rng::{RngBackend, SimRng};
+use super::warmup::WarmUp;
+use crate::fusion::kernels;
+use crate::models::{ImuData, Quaternion, Vec3};
 use rand::Rng;
 use rand_distr::{Distribution, Normal};
+use serde::Serialize;
 use std::f64::consts::PI;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Default simulated time between readings (50 Hz)
+const READ_INTERVAL: f64 = 0.02;
+
+/// Largest extra gyro bias per axis at the start of a warm-up (rad/s)
+const WARM_UP_GYRO_BIAS: f64 = 0.05;
+
+/// IMU simulator settings
+#[derive(Debug, Clone, Serialize)]
+pub struct ImuConfig {
+    /// Internal sub-samples averaged into each reading (1 = no averaging)
+    ///
+    /// Averaging K sub-samples cuts noise by √K at the cost of roughly half
+    /// a reading interval of extra latency.
+    pub oversampling: u32,
+
+    /// Mounting rotation taking body-frame vectors into the sensor frame
+    ///
+    /// Identity for a sensor aligned with the vehicle body. Accelerometer
+    /// and gyroscope readings are rotated by it; bias and noise are added
+    /// afterwards, in the sensor frame.
+    pub mounting: Quaternion,
+
+    /// Gyro g-sensitivity in (rad/s) per (m/s²) (0 = off)
+    ///
+    /// Each gyro axis reads this fraction of the specific force along the
+    /// same axis as spurious rotation, so the error grows with the
+    /// accelerometer magnitude, gravity included. MEMS gyros are typically
+    /// around 1e-4 (~0.06°/s per g).
+    pub g_sensitivity: f64,
+
+    /// Accelerometer output data rate in Hz (a new sample every read when unset)
+    ///
+    /// Between samples, reads repeat the last accelerometer sample (and its
+    /// noise level). Rates at or above the read rate sample on every read.
+    pub accel_rate_hz: Option<f64>,
+
+    /// Gyroscope output data rate in Hz (a new sample every read when unset)
+    ///
+    /// Between samples, reads repeat the last gyroscope sample.
+    pub gyro_rate_hz: Option<f64>,
+}
+
+impl Default for ImuConfig {
+    fn default() -> Self {
+        Self {
+            oversampling: 1,
+            mounting: Quaternion::identity(),
+            g_sensitivity: 0.0,
+            accel_rate_hz: None,
+            gyro_rate_hz: None,
+        }
+    }
+}
+
+impl ImuConfig {
+    /// Reject settings the simulator can't run with
+    pub fn validate(&self) -> Result<(), ImuConfigError> {
+        if self.oversampling == 0 {
+            return Err(ImuConfigError::ZeroOversampling);
+        }
+        let m = self.mounting;
+        if !m.is_finite() || m.w * m.w + m.x * m.x + m.y * m.y + m.z * m.z == 0.0 {
+            return Err(ImuConfigError::InvalidMounting);
+        }
+        if !self.g_sensitivity.is_finite() || self.g_sensitivity < 0.0 {
+            return Err(ImuConfigError::InvalidGSensitivity(self.g_sensitivity));
+        }
+        for rate in [self.accel_rate_hz, self.gyro_rate_hz].into_iter().flatten() {
+            if !rate.is_finite() || rate <= 0.0 {
+                return Err(ImuConfigError::InvalidOutputRate(rate));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors from invalid IMU simulator settings
+#[derive(Debug, Error)]
+pub enum ImuConfigError {
+    /// At least one sub-sample is needed per reading
+    #[error("IMU oversampling must be at least 1")]
+    ZeroOversampling,
+
+    /// The mounting rotation is not a usable quaternion
+    #[error("IMU mounting quaternion must be finite and non-zero")]
+    InvalidMounting,
+
+    /// The g-sensitivity coefficient is negative or not a number
+    #[error("IMU g_sensitivity must be finite and >= 0, got {0}")]
+    InvalidGSensitivity(f64),
+
+    /// An output data rate is zero, negative or not a number
+    #[error("IMU accel_rate_hz and gyro_rate_hz must be > 0, got {0}")]
+    InvalidOutputRate(f64),
+}
 
 /// IMU sensor simulator with realistic noise characteristics
 pub struct ImuSimulator {
@@ -34,14 +141,55 @@ pub struct ImuSimulator {
     /// Simulation time step counter
     tick_count: u64,
     
-    /// Random number generator (using thread-safe StdRng)
-    rng: rand::rngs::StdRng,
+    /// Simulated time between reads (s)
+    read_interval: f64,
+    
+    /// Accelerometer output data rate (Hz, every read when unset)
+    accel_rate_hz: Option<f64>,
+    
+    /// Gyroscope output data rate (Hz, every read when unset)
+    gyro_rate_hz: Option<f64>,
+    
+    /// Last accelerometer sample and its noise level, repeated until the
+    /// next one is due
+    held_accel: Option<(Vec3, f64)>,
+    
+    /// Last gyroscope sample, repeated until the next one is due
+    held_gyro: Option<Vec3>,
+    
+    /// Sub-samples averaged into each reading
+    oversampling: u32,
+    
+    /// Body-to-sensor mounting rotation (unit quaternion)
+    mounting: Quaternion,
+    
+    /// Gyro error per unit of specific force ((rad/s) per (m/s²))
+    g_sensitivity: f64,
+    
+    /// Period over which the gyro bias settles after start
+    warm_up: WarmUp,
+    
+    /// Extra gyro bias at start, fading out over the warm-up (rad/s)
+    warm_up_bias: Vec3,
+    
+    /// Reported health forced by the user, replacing the computed value
+    health_override: Option<f64>,
+    
+    /// Injected accelerometer spike, replacing the modelled linear
+    /// acceleration until it is taken or cleared
+    accel_spike: Option<Spike>,
+    
+    /// Injected gyroscope spike, replacing the modelled angular velocity
+    /// until it is taken or cleared
+    gyro_spike: Option<Spike>,
+    
+    /// Random number generator (StdRng unless configured otherwise)
+    rng: SimRng,
 }
 
 impl ImuSimulator {
     /// Create a new IMU simulator with default parameters
     pub fn new() -> Self {
-        use rand::SeedableRng;
         Self {
             orientation: (0.0, 0.0, 0.0),
             angular_velocity: Vec3::zero(),
@@ -50,35 +198,106 @@ impl ImuSimulator {
             accel_noise_std: 0.05,  // 0.05 m/s² noise
             gyro_noise_std: 0.005,  // 0.005 rad/s noise
             tick_count: 0,
-            rng: rand::rngs::StdRng::from_entropy(),
+            read_interval: READ_INTERVAL,
+            accel_rate_hz: None,
+            gyro_rate_hz: None,
+            held_accel: None,
+            held_gyro: None,
+            oversampling: 1,
+            mounting: Quaternion::identity(),
+            g_sensitivity: 0.0,
+            warm_up: WarmUp::default(),
+            warm_up_bias: Vec3::zero(),
+            health_override: None,
+            accel_spike: None,
+            gyro_spike: None,
+            rng: SimRng::from_entropy(RngBackend::Std),
         }
     }
 
+    /// Create an IMU simulator with the given settings
+    pub fn with_config(config: ImuConfig) -> Result<Self, ImuConfigError> {
+        config.validate()?;
+        Ok(Self {
+            oversampling: config.oversampling,
+            mounting: kernels::normalize(config.mounting),
+            g_sensitivity: config.g_sensitivity,
+            accel_rate_hz: config.accel_rate_hz,
+            gyro_rate_hz: config.gyro_rate_hz,
+            ..Self::new()
+        })
+    }
+
     /// Read current IMU sensor data with simulated dynamics
+    /// 
+    /// With oversampling, the reading interval is split into K sub-steps
+    /// and the reading is the average of one motion+noise sample per step.
+    /// With output data rates set, a sensor not due for a new sample since
+    /// the previous read repeats its last sample. An injected spike replaces
+    /// the modelled motion in every sub-sample of the reading.
     pub fn read(&mut self) -> ImuData {
         self.tick_count += 1;
         
-        // Simulate realistic motion dynamics
-        self.simulate_motion();
-        
-        // Get gravity vector in sensor frame
-        let gravity = self.calculate_gravity_vector();
+        let k = self.oversampling.max(1);
+        let dt = self.read_interval / k as f64;
+        let start = (self.tick_count - 1) as f64 * self.read_interval;
         
-        // Simulate accelerometer reading (linear accel + gravity + noise)
-        let accel_noise = self.generate_accel_noise();
-        let measured_accel = Vec3::new(
-            self.linear_acceleration.x + gravity.x + accel_noise.x,
-            self.linear_acceleration.y + gravity.y + accel_noise.y,
-            self.linear_acceleration.z + gravity.z + accel_noise.z,
+        // Remaining share of the start-up bias (0 once warmed up)
+        let settling = 1.0 - self.warm_up.progress(Duration::from_secs_f64(start));
+        let bias = Vec3::new(
+            self.gyro_bias.x + self.warm_up_bias.x * settling,
+            self.gyro_bias.y + self.warm_up_bias.y * settling,
+            self.gyro_bias.z + self.warm_up_bias.z * settling,
         );
         
-        // Simulate gyroscope reading (angular velocity + bias + noise)
-        let gyro_noise = self.generate_gyro_noise();
-        let measured_gyro = Vec3::new(
-            self.angular_velocity.x + self.gyro_bias.x + gyro_noise.x,
-            self.angular_velocity.y + self.gyro_bias.y + gyro_noise.y,
-            self.angular_velocity.z + self.gyro_bias.z + gyro_noise.z,
-        );
+        let mut accel_sum = Vec3::zero();
+        let mut gyro_sum = Vec3::zero();
+        let mut noise_sum = Vec3::zero();
+        for step in 1..=k {
+            // Simulate realistic motion dynamics
+            self.simulate_motion(start + step as f64 * dt, dt);
+            if let Some(spike) = self.accel_spike {
+                self.linear_acceleration = spike.value;
+            }
+            if let Some(spike) = self.gyro_spike {
+                self.angular_velocity = spike.value;
+            }
+            
+            // Get gravity vector in body frame
+            let gravity = self.calculate_gravity_vector();
+            
+            // Simulate accelerometer reading (linear accel + gravity + noise)
+            let specific_force = self.mounting.rotate(Vec3::new(
+                self.linear_acceleration.x + gravity.x,
+                self.linear_acceleration.y + gravity.y,
+                self.linear_acceleration.z + gravity.z,
+            ));
+            let accel_noise = self.generate_accel_noise();
+            accel_sum.x += specific_force.x + accel_noise.x;
+            accel_sum.y += specific_force.y + accel_noise.y;
+            accel_sum.z += specific_force.z + accel_noise.z;
+            noise_sum.x += accel_noise.x;
+            noise_sum.y += accel_noise.y;
+            noise_sum.z += accel_noise.z;
+            
+            // Simulate gyroscope reading (angular velocity + bias +
+            // g-sensitivity + noise)
+            let rate = self.mounting.rotate(self.angular_velocity);
+            let gyro_noise = self.generate_gyro_noise();
+            let g = self.g_sensitivity;
+            gyro_sum.x += rate.x + bias.x + g * specific_force.x + gyro_noise.x;
+            gyro_sum.y += rate.y + bias.y + g * specific_force.y + gyro_noise.y;
+            gyro_sum.z += rate.z + bias.z + g * specific_force.z + gyro_noise.z;
+        }
+        
+        let k = k as f64;
+        let measured_accel = Vec3::new(accel_sum.x / k, accel_sum.y / k, accel_sum.z / k);
+        let measured_gyro = Vec3::new(gyro_sum.x / k, gyro_sum.y / k, gyro_sum.z / k);
+        let accel_noise = Vec3::new(noise_sum.x / k, noise_sum.y / k, noise_sum.z / k);
+        
+        // One-shot spikes have been taken; held ones last until cleared
+        self.accel_spike = self.accel_spike.filter(|spike| spike.held);
+        self.gyro_spike = self.gyro_spike.filter(|spike| spike.held);
         
         // Update gyroscope bias (simulates slow drift over time)
         self.update_gyro_bias();
@@ -86,8 +305,23 @@ impl ImuSimulator {
         // Calculate noise level metric for health monitoring
         let noise_level = (accel_noise.magnitude() / self.accel_noise_std).min(1.0);
         
+        // Hold each sensor's last sample until its next one is due
+        let end = start + self.read_interval;
+        let (measured_accel, noise_level) = match self.held_accel {
+            Some(held) if !sample_due(self.accel_rate_hz, start, end) => held,
+            _ => (measured_accel, noise_level),
+        };
+        let measured_gyro = match self.held_gyro {
+            Some(held) if !sample_due(self.gyro_rate_hz, start, end) => held,
+            _ => measured_gyro,
+        };
+        self.held_accel = Some((measured_accel, noise_level));
+        self.held_gyro = Some(measured_gyro);
+        
         // Simulate sensor health (occasionally inject minor degradation)
-        let health = if self.tick_count % 500 == 0 {
+        let health = if let Some(health) = self.health_override {
+            health
+        } else if self.tick_count.is_multiple_of(500) {
             0.85 + self.rng.gen::<f64>() * 0.15  // 85-100% health
         } else {
             0.95 + self.rng.gen::<f64>() * 0.05  // 95-100% health
@@ -103,9 +337,9 @@ impl ImuSimulator {
     }
 
     /// Simulate realistic motion dynamics (sinusoidal movement patterns)
-    fn simulate_motion(&mut self) {
-        let t = self.tick_count as f64 * 0.02; // Time in seconds (50 Hz)
-        
+    /// 
+    /// Advances the state by `dt` seconds to simulation time `t`.
+    fn simulate_motion(&mut self, t: f64, dt: f64) {
         // Simulate smooth rotation patterns (like a drone or vehicle maneuvering)
         self.angular_velocity = Vec3::new(
             0.1 * (0.3 * t).sin(),  // Roll rate
@@ -114,7 +348,6 @@ impl ImuSimulator {
         );
         
         // Update orientation based on angular velocity
-        let dt = 0.02; // 50 Hz = 0.02s timestep
         self.orientation.0 += self.angular_velocity.x * dt;
         self.orientation.1 += self.angular_velocity.y * dt;
         self.orientation.2 += self.angular_velocity.z * dt;
@@ -132,12 +365,12 @@ impl ImuSimulator {
         );
     }
 
-    /// Calculate gravity vector in sensor frame based on current orientation
+    /// Calculate gravity vector in body frame based on current orientation
     fn calculate_gravity_vector(&self) -> Vec3 {
         let (roll, pitch, _yaw) = self.orientation;
         
         // Gravity is 9.81 m/s² in world frame (pointing down)
-        // Transform to sensor frame based on orientation
+        // Transform to body frame based on orientation
         let g = 9.81;
         
         Vec3::new(
@@ -185,22 +418,86 @@ impl ImuSimulator {
         self.gyro_bias.z = self.gyro_bias.z.clamp(-0.01, 0.01);
     }
 
+    /// Start with an elevated gyro bias that settles linearly to nominal
+    /// over `duration` of readings (zero = nominal from the first reading)
+    /// 
+    /// The start-up bias is drawn at random, up to `WARM_UP_GYRO_BIAS`
+    /// per axis.
+    pub fn set_warm_up(&mut self, duration: Duration) {
+        self.warm_up = WarmUp::new(duration);
+        self.warm_up_bias = Vec3::new(
+            self.rng.gen_range(-WARM_UP_GYRO_BIAS..=WARM_UP_GYRO_BIAS),
+            self.rng.gen_range(-WARM_UP_GYRO_BIAS..=WARM_UP_GYRO_BIAS),
+            self.rng.gen_range(-WARM_UP_GYRO_BIAS..=WARM_UP_GYRO_BIAS),
+        );
+    }
+
+    /// Get the warm-up period
+    pub fn warm_up(&self) -> Duration {
+        self.warm_up.duration()
+    }
+
+    /// Get the true gyro bias (rad/s) the next reading will carry, any
+    /// remaining warm-up bias included, for checking a bias estimator
+    /// 
+    /// The bias random-walks slowly, so it moves a little with each read.
+    pub fn gyro_bias(&self) -> Vec3 {
+        let next = self.tick_count as f64 * self.read_interval;
+        let settling = 1.0 - self.warm_up.progress(Duration::from_secs_f64(next));
+        Vec3::new(
+            self.gyro_bias.x + self.warm_up_bias.x * settling,
+            self.gyro_bias.y + self.warm_up_bias.y * settling,
+            self.gyro_bias.z + self.warm_up_bias.z * settling,
+        )
+    }
+
+    /// Advance simulated time by `1 / hz` per read instead of the default
+    /// 50 Hz, to match the rate `read` is called at
+    pub fn set_read_rate(&mut self, hz: u32) {
+        self.read_interval = 1.0 / hz.max(1) as f64;
+    }
+
+    /// Replace the random number generator, e.g. with a seeded one for a
+    /// reproducible run
+    /// 
+    /// Call before `set_warm_up`, which draws the start-up bias.
+    pub fn set_rng(&mut self, rng: SimRng) {
+        self.rng = rng;
+    }
+
     /// Inject a fault for testing anomaly detection
+    /// 
+    /// A spike shows in the next reading only; high noise lasts until
+    /// cleared.
     pub fn inject_fault(&mut self, fault_type: FaultType) {
+        self.inject(fault_type, false);
+    }
+
+    /// Inject a fault that lasts until cleared
+    /// 
+    /// Unlike `inject_fault`, a spike repeats in every reading until
+    /// `clear_fault` or `reset_faults`, for faults given a duration.
+    pub fn inject_held_fault(&mut self, fault_type: FaultType) {
+        self.inject(fault_type, true);
+    }
+
+    fn inject(&mut self, fault_type: FaultType, held: bool) {
         match fault_type {
             FaultType::AccelSpike => {
-                self.linear_acceleration = Vec3::new(
+                let value = Vec3::new(
                     self.rng.gen_range(-20.0..20.0),
                     self.rng.gen_range(-20.0..20.0),
                     self.rng.gen_range(-20.0..20.0),
                 );
+                self.accel_spike = Some(Spike { value, held });
             }
             FaultType::GyroSpike => {
-                self.angular_velocity = Vec3::new(
+                let value = Vec3::new(
                     self.rng.gen_range(-5.0..5.0),
                     self.rng.gen_range(-5.0..5.0),
                     self.rng.gen_range(-5.0..5.0),
                 );
+                self.gyro_spike = Some(Spike { value, held });
             }
             FaultType::HighNoise => {
                 self.accel_noise_std = 0.5;  // 10x normal noise
@@ -213,6 +510,36 @@ impl ImuSimulator {
     pub fn reset_faults(&mut self) {
         self.accel_noise_std = 0.05;
         self.gyro_noise_std = 0.005;
+        self.accel_spike = None;
+        self.gyro_spike = None;
+    }
+
+    /// Clear a single fault, leaving any others in effect
+    /// 
+    /// Drops a spike not yet taken (or held), or returns the noise to
+    /// normal.
+    pub fn clear_fault(&mut self, fault_type: FaultType) {
+        match fault_type {
+            FaultType::AccelSpike => self.accel_spike = None,
+            FaultType::GyroSpike => self.gyro_spike = None,
+            FaultType::HighNoise => {
+                self.accel_noise_std = 0.05;
+                self.gyro_noise_std = 0.005;
+            }
+        }
+    }
+
+    /// Force the reported health to `health` (clamped to 0.0 - 1.0), or
+    /// return to computed health with `None`
+    /// 
+    /// Independent of fault injection; resetting faults keeps the override.
+    pub fn set_health_override(&mut self, health: Option<f64>) {
+        self.health_override = health.filter(|h| h.is_finite()).map(|h| h.clamp(0.0, 1.0));
+    }
+
+    /// Get the forced health value, if any
+    pub fn health_override(&self) -> Option<f64> {
+        self.health_override
     }
 }
 
@@ -222,8 +549,18 @@ impl Default for ImuSimulator {
     }
 }
 
-/// Types of faults that can be injected for testing
+/// An injected spike waiting for (or held across) readings
 #[derive(Debug, Clone, Copy)]
+struct Spike {
+    /// Motion the spike reports in place of the model's
+    value: Vec3,
+
+    /// Repeat in every reading until cleared, rather than only the next
+    held: bool,
+}
+
+/// Types of faults that can be injected for testing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FaultType {
     /// Sudden spike in accelerometer readings
     AccelSpike,
@@ -233,6 +570,18 @@ pub enum FaultType {
     HighNoise,
 }
 
+/// Check whether a sensor sampling at `rate_hz` (every read when unset)
+/// takes a new sample between simulated times `start` and `end`
+/// 
+/// Sample instants are whole multiples of the sample period, with a little
+/// slack so rates that divide the read rate evenly aren't lost to rounding.
+fn sample_due(rate_hz: Option<f64>, start: f64, end: f64) -> bool {
+    match rate_hz {
+        Some(rate) => (end * rate + 1e-9).floor() > (start * rate + 1e-9).floor(),
+        None => true,
+    }
+}
+
 /// Normalize angle to [-π, π] range
 fn normalize_angle(angle: f64) -> f64 {
     let mut a = angle;
@@ -243,4 +592,218 @@ fn normalize_angle(angle: f64) -> f64 {
         a += 2.0 * PI;
     }
     a
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seeded simulator averaging `oversampling` sub-samples
+    fn seeded_imu(oversampling: u32) -> ImuSimulator {
+        let mut imu = ImuSimulator::with_config(ImuConfig {
+            oversampling,
+            ..ImuConfig::default()
+        })
+        .unwrap();
+        imu.rng = SimRng::seeded(RngBackend::Std, 7);
+        imu
+    }
+
+    /// Per-axis accelerometer noise standard deviation and mean noise level
+    /// 
+    /// The noise is measured against a noise-free simulator following the
+    /// same motion.
+    fn noise_stats(oversampling: u32) -> (f64, f64) {
+        let mut imu = seeded_imu(oversampling);
+        let mut clean = seeded_imu(oversampling);
+        clean.accel_noise_std = 0.0;
+        let samples: Vec<(f64, f64)> = (0..4000)
+            .map(|_| {
+                let reading = imu.read();
+                (reading.acceleration.x - clean.read().acceleration.x, reading.noise_level)
+            })
+            .collect();
+        let n = samples.len() as f64;
+        let mean_x = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let std_x = (samples.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>() / n).sqrt();
+        let noise_level = samples.iter().map(|(_, level)| level).sum::<f64>() / n;
+        (std_x, noise_level)
+    }
+
+    #[test]
+    fn test_oversampling_16_quarters_the_noise() {
+        let (std_1, level_1) = noise_stats(1);
+        let (std_16, level_16) = noise_stats(16);
+
+        let ratio = std_16 / std_1;
+        assert!((0.2..=0.3).contains(&ratio), "noise ratio {ratio:.3}");
+
+        // Unaveraged readings saturate noise_level at 1.0 more often than
+        // not, so its ratio lands above the true quarter
+        assert!((0.35..=0.45).contains(&level_16), "K=16 noise level {level_16:.3}");
+        assert!(level_16 < level_1 * 0.6, "noise levels {level_1:.3} -> {level_16:.3}");
+    }
+
+    /// Noise-free, seeded simulator on the given mount
+    fn noiseless_imu(mounting: Quaternion) -> ImuSimulator {
+        let mut imu = ImuSimulator::with_config(ImuConfig {
+            mounting,
+            ..ImuConfig::default()
+        })
+        .unwrap();
+        imu.rng = SimRng::seeded(RngBackend::Std, 7);
+        imu.accel_noise_std = 0.0;
+        imu
+    }
+
+    #[test]
+    fn test_mounting_rotates_measured_gravity() {
+        // Rolled 90° on its mount, gravity moves off Z onto Y
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        let mounting = Quaternion::new(h, h, 0.0, 0.0);
+        let mut aligned = noiseless_imu(Quaternion::identity());
+        let mut mounted = noiseless_imu(mounting);
+        for _ in 0..200 {
+            let body = aligned.read().acceleration;
+            let sensor = mounted.read().acceleration;
+            let expected = mounting.rotate(body);
+            assert!(body.z > 9.0 && expected.y.abs() > 9.0, "expected {expected:?}");
+            for (got, want) in [(sensor.x, expected.x), (sensor.y, expected.y), (sensor.z, expected.z)] {
+                assert!((got - want).abs() < 1e-9, "mounted reading {sensor:?}, expected {expected:?}");
+            }
+        }
+    }
+
+    /// Mean gyroscope and accelerometer readings of a seeded simulator
+    fn mean_readings(g_sensitivity: f64, mounting: Quaternion) -> (Vec3, Vec3) {
+        let mut imu = ImuSimulator::with_config(ImuConfig {
+            mounting,
+            g_sensitivity,
+            ..ImuConfig::default()
+        })
+        .unwrap();
+        imu.rng = SimRng::seeded(RngBackend::Std, 7);
+        let n = 2000;
+        let (gyro, accel) = (0..n).map(|_| imu.read()).fold((Vec3::zero(), Vec3::zero()), |(w, a), r| {
+            let (dw, da) = (r.gyroscope, r.acceleration);
+            (Vec3::new(w.x + dw.x, w.y + dw.y, w.z + dw.z), Vec3::new(a.x + da.x, a.y + da.y, a.z + da.z))
+        });
+        let mean = |v: Vec3| Vec3::new(v.x / n as f64, v.y / n as f64, v.z / n as f64);
+        (mean(gyro), mean(accel))
+    }
+
+    #[test]
+    fn test_g_sensitivity_turns_acceleration_into_gyro_rate() {
+        // Same seed, so the difference is the g-sensitivity term alone
+        let (clean, _) = mean_readings(0.0, Quaternion::identity());
+        let (sensitive, accel) = mean_readings(0.01, Quaternion::identity());
+        let spurious = Vec3::new(sensitive.x - clean.x, sensitive.y - clean.y, sensitive.z - clean.z);
+        assert!(accel.z > 8.0, "mean acceleration {accel:?}");
+        for (rate, force) in [(spurious.x, accel.x), (spurious.y, accel.y), (spurious.z, accel.z)] {
+            assert!((rate - 0.01 * force).abs() < 1e-3, "spurious rate {spurious:?} for acceleration {accel:?}");
+        }
+
+        // Rolled onto its side, gravity and the error move to Y
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        let mounting = Quaternion::new(h, h, 0.0, 0.0);
+        let (clean, _) = mean_readings(0.0, mounting);
+        let (sensitive, accel) = mean_readings(0.01, mounting);
+        assert!(accel.y.abs() > 8.0, "mean acceleration {accel:?}");
+        assert!(((sensitive.y - clean.y) - 0.01 * accel.y).abs() < 1e-3);
+        assert!(((sensitive.z - clean.z) - 0.01 * accel.z).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_negative_g_sensitivity_rejected() {
+        let config = ImuConfig {
+            g_sensitivity: -1e-4,
+            ..ImuConfig::default()
+        };
+        assert!(matches!(ImuSimulator::with_config(config), Err(ImuConfigError::InvalidGSensitivity(_))));
+    }
+
+    #[test]
+    fn test_slower_accel_rate_holds_samples_between_updates() {
+        // 100 Hz reads with a 100 Hz gyro and a 50 Hz accelerometer
+        let mut imu = ImuSimulator::with_config(ImuConfig {
+            accel_rate_hz: Some(50.0),
+            gyro_rate_hz: Some(100.0),
+            ..ImuConfig::default()
+        })
+        .unwrap();
+        imu.set_read_rate(100);
+        let readings: Vec<ImuData> = (0..40).map(|_| imu.read()).collect();
+        for (i, pair) in readings.windows(2).enumerate() {
+            // Accel samples fall every 20 ms, at the end of even reads, and
+            // odd reads after the first repeat them
+            let accel_repeats = pair[0].acceleration.to_array() == pair[1].acceleration.to_array();
+            assert_eq!(accel_repeats, i % 2 == 1, "accel at reads {} and {}", i + 1, i + 2);
+            if accel_repeats {
+                assert_eq!(pair[0].noise_level, pair[1].noise_level);
+            }
+            assert_ne!(pair[0].gyroscope.to_array(), pair[1].gyroscope.to_array(), "gyro held at read {}", i + 2);
+        }
+    }
+
+    #[test]
+    fn test_injected_spike_reaches_the_reading() {
+        let mut imu = noiseless_imu(Quaternion::identity());
+        let mut clean = noiseless_imu(Quaternion::identity());
+        let offset = |imu: &mut ImuSimulator, clean: &mut ImuSimulator| {
+            let (spiked, nominal) = (imu.read(), clean.read());
+            (spiked.acceleration.x - nominal.acceleration.x).abs() + (spiked.gyroscope.x - nominal.gyroscope.x).abs()
+        };
+        assert!(offset(&mut imu, &mut clean) < 1e-9);
+
+        // The reference draws (and drops) the same spikes to keep the two
+        // random streams in step
+        let inject = |imu: &mut ImuSimulator, clean: &mut ImuSimulator, fault, held| {
+            if held {
+                imu.inject_held_fault(fault);
+            } else {
+                imu.inject_fault(fault);
+            }
+            clean.inject_fault(fault);
+            clean.clear_fault(fault);
+        };
+
+        // One-shot: only the next reading carries the spike
+        inject(&mut imu, &mut clean, FaultType::AccelSpike, false);
+        let spike = imu.accel_spike.unwrap().value;
+        let reading = imu.read();
+        let nominal = clean.read();
+        let jump = Vec3::new(
+            reading.acceleration.x - nominal.acceleration.x,
+            reading.acceleration.y - nominal.acceleration.y,
+            reading.acceleration.z - nominal.acceleration.z,
+        );
+        assert!(jump.magnitude() > 1.0, "spike {spike:?} missing from reading, moved it by {jump:?}");
+        assert!(offset(&mut imu, &mut clean) < 1e-9, "one-shot spike repeated");
+
+        // Held: every reading carries it until cleared
+        inject(&mut imu, &mut clean, FaultType::GyroSpike, true);
+        for _ in 0..5 {
+            assert!(offset(&mut imu, &mut clean) > 1e-3, "held gyro spike missing from reading");
+        }
+        imu.clear_fault(FaultType::GyroSpike);
+        assert!(offset(&mut imu, &mut clean) < 1e-9, "gyro spike outlived its clear");
+    }
+
+    #[test]
+    fn test_zero_output_rate_rejected() {
+        let config = ImuConfig {
+            gyro_rate_hz: Some(0.0),
+            ..ImuConfig::default()
+        };
+        assert!(matches!(ImuSimulator::with_config(config), Err(ImuConfigError::InvalidOutputRate(_))));
+    }
+
+    #[test]
+    fn test_zero_oversampling_rejected() {
+        let config = ImuConfig {
+            oversampling: 0,
+            ..ImuConfig::default()
+        };
+        assert!(matches!(ImuSimulator::with_config(config), Err(ImuConfigError::ZeroOversampling)));
+    }
+}
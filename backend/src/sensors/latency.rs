@@ -0,0 +1,96 @@
+//! Sensor Latency Simulation
+//!
+//! Real sensors deliver measurements late: a GPS fix typically describes
+//! where the receiver was ~100 ms before it arrives. A `DelayQueue` holds
+//! each simulated sample back for a fixed latency before the fusion loop
+//! can see it, while the sample keeps its measurement timestamp.
+//!
+//! Samples are released in measurement order. With a latency longer than
+//! the sensor's update interval several samples are in flight at once;
+//! they queue up rather than overwrite or overtake each other.
+
+use crate::models::duration_secs;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Most samples held in flight; beyond this the oldest are dropped
+const MAX_IN_FLIGHT: usize = 1024;
+
+/// Per-sensor delivery latency for the simulators
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SensorLatency {
+    /// Delay before an IMU reading reaches the fusion loop
+    #[serde(rename = "imu_secs", serialize_with = "duration_secs::serialize")]
+    pub imu: Duration,
+
+    /// Delay before a GPS fix reaches the fusion loop
+    #[serde(rename = "gps_secs", serialize_with = "duration_secs::serialize")]
+    pub gps: Duration,
+}
+
+/// Holds samples back until their latency has elapsed
+#[derive(Debug, Clone)]
+pub struct DelayQueue<T> {
+    latency: Duration,
+    in_flight: VecDeque<(Instant, T)>,
+}
+
+impl<T> DelayQueue<T> {
+    /// Create a queue delaying every sample by `latency`
+    pub fn new(latency: Duration) -> Self {
+        Self {
+            latency,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    /// Delay applied to each sample
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+
+    /// Queue a sample measured at `measured_at`
+    pub fn push(&mut self, sample: T, measured_at: Instant) {
+        let ready_at = measured_at + self.latency;
+
+        // Keep release order by measurement time even if pushed out of order
+        let index = self.in_flight.partition_point(|(at, _)| *at <= ready_at);
+        self.in_flight.insert(index, (ready_at, sample));
+        if self.in_flight.len() > MAX_IN_FLIGHT {
+            self.in_flight.pop_front();
+        }
+    }
+
+    /// Take the oldest sample whose latency has elapsed by `now`
+    pub fn pop_ready(&mut self, now: Instant) -> Option<T> {
+        match self.in_flight.front() {
+            Some((ready_at, _)) if *ready_at <= now => self.in_flight.pop_front().map(|(_, sample)| sample),
+            _ => None,
+        }
+    }
+
+    /// Take every sample that has arrived by `now` and return the newest
+    pub fn latest_ready(&mut self, now: Instant) -> Option<T> {
+        let mut latest = None;
+        while let Some(sample) = self.pop_ready(now) {
+            latest = Some(sample);
+        }
+        latest
+    }
+
+    /// Number of samples still in flight
+    pub fn len(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Whether no samples are in flight
+    pub fn is_empty(&self) -> bool {
+        self.in_flight.is_empty()
+    }
+
+    /// Drop every sample still in flight
+    pub fn clear(&mut self) {
+        self.in_flight.clear();
+    }
+}
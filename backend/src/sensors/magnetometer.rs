@@ -0,0 +1,244 @@
+//! Magnetometer Calibration
+//!
+//! Estimates hard-iron (offset) and soft-iron (per-axis scale) corrections
+//! from magnetometer samples collected while the sensor is rotated:
+//! - Samples from an ideal sensor lie on a sphere centered at the origin
+//! - Hard-iron effects shift the sphere's center
+//! - Soft-iron effects stretch it into an axis-aligned ellipsoid
+//!
+//! The calibration fits an axis-aligned ellipsoid by linear least squares
+//! and maps it back onto a sphere of the mean radius.
+
+use crate::models::Vec3;
+use nalgebra::{Matrix6, Vector6};
+use thiserror::Error;
+
+/// Errors produced by magnetometer calibration
+#[derive(Debug, Error)]
+pub enum CalibrationError {
+    /// Not enough samples to fit the ellipsoid
+    #[error("insufficient samples: got {got}, need at least {need}")]
+    InsufficientSamples { got: usize, need: usize },
+
+    /// Samples don't cover enough orientations to constrain the fit
+    #[error("insufficient rotation coverage: {octants}/8 octants sampled, need {need}")]
+    InsufficientCoverage { octants: usize, need: usize },
+
+    /// Samples don't describe an ellipsoid (degenerate or non-finite fit)
+    #[error("degenerate sample set, ellipsoid fit failed")]
+    DegenerateFit,
+}
+
+/// Hard- and soft-iron corrections for a magnetometer
+#[derive(Debug, Clone, Copy)]
+pub struct MagCorrections {
+    /// Hard-iron offset subtracted from raw readings
+    pub hard_iron: Vec3,
+
+    /// Per-axis soft-iron scale applied after offset removal
+    pub soft_iron: Vec3,
+
+    /// Fraction of orientation octants covered by the samples (0.0 - 1.0)
+    pub coverage: f64,
+}
+
+impl MagCorrections {
+    /// Identity corrections (no offset, unit scale)
+    pub fn identity() -> Self {
+        Self {
+            hard_iron: Vec3::zero(),
+            soft_iron: Vec3::new(1.0, 1.0, 1.0),
+            coverage: 0.0,
+        }
+    }
+
+    /// Apply corrections to a raw magnetometer reading
+    pub fn apply(&self, raw: Vec3) -> Vec3 {
+        Vec3::new(
+            (raw.x - self.hard_iron.x) * self.soft_iron.x,
+            (raw.y - self.hard_iron.y) * self.soft_iron.y,
+            (raw.z - self.hard_iron.z) * self.soft_iron.z,
+        )
+    }
+}
+
+impl Default for MagCorrections {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// Magnetometer hard/soft-iron calibration routine
+#[derive(Debug, Clone)]
+pub struct MagnetometerCalibration {
+    /// Minimum number of samples required for a fit
+    min_samples: usize,
+
+    /// Minimum number of octants (around the fitted center) that must hold samples
+    min_octants: usize,
+}
+
+impl MagnetometerCalibration {
+    /// Create a calibration routine with default requirements
+    pub fn new() -> Self {
+        Self {
+            min_samples: 50,
+            min_octants: 6,
+        }
+    }
+
+    /// Set minimum sample count (at least 6 are needed for the fit)
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(6);
+        self
+    }
+
+    /// Set minimum number of covered octants (1-8)
+    pub fn with_min_octants(mut self, min_octants: usize) -> Self {
+        self.min_octants = min_octants.clamp(1, 8);
+        self
+    }
+
+    /// Estimate corrections from samples collected over a rotation
+    pub fn calibrate(&self, samples: &[Vec3]) -> Result<MagCorrections, CalibrationError> {
+        if samples.len() < self.min_samples {
+            return Err(CalibrationError::InsufficientSamples {
+                got: samples.len(),
+                need: self.min_samples,
+            });
+        }
+
+        // Fit A x² + B y² + C z² + D x + E y + F z = 1 via normal equations
+        let mut ata = Matrix6::<f64>::zeros();
+        let mut atb = Vector6::<f64>::zeros();
+        for s in samples {
+            let row = Vector6::new(s.x * s.x, s.y * s.y, s.z * s.z, s.x, s.y, s.z);
+            ata += row * row.transpose();
+            atb += row;
+        }
+
+        let p = ata
+            .cholesky()
+            .ok_or(CalibrationError::DegenerateFit)?
+            .solve(&atb);
+        let (a, b, c, d, e, f) = (p[0], p[1], p[2], p[3], p[4], p[5]);
+        if a <= 0.0 || b <= 0.0 || c <= 0.0 {
+            return Err(CalibrationError::DegenerateFit);
+        }
+
+        // Complete the square to recover center and semi-axes
+        let center = Vec3::new(-d / (2.0 * a), -e / (2.0 * b), -f / (2.0 * c));
+        let g = 1.0 + d * d / (4.0 * a) + e * e / (4.0 * b) + f * f / (4.0 * c);
+        if g <= 0.0 {
+            return Err(CalibrationError::DegenerateFit);
+        }
+        let radii = Vec3::new((g / a).sqrt(), (g / b).sqrt(), (g / c).sqrt());
+        let mean_radius = (radii.x + radii.y + radii.z) / 3.0;
+
+        let corrections = MagCorrections {
+            hard_iron: center,
+            soft_iron: Vec3::new(mean_radius / radii.x, mean_radius / radii.y, mean_radius / radii.z),
+            coverage: 0.0,
+        };
+        if !(corrections.hard_iron.magnitude().is_finite() && corrections.soft_iron.magnitude().is_finite()) {
+            return Err(CalibrationError::DegenerateFit);
+        }
+
+        // A fit from a narrow arc can look valid but extrapolates badly
+        let octants = count_octants(samples, center);
+        if octants < self.min_octants {
+            return Err(CalibrationError::InsufficientCoverage {
+                octants,
+                need: self.min_octants,
+            });
+        }
+
+        Ok(MagCorrections {
+            coverage: octants as f64 / 8.0,
+            ..corrections
+        })
+    }
+}
+
+impl Default for MagnetometerCalibration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Count the octants around `center` that contain at least one sample
+fn count_octants(samples: &[Vec3], center: Vec3) -> usize {
+    let mut seen = [false; 8];
+    for s in samples {
+        let index = (s.x >= center.x) as usize
+            | ((s.y >= center.y) as usize) << 1
+            | ((s.z >= center.z) as usize) << 2;
+        seen[index] = true;
+    }
+    seen.iter().filter(|&&hit| hit).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Samples of a field of magnitude `radius` seen through the given
+    /// hard-iron offset and per-axis soft-iron stretch, spread over the sphere
+    fn ellipsoid_samples(offset: Vec3, stretch: Vec3, radius: f64) -> Vec<Vec3> {
+        let mut samples = Vec::new();
+        for i in 0..12 {
+            let polar = std::f64::consts::PI * (i as f64 + 0.5) / 12.0;
+            for j in 0..24 {
+                let azimuth = 2.0 * std::f64::consts::PI * j as f64 / 24.0;
+                samples.push(Vec3::new(
+                    offset.x + stretch.x * radius * polar.sin() * azimuth.cos(),
+                    offset.y + stretch.y * radius * polar.sin() * azimuth.sin(),
+                    offset.z + stretch.z * radius * polar.cos(),
+                ));
+            }
+        }
+        samples
+    }
+
+    fn assert_close(actual: Vec3, expected: Vec3, tolerance: f64) {
+        let error = Vec3::new(actual.x - expected.x, actual.y - expected.y, actual.z - expected.z);
+        assert!(error.magnitude() < tolerance, "{actual:?} differs from {expected:?}");
+    }
+
+    #[test]
+    fn test_recovers_hard_iron_offset() {
+        let offset = Vec3::new(12.0, -7.5, 30.0);
+        let samples = ellipsoid_samples(offset, Vec3::new(1.0, 1.0, 1.0), 48.0);
+
+        let corrections = MagnetometerCalibration::new().calibrate(&samples).unwrap();
+        assert_close(corrections.hard_iron, offset, 1e-6);
+        assert_close(corrections.soft_iron, Vec3::new(1.0, 1.0, 1.0), 1e-6);
+        assert_eq!(corrections.coverage, 1.0);
+    }
+
+    #[test]
+    fn test_corrected_samples_lie_on_a_sphere() {
+        let samples = ellipsoid_samples(Vec3::new(-4.0, 9.0, 2.5), Vec3::new(1.2, 0.9, 1.05), 50.0);
+
+        let corrections = MagnetometerCalibration::new().calibrate(&samples).unwrap();
+        let radii: Vec<f64> = samples.iter().map(|&s| corrections.apply(s).magnitude()).collect();
+        let mean = radii.iter().sum::<f64>() / radii.len() as f64;
+        assert!(radii.iter().all(|r| (r - mean).abs() < 1e-6 * mean));
+    }
+
+    #[test]
+    fn test_rejects_too_few_samples_and_narrow_arcs() {
+        let samples = ellipsoid_samples(Vec3::zero(), Vec3::new(1.0, 1.0, 1.0), 50.0);
+        assert!(matches!(
+            MagnetometerCalibration::new().calibrate(&samples[..10]),
+            Err(CalibrationError::InsufficientSamples { got: 10, need: 50 })
+        ));
+
+        // Upper hemisphere, positive x only
+        let arc: Vec<Vec3> = samples.into_iter().filter(|s| s.z > 0.0 && s.x > 0.0).collect();
+        assert!(matches!(
+            MagnetometerCalibration::new().with_min_samples(6).calibrate(&arc),
+            Err(CalibrationError::InsufficientCoverage { octants: 2, need: 6 })
+        ));
+    }
+}
@@ -5,7 +5,25 @@
 
 pub mod imu;
 pub mod gps;
+pub mod gps_timing;
+pub mod magnetometer;
+pub mod replay;
+pub mod chaos;
+pub mod external;
+pub mod latency;
+pub mod fault_timers;
+pub mod warmup;
+pub mod rng;
 
 // Re-export commonly used types
-pub use imu::ImuSimulator;
-pub use gps::GpsSimulator;
\ No newline at end of file
+pub use imu::{ImuConfig, ImuSimulator};
+pub use gps::{GpsSimulator, HealthSmoothing, WaypointRoute};
+pub use gps_timing::{GpsScheduler, GpsTiming};
+pub use magnetometer::{MagnetometerCalibration, MagCorrections};
+pub use replay::ReplaySource;
+pub use chaos::{ChaosConfig, ChaosSchedule};
+pub use external::{ExternalLimits, ExternalSample, SensorInput};
+pub use latency::{DelayQueue, SensorLatency};
+pub use fault_timers::FaultTimers;
+pub use warmup::WarmUp;
+pub use rng::{RngBackend, SimRng, SimRngConfig};
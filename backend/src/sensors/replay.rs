@@ -0,0 +1,208 @@
+//! Recorded Data Replay
+//!
+//! Plays back previously recorded fused frames (JSON Lines, one
+//! `FusedSensorData` per line) with the original inter-sample timing:
+//! - Delays derived from the recorded timestamps
+//! - Adjustable playback speed (2.0 = twice as fast, 0.0 = paused)
+//! - Speeds beyond what the pipeline can keep up with run as fast as possible
+
+use crate::models::FusedSensorData;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Errors produced while loading or controlling a replay
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    /// Recording could not be read
+    #[error("failed to read recording: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A line of the recording is not a valid frame
+    #[error("invalid frame on line {line}: {source}")]
+    Parse {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    /// Playback speed must be a finite, non-negative number
+    #[error("invalid playback speed {0} (must be finite and >= 0)")]
+    InvalidSpeed(f64),
+}
+
+/// Source of recorded fused frames paced by their recorded timestamps
+pub struct ReplaySource {
+    /// Recorded frames in playback order
+    frames: Vec<FusedSensorData>,
+
+    /// Index of the next frame to emit
+    cursor: usize,
+
+    /// Playback speed multiplier (1.0 = real time, 0.0 = paused)
+    playback_speed: f64,
+
+    /// Wall-clock time the previous frame was emitted
+    last_emit: Option<Instant>,
+}
+
+impl ReplaySource {
+    /// Create a replay source from frames already in memory
+    pub fn from_frames(frames: Vec<FusedSensorData>) -> Self {
+        Self {
+            frames,
+            cursor: 0,
+            playback_speed: 1.0,
+            last_emit: None,
+        }
+    }
+
+    /// Load a JSON Lines recording (blank lines are skipped)
+    pub fn from_jsonl(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut frames = Vec::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let frame = serde_json::from_str(line).map_err(|source| ReplayError::Parse {
+                line: index + 1,
+                source,
+            })?;
+            frames.push(frame);
+        }
+
+        Ok(Self::from_frames(frames))
+    }
+
+    /// Get current playback speed
+    pub fn playback_speed(&self) -> f64 {
+        self.playback_speed
+    }
+
+    /// Set playback speed (1.0 = real time, 0.0 = paused)
+    pub fn set_playback_speed(&mut self, speed: f64) -> Result<(), ReplayError> {
+        if !speed.is_finite() || speed < 0.0 {
+            return Err(ReplayError::InvalidSpeed(speed));
+        }
+        self.playback_speed = speed;
+        Ok(())
+    }
+
+    /// Total number of frames in the recording
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check whether the recording has no frames
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Wait until the next frame is due and return it
+    ///
+    /// Returns `None` at the end of the recording. While the playback speed
+    /// is 0.0 this never completes, so callers should race it against their
+    /// control inputs. Cancel-safe: no frame is consumed until it's returned.
+    pub async fn next(&mut self) -> Option<FusedSensorData> {
+        let frame = self.frames.get(self.cursor)?;
+
+        if self.playback_speed == 0.0 {
+            std::future::pending::<()>().await;
+        }
+
+        if let (Some(last_emit), Some(previous)) = (self.last_emit, self.cursor.checked_sub(1)) {
+            let recorded_gap = (frame.timestamp - self.frames[previous].timestamp)
+                .to_std()
+                .unwrap_or(Duration::ZERO);
+            // At a tiny speed the wait can be too long to represent; the
+            // frame is then never due, as if paused
+            let due = Duration::try_from_secs_f64(recorded_gap.as_secs_f64() / self.playback_speed)
+                .ok()
+                .and_then(|wait| last_emit.checked_add(wait));
+            let Some(due) = due else {
+                return std::future::pending().await;
+            };
+
+            // A deadline already in the past returns immediately, which
+            // gives "as fast as possible" at very high speeds
+            tokio::time::sleep_until(due.into()).await;
+        }
+
+        let frame = self.frames[self.cursor].clone();
+        self.cursor += 1;
+        self.last_emit = Some(Instant::now());
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `count` frames recorded `gap_ms` apart
+    fn recording(count: usize, gap_ms: i64) -> Vec<FusedSensorData> {
+        let start = chrono::Utc::now();
+        (0..count)
+            .map(|i| FusedSensorData {
+                timestamp: start + chrono::Duration::milliseconds(gap_ms * i as i64),
+                ..FusedSensorData::default()
+            })
+            .collect()
+    }
+
+    /// Play a source to the end, returning the frame count and how long it took
+    async fn play(source: &mut ReplaySource) -> (usize, Duration) {
+        let started = Instant::now();
+        let mut frames = 0;
+        while source.next().await.is_some() {
+            frames += 1;
+        }
+        (frames, started.elapsed())
+    }
+
+    #[tokio::test]
+    async fn test_double_speed_takes_half_the_recorded_time() {
+        // 450 ms recorded
+        let mut source = ReplaySource::from_frames(recording(10, 50));
+        source.set_playback_speed(2.0).unwrap();
+
+        let (frames, elapsed) = play(&mut source).await;
+        assert_eq!(frames, 10);
+        assert!(elapsed >= Duration::from_millis(220), "replayed in {elapsed:?}");
+        assert!(elapsed < Duration::from_millis(350), "replayed in {elapsed:?}");
+    }
+
+    #[tokio::test]
+    async fn test_zero_speed_pauses_playback() {
+        let mut source = ReplaySource::from_frames(recording(3, 10));
+        source.set_playback_speed(0.0).unwrap();
+        assert!(tokio::time::timeout(Duration::from_millis(100), source.next()).await.is_err());
+
+        // Nothing was consumed while paused
+        source.set_playback_speed(1.0).unwrap();
+        assert_eq!(play(&mut source).await.0, 3);
+    }
+
+    #[tokio::test]
+    async fn test_tiny_speed_waits_instead_of_overflowing() {
+        let mut source = ReplaySource::from_frames(recording(3, 10));
+        source.set_playback_speed(1e-300).unwrap();
+        assert!(source.next().await.is_some());
+        assert!(tokio::time::timeout(Duration::from_millis(100), source.next()).await.is_err());
+
+        // Nothing was consumed while waiting
+        source.set_playback_speed(1.0).unwrap();
+        assert_eq!(play(&mut source).await.0, 2);
+    }
+
+    #[test]
+    fn test_rejects_invalid_speeds() {
+        let mut source = ReplaySource::from_frames(recording(1, 0));
+        for speed in [-1.0, f64::NAN, f64::INFINITY] {
+            assert!(source.set_playback_speed(speed).is_err());
+        }
+        assert_eq!(source.playback_speed(), 1.0);
+    }
+}
@@ -0,0 +1,181 @@
+//! Simulator Randomness
+//!
+//! The simulators draw their noise, drift and signal changes from a
+//! `SimRng`, which is either `StdRng` (ChaCha12, the default) or the much
+//! faster `SmallRng` (Xoshiro256++) for runs with many simulated sensors.
+//! Both are seedable, so with a seed a whole simulated run repeats exactly.
+//!
+//! Nothing here needs cryptographic strength: the numbers only shape
+//! simulated sensor errors, so `SmallRng`'s weaker guarantees are fine.
+//! A seeded `SmallRng` sequence is only reproducible on the same platform
+//! and `rand` version, which is all a test run needs.
+
+use rand::rngs::{SmallRng, StdRng};
+use rand::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Stream of the IMU simulator's generator
+pub const IMU_STREAM: u64 = 0;
+
+/// Stream of the GPS simulator's generator
+pub const GPS_STREAM: u64 = 1;
+
+/// Stream of the GPS update scheduler's generator (jitter and dropped fixes)
+pub const GPS_TIMING_STREAM: u64 = 2;
+
+/// Random number generator algorithm used by the simulators
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RngBackend {
+    /// `StdRng`: slower, stable sequences for a given seed
+    #[default]
+    Std,
+
+    /// `SmallRng`: fastest, for throughput with many sensors
+    Small,
+}
+
+/// Generator settings shared by the simulators
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct SimRngConfig {
+    /// Algorithm to use
+    pub backend: RngBackend,
+
+    /// Seed for reproducible runs (random when unset)
+    pub seed: Option<u64>,
+}
+
+impl SimRngConfig {
+    /// Build the generator for one simulator
+    ///
+    /// Each simulator passes its own `stream`, so simulators sharing a
+    /// seed still draw independent numbers.
+    pub fn build(&self, stream: u64) -> SimRng {
+        match self.seed {
+            Some(seed) => SimRng::seeded(self.backend, seed.wrapping_add(stream.wrapping_mul(0x9E37_79B9_7F4A_7C15))),
+            None => SimRng::from_entropy(self.backend),
+        }
+    }
+}
+
+/// A simulator's random number generator
+#[derive(Debug, Clone)]
+pub enum SimRng {
+    /// Seeded or entropy-initialized `StdRng` (boxed to keep the enum small)
+    Std(Box<StdRng>),
+
+    /// Seeded or entropy-initialized `SmallRng`
+    Small(SmallRng),
+}
+
+impl SimRng {
+    /// Create a generator with a random seed
+    pub fn from_entropy(backend: RngBackend) -> Self {
+        match backend {
+            RngBackend::Std => Self::Std(Box::new(StdRng::from_entropy())),
+            RngBackend::Small => Self::Small(SmallRng::from_entropy()),
+        }
+    }
+
+    /// Create a generator that always yields the same sequence for `seed`
+    pub fn seeded(backend: RngBackend, seed: u64) -> Self {
+        match backend {
+            RngBackend::Std => Self::Std(Box::new(StdRng::seed_from_u64(seed))),
+            RngBackend::Small => Self::Small(SmallRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// Get the algorithm in use
+    pub fn backend(&self) -> RngBackend {
+        match self {
+            Self::Std(_) => RngBackend::Std,
+            Self::Small(_) => RngBackend::Small,
+        }
+    }
+}
+
+impl RngCore for SimRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Self::Std(rng) => rng.next_u32(),
+            Self::Small(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Self::Std(rng) => rng.next_u64(),
+            Self::Small(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            Self::Std(rng) => rng.fill_bytes(dest),
+            Self::Small(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            Self::Std(rng) => rng.try_fill_bytes(dest),
+            Self::Small(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::gps::GpsFaultType;
+    use crate::sensors::{GpsSimulator, ImuSimulator};
+
+    /// IMU accelerometer and gyro readings from a simulator seeded with `seed`
+    fn imu_run(backend: RngBackend, seed: u64) -> Vec<[f64; 7]> {
+        let mut imu = ImuSimulator::new();
+        imu.set_rng(SimRngConfig { backend, seed: Some(seed) }.build(IMU_STREAM));
+        (0..200)
+            .map(|_| {
+                let r = imu.read();
+                let (a, w) = (r.acceleration, r.gyroscope);
+                [a.x, a.y, a.z, w.x, w.y, w.z, r.health]
+            })
+            .collect()
+    }
+
+    /// GPS fixes from a simulator seeded with `seed`, including a random jump
+    fn gps_run(backend: RngBackend, seed: u64) -> Vec<[f64; 4]> {
+        let mut gps = GpsSimulator::new();
+        gps.set_rng(SimRngConfig { backend, seed: Some(seed) }.build(GPS_STREAM));
+        (0..60)
+            .map(|i| {
+                if i == 30 {
+                    gps.inject_fault(GpsFaultType::PositionJump);
+                }
+                gps.update();
+                let fix = gps.get_latest();
+                [fix.latitude, fix.longitude, fix.altitude, fix.hdop]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seeded_simulators_are_reproducible_on_both_backends() {
+        for backend in [RngBackend::Std, RngBackend::Small] {
+            assert_eq!(imu_run(backend, 42), imu_run(backend, 42), "{backend:?} IMU");
+            assert_eq!(gps_run(backend, 42), gps_run(backend, 42), "{backend:?} GPS");
+            assert_ne!(imu_run(backend, 42), imu_run(backend, 43), "{backend:?} IMU ignores the seed");
+            assert_ne!(gps_run(backend, 42), gps_run(backend, 43), "{backend:?} GPS ignores the seed");
+        }
+        assert_ne!(imu_run(RngBackend::Std, 42), imu_run(RngBackend::Small, 42));
+    }
+
+    #[test]
+    fn test_streams_differ_for_a_shared_seed() {
+        let config = SimRngConfig { backend: RngBackend::Small, seed: Some(7) };
+        let (mut imu, mut gps, mut timing) = (config.build(IMU_STREAM), config.build(GPS_STREAM), config.build(GPS_TIMING_STREAM));
+        assert_eq!(imu.backend(), RngBackend::Small);
+        let draws = [imu.next_u64(), gps.next_u64(), timing.next_u64()];
+        assert!(draws[0] != draws[1] && draws[1] != draws[2] && draws[0] != draws[2], "{draws:?}");
+    }
+}
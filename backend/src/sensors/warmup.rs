@@ -0,0 +1,40 @@
+//! Sensor Warm-Up
+//!
+//! Real sensors don't deliver nominal data from the first sample: a GPS
+//! receiver starting cold acquires satellites over tens of seconds, and a
+//! MEMS gyro's bias settles as the die reaches operating temperature.
+//! `WarmUp` tracks how far through such a period a simulator is, so it
+//! can blend from degraded to nominal output.
+//!
+//! Progress is measured in each simulator's own simulated time (50 Hz IMU
+//! ticks, 1 s GPS updates), which matches wall time at the default rates.
+
+use std::time::Duration;
+
+/// A warm-up period at sensor start
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarmUp {
+    /// Time from start until the sensor is nominal (zero = instant)
+    duration: Duration,
+}
+
+impl WarmUp {
+    /// Create a warm-up lasting `duration` (zero means no warm-up)
+    pub fn new(duration: Duration) -> Self {
+        Self { duration }
+    }
+
+    /// Time from start until the sensor is nominal
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Fraction of the warm-up done after `elapsed`, from 0.0 at start to
+    /// 1.0 once complete (always 1.0 without a warm-up)
+    pub fn progress(&self, elapsed: Duration) -> f64 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+    }
+}
@@ -0,0 +1,128 @@
+//! WebSocket Broadcast Sink
+//!
+//! Feeds the channels the WebSocket server streams from: the broadcast
+//! channel (every frame) and the watch channel (latest frame only).
+//!
+//! Having no WebSocket clients is normal (none connected yet) and frames
+//! keep flowing to the other sinks and the server's own subscribers
+//! (long-poll history, analysis loops), but a long stretch of it usually
+//! means a misconfiguration, so it is logged, throttled to an occasional
+//! note. Clients are counted with the server's `ConnectionStats`; the
+//! channel's receiver count can't tell them from internal subscribers.
+
+use super::{Sink, SinkFuture};
+use crate::models::FusedSensorData;
+use crate::websocket::ConnectionStats;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+use tracing::{info, warn};
+
+/// How long the broadcast can go without clients before it is logged
+const NO_SUBSCRIBERS_NOTE_AFTER: Duration = Duration::from_secs(60);
+
+/// Minimum time between repeated no-subscriber notes
+const NO_SUBSCRIBERS_NOTE_EVERY: Duration = Duration::from_secs(600);
+
+/// Publishes frames to connected WebSocket clients
+pub struct BroadcastSink {
+    tx: Arc<broadcast::Sender<FusedSensorData>>,
+    latest_tx: watch::Sender<FusedSensorData>,
+
+    /// Connected WebSocket clients, if counted
+    clients: Option<Arc<ConnectionStats>>,
+
+    /// When the broadcast last started going out to no clients
+    unheard_since: Option<Instant>,
+
+    /// When the no-subscriber note was last logged
+    last_note: Option<Instant>,
+}
+
+impl BroadcastSink {
+    /// Create a sink over the server's frame channels
+    pub fn new(tx: Arc<broadcast::Sender<FusedSensorData>>, latest_tx: watch::Sender<FusedSensorData>) -> Self {
+        Self {
+            tx,
+            latest_tx,
+            clients: None,
+            unheard_since: None,
+            last_note: None,
+        }
+    }
+
+    /// Note long stretches without WebSocket clients, counted by `clients`
+    /// (without it, nothing is noted)
+    pub fn with_connection_stats(mut self, clients: Arc<ConnectionStats>) -> Self {
+        self.clients = Some(clients);
+        self
+    }
+
+    /// Track stretches without clients and log the long ones
+    fn note_delivery(&mut self, heard: bool, now: Instant) {
+        if heard {
+            if self.last_note.take().is_some() {
+                info!("📡 Broadcast has clients again");
+            }
+            self.unheard_since = None;
+            return;
+        }
+
+        let since = *self.unheard_since.get_or_insert(now);
+        let unheard = now.duration_since(since);
+        let due = self
+            .last_note
+            .is_none_or(|last| now.duration_since(last) >= NO_SUBSCRIBERS_NOTE_EVERY);
+        if unheard >= NO_SUBSCRIBERS_NOTE_AFTER && due {
+            warn!(
+                "📭 No WebSocket clients for {}s; frames are still fused and sent to the other sinks",
+                unheard.as_secs()
+            );
+            self.last_note = Some(now);
+        }
+    }
+}
+
+impl Sink for BroadcastSink {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    fn send<'a>(&'a mut self, frame: &'a FusedSensorData) -> SinkFuture<'a> {
+        self.latest_tx.send_replace(frame.clone());
+
+        // Having no subscribers at all is not a failure
+        let _ = self.tx.send(frame.clone());
+        if let Some(clients) = &self.clients {
+            let heard = clients.current() > 0;
+            self.note_delivery(heard, Instant::now());
+        }
+        Box::pin(std::future::ready(Ok(())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn test_internal_subscribers_dont_count_as_clients() {
+        let clients = Arc::new(ConnectionStats::new());
+        let (tx, _history_recorder) = broadcast::channel(16);
+        let (latest_tx, _) = watch::channel(FusedSensorData::default());
+        let mut sink = BroadcastSink::new(Arc::new(tx), latest_tx).with_connection_stats(clients.clone());
+        let frame = FusedSensorData::default();
+
+        // Delivered to the internal subscriber, but no client is connected
+        sink.send(&frame).await.unwrap();
+        let since = sink.unheard_since.expect("an internal subscriber counted as a client");
+        sink.note_delivery(false, since + NO_SUBSCRIBERS_NOTE_AFTER);
+        assert_eq!(sink.last_note, Some(since + NO_SUBSCRIBERS_NOTE_AFTER));
+
+        // A client connects: the stretch ends
+        let _client = clients.connect();
+        sink.send(&frame).await.unwrap();
+        assert_eq!((sink.unheard_since, sink.last_note), (None, None));
+    }
+}
@@ -0,0 +1,69 @@
+//! JSON Lines Sink
+//!
+//! Writes one `FusedSensorData` per line, the format `ReplaySource`
+//! loads, to a file (a recording) or stdout (for piping into other tools).
+//! Each line is flushed as it is written so a killed process loses at most
+//! the frame in flight.
+
+use super::{Sink, SinkFuture};
+use crate::models::FusedSensorData;
+use std::path::Path;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Writes frames as JSON Lines to any async writer
+pub struct JsonlSink<W> {
+    name: String,
+    writer: W,
+    line: Vec<u8>,
+}
+
+impl<W: AsyncWrite + Unpin + Send> JsonlSink<W> {
+    /// Wrap a writer, naming the sink for logs
+    pub fn new(name: impl Into<String>, writer: W) -> Self {
+        Self {
+            name: name.into(),
+            writer,
+            line: Vec::new(),
+        }
+    }
+}
+
+impl JsonlSink<tokio::fs::File> {
+    /// Record to a file, replacing any existing contents
+    pub async fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let file = tokio::fs::File::create(path).await?;
+        Ok(Self::new(format!("file:{}", path.display()), file))
+    }
+}
+
+impl JsonlSink<tokio::io::Stdout> {
+    /// Print frames to stdout
+    pub fn stdout() -> Self {
+        Self::new("stdout", tokio::io::stdout())
+    }
+}
+
+impl<W: AsyncWrite + Unpin + Send> Sink for JsonlSink<W> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send<'a>(&'a mut self, frame: &'a FusedSensorData) -> SinkFuture<'a> {
+        Box::pin(async move {
+            self.line.clear();
+            serde_json::to_writer(&mut self.line, frame)?;
+            self.line.push(b'\n');
+            self.writer.write_all(&self.line).await?;
+            self.writer.flush().await?;
+            Ok(())
+        })
+    }
+
+    fn close(&mut self) -> SinkFuture<'_> {
+        Box::pin(async move {
+            self.writer.shutdown().await?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,290 @@
+//! Output Sinks
+//!
+//! Every fused frame is pushed to a set of sinks: the WebSocket broadcast,
+//! a JSON Lines recorder, a stdout printer, and whatever else is plugged
+//! in. Sinks are independent; one that fails is logged and skipped for
+//! that frame while the others keep receiving data.
+//!
+//! Each sink runs in its own task behind a bounded queue, so a slow sink
+//! (a stalled broker, a full disk) never holds up the fusion loop or the
+//! other sinks: once it is `SINK_QUEUE_FRAMES` behind, new frames are
+//! dropped for it until it catches up.
+
+pub mod broadcast;
+pub mod jsonl;
+pub mod mqtt;
+
+pub use broadcast::BroadcastSink;
+pub use jsonl::JsonlSink;
+pub use mqtt::{MqttConfig, MqttQos, MqttSink};
+
+use crate::models::FusedSensorData;
+use std::future::Future;
+use std::pin::Pin;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Frames a sink can fall behind by before new ones are dropped for it
+pub const SINK_QUEUE_FRAMES: usize = 64;
+
+/// Errors produced while delivering a frame to a sink
+#[derive(Debug, Error)]
+pub enum SinkError {
+    /// Writing to the underlying output failed
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The frame could not be serialized
+    #[error("serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// The output has shut down and will accept no more frames
+    #[error("sink closed")]
+    Closed,
+
+    /// The output is backed up; the frame was dropped rather than waited on
+    #[error("output backlogged, frame dropped")]
+    Dropped,
+}
+
+/// Future returned by [`Sink::send`]
+pub type SinkFuture<'a> = Pin<Box<dyn Future<Output = Result<(), SinkError>> + Send + 'a>>;
+
+/// Destination for fused frames
+pub trait Sink: Send {
+    /// Short name used in logs
+    fn name(&self) -> &str;
+
+    /// Deliver one frame
+    fn send<'a>(&'a mut self, frame: &'a FusedSensorData) -> SinkFuture<'a>;
+
+    /// Flush buffered frames and release the output at shutdown
+    ///
+    /// No frames are sent after this. The default has nothing to flush.
+    fn close(&mut self) -> SinkFuture<'_> {
+        Box::pin(std::future::ready(Ok(())))
+    }
+}
+
+/// A sink's queue and the task draining it into the sink
+struct Slot {
+    name: String,
+    tx: mpsc::Sender<FusedSensorData>,
+
+    /// Resolves once the sink is closed: true if it closed cleanly
+    task: JoinHandle<bool>,
+
+    /// Whether the last frame was dropped because the queue was full
+    backlogged: bool,
+}
+
+/// Fans each frame out to every registered sink, isolating failures
+#[derive(Default)]
+pub struct SinkSet {
+    slots: Vec<Slot>,
+}
+
+impl SinkSet {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sink, starting the task that feeds it; each sink receives
+    /// frames in the order they were sent
+    /// 
+    /// Must be called within a Tokio runtime.
+    pub fn push(&mut self, sink: Box<dyn Sink>) {
+        let name = sink.name().to_string();
+        let (tx, rx) = mpsc::channel(SINK_QUEUE_FRAMES);
+        let task = tokio::spawn(drive(sink, rx));
+        self.slots.push(Slot { name, tx, task, backlogged: false });
+    }
+
+    /// Number of registered sinks
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Check whether no sinks are registered
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Queue a frame for every sink without waiting on any of them
+    ///
+    /// A sink whose queue is full misses the frame; that is logged when it
+    /// starts and stops happening (not on every frame). Delivery failures
+    /// are logged by the sink's task. Returns the number of sinks that
+    /// missed this frame.
+    pub fn send(&mut self, frame: &FusedSensorData) -> usize {
+        let mut missed = 0;
+        for slot in &mut self.slots {
+            match slot.tx.try_send(frame.clone()) {
+                Ok(()) if slot.backlogged => {
+                    info!("🚰 Sink {} caught up", slot.name);
+                    slot.backlogged = false;
+                }
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    if !slot.backlogged {
+                        warn!("🚰 Sink {} failed: {}", slot.name, SinkError::Dropped);
+                        slot.backlogged = true;
+                    }
+                    missed += 1;
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => missed += 1,
+            }
+        }
+        missed
+    }
+
+    /// Close every sink once it has delivered its queued frames, flushing
+    /// what it has buffered
+    ///
+    /// Failures are logged and don't stop the remaining sinks closing.
+    /// Returns the number of sinks that failed to close cleanly.
+    pub async fn close(&mut self) -> usize {
+        let mut failures = 0;
+        for slot in self.slots.drain(..) {
+            drop(slot.tx);
+            match slot.task.await {
+                Ok(true) => {}
+                Ok(false) => failures += 1,
+                Err(e) => {
+                    warn!("🚰 Sink {} task ended abnormally: {}", slot.name, e);
+                    failures += 1;
+                }
+            }
+        }
+        failures
+    }
+}
+
+/// Deliver queued frames to `sink` until the queue closes, then close it
+/// 
+/// A failing sink is logged when it starts and stops failing (not on every
+/// frame). Returns whether the sink closed cleanly.
+async fn drive(mut sink: Box<dyn Sink>, mut rx: mpsc::Receiver<FusedSensorData>) -> bool {
+    let mut failing = false;
+    while let Some(frame) = rx.recv().await {
+        match sink.send(&frame).await {
+            Ok(()) if failing => {
+                info!("🚰 Sink {} recovered", sink.name());
+                failing = false;
+            }
+            Ok(()) => {}
+            Err(e) => {
+                if !failing {
+                    warn!("🚰 Sink {} failed: {}", sink.name(), e);
+                    failing = true;
+                }
+            }
+        }
+    }
+    match sink.close().await {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("🚰 Sink {} failed to close: {}", sink.name(), e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    /// Counts the frames it receives
+    struct CountingSink(Arc<AtomicUsize>);
+
+    impl Sink for CountingSink {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn send<'a>(&'a mut self, _frame: &'a FusedSensorData) -> SinkFuture<'a> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Box::pin(std::future::ready(Ok(())))
+        }
+    }
+
+    /// Fails every delivery
+    struct ClosedSink;
+
+    impl Sink for ClosedSink {
+        fn name(&self) -> &str {
+            "closed"
+        }
+
+        fn send<'a>(&'a mut self, _frame: &'a FusedSensorData) -> SinkFuture<'a> {
+            Box::pin(std::future::ready(Err(SinkError::Closed)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_counting_sink_receives_every_frame_despite_failing_neighbour() {
+        let before = Arc::new(AtomicUsize::new(0));
+        let after = Arc::new(AtomicUsize::new(0));
+        let mut sinks = SinkSet::new();
+        sinks.push(Box::new(CountingSink(before.clone())));
+        sinks.push(Box::new(ClosedSink));
+        sinks.push(Box::new(CountingSink(after.clone())));
+
+        let frame = FusedSensorData::default();
+        for _ in 0..25 {
+            assert_eq!(sinks.send(&frame), 0);
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(sinks.close().await, 0);
+        assert_eq!(before.load(Ordering::Relaxed), 25);
+        assert_eq!(after.load(Ordering::Relaxed), 25);
+    }
+
+    /// Takes 50 ms over every frame
+    struct SlowSink(Arc<AtomicUsize>);
+
+    impl Sink for SlowSink {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn send<'a>(&'a mut self, _frame: &'a FusedSensorData) -> SinkFuture<'a> {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_sink_drops_frames_instead_of_stalling_the_others() {
+        let slow = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::new(AtomicUsize::new(0));
+        let mut sinks = SinkSet::new();
+        sinks.push(Box::new(SlowSink(slow.clone())));
+        sinks.push(Box::new(CountingSink(counted.clone())));
+
+        // 200 frames at 1 ms: the slow sink would need 10 s for them
+        let frame = FusedSensorData::default();
+        let started = Instant::now();
+        let mut missed = 0;
+        for _ in 0..200 {
+            missed += sinks.send(&frame);
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        assert!(started.elapsed() < Duration::from_secs(2), "sending took {:?}", started.elapsed());
+        assert!(missed > 0);
+
+        assert_eq!(sinks.close().await, 0);
+        assert_eq!(counted.load(Ordering::Relaxed), 200);
+        assert_eq!(slow.load(Ordering::Relaxed), 200 - missed);
+    }
+}
@@ -0,0 +1,390 @@
+//! MQTT Publisher Sink
+//!
+//! Publishes each frame as JSON to a topic on an MQTT broker so standard
+//! IoT tooling can consume the stream, using rumqttc's `AsyncClient`:
+//! - QoS 0 (at most once) or QoS 1 (at least once: rumqttc tracks each
+//!   PUBACK and resends unacknowledged messages after a reconnect)
+//! - Clean sessions, so nothing is queued by the broker between connections
+//!
+//! rumqttc's event loop runs in its own task. Frames reach it through the
+//! client's bounded request queue: while the broker is unreachable they
+//! accumulate there, and once the queue is full new frames are dropped, so
+//! the fusion loop never waits on the network. Lost connections are retried
+//! with exponential backoff.
+
+use super::{Sink, SinkError, SinkFuture};
+use crate::models::FusedSensorData;
+use rumqttc::{AsyncClient, ConnectionError, Event, EventLoop, MqttOptions, Outgoing, Packet, QoS};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+/// First delay before reconnecting to the broker
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest delay between reconnection attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Longest wait for queued frames to be written when the sink closes
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Largest MQTT packet sent or accepted; bigger frames are dropped
+const MAX_PACKET_SIZE: usize = 1024 * 1024;
+
+/// MQTT delivery guarantee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MqttQos {
+    /// QoS 0: fire and forget
+    #[default]
+    AtMostOnce,
+
+    /// QoS 1: the broker acknowledges each message
+    AtLeastOnce,
+}
+
+impl From<MqttQos> for QoS {
+    fn from(qos: MqttQos) -> Self {
+        match qos {
+            MqttQos::AtMostOnce => QoS::AtMostOnce,
+            MqttQos::AtLeastOnce => QoS::AtLeastOnce,
+        }
+    }
+}
+
+/// Settings for the MQTT sink
+#[derive(Debug, Clone, Serialize)]
+pub struct MqttConfig {
+    /// Broker address as `host:port`
+    pub broker: String,
+
+    /// Topic frames are published to
+    pub topic: String,
+
+    /// Delivery guarantee for each frame
+    pub qos: MqttQos,
+
+    /// Client identifier presented to the broker
+    pub client_id: String,
+
+    /// Keep-alive interval in seconds
+    pub keep_alive_secs: u16,
+
+    /// Frames held while the broker is unreachable before new ones are dropped
+    pub queue_capacity: usize,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            broker: "127.0.0.1:1883".to_string(),
+            topic: "sensor-fusion/fused".to_string(),
+            qos: MqttQos::AtMostOnce,
+            client_id: "sensor-fusion-backend".to_string(),
+            keep_alive_secs: 30,
+            queue_capacity: 256,
+        }
+    }
+}
+
+impl MqttConfig {
+    /// Reject settings the publisher can't run with
+    pub fn validate(&self) -> Result<(), MqttConfigError> {
+        if self.broker.trim().is_empty() {
+            return Err(MqttConfigError::MissingBroker);
+        }
+        if self.host_port().is_none() {
+            return Err(MqttConfigError::InvalidBroker(self.broker.clone()));
+        }
+        if self.topic.is_empty() || self.topic.contains(['+', '#', '\0']) || self.topic.len() > u16::MAX as usize {
+            return Err(MqttConfigError::InvalidTopic(self.topic.clone()));
+        }
+        if self.client_id.len() > u16::MAX as usize {
+            return Err(MqttConfigError::InvalidClientId);
+        }
+        if self.keep_alive_secs == 0 {
+            return Err(MqttConfigError::ZeroKeepAlive);
+        }
+        if self.queue_capacity == 0 {
+            return Err(MqttConfigError::ZeroQueue);
+        }
+        Ok(())
+    }
+
+    /// Broker host and port from `broker`
+    fn host_port(&self) -> Option<(&str, u16)> {
+        let (host, port) = self.broker.trim().rsplit_once(':')?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port = port.parse().ok()?;
+        (!host.is_empty()).then_some((host, port))
+    }
+}
+
+/// Errors from invalid MQTT sink settings
+#[derive(Debug, Error)]
+pub enum MqttConfigError {
+    /// No broker address was given
+    #[error("MQTT broker address must not be empty")]
+    MissingBroker,
+
+    /// The broker address is not `host:port`
+    #[error("invalid MQTT broker address {0:?} (expected host:port)")]
+    InvalidBroker(String),
+
+    /// Publish topics must be non-empty and free of wildcards
+    #[error("invalid MQTT topic {0:?} (must be non-empty without + or # wildcards)")]
+    InvalidTopic(String),
+
+    /// The client identifier is too long to encode
+    #[error("MQTT client id is too long")]
+    InvalidClientId,
+
+    /// A keep-alive of zero would disable broker liveness checks
+    #[error("MQTT keep-alive must be at least 1 second")]
+    ZeroKeepAlive,
+
+    /// The queue needs room for at least one frame
+    #[error("MQTT queue capacity must be at least 1")]
+    ZeroQueue,
+}
+
+/// Publishes frames to an MQTT broker from a background task
+pub struct MqttSink {
+    name: String,
+    topic: String,
+    qos: QoS,
+    client: AsyncClient,
+
+    /// Event loop task, finished once the sink can publish no more
+    task: tokio::task::JoinHandle<()>,
+
+    /// Dropped with the sink to stop reconnection attempts
+    _stop: oneshot::Sender<()>,
+}
+
+impl MqttSink {
+    /// Start the event loop task and return the sink feeding it
+    ///
+    /// Must be called within a Tokio runtime. The task ends when the sink
+    /// is closed or dropped.
+    pub fn spawn(config: MqttConfig) -> Result<Self, MqttConfigError> {
+        config.validate()?;
+        let Some((host, port)) = config.host_port() else {
+            return Err(MqttConfigError::InvalidBroker(config.broker.clone()));
+        };
+        let mut options = MqttOptions::new(config.client_id.clone(), host, port);
+        options
+            .set_keep_alive(Duration::from_secs(config.keep_alive_secs as u64))
+            .set_clean_session(true)
+            .set_max_packet_size(MAX_PACKET_SIZE, MAX_PACKET_SIZE);
+        let (client, eventloop) = AsyncClient::new(options, config.queue_capacity);
+
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(run_event_loop(eventloop, config.broker.clone(), stopped));
+        Ok(Self {
+            name: format!("mqtt:{}/{}", config.broker, config.topic),
+            topic: config.topic,
+            qos: config.qos.into(),
+            client,
+            task,
+            _stop: stop,
+        })
+    }
+}
+
+impl Sink for MqttSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send<'a>(&'a mut self, frame: &'a FusedSensorData) -> SinkFuture<'a> {
+        let result = serde_json::to_vec(frame).map_err(SinkError::from).and_then(|payload| {
+            // rumqttc would fail the whole connection over an oversized packet
+            if payload.len() + self.topic.len() + 16 > MAX_PACKET_SIZE {
+                return Err(SinkError::Dropped);
+            }
+            self.client.try_publish(&self.topic, self.qos, false, payload).map_err(|_| {
+                if self.task.is_finished() {
+                    SinkError::Closed
+                } else {
+                    SinkError::Dropped
+                }
+            })
+        });
+        Box::pin(std::future::ready(result))
+    }
+
+    fn close(&mut self) -> SinkFuture<'_> {
+        Box::pin(async move {
+            // The disconnect queues behind any frames still waiting to go out
+            self.client.try_disconnect().map_err(|_| SinkError::Closed)?;
+            match tokio::time::timeout(CLOSE_TIMEOUT, &mut self.task).await {
+                Ok(_) => Ok(()),
+                Err(_) => {
+                    self.task.abort();
+                    Err(SinkError::Dropped)
+                }
+            }
+        })
+    }
+}
+
+/// Drive rumqttc's event loop, reconnecting with backoff, until the sink
+/// is closed or dropped
+///
+/// Polling the event loop writes queued publishes, handles acks and
+/// keep-alive pings, and, after an error, reconnects on the next poll.
+async fn run_event_loop(mut eventloop: EventLoop, broker: String, mut stopped: oneshot::Receiver<()>) {
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                info!("📤 Connected to MQTT broker {}", broker);
+                backoff = INITIAL_BACKOFF;
+            }
+            // Closed: everything queued before the disconnect was written
+            Ok(Event::Outgoing(Outgoing::Disconnect)) => return,
+            Ok(_) => {}
+
+            // The client was dropped and every queued frame handed over
+            Err(ConnectionError::RequestsDone) => return,
+
+            Err(e) => {
+                warn!("📤 MQTT connection to {} failed: {} (retrying in {:?})", broker, e, backoff);
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = &mut stopped => return,
+                }
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Read one MQTT packet, returning its first header byte and its body
+    async fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let header = stream.read_u8().await.unwrap();
+        let (mut len, mut shift) = (0usize, 0);
+        loop {
+            let byte = stream.read_u8().await.unwrap();
+            len |= ((byte & 0x7F) as usize) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        let mut body = vec![0; len];
+        stream.read_exact(&mut body).await.unwrap();
+        (header, body)
+    }
+
+    /// Accept a client and complete its CONNECT
+    async fn accept(listener: &TcpListener) -> TcpStream {
+        let (mut stream, _) = tokio::time::timeout(Duration::from_secs(5), listener.accept()).await.unwrap().unwrap();
+        let (header, _) = read_packet(&mut stream).await;
+        assert_eq!(header, 0x10, "expected CONNECT");
+        stream.write_all(&[0x20, 0x02, 0x00, 0x00]).await.unwrap();
+        stream
+    }
+
+    /// Next PUBLISH: QoS, topic, packet id, and the frame it carries
+    async fn read_publish(stream: &mut TcpStream) -> (u8, String, u16, FusedSensorData) {
+        loop {
+            let (header, body) = tokio::time::timeout(Duration::from_secs(5), read_packet(stream)).await.unwrap();
+            if header & 0xF0 != 0x30 {
+                continue; // PINGREQ
+            }
+            let qos = (header >> 1) & 0x03;
+            let topic_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+            let topic = String::from_utf8(body[2..2 + topic_len].to_vec()).unwrap();
+            let rest = &body[2 + topic_len..];
+            let (id, payload) = if qos > 0 { (u16::from_be_bytes([rest[0], rest[1]]), &rest[2..]) } else { (0, rest) };
+            return (qos, topic, id, serde_json::from_slice(payload).unwrap());
+        }
+    }
+
+    fn config(port: u16, qos: MqttQos) -> MqttConfig {
+        MqttConfig {
+            broker: format!("127.0.0.1:{port}"),
+            topic: "test/fused".to_string(),
+            qos,
+            ..MqttConfig::default()
+        }
+    }
+
+    fn frame(seq: u32) -> FusedSensorData {
+        FusedSensorData {
+            gps_speed: seq as f64,
+            ..FusedSensorData::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_frames_published_on_configured_topic() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut sink = MqttSink::spawn(config(port, MqttQos::AtLeastOnce)).unwrap();
+        let mut broker = accept(&listener).await;
+
+        for seq in 1..=3 {
+            sink.send(&frame(seq)).await.unwrap();
+            let (qos, topic, id, received) = read_publish(&mut broker).await;
+            assert_eq!((qos, topic.as_str()), (1, "test/fused"));
+            assert_eq!(received.gps_speed, seq as f64);
+            broker.write_all(&[0x40, 0x02, (id >> 8) as u8, id as u8]).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unacknowledged_frame_resent_after_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut sink = MqttSink::spawn(config(port, MqttQos::AtLeastOnce)).unwrap();
+
+        // The broker takes the frame but drops the connection before PUBACK
+        let mut broker = accept(&listener).await;
+        sink.send(&frame(7)).await.unwrap();
+        assert_eq!(read_publish(&mut broker).await.3.gps_speed, 7.0);
+        drop(broker);
+
+        let mut broker = accept(&listener).await;
+        let (qos, _, _, resent) = read_publish(&mut broker).await;
+        assert_eq!((qos, resent.gps_speed), (1, 7.0));
+    }
+
+    #[tokio::test]
+    async fn test_close_writes_queued_frames_then_disconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mut sink = MqttSink::spawn(config(port, MqttQos::AtMostOnce)).unwrap();
+        let mut broker = accept(&listener).await;
+
+        sink.send(&frame(1)).await.unwrap();
+        sink.send(&frame(2)).await.unwrap();
+        sink.close().await.unwrap();
+        assert!(sink.task.is_finished());
+
+        assert_eq!(read_publish(&mut broker).await.3.gps_speed, 1.0);
+        assert_eq!(read_publish(&mut broker).await.3.gps_speed, 2.0);
+        let (header, _) = read_packet(&mut broker).await;
+        assert_eq!(header, 0xE0, "expected DISCONNECT");
+    }
+
+    #[test]
+    fn test_broker_must_be_host_and_port() {
+        let invalid = |broker: &str| MqttConfig { broker: broker.to_string(), ..MqttConfig::default() }.validate();
+        assert!(matches!(invalid("localhost"), Err(MqttConfigError::InvalidBroker(_))));
+        assert!(matches!(invalid(":1883"), Err(MqttConfigError::InvalidBroker(_))));
+        assert!(matches!(invalid("localhost:http"), Err(MqttConfigError::InvalidBroker(_))));
+        assert!(invalid("broker.example.com:8883").is_ok());
+    }
+}
@@ -0,0 +1,116 @@
+//! Per-Connection Bandwidth Counters
+//!
+//! Counts the bytes a connection's writer puts on the socket, and for
+//! frames also what they would have taken as plain full-width JSON (no
+//! delta encoding, double precision floats). Their ratio shows whether
+//! the encodings a client chose actually save bandwidth: 1.0 when the
+//! client uses none, above 1.0 when they help. There is no compression
+//! (such as permessage-deflate) on the socket, so the ratio only ever
+//! reflects delta encoding and single precision.
+//!
+//! Re-encoding every encoded frame as plain JSON would double the cost of
+//! encoding, so only their number is counted. A report estimates their
+//! plain size from one frame encoded plainly when the report is made;
+//! frames of a client using no encoding are counted exactly.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Byte counters shared by a connection's writer and its broadcast loop
+#[derive(Debug, Default)]
+pub struct BandwidthStats {
+    /// Payload bytes of every message sent (frames, replies, notices)
+    bytes_sent: AtomicU64,
+
+    /// Payload bytes of frames as sent
+    frame_bytes: AtomicU64,
+
+    /// Payload bytes of frames sent as plain JSON
+    plain_frame_bytes: AtomicU64,
+
+    /// Frames sent smaller than plain JSON, their plain size left to reports
+    encoded_frames: AtomicU64,
+}
+
+impl BandwidthStats {
+    /// Create counters starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a non-frame message
+    pub fn record(&self, sent: usize) {
+        self.bytes_sent.fetch_add(sent as u64, Ordering::Relaxed);
+    }
+
+    /// Count a frame sent as `sent` bytes, `encoded` when delta encoding or
+    /// single precision made it smaller than plain JSON
+    pub fn record_frame(&self, sent: usize, encoded: bool) {
+        self.record(sent);
+        self.frame_bytes.fetch_add(sent as u64, Ordering::Relaxed);
+        if encoded {
+            self.encoded_frames.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.plain_frame_bytes.fetch_add(sent as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Totals so far
+    ///
+    /// `plain_frame_len` gives the plain JSON size of a current frame, and is
+    /// only called when encoded frames were sent.
+    pub fn report(&self, plain_frame_len: impl FnOnce() -> usize) -> BandwidthReport {
+        let frame_bytes = self.frame_bytes.load(Ordering::Relaxed);
+        let encoded_frames = self.encoded_frames.load(Ordering::Relaxed);
+        let estimated = if encoded_frames > 0 { encoded_frames * plain_frame_len() as u64 } else { 0 };
+        let raw_frame_bytes = self.plain_frame_bytes.load(Ordering::Relaxed) + estimated;
+        BandwidthReport {
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            frame_bytes,
+            raw_frame_bytes,
+            // Nothing sent yet saves nothing either
+            compression_ratio: if frame_bytes > 0 { raw_frame_bytes as f64 / frame_bytes as f64 } else { 1.0 },
+        }
+    }
+}
+
+/// Bandwidth totals for one connection
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BandwidthReport {
+    /// Payload bytes of every message sent
+    pub bytes_sent: u64,
+
+    /// Payload bytes of frames as sent
+    pub frame_bytes: u64,
+
+    /// Payload bytes the frames would have taken as plain JSON (estimated
+    /// for encoded frames)
+    pub raw_frame_bytes: u64,
+
+    /// `raw_frame_bytes / frame_bytes` (1.0 before any frame)
+    pub compression_ratio: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ratio_compares_raw_frames_to_sent_frames() {
+        let stats = BandwidthStats::new();
+        let unused = || panic!("no encoded frames to estimate");
+        assert_eq!(stats.report(unused).compression_ratio, 1.0);
+
+        stats.record(40);
+        stats.record_frame(100, false);
+        assert_eq!(stats.report(unused).compression_ratio, 1.0);
+
+        stats.record_frame(50, true);
+        stats.record_frame(30, true);
+        let report = stats.report(|| 125);
+        assert_eq!(report.bytes_sent, 220);
+        assert_eq!(report.frame_bytes, 180);
+        assert_eq!(report.raw_frame_bytes, 350);
+        assert!((report.compression_ratio - 350.0 / 180.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,122 @@
+//! Frame Integrity Checksums
+//!
+//! Optional end-to-end integrity check for stored or forwarded frames.
+//! A CRC-32 (IEEE 802.3, the zlib/PNG variant) is appended to each frame
+//! as its last field:
+//!
+//! ```text
+//! {"timestamp":...,"system_health":0.98,"checksum":"xxxxxxxx"}
+//! ```
+//!
+//! The checksum covers the exact UTF-8 bytes of the frame as sent, with
+//! the trailing `,"checksum":"xxxxxxxx"` removed (so the covered bytes end
+//! in the closing `}`). Verifiers strip that fixed-length suffix instead of
+//! re-serializing, since float formatting differs between JSON libraries.
+//! The value is 8 lowercase hex digits.
+
+/// Name of the checksum field
+pub const CHECKSUM_FIELD: &str = "checksum";
+
+/// Length of the `,"checksum":"xxxxxxxx"}` tail of a checksummed frame
+const SUFFIX_LEN: usize = 23;
+
+/// CRC-32 lookup table for the reflected polynomial 0xEDB88320
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32 (IEEE) of a byte slice
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| {
+        CRC_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Append the checksum field to a serialized JSON object
+///
+/// Payloads that aren't non-empty objects are returned unchanged.
+pub fn append_checksum(mut frame: String) -> String {
+    if !frame.starts_with('{') || !frame.ends_with('}') || frame.len() < 3 {
+        return frame;
+    }
+    let crc = crc32(frame.as_bytes());
+    frame.pop();
+    frame.push_str(&format!(",\"{CHECKSUM_FIELD}\":\"{crc:08x}\"}}"));
+    frame
+}
+
+/// Check a frame produced by [`append_checksum`]
+///
+/// Returns `false` when the checksum field is missing or doesn't match.
+pub fn verify_checksum(frame: &str) -> bool {
+    let bytes = frame.as_bytes();
+    let Some(split) = bytes.len().checked_sub(SUFFIX_LEN) else {
+        return false;
+    };
+    let (covered, suffix) = bytes.split_at(split);
+
+    let prefix = format!(",\"{CHECKSUM_FIELD}\":\"");
+    let Some(hex) = suffix
+        .strip_prefix(prefix.as_bytes())
+        .and_then(|rest| rest.strip_suffix(b"\"}"))
+    else {
+        return false;
+    };
+    let Some(expected) = std::str::from_utf8(hex)
+        .ok()
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+    else {
+        return false;
+    };
+
+    let mut digest = covered.to_vec();
+    digest.push(b'}');
+    crc32(&digest) == expected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FusedSensorData;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_mutated_frame_fails_verification() {
+        let json = serde_json::to_string(&FusedSensorData::default()).unwrap();
+        let frame = append_checksum(json.clone());
+        assert!(frame.starts_with(&json[..json.len() - 1]));
+        assert!(verify_checksum(&frame));
+
+        let value: serde_json::Value = serde_json::from_str(&frame).unwrap();
+        assert_eq!(value[CHECKSUM_FIELD], format!("{:08x}", crc32(json.as_bytes())));
+
+        let mut bytes = frame.into_bytes();
+        let index = bytes.iter().position(|&b| b.is_ascii_digit()).unwrap();
+        bytes[index] = if bytes[index] == b'9' { b'8' } else { bytes[index] + 1 };
+        assert!(!verify_checksum(&String::from_utf8(bytes).unwrap()));
+    }
+
+    #[test]
+    fn test_missing_checksum_fails_verification() {
+        assert!(!verify_checksum(r#"{"system_health":1.0}"#));
+        assert!(!verify_checksum(""));
+        assert_eq!(append_checksum("[1,2]".to_string()), "[1,2]");
+    }
+}
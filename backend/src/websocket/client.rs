@@ -0,0 +1,851 @@
+//! Per-Client Settings and Frame Encoding
+//! 
+//! Each connection can tailor what it receives (delivery mode, coordinate
+//! output, orientation output and convention, on-change suppression, delta encoding, float width, timestamp format,
+//! frame format, pretty-printing, acceleration units, subscribed message types). Settings are changed by client messages
+//! and applied when a frame is encoded for that client.
+//! 
+//! Frames with NaN or infinite values fail to encode (serde_json would
+//! write them as `null`, which clients can't read back). After
+//! `SANITIZE_AFTER` failures in a row, an encoder replaces such values
+//! instead, so a persistently corrupt source doesn't starve the client.
+
+use chrono::{DateTime, Utc};
+use serde::ser::Error as _;
+use crate::models::{FusedSensorData, FusedSensorDataF32, TimestampFormat, Vec3, WireTimestamp, geodetic_to_enu};
+use super::checksum::append_checksum;
+use super::metadata::{FrameMetadata, MetadataConfig};
+use super::precision::OutputPrecision;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How a client receives fused frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryMode {
+    /// Every broadcast frame (may report lag for slow clients)
+    All,
+    /// Only the most recent frame from the watch channel (never lags)
+    Latest,
+}
+
+/// Which position representation a client receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinateMode {
+    /// Latitude, longitude, altitude tuple (`position`)
+    Geodetic,
+    /// East/north/up meters from the fusion origin (`local_position`)
+    Local,
+}
+
+/// Which orientation representations a client receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrientationFormat {
+    /// Quaternion (`orientation`) and Euler angles (`euler_degrees`)
+    #[default]
+    Both,
+    /// Euler angles only
+    Euler,
+    /// Quaternion only
+    Quaternion,
+}
+
+/// Axis convention of a client's orientation output
+/// 
+/// Fusion always runs in the aerospace convention; a different convention
+/// is only applied to `orientation` and `euler_degrees` when encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameConvention {
+    /// Aerospace: FRD body in an NED world, yaw is the compass heading
+    #[default]
+    Ned,
+    /// ROS (REP 103): FLU body in an ENU world, yaw counter-clockwise from east
+    Enu,
+}
+
+/// Shape of the frames a client receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameFormat {
+    /// The fused frame on its own
+    #[default]
+    Fused,
+    /// `{"type": "combined"}` with the raw IMU/GPS readings and the fused frame
+    /// 
+    /// Always sent in full: delta encoding only applies to `Fused`.
+    Combined,
+}
+
+/// Units of acceleration in a client's frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccelUnits {
+    /// Meters per second squared (SI, as used internally)
+    #[default]
+    Mps2,
+    /// Multiples of standard gravity (a stationary sensor reads ~1.0 on z)
+    G,
+}
+
+/// Standard gravity for g units (m/s², the value the fusion filter uses)
+const STANDARD_GRAVITY: f64 = 9.81;
+
+/// Kinds of streamed message a client can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamType {
+    /// Sensor frames (fused or combined)
+    Fused,
+    /// Vibration spectrum (`spectrum`)
+    Spectrum,
+    /// Rolling field statistics (`field_stats`)
+    FieldStats,
+    /// Fusion filter internals (`filter_diag`)
+    FilterDiag,
+}
+
+impl StreamType {
+    /// Look up a type by its subscription name: the message's `type`, or
+    /// `fused` for sensor frames
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fused" => Some(Self::Fused),
+            "spectrum" => Some(Self::Spectrum),
+            "field_stats" => Some(Self::FieldStats),
+            "filter_diag" => Some(Self::FilterDiag),
+            _ => None,
+        }
+    }
+
+    /// Type of a server-pushed notice, from its `type` field
+    pub fn of_notice(json: &str) -> Option<Self> {
+        #[derive(serde::Deserialize)]
+        struct Tagged<'a> {
+            #[serde(rename = "type", borrow)]
+            kind: &'a str,
+        }
+        let tagged: Tagged = serde_json::from_str(json).ok()?;
+        Self::from_name(tagged.kind).filter(|kind| *kind != Self::Fused)
+    }
+}
+
+/// Float width of numbers in a client's frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireType {
+    /// Full double precision
+    F64,
+    /// Single precision (latitude/longitude stay double)
+    F32,
+}
+
+/// Per-connection settings selected by client messages
+#[derive(Debug, Clone)]
+pub struct ClientSettings {
+    /// Frame delivery mode
+    pub delivery: DeliveryMode,
+    
+    /// Position representation
+    pub coords: CoordinateMode,
+    
+    /// Orientation representation
+    pub orientation: OrientationFormat,
+    
+    /// Axis convention of the orientation
+    pub convention: FrameConvention,
+    
+    /// Suppress frames that barely changed (every frame is sent when unset)
+    pub on_change: Option<ChangeThresholds>,
+    
+    /// Send only changed fields between full keyframes (full frames when unset)
+    pub delta: Option<DeltaSettings>,
+    
+    /// Float width of frame fields
+    pub wire_type: WireType,
+    
+    /// How frame timestamps are written
+    pub timestamp_format: TimestampFormat,
+    
+    /// Fused frames alone or combined with their raw readings
+    pub frame_format: FrameFormat,
+    
+    /// Indent JSON for reading in a raw WebSocket tool (debugging only)
+    pub pretty: bool,
+    
+    /// Units of `raw_acceleration` (and the raw IMU reading in combined frames)
+    pub accel_units: AccelUnits,
+    
+    /// Streamed message types to send (all when unset)
+    pub subscriptions: Option<Vec<StreamType>>,
+    
+    /// Period of `bandwidth` reports (none when unset)
+    pub stats_interval: Option<Duration>,
+}
+
+impl ClientSettings {
+    /// Check whether the client wants streamed messages of this type
+    pub fn subscribed(&self, kind: StreamType) -> bool {
+        self.subscriptions.as_ref().is_none_or(|types| types.contains(&kind))
+    }
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            delivery: DeliveryMode::All,
+            coords: CoordinateMode::Geodetic,
+            orientation: OrientationFormat::Both,
+            convention: FrameConvention::Ned,
+            on_change: None,
+            delta: None,
+            wire_type: WireType::F64,
+            timestamp_format: TimestampFormat::Rfc3339,
+            frame_format: FrameFormat::Fused,
+            pretty: false,
+            accel_units: AccelUnits::Mps2,
+            subscriptions: None,
+            stats_interval: None,
+        }
+    }
+}
+
+/// Consecutive encoding failures after which an encoder sanitizes frames
+pub const SANITIZE_AFTER: u32 = 3;
+
+/// Longest allowed gap between frames in on-change mode
+pub const MAX_KEEPALIVE: Duration = Duration::from_secs(5);
+
+/// Minimum change from the last sent frame before a new frame is sent
+#[derive(Debug, Clone, Copy)]
+pub struct ChangeThresholds {
+    /// Position change in meters
+    pub position_m: f64,
+    
+    /// Orientation change in degrees
+    pub orientation_deg: f64,
+    
+    /// Velocity change in m/s
+    pub velocity_mps: f64,
+    
+    /// Send a frame at least this often even without change (capped at `MAX_KEEPALIVE`)
+    pub keepalive: Duration,
+}
+
+impl Default for ChangeThresholds {
+    fn default() -> Self {
+        Self {
+            position_m: 0.1,
+            orientation_deg: 0.5,
+            velocity_mps: 0.05,
+            keepalive: Duration::from_secs(1),
+        }
+    }
+}
+
+impl ChangeThresholds {
+    /// Check whether `frame` differs enough from `last` to be worth sending
+    pub fn exceeded(&self, last: &FusedSensorData, frame: &FusedSensorData) -> bool {
+        let moved = geodetic_to_enu(last.position, frame.position).magnitude();
+        
+        let rotated = last.orientation.angle_to(&frame.orientation).to_degrees();
+        
+        let dv = Vec3::new(
+            frame.velocity.x - last.velocity.x,
+            frame.velocity.y - last.velocity.y,
+            frame.velocity.z - last.velocity.z,
+        )
+        .magnitude();
+        
+        moved >= self.position_m || rotated >= self.orientation_deg || dv >= self.velocity_mps
+    }
+}
+
+/// Delta encoding settings
+/// 
+/// After a full keyframe, frames are sent as `{"type": "delta", ...}`
+/// messages holding the timestamp and only the top-level fields that
+/// changed. A field counts as changed once any number in it moved more
+/// than `threshold` from the value the client last received, so small
+/// changes can't add up unnoticed.
+#[derive(Debug, Clone, Copy)]
+pub struct DeltaSettings {
+    /// Smallest change of any number in a field before the field is resent
+    pub threshold: f64,
+    
+    /// Send a full keyframe every this many frames to resync the client
+    pub keyframe_every: u32,
+}
+
+impl Default for DeltaSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 1e-6,
+            keyframe_every: 50,
+        }
+    }
+}
+
+impl DeltaSettings {
+    /// Check whether any number in `new` moved more than the threshold
+    /// from `old` (or anything else about the value changed)
+    fn changed(&self, old: &serde_json::Value, new: &serde_json::Value) -> bool {
+        use serde_json::Value;
+        match (old, new) {
+            (Value::Number(a), Value::Number(b)) => match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => (a - b).abs() > self.threshold,
+                _ => a != b,
+            },
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() != b.len() || a.iter().zip(b).any(|(a, b)| self.changed(a, b))
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                a.len() != b.len()
+                    || b.iter().any(|(key, b)| a.get(key).is_none_or(|a| self.changed(a, b)))
+            }
+            (a, b) => a != b,
+        }
+    }
+}
+
+/// Per-connection encoder applying the client's settings to each frame
+pub struct ClientEncoder {
+    /// Current client settings
+    settings: ClientSettings,
+    
+    /// Server-wide float rounding
+    precision: Option<OutputPrecision>,
+    
+    /// Append an integrity checksum to each frame
+    checksums: bool,
+    
+    /// Frame IDs and other server-wide inputs to the coordinate metadata
+    metadata: Arc<MetadataConfig>,
+    
+    /// Encoded metadata added to every frame (only when configured)
+    metadata_field: Option<String>,
+    
+    /// Last frame actually sent and when (for on-change mode)
+    last_sent: Option<(FusedSensorData, Instant)>,
+    
+    /// Frame as the client has reconstructed it (for delta encoding; the
+    /// next frame is a keyframe when unset)
+    delta_base: Option<serde_json::Map<String, serde_json::Value>>,
+    
+    /// Delta frames sent since the last keyframe
+    deltas_since_keyframe: u32,
+    
+    /// Frames that failed to encode in a row
+    failures: u32,
+    
+    /// Replace non-finite values instead of failing, once failures persisted
+    sanitize: bool,
+}
+
+impl ClientEncoder {
+    /// Create an encoder with default client settings
+    pub fn new(precision: Option<OutputPrecision>) -> Self {
+        Self {
+            settings: ClientSettings::default(),
+            precision,
+            checksums: false,
+            metadata: Arc::default(),
+            metadata_field: None,
+            last_sent: None,
+            delta_base: None,
+            deltas_since_keyframe: 0,
+            failures: 0,
+            sanitize: false,
+        }
+    }
+    
+    /// Append a CRC-32 `checksum` field to every encoded frame
+    pub fn with_checksums(mut self, enabled: bool) -> Self {
+        self.checksums = enabled;
+        self
+    }
+    
+    /// Start clients with this timestamp format until they choose another
+    pub fn with_timestamp_format(mut self, format: TimestampFormat) -> Self {
+        self.settings.timestamp_format = format;
+        self.refresh_metadata();
+        self
+    }
+    
+    /// Describe frames with this configuration's frame IDs, and add the
+    /// metadata to every frame if it asks for that
+    pub fn with_metadata(mut self, metadata: Arc<MetadataConfig>) -> Self {
+        self.metadata = metadata;
+        self.refresh_metadata();
+        self
+    }
+    
+    /// Coordinate metadata of the frames this client currently receives
+    pub fn metadata(&self) -> FrameMetadata {
+        FrameMetadata::new(&self.metadata, &self.settings)
+    }
+    
+    /// Re-encode the per-frame metadata after a settings change
+    fn refresh_metadata(&mut self) {
+        self.metadata_field = self.metadata.every_frame
+            .then(|| serde_json::to_string(&self.metadata()).ok())
+            .flatten();
+    }
+    
+    /// Current client settings
+    pub fn settings(&self) -> &ClientSettings {
+        &self.settings
+    }
+    
+    /// Replace client settings; the next frame is always sent, and is a
+    /// keyframe in delta mode
+    pub fn set_settings(&mut self, settings: ClientSettings) {
+        self.settings = settings;
+        self.last_sent = None;
+        self.delta_base = None;
+        self.refresh_metadata();
+    }
+    
+    /// Make the next frame a keyframe in delta mode, e.g. because the
+    /// client won't see the frame the next delta would be based on
+    pub fn force_keyframe(&mut self) {
+        self.delta_base = None;
+    }
+    
+    /// Whether non-finite values are replaced rather than failing the frame
+    pub fn sanitizing(&self) -> bool {
+        self.sanitize
+    }
+    
+    /// Whether frames are sent smaller than plain JSON (delta encoding or
+    /// single precision), so their plain size differs from the sent size
+    pub fn encodes(&self) -> bool {
+        self.settings.delta.is_some() || self.settings.wire_type == WireType::F32
+    }
+    
+    /// Size a frame takes without delta encoding and at full double
+    /// precision, with the client's other settings
+    /// 
+    /// Encodes the frame again, so bandwidth reports call it once per
+    /// report rather than for every frame.
+    pub fn plain_len(&self, sensor_data: &FusedSensorData) -> usize {
+        let sanitized;
+        let sensor_data = if sensor_data.is_finite() {
+            sensor_data
+        } else {
+            sanitized = sensor_data.sanitized();
+            &sanitized
+        };
+        let plain = ClientSettings { delta: None, wire_type: WireType::F64, ..self.settings.clone() };
+        let frame = match plain.frame_format {
+            FrameFormat::Fused => encode_frame(sensor_data, &plain, self.precision.as_ref()),
+            FrameFormat::Combined => encode_combined(sensor_data, &plain, self.precision.as_ref()),
+        };
+        self.finish(frame.map(|frame| self.add_metadata(frame))).map_or(0, |frame| frame.len())
+    }
+    
+    /// Encode a frame for this client
+    /// 
+    /// Returns `None` when on-change mode suppresses the frame. A frame with
+    /// non-finite values is an error until failures persist, after which
+    /// it is sanitized (see the module docs).
+    pub fn encode(&mut self, sensor_data: &FusedSensorData, now: Instant) -> Option<serde_json::Result<String>> {
+        let sanitized;
+        let sensor_data = if self.sanitize && !sensor_data.is_finite() {
+            sanitized = sensor_data.sanitized();
+            &sanitized
+        } else {
+            sensor_data
+        };
+        let result = self.encode_checked(sensor_data, now)?;
+        if result.is_ok() {
+            self.failures = 0;
+        } else {
+            self.failures += 1;
+            self.sanitize |= self.failures >= SANITIZE_AFTER;
+        }
+        Some(result)
+    }
+    
+    /// Encode a frame, failing on non-finite values
+    fn encode_checked(&mut self, sensor_data: &FusedSensorData, now: Instant) -> Option<serde_json::Result<String>> {
+        if !sensor_data.is_finite() {
+            return Some(Err(serde_json::Error::custom("frame contains NaN or infinite values")));
+        }
+        if let (Some(thresholds), Some((last, sent_at))) = (&self.settings.on_change, &self.last_sent) {
+            let keepalive = thresholds.keepalive.min(MAX_KEEPALIVE);
+            if now.duration_since(*sent_at) < keepalive && !thresholds.exceeded(last, sensor_data) {
+                return None;
+            }
+        }
+        
+        if self.settings.on_change.is_some() {
+            self.last_sent = Some((sensor_data.clone(), now));
+        }
+        // Metadata goes in before delta encoding, so it drops out of deltas
+        let frame = match (self.settings.frame_format, self.settings.delta) {
+            (FrameFormat::Fused, Some(delta)) => encode_frame(sensor_data, &self.settings, self.precision.as_ref())
+                .map(|frame| self.add_metadata(frame))
+                .and_then(|frame| self.encode_delta(frame, delta)),
+            (FrameFormat::Fused, None) => encode_frame(sensor_data, &self.settings, self.precision.as_ref())
+                .map(|frame| self.add_metadata(frame)),
+            // Combined frames are always sent in full, delta mode or not
+            (FrameFormat::Combined, _) => encode_combined(sensor_data, &self.settings, self.precision.as_ref())
+                .map(|frame| self.add_metadata(frame)),
+        };
+        Some(self.finish(frame))
+    }
+    
+    /// Append the `metadata` field to an encoded JSON object, when every
+    /// frame carries it
+    fn add_metadata(&self, mut frame: String) -> String {
+        if let Some(metadata) = &self.metadata_field {
+            if frame.ends_with('}') && frame != "{}" {
+                frame.pop();
+                frame.push_str(",\"metadata\":");
+                frame.push_str(metadata);
+                frame.push('}');
+            }
+        }
+        frame
+    }
+    
+    /// Indent and checksum an encoded frame as the client asked
+    fn finish(&self, frame: serde_json::Result<String>) -> serde_json::Result<String> {
+        // Checksummed after indenting, so it still covers the bytes sent
+        let frame = if self.settings.pretty { frame.and_then(|f| pretty_print(&f)) } else { frame };
+        if self.checksums { frame.map(append_checksum) } else { frame }
+    }
+    
+    /// Turn an encoded full frame into a keyframe or a delta message
+    /// 
+    /// Works on the encoded frame, so deltas carry exactly the digits and
+    /// representations a full frame would. A field that disappeared (e.g.
+    /// `grid_cell` dropped) is sent as `null`.
+    fn encode_delta(&mut self, frame: String, delta: DeltaSettings) -> serde_json::Result<String> {
+        let fields: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&frame)?;
+        let base = match self.delta_base.as_mut() {
+            Some(base) if self.deltas_since_keyframe + 1 < delta.keyframe_every.max(1) => base,
+            _ => {
+                self.delta_base = Some(fields);
+                self.deltas_since_keyframe = 0;
+                return Ok(frame);
+            }
+        };
+        
+        let mut message = serde_json::Map::new();
+        message.insert("type".to_string(), "delta".into());
+        for key in base.keys() {
+            if !fields.contains_key(key) {
+                message.insert(key.clone(), serde_json::Value::Null);
+            }
+        }
+        base.retain(|key, _| fields.contains_key(key));
+        for (key, value) in fields {
+            if key == "timestamp" || base.get(&key).is_none_or(|old| delta.changed(old, &value)) {
+                message.insert(key.clone(), value.clone());
+                base.insert(key, value);
+            }
+        }
+        self.deltas_since_keyframe += 1;
+        serde_json::to_string(&message)
+    }
+}
+
+/// Serialize a frame as this client wants to see it
+/// 
+/// Only the selected position and orientation representations are sent,
+/// and floats are rounded when an output precision is configured.
+pub fn encode_frame(
+    sensor_data: &FusedSensorData,
+    settings: &ClientSettings,
+    precision: Option<&OutputPrecision>,
+) -> serde_json::Result<String> {
+    let converted;
+    let sensor_data = match settings.convention {
+        FrameConvention::Ned => sensor_data,
+        FrameConvention::Enu => {
+            converted = in_enu(sensor_data);
+            &converted
+        }
+    };
+    if settings.wire_type == WireType::F32 {
+        return encode_frame_f32(sensor_data, settings, precision);
+    }
+    
+    let mut json = serde_json::to_value(sensor_data)?;
+    
+    if let Some(fields) = json.as_object_mut() {
+        match settings.coords {
+            CoordinateMode::Geodetic => fields.remove("local_position"),
+            CoordinateMode::Local => fields.remove("position"),
+        };
+        match settings.orientation {
+            OrientationFormat::Both => None,
+            OrientationFormat::Euler => fields.remove("orientation"),
+            OrientationFormat::Quaternion => fields.remove("euler_degrees"),
+        };
+        if settings.timestamp_format == TimestampFormat::EpochMillis {
+            fields.insert("timestamp".to_string(), sensor_data.timestamp.timestamp_millis().into());
+        }
+        if settings.accel_units == AccelUnits::G {
+            if let Some(accel) = fields.get_mut("raw_acceleration") {
+                to_g(accel);
+            }
+        }
+    }
+    
+    if let Some(precision) = precision {
+        precision.quantize(&mut json);
+    }
+    
+    serde_json::to_string(&json)
+}
+
+/// Copy of a frame with its orientation in the ROS (ENU/FLU) convention
+/// 
+/// Euler angles are recomputed from the converted quaternion, so they are
+/// ENU roll, pitch and yaw rather than remapped aerospace angles.
+fn in_enu(sensor_data: &FusedSensorData) -> FusedSensorData {
+    let orientation = sensor_data.orientation.ned_to_enu();
+    let (roll, pitch, yaw) = orientation.to_euler();
+    FusedSensorData {
+        orientation,
+        euler_degrees: (roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees()),
+        ..sensor_data.clone()
+    }
+}
+
+/// Convert a serialized `{x, y, z}` acceleration from m/s² to g in place
+fn to_g(accel: &mut serde_json::Value) {
+    let Some(components) = accel.as_object_mut() else {
+        return;
+    };
+    for axis in ["x", "y", "z"] {
+        if let Some(value) = components.get_mut(axis) {
+            if let Some(mps2) = value.as_f64() {
+                *value = (mps2 / STANDARD_GRAVITY).into();
+            }
+        }
+    }
+}
+
+/// Re-indent a compact JSON message with `serde_json::to_string_pretty`
+/// 
+/// Goes back through a JSON value, so it costs a parse per message on top
+/// of the extra bytes; only clients that asked for it pay that.
+pub fn pretty_print(json: &str) -> serde_json::Result<String> {
+    serde_json::from_str::<serde_json::Value>(json).and_then(|value| serde_json::to_string_pretty(&value))
+}
+
+/// Serialize a frame together with the raw readings it was fused from
+/// 
+/// `fused` is encoded exactly as `encode_frame` would; the raw readings
+/// keep full width but follow the timestamp format and output precision.
+/// Frames without raw readings (replays) send `null` for `imu`, `gps`,
+/// and `gps_stale`.
+fn encode_combined(
+    sensor_data: &FusedSensorData,
+    settings: &ClientSettings,
+    precision: Option<&OutputPrecision>,
+) -> serde_json::Result<String> {
+    let fused = encode_frame(sensor_data, settings, precision)?;
+    let timestamp = serde_json::to_string(&WireTimestamp::new(sensor_data.timestamp, settings.timestamp_format))?;
+    
+    let raw = |mut json: serde_json::Value, timestamp: DateTime<Utc>| -> serde_json::Result<String> {
+        if let Some(fields) = json.as_object_mut() {
+            if settings.timestamp_format == TimestampFormat::EpochMillis {
+                fields.insert("timestamp".to_string(), timestamp.timestamp_millis().into());
+            }
+            if settings.accel_units == AccelUnits::G {
+                if let Some(accel) = fields.get_mut("acceleration") {
+                    to_g(accel);
+                }
+            }
+        }
+        if let Some(precision) = precision {
+            precision.quantize(&mut json);
+        }
+        serde_json::to_string(&json)
+    };
+    let (imu, gps, gps_stale) = match &sensor_data.inputs {
+        Some(inputs) => (
+            raw(serde_json::to_value(&inputs.imu)?, inputs.imu.timestamp)?,
+            raw(serde_json::to_value(&inputs.gps)?, inputs.gps.timestamp)?,
+            inputs.gps_stale.to_string(),
+        ),
+        None => ("null".to_string(), "null".to_string(), "null".to_string()),
+    };
+    
+    // Assembled from pre-encoded parts so `fused` keeps its exact digits
+    // (an f32 frame would widen if it went back through a JSON value)
+    Ok(format!(
+        r#"{{"type":"combined","timestamp":{timestamp},"imu":{imu},"gps":{gps},"gps_stale":{gps_stale},"fused":{fused}}}"#
+    ))
+}
+
+/// Serialize a frame with single-precision floats
+/// 
+/// Serialized straight from the `f32` struct: going through a JSON value
+/// would widen each float back to `f64` and print all its digits.
+fn encode_frame_f32(
+    sensor_data: &FusedSensorData,
+    settings: &ClientSettings,
+    precision: Option<&OutputPrecision>,
+) -> serde_json::Result<String> {
+    let mut frame = FusedSensorDataF32::from(sensor_data);
+    frame.timestamp = WireTimestamp::new(sensor_data.timestamp, settings.timestamp_format);
+    if settings.accel_units == AccelUnits::G {
+        let a = sensor_data.raw_acceleration;
+        frame.raw_acceleration = Vec3::new(a.x / STANDARD_GRAVITY, a.y / STANDARD_GRAVITY, a.z / STANDARD_GRAVITY).into();
+    }
+    match settings.coords {
+        CoordinateMode::Geodetic => frame.local_position = None,
+        CoordinateMode::Local => frame.position = None,
+    }
+    match settings.orientation {
+        OrientationFormat::Both => {}
+        OrientationFormat::Euler => frame.orientation = None,
+        OrientationFormat::Quaternion => frame.euler_degrees = None,
+    }
+    
+    match precision {
+        Some(precision) => precision.to_json(&frame),
+        None => serde_json::to_string(&frame),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Encoder in on-change mode with default thresholds
+    fn on_change_encoder() -> ClientEncoder {
+        let mut encoder = ClientEncoder::new(None);
+        encoder.set_settings(ClientSettings {
+            on_change: Some(ChangeThresholds::default()),
+            ..ClientSettings::default()
+        });
+        encoder
+    }
+
+    #[test]
+    fn test_frozen_frames_suppressed_except_keepalives() {
+        let mut encoder = on_change_encoder();
+        let frozen = FusedSensorData::default();
+        let start = Instant::now();
+
+        // Three seconds of identical frames at 50 Hz, keepalive every second
+        let sent: Vec<u64> = (0..150u64)
+            .filter(|i| {
+                let now = start + Duration::from_millis(20 * i);
+                encoder.encode(&frozen, now).is_some()
+            })
+            .map(|i| 20 * i)
+            .collect();
+        assert_eq!(sent, [0, 1000, 2000]);
+    }
+
+    #[test]
+    fn test_changed_frames_are_sent() {
+        let mut encoder = on_change_encoder();
+        let mut frame = FusedSensorData::default();
+        let start = Instant::now();
+        assert!(encoder.encode(&frame, start).is_some());
+
+        // Below every threshold
+        frame.velocity = Vec3::new(0.01, 0.0, 0.0);
+        assert!(encoder.encode(&frame, start + Duration::from_millis(20)).is_none());
+
+        frame.velocity = Vec3::new(0.1, 0.0, 0.0);
+        assert!(encoder.encode(&frame, start + Duration::from_millis(40)).is_some());
+
+        frame.position.0 += 1e-5; // ~1.1 m north
+        assert!(encoder.encode(&frame, start + Duration::from_millis(60)).is_some());
+    }
+
+    /// Encode `frame` with a delta encoder, parsed
+    fn encode_json(encoder: &mut ClientEncoder, frame: &FusedSensorData) -> serde_json::Value {
+        let text = encoder.encode(frame, Instant::now()).unwrap().unwrap();
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn test_delta_frames_carry_only_changed_fields_between_keyframes() {
+        let mut encoder = ClientEncoder::new(None);
+        encoder.set_settings(ClientSettings {
+            delta: Some(DeltaSettings { threshold: 0.01, keyframe_every: 4 }),
+            ..ClientSettings::default()
+        });
+        let mut frame = FusedSensorData::default();
+
+        // Keyframe first: a complete frame
+        let key = encode_json(&mut encoder, &frame);
+        assert!(key.get("type").is_none() && key.get("velocity").is_some());
+
+        frame.gps_speed = 3.0;
+        frame.velocity.x = 0.001; // below the threshold
+        let delta = encode_json(&mut encoder, &frame);
+        let mut fields: Vec<_> = delta.as_object().unwrap().keys().cloned().collect();
+        fields.sort();
+        assert_eq!(fields, ["gps_speed", "timestamp", "type"]);
+        assert_eq!(delta["type"], "delta");
+
+        // Small steps add up against what the client last received
+        frame.velocity.x = 0.02;
+        let delta = encode_json(&mut encoder, &frame);
+        assert_eq!(delta["velocity"]["x"], 0.02);
+        assert!(delta.get("gps_speed").is_none());
+
+        // Every fourth frame resyncs with a keyframe
+        encode_json(&mut encoder, &frame);
+        assert!(encode_json(&mut encoder, &frame).get("type").is_none());
+        assert_eq!(encode_json(&mut encoder, &frame)["type"], "delta");
+
+        // Changing settings (or a resync) starts over with a keyframe
+        encoder.set_settings(encoder.settings().clone());
+        assert!(encode_json(&mut encoder, &frame).get("type").is_none());
+    }
+
+    #[test]
+    fn test_plain_len_is_the_plain_frame_size() {
+        let frame = FusedSensorData::default();
+        let now = Instant::now();
+        let mut encoder = ClientEncoder::new(None);
+        let plain = encoder.encode(&frame, now).unwrap().unwrap();
+        assert!(!encoder.encodes());
+        assert_eq!(encoder.plain_len(&frame), plain.len());
+
+        encoder.set_settings(ClientSettings { delta: Some(DeltaSettings::default()), ..ClientSettings::default() });
+        encoder.encode(&frame, now);
+        let delta = encoder.encode(&frame, now).unwrap().unwrap();
+        assert!(delta.len() < plain.len());
+        assert!(encoder.encodes());
+        assert_eq!(encoder.plain_len(&frame), plain.len());
+    }
+
+    #[test]
+    fn test_enu_convention_remaps_orientation_on_both_wire_types() {
+        // Level, facing north
+        let frame = FusedSensorData::from_orientation(0.0, 0.0, 0.0);
+        for wire_type in [WireType::F64, WireType::F32] {
+            let settings = ClientSettings {
+                convention: FrameConvention::Enu,
+                wire_type,
+                ..ClientSettings::default()
+            };
+            let json: serde_json::Value = serde_json::from_str(&encode_frame(&frame, &settings, None).unwrap()).unwrap();
+            let yaw = json["euler_degrees"][2].as_f64().unwrap();
+            assert!((yaw - 90.0).abs() < 1e-4, "{wire_type:?} yaw {yaw}");
+            let (w, z) = (json["orientation"]["w"].as_f64().unwrap(), json["orientation"]["z"].as_f64().unwrap());
+            assert!((w - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-6 && (z - w).abs() < 1e-6, "{wire_type:?} {}", json["orientation"]);
+        }
+
+        // Aerospace by default
+        let json: serde_json::Value = serde_json::from_str(&encode_frame(&frame, &ClientSettings::default(), None).unwrap()).unwrap();
+        assert_eq!(json["orientation"]["w"], 1.0);
+    }
+
+    #[test]
+    fn test_every_frame_sent_without_on_change() {
+        let mut encoder = ClientEncoder::new(None);
+        let frame = FusedSensorData::default();
+        let now = Instant::now();
+        assert!((0..10).all(|_| encoder.encode(&frame, now).is_some()));
+    }
+}
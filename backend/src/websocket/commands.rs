@@ -0,0 +1,146 @@
+//! Command History
+//!
+//! Bounded log of the commands clients sent, for reconstructing how a
+//! fault scenario came about and for re-running the last fault with
+//! `replay_last`.
+
+use crate::fusion::FilterKind;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Commands kept when no capacity is configured
+pub const DEFAULT_COMMAND_HISTORY: usize = 50;
+
+/// A command for the sensor loop
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlCommand {
+    /// Fault type or simulation control action (e.g. `high_noise`, `pause`)
+    pub action: String,
+
+    /// Clear the injected fault automatically after this long (faults only)
+    pub duration: Option<Duration>,
+
+    /// Filter to switch to (`set_filter` only)
+    pub filter: Option<FilterKind>,
+}
+
+impl ControlCommand {
+    /// Command without a duration
+    pub fn new(action: impl Into<String>) -> Self {
+        Self {
+            action: action.into(),
+            duration: None,
+            filter: None,
+        }
+    }
+
+    /// Clear the injected fault automatically after `duration`
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Switch the fusion loop to `filter`
+    pub fn set_filter(filter: FilterKind) -> Self {
+        Self {
+            filter: Some(filter),
+            ..Self::new("set_filter")
+        }
+    }
+}
+
+/// A command forwarded to the sensor loop
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandRecord {
+    /// When the command was received
+    pub timestamp: DateTime<Utc>,
+
+    /// Client that sent it
+    pub peer: String,
+
+    /// Command as executed by the sensor loop (e.g. `accel_spike`, `pause`)
+    pub command: String,
+
+    /// Seconds after which the injected fault clears itself, if timed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+
+    /// Filter switched to, for `set_filter`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<FilterKind>,
+
+    /// Injects a fault (and so can be repeated with `replay_last`)
+    pub fault: bool,
+
+    /// Executed by `replay_last` rather than sent directly
+    pub replayed: bool,
+}
+
+/// Bounded log of recent commands, oldest first
+pub struct CommandLog {
+    /// Recorded commands
+    records: Mutex<VecDeque<CommandRecord>>,
+
+    /// Maximum number of commands kept
+    capacity: usize,
+}
+
+impl CommandLog {
+    /// Create a log that keeps up to `capacity` commands
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            records: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    /// Record a command, evicting the oldest when full
+    ///
+    /// `reset` clears faults rather than injecting one, so it is never
+    /// counted as a fault.
+    pub fn push(&self, peer: impl std::fmt::Display, command: &ControlCommand, fault: bool, replayed: bool) {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(CommandRecord {
+            timestamp: Utc::now(),
+            peer: peer.to_string(),
+            command: command.action.clone(),
+            duration_secs: command.duration.map(|d| d.as_secs_f64()),
+            filter: command.filter,
+            fault: fault && command.action != "reset",
+            replayed,
+        });
+    }
+
+    /// Copy of all recorded commands, oldest first
+    pub fn records(&self) -> Vec<CommandRecord> {
+        self.records
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Most recent fault command still in the log
+    ///
+    /// A timed fault is returned with its original duration.
+    pub fn last_fault(&self) -> Option<ControlCommand> {
+        self.records
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .rev()
+            .find(|record| record.fault)
+            .map(|record| {
+                ControlCommand::new(record.command.clone())
+                    .with_duration(record.duration_secs.map(Duration::from_secs_f64))
+            })
+    }
+}
@@ -0,0 +1,83 @@
+//! Connected Client Counts
+//!
+//! Tracks how many WebSocket clients are connected, the most seen at once,
+//! and how many have connected in total. Each connection holds a guard
+//! that releases its slot when dropped, however the connection ends.
+//!
+//! Clients taking combined frames are counted the same way, so the fusion
+//! loop only gathers the raw readings those frames nest while one is
+//! connected.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Shared client connection counters
+#[derive(Debug, Default)]
+pub struct ConnectionStats {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    total: AtomicU64,
+    combined: AtomicUsize,
+}
+
+impl ConnectionStats {
+    /// Create counters starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a new connection until the returned guard is dropped
+    pub fn connect(self: &Arc<Self>) -> ConnectionGuard {
+        let now = self.current.fetch_add(1, Ordering::Relaxed) + 1;
+        self.peak.fetch_max(now, Ordering::Relaxed);
+        self.total.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard(self.clone())
+    }
+
+    /// Clients connected right now
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Most clients connected at once
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Connections accepted since startup
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Count a client taking combined frames until the returned guard is
+    /// dropped
+    pub fn take_combined(self: &Arc<Self>) -> CombinedGuard {
+        self.combined.fetch_add(1, Ordering::Relaxed);
+        CombinedGuard(self.clone())
+    }
+
+    /// Clients taking combined frames right now
+    pub fn combined(&self) -> usize {
+        self.combined.load(Ordering::Relaxed)
+    }
+}
+
+/// Keeps one connection counted while alive
+#[derive(Debug)]
+pub struct ConnectionGuard(Arc<ConnectionStats>);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.current.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Keeps one client counted as taking combined frames while alive
+#[derive(Debug)]
+pub struct CombinedGuard(Arc<ConnectionStats>);
+
+impl Drop for CombinedGuard {
+    fn drop(&mut self) {
+        self.0.combined.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,116 @@
+//! Embedded Debug Dashboard
+//!
+//! A single static page, compiled into the binary, that connects to the
+//! WebSocket stream and plots the Euler angles and the local position
+//! track. It is served on its own HTTP port so a quick look at the
+//! telemetry needs no frontend build. The page learns the WebSocket URL
+//! from the server, which fills it in before serving.
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::io::AsyncReadExt;
+use tracing::{debug, info, error};
+
+use crate::error::SensorFusionError;
+use super::http::{peek_request_head, write_response};
+
+/// The page, with `{{WS_URL}}` where the WebSocket URL goes
+const PAGE_TEMPLATE: &str = include_str!("dashboard.html");
+
+/// HTTP server for the embedded dashboard page
+pub struct DashboardServer {
+    /// Port to listen on (not the WebSocket port)
+    port: u16,
+
+    /// The page with the WebSocket URL filled in
+    page: String,
+
+    /// Turns true when the server should stop, if shutdown is signalled
+    shutdown: Option<watch::Receiver<bool>>,
+}
+
+impl DashboardServer {
+    /// Create a dashboard server
+    ///
+    /// # Arguments
+    /// * `port` - Port to serve the page on
+    /// * `ws_url` - WebSocket URL the page connects to, e.g. `ws://127.0.0.1:8080/stream`
+    pub fn new(port: u16, ws_url: &str) -> Self {
+        Self {
+            port,
+            page: PAGE_TEMPLATE.replace("{{WS_URL}}", &html_escape(ws_url)),
+            shutdown: None,
+        }
+    }
+
+    /// Stop accepting once `shutdown` turns true
+    pub fn with_shutdown(mut self, shutdown: watch::Receiver<bool>) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Serve the page until shut down or cancelled
+    ///
+    /// Returns `SensorFusionError::Bind` if the port can't be bound.
+    pub async fn run(self) -> Result<(), SensorFusionError> {
+        let addr = format!("127.0.0.1:{}", self.port);
+        let listener = TcpListener::bind(&addr)
+            .await
+            .map_err(|source| SensorFusionError::Bind { addr: addr.clone(), source })?;
+        info!("📈 Dashboard available at http://{}/", addr);
+
+        let page: std::sync::Arc<str> = self.page.into();
+        let mut shutdown = self.shutdown;
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = shutdown_signalled(&mut shutdown) => break,
+            };
+            match accepted {
+                Ok((stream, peer_addr)) => {
+                    let page = page.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve_request(stream, &page).await {
+                            debug!("Dashboard request from {} failed: {}", peer_addr, e);
+                        }
+                    });
+                }
+                Err(e) => error!("❌ Failed to accept dashboard connection: {}", e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Answer one request: the page at `/`, 404 elsewhere
+async fn serve_request(mut stream: TcpStream, page: &str) -> anyhow::Result<()> {
+    let head = peek_request_head(&stream).await?;
+    let mut discard = vec![0u8; head.len];
+    stream.read_exact(&mut discard).await?;
+
+    match (head.method.as_str(), head.path.as_str()) {
+        ("GET", "/" | "/index.html") => {
+            write_response(&mut stream, "200 OK", "text/html; charset=utf-8", page.as_bytes()).await
+        }
+        _ => write_response(&mut stream, "404 Not Found", "text/plain", b"not found").await,
+    }
+}
+
+/// Wait until `shutdown` turns true, or forever without one
+async fn shutdown_signalled(shutdown: &mut Option<watch::Receiver<bool>>) {
+    if let Some(rx) = shutdown {
+        if rx.wait_for(|stop| *stop).await.is_ok() {
+            return;
+        }
+    }
+    std::future::pending().await
+}
+
+/// Escape text for an HTML attribute or element
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
@@ -0,0 +1,237 @@
+//! Fused Frame History
+//!
+//! Bounded buffer of recent fused frames tagged with monotonically
+//! increasing sequence numbers. Lets clients that can't hold a WebSocket
+//! open (HTTP long-poll) ask for "everything after sequence N", and lets
+//! clients aligning the fusion output with other timestamped data ask for
+//! the estimated state at a point in time.
+
+use crate::models::FusedSensorData;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// A fused frame with its history sequence number
+#[derive(Debug, Clone, Serialize)]
+pub struct SequencedFrame {
+    /// Sequence number (starts at 1, increases by one per frame)
+    pub seq: u64,
+
+    /// The fused frame
+    pub frame: FusedSensorData,
+}
+
+/// Result of a history query
+#[derive(Debug, Clone, Serialize)]
+pub struct HistorySlice {
+    /// Frames newer than the requested sequence, oldest first
+    pub frames: Vec<SequencedFrame>,
+
+    /// Sequence number of the newest frame in the history (0 if empty)
+    pub latest_seq: u64,
+
+    /// True if frames after the requested sequence were already evicted,
+    /// or the requested sequence is newer than any frame here (e.g. a
+    /// cursor from before a backend restart); only the latest frame is
+    /// returned in that case
+    pub gap: bool,
+}
+
+/// Result of a point-in-time query
+#[derive(Debug, Clone, Serialize)]
+pub struct FrameAt {
+    /// Estimated state at the requested time
+    pub frame: FusedSensorData,
+
+    /// True if the requested time is outside the buffered frames, in which
+    /// case `frame` is the nearest buffered frame rather than an estimate
+    pub out_of_range: bool,
+}
+
+/// Ring buffer of recent fused frames
+pub struct FrameHistory {
+    /// Buffered frames, oldest first
+    frames: Mutex<VecDeque<SequencedFrame>>,
+
+    /// Maximum number of frames kept
+    capacity: usize,
+
+    /// Wakes pollers when a frame is pushed
+    notify: Notify,
+}
+
+impl FrameHistory {
+    /// Create a history that keeps up to `capacity` frames
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            frames: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            notify: Notify::new(),
+        }
+    }
+
+    /// Append a frame, evicting the oldest when full; returns its sequence
+    pub fn push(&self, frame: FusedSensorData) -> u64 {
+        let seq = {
+            let mut frames = self.frames.lock().unwrap_or_else(|e| e.into_inner());
+            let seq = frames.back().map_or(1, |f| f.seq + 1);
+            if frames.len() == self.capacity {
+                frames.pop_front();
+            }
+            frames.push_back(SequencedFrame { seq, frame });
+            seq
+        };
+        self.notify.notify_waiters();
+        seq
+    }
+
+    /// Frames with a sequence number greater than `since`
+    pub fn since(&self, since: u64) -> HistorySlice {
+        let frames = self.frames.lock().unwrap_or_else(|e| e.into_inner());
+        let latest_seq = frames.back().map_or(0, |f| f.seq);
+        let oldest_seq = frames.front().map_or(0, |f| f.seq);
+
+        // The client fell behind further than we remember, or holds a
+        // cursor from an earlier run of the sequence
+        if since.saturating_add(1) < oldest_seq || (latest_seq > 0 && since > latest_seq) {
+            return HistorySlice {
+                frames: frames.back().cloned().into_iter().collect(),
+                latest_seq,
+                gap: true,
+            };
+        }
+
+        HistorySlice {
+            frames: frames.iter().filter(|f| f.seq > since).cloned().collect(),
+            latest_seq,
+            gap: false,
+        }
+    }
+
+    /// Most recent frame, if any
+    pub fn latest(&self) -> Option<SequencedFrame> {
+        self.frames
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .back()
+            .cloned()
+    }
+
+    /// Estimated state at `timestamp`, interpolated between the two
+    /// buffered frames around it
+    ///
+    /// Outside the buffered time range the nearest frame is returned
+    /// unchanged and flagged `out_of_range`. `None` while empty.
+    pub fn at(&self, timestamp: DateTime<Utc>) -> Option<FrameAt> {
+        let frames = self.frames.lock().unwrap_or_else(|e| e.into_inner());
+        let (oldest, newest) = (frames.front()?, frames.back()?);
+        if timestamp <= oldest.frame.timestamp || timestamp >= newest.frame.timestamp {
+            let nearest = if timestamp <= oldest.frame.timestamp { oldest } else { newest };
+            return Some(FrameAt {
+                out_of_range: timestamp != nearest.frame.timestamp,
+                frame: nearest.frame.clone(),
+            });
+        }
+
+        // Frames are pushed in time order; first frame after `timestamp`
+        let after = frames.partition_point(|f| f.frame.timestamp <= timestamp);
+        let (before, after) = (&frames[after - 1].frame, &frames[after].frame);
+        Some(FrameAt {
+            frame: before.interpolate(after, timestamp),
+            out_of_range: false,
+        })
+    }
+
+    /// Wait up to `timeout` for frames newer than `since`
+    ///
+    /// Returns immediately if such frames are already buffered; returns an
+    /// empty slice if none arrive before the timeout.
+    pub async fn wait_since(&self, since: u64, timeout: Duration) -> HistorySlice {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Register interest before checking to avoid missing a push
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let slice = self.since(since);
+            if !slice.frames.is_empty() {
+                return slice;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return slice;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Quaternion, Vec3};
+
+    /// Frame at `secs` past a fixed start with the given yaw, position and velocity
+    fn frame(secs: i64, yaw_deg: f64, latitude: f64, velocity: f64) -> FusedSensorData {
+        let mut frame = FusedSensorData::from_orientation(0.0, 0.0, yaw_deg.to_radians());
+        frame.timestamp = DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap();
+        frame.position = (latitude, -105.0, 1600.0);
+        frame.velocity = Vec3::new(velocity, 0.0, 0.0);
+        frame
+    }
+
+    #[test]
+    fn test_at_interpolates_between_neighbouring_frames() {
+        let history = FrameHistory::new(10);
+        history.push(frame(0, 0.0, 39.0, 0.0));
+        history.push(frame(4, 40.0, 39.4, 2.0));
+        history.push(frame(8, 90.0, 39.8, 4.0));
+
+        // A quarter of the way from the frame at 4 s to the one at 8 s
+        let at = history.at(DateTime::from_timestamp(1_700_000_005, 0).unwrap()).unwrap();
+        assert!(!at.out_of_range);
+        let expected = Quaternion::from_euler(0.0, 0.0, 52.5_f64.to_radians());
+        assert!(at.frame.orientation.angle_to(&expected) < 1e-6, "orientation {:?}", at.frame.orientation);
+        assert!((at.frame.euler_degrees.2 - 52.5).abs() < 1e-9);
+        assert!((at.frame.position.0 - 39.5).abs() < 1e-9, "position {:?}", at.frame.position);
+        assert!((at.frame.velocity.x - 2.5).abs() < 1e-9, "velocity {:?}", at.frame.velocity);
+        assert_eq!(at.frame.timestamp.timestamp(), 1_700_000_005);
+    }
+
+    #[test]
+    fn test_at_outside_the_buffer_returns_the_nearest_frame() {
+        let history = FrameHistory::new(10);
+        assert!(history.at(Utc::now()).is_none());
+        history.push(frame(0, 0.0, 39.0, 0.0));
+        history.push(frame(4, 40.0, 39.4, 2.0));
+
+        let before = history.at(DateTime::from_timestamp(1_699_999_990, 0).unwrap()).unwrap();
+        assert!(before.out_of_range);
+        assert_eq!(before.frame.position.0, 39.0);
+
+        let after = history.at(DateTime::from_timestamp(1_700_000_060, 0).unwrap()).unwrap();
+        assert!(after.out_of_range);
+        assert_eq!(after.frame.position.0, 39.4);
+
+        // The newest frame's own time is in range
+        let exact = history.at(DateTime::from_timestamp(1_700_000_004, 0).unwrap()).unwrap();
+        assert!(!exact.out_of_range);
+    }
+
+    #[test]
+    fn test_since_the_largest_cursor_reports_a_gap() {
+        let history = FrameHistory::new(10);
+        assert!(history.since(u64::MAX).frames.is_empty());
+
+        history.push(frame(0, 0.0, 39.0, 0.0));
+        history.push(frame(4, 40.0, 39.4, 2.0));
+        let slice = history.since(u64::MAX);
+        assert!(slice.gap);
+        assert_eq!(slice.latest_seq, 2);
+        assert_eq!(slice.frames.len(), 1);
+        assert_eq!(slice.frames[0].seq, 2);
+    }
+}
@@ -0,0 +1,166 @@
+//! Minimal HTTP Handling
+//!
+//! The server port speaks both WebSocket and plain HTTP. The request head
+//! is peeked (not consumed) so WebSocket upgrades can still be handed to
+//! the tungstenite handshake untouched, while other requests are answered
+//! here:
+//! - `GET /poll?since=<seq>` long-poll fallback for clients without WebSocket
+
+use anyhow::{Result, Context, bail};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use std::time::Duration;
+
+use super::history::FrameHistory;
+
+/// Largest request head we are willing to buffer
+const MAX_HEAD_BYTES: usize = 8192;
+
+/// How long a client may take to send its request head
+const HEAD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Parsed HTTP request line and headers
+#[derive(Debug, Clone)]
+pub struct RequestHead {
+    /// Request method (e.g. `GET`)
+    pub method: String,
+
+    /// Request path without the query string
+    pub path: String,
+
+    /// Raw query string, if any
+    pub query: Option<String>,
+
+    /// Header names (lowercased) and values
+    pub headers: Vec<(String, String)>,
+
+    /// Length of the head in bytes, including the blank line
+    pub len: usize,
+}
+
+impl RequestHead {
+    /// Look up a header value by case-insensitive name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Look up a query parameter by name
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query.as_deref()?.split('&').find_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key == name).then_some(value)
+        })
+    }
+
+    /// Check whether this is a WebSocket upgrade request
+    pub fn is_websocket_upgrade(&self) -> bool {
+        self.header("upgrade")
+            .is_some_and(|v| v.eq_ignore_ascii_case("websocket"))
+    }
+}
+
+/// Read the request head without consuming it from the stream
+pub async fn peek_request_head(stream: &TcpStream) -> Result<RequestHead> {
+    let deadline = tokio::time::Instant::now() + HEAD_TIMEOUT;
+    let mut buf = vec![0u8; MAX_HEAD_BYTES];
+
+    loop {
+        let n = tokio::time::timeout_at(deadline, stream.peek(&mut buf))
+            .await
+            .context("Timed out waiting for request head")??;
+        if n == 0 {
+            bail!("Connection closed before request head");
+        }
+
+        if let Some(end) = buf[..n].windows(4).position(|w| w == b"\r\n\r\n") {
+            return parse_request_head(&buf[..end + 4]);
+        }
+        if n == MAX_HEAD_BYTES {
+            bail!("Request head exceeds {} bytes", MAX_HEAD_BYTES);
+        }
+
+        // Head only partially arrived; peek returns immediately, so back off
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+/// Parse a complete request head
+fn parse_request_head(bytes: &[u8]) -> Result<RequestHead> {
+    let text = std::str::from_utf8(bytes).context("Request head is not UTF-8")?;
+    let mut lines = text.split("\r\n");
+
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        bail!("Malformed request line: {:?}", request_line);
+    };
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query.to_string())),
+        None => (target, None),
+    };
+
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    Ok(RequestHead {
+        method: method.to_string(),
+        path: path.to_string(),
+        query,
+        headers,
+        len: bytes.len(),
+    })
+}
+
+/// Answer a plain HTTP request
+pub async fn handle_http_request(
+    mut stream: TcpStream,
+    head: RequestHead,
+    history: Option<&FrameHistory>,
+    poll_timeout: Duration,
+) -> Result<()> {
+    // Consume the head we peeked earlier
+    let mut discard = vec![0u8; head.len];
+    stream.read_exact(&mut discard).await?;
+
+    match (head.method.as_str(), head.path.as_str(), history) {
+        ("GET", "/poll", Some(history)) => {
+            let slice = match head.query_param("since") {
+                Some(since) => {
+                    let Ok(since) = since.parse::<u64>() else {
+                        return write_response(&mut stream, "400 Bad Request", "text/plain", b"invalid since").await;
+                    };
+                    history.wait_since(since, poll_timeout).await
+                }
+                // No cursor yet: hand out the newest frame to start from
+                None => history.since(history.latest().map_or(0, |f| f.seq.saturating_sub(1))),
+            };
+            let body = serde_json::to_vec(&slice)?;
+            write_response(&mut stream, "200 OK", "application/json", &body).await
+        }
+        _ => write_response(&mut stream, "404 Not Found", "text/plain", b"not found").await,
+    }
+}
+
+/// Write a complete HTTP/1.1 response and close the connection
+pub async fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> Result<()> {
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len(),
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,168 @@
+//! Coordinate Metadata
+//!
+//! Describes the reference frames, axis convention and units of the frames
+//! a client receives, so robotics and multi-sensor consumers can configure
+//! themselves instead of assuming. Most of it follows the client's own
+//! settings (units, convention, position representation), so it is built
+//! per connection. It goes out once in the connection message, again on a
+//! `get_metadata` request, and on every frame only when the server is
+//! configured to repeat it.
+
+use serde::Serialize;
+
+use crate::fusion::AccelFrame;
+use crate::models::TimestampFormat;
+use super::client::{AccelUnits, ClientSettings, CoordinateMode, FrameConvention};
+
+/// Names of the coordinate frames the backend reports in
+///
+/// The defaults follow ROS naming (REP 105) so the IDs can be used as
+/// `frame_id`s without translation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FrameIds {
+    /// Navigation frame that orientation and world-frame vectors refer to
+    pub world: String,
+
+    /// Vehicle body frame
+    pub body: String,
+
+    /// IMU frame; raw IMU readings are in its axes
+    pub imu: String,
+
+    /// GPS antenna frame
+    pub gps: String,
+}
+
+impl Default for FrameIds {
+    fn default() -> Self {
+        Self {
+            world: "map".to_string(),
+            body: "base_link".to_string(),
+            imu: "imu_link".to_string(),
+            gps: "gps_link".to_string(),
+        }
+    }
+}
+
+/// Server-wide inputs to the metadata sent to clients
+#[derive(Debug, Clone, Default)]
+pub struct MetadataConfig {
+    /// Configured frame names
+    pub frame_ids: FrameIds,
+
+    /// Frame `raw_acceleration` is reported in
+    pub accel_frame: AccelFrame,
+
+    /// Repeat the metadata in every frame, not only at connect
+    pub every_frame: bool,
+}
+
+/// Coordinate metadata of the frames one client receives
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrameMetadata {
+    /// Configured frame names
+    pub frame_ids: FrameIds,
+
+    /// Axis convention of `orientation` and `euler_degrees`: `ned` (FRD
+    /// body in an NED world) or `enu` (FLU body in an ENU world)
+    pub axis_convention: &'static str,
+
+    /// ID of the frame `raw_acceleration` is in (the IMU or world frame)
+    pub acceleration_frame: String,
+
+    /// Position field sent: `geodetic` (`position`) or `local`
+    /// (`local_position`, meters east/north/up of the fusion origin)
+    pub position: &'static str,
+
+    /// Units of the frame fields
+    pub units: Units,
+}
+
+/// Units of the frame fields, as sent to this client
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Units {
+    /// `raw_acceleration`: `m/s^2` or `g`
+    pub acceleration: &'static str,
+
+    /// `raw_gyroscope` and `world_angular_velocity`
+    pub angular_velocity: &'static str,
+
+    /// `euler_degrees` and `gps_heading`
+    pub angle: &'static str,
+
+    /// Horizontal position: `deg` (latitude, longitude) or `m` (local)
+    pub horizontal_position: &'static str,
+
+    /// Altitude and the local up axis
+    pub vertical_position: &'static str,
+
+    /// `velocity` and `gps_speed`
+    pub velocity: &'static str,
+
+    /// `timestamp`: `rfc3339` or `epoch_millis`, as named by
+    /// `set_timestamp_format`
+    pub timestamp: &'static str,
+}
+
+impl FrameMetadata {
+    /// Metadata for a client with these settings
+    pub fn new(config: &MetadataConfig, settings: &ClientSettings) -> Self {
+        let ids = &config.frame_ids;
+        let local = settings.coords == CoordinateMode::Local;
+        Self {
+            frame_ids: ids.clone(),
+            axis_convention: match settings.convention {
+                FrameConvention::Ned => "ned",
+                FrameConvention::Enu => "enu",
+            },
+            acceleration_frame: match config.accel_frame {
+                AccelFrame::Body => ids.imu.clone(),
+                AccelFrame::World => ids.world.clone(),
+            },
+            position: if local { "local" } else { "geodetic" },
+            units: Units {
+                acceleration: match settings.accel_units {
+                    AccelUnits::Mps2 => "m/s^2",
+                    AccelUnits::G => "g",
+                },
+                angular_velocity: "rad/s",
+                angle: "deg",
+                horizontal_position: if local { "m" } else { "deg" },
+                vertical_position: "m",
+                velocity: "m/s",
+                timestamp: match settings.timestamp_format {
+                    TimestampFormat::Rfc3339 => "rfc3339",
+                    TimestampFormat::EpochMillis => "epoch_millis",
+                },
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_follows_client_settings() {
+        let config = MetadataConfig { accel_frame: AccelFrame::World, ..MetadataConfig::default() };
+        let settings = ClientSettings {
+            coords: CoordinateMode::Local,
+            convention: FrameConvention::Enu,
+            accel_units: AccelUnits::G,
+            ..ClientSettings::default()
+        };
+        let metadata = FrameMetadata::new(&config, &settings);
+        assert_eq!(metadata.axis_convention, "enu");
+        assert_eq!(metadata.acceleration_frame, "map");
+        assert_eq!(metadata.position, "local");
+        assert_eq!(metadata.units.acceleration, "g");
+        assert_eq!(metadata.units.horizontal_position, "m");
+
+        let metadata = FrameMetadata::new(&MetadataConfig::default(), &ClientSettings::default());
+        assert_eq!(metadata.axis_convention, "ned");
+        assert_eq!(metadata.acceleration_frame, "imu_link");
+        assert_eq!(metadata.units.acceleration, "m/s^2");
+        assert_eq!(metadata.units.horizontal_position, "deg");
+    }
+}
@@ -4,6 +4,24 @@
 //! sensor data to clients and receiving ML predictions.
 
 pub mod server;
+pub mod bandwidth;
+pub mod checksum;
+pub mod client;
+pub mod commands;
+pub mod connections;
+pub mod dashboard;
+pub mod history;
+pub mod http;
+pub mod metadata;
+pub mod origin;
+pub mod outbound;
+pub mod precision;
 
 // Re-export commonly used types
-pub use server::WebSocketServer;
\ No newline at end of file
+pub use server::WebSocketServer;
+pub use commands::ControlCommand;
+pub use connections::ConnectionStats;
+pub use dashboard::DashboardServer;
+pub use metadata::{FrameIds, MetadataConfig};
+pub use origin::OriginPolicy;
+pub use precision::OutputPrecision;
\ No newline at end of file
@@ -0,0 +1,80 @@
+//! Handshake Origin Checking
+//!
+//! Browsers attach an `Origin` header to WebSocket handshakes but do not
+//! enforce the same-origin policy on them, so any page a user visits could
+//! open a connection to the backend (cross-site WebSocket hijacking). An
+//! allowlist of dashboard origins shuts that out; non-browser clients,
+//! which usually send no `Origin` at all, can still be let through.
+
+/// Which handshake origins are accepted
+#[derive(Debug, Clone)]
+pub struct OriginPolicy {
+    /// Accepted origins, e.g. `https://dashboard.example.com` (empty = any)
+    allowed: Vec<String>,
+
+    /// Accept handshakes without an `Origin` header
+    allow_missing: bool,
+}
+
+impl Default for OriginPolicy {
+    fn default() -> Self {
+        Self {
+            allowed: Vec::new(),
+            allow_missing: true,
+        }
+    }
+}
+
+impl OriginPolicy {
+    /// Accept only the given origins (any origin when empty)
+    ///
+    /// # Arguments
+    /// * `allowed` - Origins as `scheme://host[:port]`
+    /// * `allow_missing` - Accept clients that send no `Origin` header
+    pub fn new(allowed: impl IntoIterator<Item = impl Into<String>>, allow_missing: bool) -> Self {
+        Self {
+            allowed: allowed
+                .into_iter()
+                .map(|origin| normalize(&origin.into()))
+                .collect(),
+            allow_missing,
+        }
+    }
+
+    /// Check whether a handshake with this `Origin` header is accepted
+    pub fn permits(&self, origin: Option<&str>) -> bool {
+        match origin {
+            None => self.allow_missing,
+            Some(_) if self.allowed.is_empty() => true,
+            Some(origin) => self.allowed.contains(&normalize(origin)),
+        }
+    }
+}
+
+/// Origins compare case-insensitively and without a trailing slash
+fn normalize(origin: &str) -> String {
+    origin.trim().trim_end_matches('/').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowlist_matches_normalized_origins() {
+        let policy = OriginPolicy::new(["https://Dashboard.example.com/"], false);
+        assert!(policy.permits(Some("https://dashboard.example.com")));
+        assert!(policy.permits(Some(" HTTPS://DASHBOARD.EXAMPLE.COM/ ")));
+        assert!(!policy.permits(Some("https://evil.example.com")));
+        assert!(!policy.permits(Some("http://dashboard.example.com")));
+        assert!(!policy.permits(None));
+    }
+
+    #[test]
+    fn test_default_accepts_everything() {
+        let policy = OriginPolicy::default();
+        assert!(policy.permits(Some("https://anywhere.example")));
+        assert!(policy.permits(None));
+        assert!(!OriginPolicy::new(Vec::<String>::new(), false).permits(None));
+    }
+}
@@ -0,0 +1,186 @@
+//! Per-Client Outbound Queue
+//!
+//! A single-slot queue between the broadcast loop and a client's socket
+//! writer. When the socket can't keep up, newer frames overwrite the one
+//! still waiting to be written, so a slow client always receives the
+//! freshest state instead of working through a growing backlog.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// State shared between the producer and consumer halves
+struct Shared<T> {
+    /// Latest value not yet taken by the writer
+    slot: Mutex<Option<T>>,
+
+    /// Wakes the writer when a value is pushed or the queue closes
+    notify: Notify,
+
+    /// Number of values overwritten before they were written
+    dropped: AtomicU64,
+
+    /// Set once the producer half has been dropped
+    closed: AtomicBool,
+}
+
+/// Producer half of a coalescing queue
+pub struct CoalescingSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Consumer half of a coalescing queue
+pub struct CoalescingReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Create a coalescing single-slot queue
+pub fn coalescing_queue<T>() -> (CoalescingSender<T>, CoalescingReceiver<T>) {
+    let shared = Arc::new(Shared {
+        slot: Mutex::new(None),
+        notify: Notify::new(),
+        dropped: AtomicU64::new(0),
+        closed: AtomicBool::new(false),
+    });
+
+    (
+        CoalescingSender { shared: shared.clone() },
+        CoalescingReceiver { shared },
+    )
+}
+
+impl<T> CoalescingSender<T> {
+    /// Queue a value, replacing any value the writer hasn't taken yet
+    ///
+    /// Returns `true` if an older pending value was dropped.
+    pub fn push(&self, value: T) -> bool {
+        let replaced = self
+            .shared
+            .slot
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(value)
+            .is_some();
+
+        if replaced {
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        self.shared.notify.notify_one();
+        replaced
+    }
+
+    /// Whether a value is waiting for the consumer, so the next push will
+    /// replace it
+    ///
+    /// With a single producer this can only turn false before that push,
+    /// never true.
+    pub fn is_pending(&self) -> bool {
+        self.shared.slot.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+
+    /// Total number of values dropped in favor of newer ones
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Drop for CoalescingSender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.notify.notify_one();
+    }
+}
+
+impl<T> CoalescingReceiver<T> {
+    /// Wait for the latest queued value
+    ///
+    /// Returns `None` once the sender is dropped and nothing is pending.
+    pub async fn recv(&mut self) -> Option<T> {
+        loop {
+            if let Some(value) = self.try_recv() {
+                return Some(value);
+            }
+            if self.shared.closed.load(Ordering::Acquire) {
+                // A final push may have raced with the close
+                return self.try_recv();
+            }
+            self.shared.notify.notified().await;
+        }
+    }
+
+    /// Take the pending value, if any, without waiting
+    pub fn try_recv(&self) -> Option<T> {
+        self.shared
+            .slot
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+    use tokio_test::{assert_pending, assert_ready_eq, task};
+
+    #[test]
+    fn test_push_replaces_pending_value() {
+        let (tx, mut rx) = coalescing_queue();
+        assert!(!tx.is_pending());
+        assert!(!tx.push(1));
+        assert!(tx.is_pending());
+        assert!(tx.push(2));
+        assert!(tx.push(3));
+        assert_eq!(tx.dropped(), 2);
+
+        let mut recv = task::spawn(rx.recv());
+        assert_ready_eq!(recv.poll(), Some(3));
+    }
+
+    #[test]
+    fn test_recv_waits_for_push_and_ends_on_close() {
+        let (tx, mut rx) = coalescing_queue();
+        {
+            let mut recv = task::spawn(rx.recv());
+            assert_pending!(recv.poll());
+            tx.push(7);
+            assert!(recv.is_woken());
+            assert_ready_eq!(recv.poll(), Some(7));
+        }
+
+        // A value pushed just before closing is still delivered
+        tx.push(8);
+        drop(tx);
+        assert_ready_eq!(task::spawn(rx.recv()).poll(), Some(8));
+        assert_ready_eq!(task::spawn(rx.recv()).poll(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_writer_receives_newest_frames() {
+        let (tx, mut rx) = coalescing_queue();
+
+        // Writer takes 50 ms per frame; frames arrive every 10 ms
+        let writer = tokio::spawn(async move {
+            let mut written = Vec::new();
+            while let Some(frame) = rx.recv().await {
+                written.push(frame);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            written
+        });
+        for frame in 1..=100u32 {
+            tx.push(frame);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let dropped = tx.dropped();
+        drop(tx);
+        let written = writer.await.unwrap();
+
+        assert!(written.len() < 30, "slow writer wrote {} of 100 frames", written.len());
+        assert!(written.windows(2).all(|pair| pair[0] < pair[1]), "frames out of order: {written:?}");
+        assert_eq!(written.last(), Some(&100));
+        assert_eq!(written.len() as u64 + dropped, 100);
+    }
+}
@@ -0,0 +1,155 @@
+//! Output Precision Reduction
+//!
+//! Lossy rounding of floating point fields at serialization time for
+//! bandwidth-constrained links. Internal state keeps full f64 precision;
+//! only the JSON sent to clients is quantized.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Minimum decimals for latitude/longitude (1e-5° ≈ 1.1 m)
+pub const MIN_LAT_LON_DECIMALS: u32 = 5;
+
+/// Number of decimals kept per field category in serialized frames
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct OutputPrecision {
+    /// Decimals for orientation quaternion components and Euler angles
+    pub angle_decimals: u32,
+
+    /// Decimals for latitude and longitude (at least `MIN_LAT_LON_DECIMALS`)
+    pub lat_lon_decimals: u32,
+
+    /// Decimals for every other floating point field
+    pub default_decimals: u32,
+}
+
+impl Default for OutputPrecision {
+    fn default() -> Self {
+        Self {
+            angle_decimals: 4,
+            lat_lon_decimals: 7, // ~1 cm
+            default_decimals: 3,
+        }
+    }
+}
+
+impl OutputPrecision {
+    /// Serialize a value to JSON with floats rounded per category
+    pub fn to_json<T: Serialize>(&self, value: &T) -> serde_json::Result<String> {
+        let mut json = serde_json::to_value(value)?;
+        self.quantize(&mut json);
+        serde_json::to_string(&json)
+    }
+
+    /// Round floats in a serialized frame in place
+    ///
+    /// `orientation` and `euler_degrees` use angle precision, the first two
+    /// elements of `position` and raw GPS `latitude`/`longitude` use lat/lon
+    /// precision, and all other floats use the default. Integer values are
+    /// never touched.
+    pub fn quantize(&self, json: &mut Value) {
+        let lat_lon = self.lat_lon_decimals.max(MIN_LAT_LON_DECIMALS);
+
+        let Value::Object(fields) = json else {
+            round_all(json, self.default_decimals);
+            return;
+        };
+
+        for (key, field) in fields.iter_mut() {
+            match key.as_str() {
+                "orientation" | "euler_degrees" => round_all(field, self.angle_decimals),
+                "latitude" | "longitude" => round_all(field, lat_lon),
+                "position" => match field {
+                    Value::Array(items) => {
+                        for (i, item) in items.iter_mut().enumerate() {
+                            let decimals = if i < 2 { lat_lon } else { self.default_decimals };
+                            round_all(item, decimals);
+                        }
+                    }
+                    other => round_all(other, self.default_decimals),
+                },
+                _ => round_all(field, self.default_decimals),
+            }
+        }
+    }
+}
+
+/// Recursively round every float in a JSON value
+fn round_all(value: &mut Value, decimals: u32) {
+    match value {
+        Value::Number(n) if n.is_f64() => {
+            if let Some(rounded) = n
+                .as_f64()
+                .map(|v| round_to(v, decimals))
+                .and_then(serde_json::Number::from_f64)
+            {
+                *n = rounded;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| round_all(v, decimals)),
+        Value::Object(fields) => fields.values_mut().for_each(|v| round_all(v, decimals)),
+        _ => {}
+    }
+}
+
+/// Round to a fixed number of decimal places
+fn round_to(value: f64, decimals: u32) -> f64 {
+    let scale = 10f64.powi(decimals.min(15) as i32);
+    (value * scale).round() / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{FusedSensorData, Quaternion, Vec3};
+    use pretty_assertions::assert_eq;
+
+    fn frame() -> FusedSensorData {
+        FusedSensorData {
+            orientation: Quaternion::new(0.9238795325112867, 0.0, 0.0, 0.3826834323650898),
+            euler_degrees: (1.23456789, -2.3456789, 45.000000001),
+            position: (37.774912345678, -122.419412345678, 12.3456789),
+            velocity: Vec3::new(1.23456789, 0.0, -0.987654321),
+            ..FusedSensorData::default()
+        }
+    }
+
+    #[test]
+    fn test_serialized_frame_has_configured_precision() {
+        let precision = OutputPrecision {
+            angle_decimals: 3,
+            lat_lon_decimals: 6,
+            default_decimals: 2,
+        };
+        let original = frame();
+        let json = precision.to_json(&original).unwrap();
+
+        assert!(json.contains(r#""w":0.924,"#), "{json}");
+        assert!(json.contains(r#""z":0.383}"#), "{json}");
+        assert!(json.contains("[1.235,-2.346,45.0]"), "{json}");
+        assert!(json.contains("[37.774912,-122.419412,12.35]"), "{json}");
+        assert!(json.contains(r#""velocity":{"x":1.23,"y":0.0,"z":-0.99}"#), "{json}");
+
+        // Only the output is rounded
+        assert_eq!(original.euler_degrees, frame().euler_degrees);
+        assert_eq!(original.orientation.w, 0.9238795325112867);
+        assert_eq!(original.position, frame().position);
+    }
+
+    #[test]
+    fn test_lat_lon_precision_has_a_floor() {
+        let precision = OutputPrecision {
+            lat_lon_decimals: 1,
+            ..OutputPrecision::default()
+        };
+        let json = precision.to_json(&frame()).unwrap();
+        assert!(json.contains("[37.77491,-122.41941,"), "{json}");
+    }
+
+    #[test]
+    fn test_integers_are_untouched() {
+        let mut json = serde_json::json!({"status_flags": 136, "confidence": 0.987654});
+        OutputPrecision { default_decimals: 0, ..OutputPrecision::default() }.quantize(&mut json);
+        assert_eq!(json, serde_json::json!({"status_flags": 136, "confidence": 1.0}));
+    }
+}
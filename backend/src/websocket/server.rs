@@ -4,16 +4,42 @@
 //! to all connected clients in real-time and handles fault injection commands
 //! and anomaly score updates from ML services.
 
-use anyhow::{Result, Context};
 use futures_util::{StreamExt, SinkExt, stream::SplitStream};
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::broadcast;
-use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
+use serde::Deserialize;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, watch};
+use tokio_tungstenite::tungstenite::handshake::server::{Callback, ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message, WebSocketStream};
 use tracing::{info, warn, error, debug};
 use std::sync::Arc;
 use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
 
-use crate::models::FusedSensorData;
+use crate::error::SensorFusionError;
+use crate::fusion::FilterKind;
+use crate::fusion::health::HealthLog;
+use crate::models::{timestamp, FusedSensorData, GpsData, ImuData, TimestampFormat, SCHEMA_VERSION};
+use crate::sensors::external::{
+    validate_gps, validate_imu, ExternalDataError, ExternalLimits, ExternalSample, SampleRateLimiter,
+    MIN_GPS_INTERVAL, MIN_IMU_INTERVAL,
+};
+use super::outbound::{coalescing_queue, CoalescingSender};
+use super::bandwidth::BandwidthStats;
+use super::origin::OriginPolicy;
+use super::precision::OutputPrecision;
+use super::history::FrameHistory;
+use super::metadata::{FrameMetadata, MetadataConfig};
+use super::commands::{CommandLog, ControlCommand, DEFAULT_COMMAND_HISTORY};
+use super::connections::{CombinedGuard, ConnectionStats};
+use super::http::{peek_request_head, handle_http_request};
+use super::client::{pretty_print, AccelUnits, ChangeThresholds, ClientEncoder, ClientSettings, CoordinateMode, DeliveryMode, DeltaSettings, FrameConvention, FrameFormat, OrientationFormat, StreamType, WireType};
+
+/// How long a shutdown waits for open connections to close before
+/// dropping them
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(2);
 
 /// WebSocket server for broadcasting sensor data and receiving commands
 pub struct WebSocketServer {
@@ -23,11 +49,230 @@ pub struct WebSocketServer {
     /// Broadcast sender for distributing sensor data
     sensor_tx: Arc<broadcast::Sender<FusedSensorData>>,
     
+    /// Latest fused frame for latest-only clients
+    latest_rx: watch::Receiver<FusedSensorData>,
+    
     /// Command sender for fault injection
-    cmd_tx: Arc<tokio::sync::mpsc::UnboundedSender<String>>,
+    cmd_tx: Arc<tokio::sync::mpsc::UnboundedSender<ControlCommand>>,
     
     /// Shared anomaly score state (updated by ML service)
     anomaly_score: Arc<tokio::sync::RwLock<Option<f64>>>,
+    
+    /// Optional float rounding applied to outgoing frames
+    output_precision: Option<OutputPrecision>,
+    
+    /// Append a CRC-32 integrity checksum to outgoing frames
+    frame_checksums: bool,
+    
+    /// Timestamp format clients start with
+    timestamp_format: TimestampFormat,
+    
+    /// Frame IDs and settings behind the coordinate metadata
+    metadata: Arc<MetadataConfig>,
+    
+    /// Origins accepted during the WebSocket handshake
+    origin_policy: Arc<OriginPolicy>,
+    
+    /// Server-pushed messages (analysis results) for streaming clients
+    notices: Option<broadcast::Sender<Arc<str>>>,
+    
+    /// Interval between server-initiated pings (disabled when unset)
+    keepalive: Option<std::time::Duration>,
+    
+    /// Frame history backing the HTTP long-poll endpoint (disabled when unset)
+    history: Option<Arc<FrameHistory>>,
+    
+    /// Maximum time a long-poll request waits for new frames
+    poll_timeout: std::time::Duration,
+    
+    /// Health event log served to `health_log` requests
+    health_log: Option<Arc<HealthLog>>,
+    
+    /// Recent commands served to `command_history` requests
+    command_log: Arc<CommandLog>,
+    
+    /// Connected WebSocket client counts, if tracked
+    connection_stats: Option<Arc<ConnectionStats>>,
+    
+    /// Accepts pushed sensor readings from authenticated sources
+    sensor_source: Option<SensorSource>,
+    
+    /// Bounds pushed readings must stay within
+    external_limits: ExternalLimits,
+    
+    /// Filters `set_filter` refuses, with the reason sent back
+    unavailable_filters: Arc<Vec<(FilterKind, String)>>,
+    
+    /// Configuration served to `get_config` requests
+    config: Option<watch::Receiver<serde_json::Value>>,
+    
+    /// Turns true when the server should stop, if shutdown is signalled
+    shutdown: Option<watch::Receiver<bool>>,
+    
+    /// Unix domain socket to listen on instead of the TCP port
+    #[cfg(unix)]
+    unix_socket: Option<PathBuf>,
+}
+
+/// Where authenticated sensor sources deliver pushed readings
+#[derive(Clone)]
+struct SensorSource {
+    /// Shared secret a client must present to push readings
+    token: Arc<str>,
+    
+    /// Channel to the fusion loop
+    samples: tokio::sync::mpsc::Sender<ExternalSample>,
+}
+
+/// Per-connection state of a client pushing sensor readings
+struct SourceSession {
+    /// Client presented the correct token
+    authenticated: bool,
+    
+    /// Rate limit for pushed IMU readings
+    imu_limiter: SampleRateLimiter,
+    
+    /// Rate limit for pushed GPS readings
+    gps_limiter: SampleRateLimiter,
+}
+
+impl SourceSession {
+    fn new() -> Self {
+        Self {
+            authenticated: false,
+            imu_limiter: SampleRateLimiter::new(MIN_IMU_INTERVAL),
+            gps_limiter: SampleRateLimiter::new(MIN_GPS_INTERVAL),
+        }
+    }
+}
+
+/// Role of a connection, chosen by the path it connected to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Endpoint {
+    /// Any other path: frames plus every client message (legacy behaviour)
+    Full,
+    
+    /// `/stream`: read-only subscribers; control messages are refused
+    Stream,
+    
+    /// `/control`: command senders; no frames are streamed
+    Control,
+}
+
+impl Endpoint {
+    /// Route a request path to an endpoint
+    fn from_path(path: &str) -> Self {
+        match path.trim_end_matches('/') {
+            "/stream" => Endpoint::Stream,
+            "/control" => Endpoint::Control,
+            _ => Endpoint::Full,
+        }
+    }
+    
+    /// Whether fused frames are sent to this connection
+    fn streams(self) -> bool {
+        self != Endpoint::Control
+    }
+    
+    /// Whether this connection may change system state
+    fn accepts_control(self) -> bool {
+        self != Endpoint::Stream
+    }
+}
+
+/// Who is at the other end of a connection, for logs and the command log
+#[derive(Debug, Clone, Copy)]
+enum Peer {
+    /// TCP client address
+    Tcp(SocketAddr),
+    
+    /// Unix socket client; these are usually unnamed, so they are
+    /// numbered in the order they connected
+    #[cfg(unix)]
+    Unix(u64),
+}
+
+impl std::fmt::Display for Peer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Peer::Tcp(addr) => addr.fmt(f),
+            #[cfg(unix)]
+            Peer::Unix(id) => write!(f, "unix#{}", id),
+        }
+    }
+}
+
+/// Everything a new connection is served with, taken from the server
+struct ConnectionSetup {
+    /// Where its frames and notices come from
+    sources: FrameSources,
+    
+    /// State its messages act on
+    context: MessageContext,
+    
+    /// Frame encoder with the server's defaults
+    encoder: ClientEncoder,
+    
+    /// Ping interval, if enabled
+    keepalive: Option<std::time::Duration>,
+    
+    /// Origins accepted during the handshake
+    origin_policy: Arc<OriginPolicy>,
+    
+    /// Connected client counts, if tracked
+    connection_stats: Option<Arc<ConnectionStats>>,
+}
+
+/// Where a connection's outgoing frames and notices come from
+struct FrameSources {
+    /// Every fused frame
+    sensor_tx: Arc<broadcast::Sender<FusedSensorData>>,
+    
+    /// Latest fused frame for latest-only delivery
+    latest_rx: watch::Receiver<FusedSensorData>,
+    
+    /// Server-pushed messages, if enabled
+    notices_rx: Option<broadcast::Receiver<Arc<str>>>,
+    
+    /// Server shutdown signal, which ends the stream
+    shutdown: Option<watch::Receiver<bool>>,
+}
+
+/// Shared state that client messages act on
+#[derive(Clone)]
+struct MessageContext {
+    /// Command sender for fault injection and simulation control
+    cmd_tx: Arc<tokio::sync::mpsc::UnboundedSender<ControlCommand>>,
+    
+    /// Shared anomaly score state (updated by ML service)
+    anomaly_score: Arc<tokio::sync::RwLock<Option<f64>>>,
+    
+    /// Health event log, if enabled
+    health_log: Option<Arc<HealthLog>>,
+    
+    /// Recent commands
+    command_log: Arc<CommandLog>,
+    
+    /// External sensor input, if enabled
+    sensor_source: Option<SensorSource>,
+    
+    /// Bounds pushed readings must stay within
+    external_limits: ExternalLimits,
+    
+    /// Filters `set_filter` refuses, with the reason sent back
+    unavailable_filters: Arc<Vec<(FilterKind, String)>>,
+    
+    /// Running configuration, if provided
+    config: Option<watch::Receiver<serde_json::Value>>,
+    
+    /// Inputs to the coordinate metadata served to `get_metadata` requests
+    metadata: Arc<MetadataConfig>,
+    
+    /// Recent frames for `query_at` requests, if kept
+    history: Option<Arc<FrameHistory>>,
+    
+    /// Path-based role of this connection
+    endpoint: Endpoint,
 }
 
 impl WebSocketServer {
@@ -36,42 +281,244 @@ impl WebSocketServer {
     /// # Arguments
     /// * `port` - Port number to bind to
     /// * `sensor_tx` - Broadcast channel sender for sensor data
+    /// * `latest_rx` - Watch channel holding the most recent fused frame
     /// * `cmd_tx` - Command channel sender for fault injection
     /// * `anomaly_score` - Shared state for anomaly scores from ML service
     pub fn new(
         port: u16,
         sensor_tx: Arc<broadcast::Sender<FusedSensorData>>,
-        cmd_tx: Arc<tokio::sync::mpsc::UnboundedSender<String>>,
+        latest_rx: watch::Receiver<FusedSensorData>,
+        cmd_tx: Arc<tokio::sync::mpsc::UnboundedSender<ControlCommand>>,
         anomaly_score: Arc<tokio::sync::RwLock<Option<f64>>>,
     ) -> Self {
-        Self { port, sensor_tx, cmd_tx, anomaly_score }
+        Self {
+            port,
+            sensor_tx,
+            latest_rx,
+            cmd_tx,
+            anomaly_score,
+            output_precision: None,
+            frame_checksums: false,
+            timestamp_format: TimestampFormat::Rfc3339,
+            metadata: Arc::default(),
+            origin_policy: Arc::new(OriginPolicy::default()),
+            notices: None,
+            keepalive: None,
+            history: None,
+            poll_timeout: std::time::Duration::from_secs(10),
+            health_log: None,
+            command_log: Arc::new(CommandLog::new(DEFAULT_COMMAND_HISTORY)),
+            connection_stats: None,
+            sensor_source: None,
+            external_limits: ExternalLimits::default(),
+            unavailable_filters: Arc::default(),
+            config: None,
+            shutdown: None,
+            #[cfg(unix)]
+            unix_socket: None,
+        }
+    }
+
+    /// Accept `imu_data`/`gps_data` pushes from clients that authenticate
+    /// with `{"type": "sensor_source", "token": ...}`
+    /// 
+    /// # Arguments
+    /// * `token` - Shared secret sensor sources must present
+    /// * `samples` - Channel to the fusion loop; readings are dropped when full
+    pub fn with_sensor_source(mut self, token: impl Into<String>, samples: tokio::sync::mpsc::Sender<ExternalSample>) -> Self {
+        self.sensor_source = Some(SensorSource {
+            token: token.into().into(),
+            samples,
+        });
+        self
+    }
+
+    /// Reject pushed readings outside these bounds (see [`ExternalLimits`])
+    pub fn with_external_limits(mut self, limits: ExternalLimits) -> Self {
+        self.external_limits = limits;
+        self
+    }
+
+    /// Refuse `set_filter` requests for `kind`, replying with `reason`
+    /// (e.g. configured settings that filter can't honor)
+    pub fn with_unavailable_filter(mut self, kind: FilterKind, reason: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.unavailable_filters).push((kind, reason.into()));
+        self
+    }
+
+    /// Answer `{"type": "health_log"}` requests from this log
+    pub fn with_health_log(mut self, health_log: Arc<HealthLog>) -> Self {
+        self.health_log = Some(health_log);
+        self
+    }
+
+    /// Answer `{"type": "get_config"}` requests with the configuration
+    /// `config` currently holds
+    /// 
+    /// The sender updates it when settings change at runtime, so replies
+    /// show what is in effect. The value is sent as-is, so secrets must
+    /// already be redacted.
+    pub fn with_config(mut self, config: watch::Receiver<serde_json::Value>) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Count connected WebSocket clients (current, peak, and total) in `stats`
+    pub fn with_connection_stats(mut self, stats: Arc<ConnectionStats>) -> Self {
+        self.connection_stats = Some(stats);
+        self
+    }
+
+    /// Keep the last `capacity` commands for `command_history` requests
+    pub fn with_command_history(mut self, capacity: usize) -> Self {
+        self.command_log = Arc::new(CommandLog::new(capacity));
+        self
+    }
+
+    /// Round floats in outgoing frames to the given precision
+    pub fn with_output_precision(mut self, precision: OutputPrecision) -> Self {
+        self.output_precision = Some(precision);
+        self
+    }
+
+    /// Append a `checksum` field (CRC-32) to every outgoing frame
+    /// 
+    /// See [`super::checksum`] for exactly which bytes are covered.
+    pub fn with_frame_checksums(mut self, enabled: bool) -> Self {
+        self.frame_checksums = enabled;
+        self
+    }
+
+    /// Write frame timestamps in this format unless a client chooses
+    /// another with `set_timestamp_format`
+    pub fn with_timestamp_format(mut self, format: TimestampFormat) -> Self {
+        self.timestamp_format = format;
+        self
+    }
+
+    /// Describe frames with these frame IDs and acceleration frame in the
+    /// coordinate metadata (see [`super::metadata`])
+    pub fn with_metadata(mut self, metadata: MetadataConfig) -> Self {
+        self.metadata = Arc::new(metadata);
+        self
+    }
+
+    /// Reject WebSocket handshakes whose `Origin` the policy doesn't permit
+    pub fn with_origin_policy(mut self, policy: OriginPolicy) -> Self {
+        self.origin_policy = Arc::new(policy);
+        self
+    }
+
+    /// Forward messages published on this channel to every streaming client
+    /// 
+    /// Messages are pre-serialized JSON and are sent like replies, so they
+    /// are never coalesced away by a slow client's frame queue.
+    pub fn with_notices(mut self, notices: broadcast::Sender<Arc<str>>) -> Self {
+        self.notices = Some(notices);
+        self
+    }
+
+    /// Ping every connection at this interval, whether or not frames flow
+    /// 
+    /// Keeps NAT and proxy mappings alive for idle, low-rate, or on-change
+    /// connections. Pongs are not required; a connection is only dropped
+    /// when the ping can't be written.
+    pub fn with_keepalive(mut self, interval: std::time::Duration) -> Self {
+        self.keepalive = Some(interval).filter(|i| !i.is_zero());
+        self
+    }
+
+    /// Shut down once `shutdown` turns true
+    /// 
+    /// The server stops accepting, and each open connection gets its
+    /// session summary and a Close frame; `run` returns once they have
+    /// closed or `SHUTDOWN_GRACE` has passed.
+    pub fn with_shutdown(mut self, shutdown: watch::Receiver<bool>) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Listen on a Unix domain socket at `path` instead of the TCP port
+    /// 
+    /// For clients on the same host (e.g. the ML service), which then skip
+    /// the TCP stack and need no free port. A socket file left at `path` by
+    /// an earlier run is replaced. The HTTP long-poll endpoint is only
+    /// served over TCP.
+    #[cfg(unix)]
+    pub fn with_unix_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.unix_socket = Some(path.into());
+        self
+    }
+
+    /// Enable the HTTP long-poll fallback (`GET /poll?since=<seq>`)
+    /// 
+    /// # Arguments
+    /// * `capacity` - Number of recent frames kept for pollers
+    /// * `timeout` - Maximum time a poll waits for new frames
+    pub fn with_long_poll(mut self, capacity: usize, timeout: std::time::Duration) -> Self {
+        self.history = Some(Arc::new(FrameHistory::new(capacity)));
+        self.poll_timeout = timeout;
+        self
     }
 
     /// Start the WebSocket server and accept connections
-    pub async fn run(self) -> Result<()> {
+    /// 
+    /// Returns `SensorFusionError::Bind` if the port (or Unix socket) can't
+    /// be bound; otherwise runs until shut down (see `with_shutdown`) or
+    /// cancelled.
+    pub async fn run(self) -> Result<(), SensorFusionError> {
+        #[cfg(unix)]
+        if let Some(path) = self.unix_socket.clone() {
+            return self.run_unix(path).await;
+        }
+        
         let addr = format!("127.0.0.1:{}", self.port);
         let listener = TcpListener::bind(&addr)
             .await
-            .context(format!("Failed to bind to {}", addr))?;
+            .map_err(|source| SensorFusionError::Bind { addr: addr.clone(), source })?;
+        // Port 0 binds whatever port is free
+        let addr = listener.local_addr().map_or(addr, |local| local.to_string());
         
         info!("🌐 WebSocket server listening on {}", addr);
+        if self.history.is_some() {
+            info!("📮 HTTP long-poll available at http://{}/poll", addr);
+        }
+        self.record_history();
 
+        let mut shutdown = self.shutdown.clone();
+        let mut connections = tokio::task::JoinSet::new();
         loop {
-            match listener.accept().await {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = shutdown_signalled(&mut shutdown) => break,
+            };
+            while connections.try_join_next().is_some() {}
+            match accepted {
                 Ok((stream, peer_addr)) => {
-                    info!("🔌 New connection from {}", peer_addr);
-                    
-                    // Clone channels and state for this connection
-                    let sensor_tx = self.sensor_tx.clone();
-                    let cmd_tx = self.cmd_tx.clone();
-                    let anomaly_score = self.anomaly_score.clone();
+                    let peer = Peer::Tcp(peer_addr);
+                    info!("🔌 New connection from {}", peer);
+                    let setup = self.connection_setup();
+                    let history = self.history.clone();
+                    let poll_timeout = self.poll_timeout;
                     
                     // Spawn a task to handle this client connection
-                    tokio::spawn(async move {
-                        if let Err(e) = handle_connection(stream, peer_addr, sensor_tx, cmd_tx, anomaly_score).await {
-                            warn!("⚠️  Connection error for {}: {}", peer_addr, e);
+                    connections.spawn(async move {
+                        // Plain HTTP requests (long-poll) share the port with WebSocket
+                        let head = match peek_request_head(&stream).await {
+                            Ok(head) => head,
+                            Err(e) => {
+                                debug!("Dropping {} before handshake: {}", peer, e);
+                                return;
+                            }
+                        };
+                        if !head.is_websocket_upgrade() {
+                            debug!("🌐 HTTP {} {} from {}", head.method, head.path, peer);
+                            if let Err(e) = handle_http_request(stream, head, history.as_deref(), poll_timeout).await {
+                                debug!("HTTP error for {}: {}", peer, e);
+                            }
+                            return;
                         }
-                        info!("👋 Client {} disconnected", peer_addr);
+                        serve_websocket(stream, peer, setup).await;
                     });
                 }
                 Err(e) => {
@@ -79,71 +526,341 @@ impl WebSocketServer {
                 }
             }
         }
+        
+        finish_connections(connections).await;
+        Ok(())
+    }
+
+    /// Accept WebSocket connections on the Unix socket at `path`
+    #[cfg(unix)]
+    async fn run_unix(self, path: PathBuf) -> Result<(), SensorFusionError> {
+        use std::os::unix::fs::FileTypeExt;
+
+        // A socket left behind by an earlier run would make the bind fail
+        if std::fs::symlink_metadata(&path).is_ok_and(|meta| meta.file_type().is_socket()) {
+            let _ = std::fs::remove_file(&path);
+        }
+        let listener = tokio::net::UnixListener::bind(&path)
+            .map_err(|source| SensorFusionError::Bind { addr: path.display().to_string(), source })?;
+        
+        info!("🌐 WebSocket server listening on unix:{}", path.display());
+        if self.history.is_some() {
+            warn!("⚠️  HTTP long-poll is only served over TCP; unavailable on the Unix socket");
+        }
+        self.record_history();
+
+        let mut shutdown = self.shutdown.clone();
+        let mut connections = tokio::task::JoinSet::new();
+        let mut connected = 0;
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = shutdown_signalled(&mut shutdown) => break,
+            };
+            while connections.try_join_next().is_some() {}
+            match accepted {
+                Ok((stream, _)) => {
+                    connected += 1;
+                    let peer = Peer::Unix(connected);
+                    info!("🔌 New connection from {}", peer);
+                    connections.spawn(serve_websocket(stream, peer, self.connection_setup()));
+                }
+                Err(e) => {
+                    error!("❌ Failed to accept connection: {}", e);
+                }
+            }
+        }
+        
+        finish_connections(connections).await;
+        let _ = std::fs::remove_file(&path);
+        Ok(())
+    }
+
+    /// Record broadcast frames for long-poll and `query_at` clients
+    fn record_history(&self) {
+        let Some(history) = self.history.clone() else {
+            return;
+        };
+        let mut history_rx = self.sensor_tx.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match history_rx.recv().await {
+                    Ok(frame) => {
+                        history.push(frame);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("⚠️  Frame history lagged, skipped {} messages", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Channels and state for a new connection
+    fn connection_setup(&self) -> ConnectionSetup {
+        ConnectionSetup {
+            sources: FrameSources {
+                sensor_tx: self.sensor_tx.clone(),
+                latest_rx: self.latest_rx.clone(),
+                notices_rx: self.notices.as_ref().map(|tx| tx.subscribe()),
+                shutdown: self.shutdown.clone(),
+            },
+            context: MessageContext {
+                cmd_tx: self.cmd_tx.clone(),
+                anomaly_score: self.anomaly_score.clone(),
+                health_log: self.health_log.clone(),
+                command_log: self.command_log.clone(),
+                sensor_source: self.sensor_source.clone(),
+                external_limits: self.external_limits,
+                unavailable_filters: self.unavailable_filters.clone(),
+                config: self.config.clone(),
+                metadata: self.metadata.clone(),
+                history: self.history.clone(),
+                endpoint: Endpoint::Full,
+            },
+            encoder: ClientEncoder::new(self.output_precision)
+                .with_checksums(self.frame_checksums)
+                .with_timestamp_format(self.timestamp_format)
+                .with_metadata(self.metadata.clone()),
+            keepalive: self.keepalive,
+            origin_policy: self.origin_policy.clone(),
+            connection_stats: self.connection_stats.clone(),
+        }
     }
 }
 
-/// Handle an individual WebSocket connection
-async fn handle_connection(
-    stream: TcpStream,
-    peer_addr: SocketAddr,
-    sensor_tx: Arc<broadcast::Sender<FusedSensorData>>,
-    cmd_tx: Arc<tokio::sync::mpsc::UnboundedSender<String>>,
-    anomaly_score: Arc<tokio::sync::RwLock<Option<f64>>>,
-) -> Result<()> {
-    // Upgrade TCP connection to WebSocket
-    let ws_stream = accept_async(stream)
+/// Complete the WebSocket handshake on a new connection and serve it
+/// until it closes
+async fn serve_websocket<S>(stream: S, peer: Peer, setup: ConnectionSetup)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let ConnectionSetup { sources, mut context, encoder, keepalive, origin_policy, connection_stats } = setup;
+    let (ws_stream, path) = match accept_websocket(stream, &origin_policy).await {
+        Ok(accepted) => accepted,
+        Err(e) => {
+            warn!("⚠️  Handshake failed for {}: {}", peer, e);
+            return;
+        }
+    };
+    context.endpoint = Endpoint::from_path(&path);
+    debug!("✅ WebSocket handshake completed for {}", peer);
+    let _counted = connection_stats.as_ref().map(|stats| stats.connect());
+    
+    if let Err(e) = handle_connection(ws_stream, peer, sources, context, encoder, keepalive, connection_stats).await {
+        warn!("⚠️  Connection error for {}: {}", peer, e);
+    }
+    info!("👋 Client {} disconnected", peer);
+}
+
+/// Upgrade a connection to WebSocket, enforcing the origin policy
+/// 
+/// Disallowed origins are answered with `403 Forbidden`. Also returns the
+/// request path, which selects the endpoint.
+async fn accept_websocket<S>(
+    stream: S,
+    origin_policy: &OriginPolicy,
+) -> Result<(WebSocketStream<S>, String), SensorFusionError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut path = String::new();
+    let ws_stream = accept_hdr_async(stream, OriginCheck { policy: origin_policy, path: &mut path })
         .await
-        .context("Failed to accept WebSocket handshake")?;
+        .map_err(|e| SensorFusionError::Handshake(Box::new(e)))?;
+    Ok((ws_stream, path))
+}
+
+/// Handshake callback applying an origin policy and noting the request path
+struct OriginCheck<'a> {
+    /// Origins accepted
+    policy: &'a OriginPolicy,
     
-    debug!("✅ WebSocket handshake completed for {}", peer_addr);
+    /// Where the request path is stored
+    path: &'a mut String,
+}
+
+impl Callback for OriginCheck<'_> {
+    fn on_request(self, request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+        *self.path = request.uri().path().to_string();
+        let origin = request.headers().get("origin").map(|v| v.to_str().unwrap_or_default());
+        if self.policy.permits(origin) {
+            return Ok(response);
+        }
+        // The rejected origin is client-controlled, so it's logged rather
+        // than echoed back
+        warn!("🚫 Rejected handshake from origin {:?}", origin.unwrap_or("<none>"));
+        let mut rejection = ErrorResponse::new(Some("origin not allowed".to_string()));
+        *rejection.status_mut() = StatusCode::FORBIDDEN;
+        Err(rejection)
+    }
+}
+
+/// Handle an individual WebSocket connection
+async fn handle_connection<S>(
+    ws_stream: WebSocketStream<S>,
+    peer: Peer,
+    sources: FrameSources,
+    context: MessageContext,
+    mut encoder: ClientEncoder,
+    keepalive: Option<std::time::Duration>,
+    connection_stats: Option<Arc<ConnectionStats>>,
+) -> Result<(), SensorFusionError>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let FrameSources { sensor_tx, mut latest_rx, notices_rx, mut shutdown } = sources;
+    let connected_at = std::time::Instant::now();
     
     // Split the WebSocket into sender and receiver
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
     
-    // Subscribe to sensor data broadcast
-    let mut sensor_rx = sensor_tx.subscribe();
+    // Subscribe to sensor data broadcast (dropped while in latest-only mode,
+    // never taken by control-only connections)
+    let endpoint = context.endpoint;
+    
+    // Latest frame for a new client to start from. The server's receiver is
+    // never read, so its clones only report a change once a frame exists.
+    let snapshot = (endpoint.streams() && latest_rx.has_changed().unwrap_or(false))
+        .then(|| latest_rx.borrow_and_update().clone());
+    
+    let mut sensor_rx = endpoint.streams().then(|| sensor_tx.subscribe());
+    let mut notices_rx = notices_rx.filter(|_| endpoint.streams());
+    
+    // Per-connection settings, changed by client messages
+    let (settings_tx, mut settings_rx) = watch::channel(encoder.settings().clone());
+    // The writer indents replies and notices; frames come indented from the encoder
+    let writer_settings = settings_tx.subscribe();
     
     // Send welcome message
     let welcome_msg = serde_json::json!({
         "type": "connection",
         "status": "connected",
         "message": "Real-Time Sensor Fusion Backend",
+        "schema_version": SCHEMA_VERSION,
+        "metadata": encoder.metadata(),
         "timestamp": chrono::Utc::now().to_rfc3339(),
     });
     
     ws_sender
         .send(Message::Text(welcome_msg.to_string()))
         .await
-        .context("Failed to send welcome message")?;
+        .map_err(|e| SensorFusionError::Transport(Box::new(e)))?;
+    
+    // Replies for the writer; never coalesced away like frames
+    let (writer_tx, mut reply_rx) = tokio::sync::mpsc::unbounded_channel::<Message>();
+    
+    // Replies to client requests pass through this task, so settings
+    // changes made before a request are applied before its reply goes out
+    let (reply_tx, mut request_replies) = tokio::sync::mpsc::unbounded_channel::<Message>();
+    
+    // Server-pushed messages and pings share the reply path
+    let notice_tx = reply_tx.clone();
+    let mut ping_ticker = keepalive.map(|period| {
+        tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+    });
     
     // Spawn task to receive messages from client (e.g., commands, anomaly scores)
     let mut receive_task = tokio::spawn(async move {
-        handle_incoming_messages(&mut ws_receiver, peer_addr, cmd_tx, anomaly_score).await
+        handle_incoming_messages(&mut ws_receiver, peer, context, settings_tx, reply_tx).await
+    });
+    
+    // Outbound queue coalesces to the latest frame so a slow socket write
+    // never holds up this task or builds a backlog of stale frames
+    let (out_tx, mut out_rx) = coalescing_queue::<(Message, bool)>();
+    if let Some(frame) = &snapshot {
+        queue_frame(&out_tx, &writer_tx, &mut encoder, frame);
+    }
+    
+    // Frames this client never got and the plain size of the latest frame,
+    // handed to the writer for the session stats
+    let (skipped_tx, skipped_rx) = tokio::sync::oneshot::channel::<(u64, usize)>();
+    let mut lagged = 0;
+    
+    // Bytes the writer sends, reported periodically when the client asks
+    let bandwidth = Arc::new(BandwidthStats::new());
+    let writer_bandwidth = bandwidth.clone();
+    let mut stats_interval = None;
+    let mut stats_ticker = None;
+    
+    // Counts this client while it takes combined frames, so the fusion
+    // loop gathers the raw readings they nest
+    let mut combined = None;
+    
+    let mut send_task = tokio::spawn(async move {
+        let indent = |text: String| if writer_settings.borrow().pretty { pretty_print(&text).unwrap_or(text) } else { text };
+        let indent_reply = |reply: Message| {
+            let reply = match reply {
+                Message::Text(text) => Message::Text(indent(text)),
+                reply => reply,
+            };
+            writer_bandwidth.record(reply.len());
+            reply
+        };
+        let mut frames_sent: u64 = 0;
+        let mut held_reply = None;
+        loop {
+            let msg = if let Some(reply) = held_reply.take() {
+                indent_reply(reply)
+            } else {
+                tokio::select! {
+                Some(reply) = reply_rx.recv() => match out_rx.try_recv() {
+                    // A frame queued before the reply goes first, so frames
+                    // after a reply are encoded with any settings it confirms
+                    Some((frame, encoded)) => {
+                        frames_sent += 1;
+                        writer_bandwidth.record_frame(frame.len(), encoded);
+                        held_reply = Some(reply);
+                        frame
+                    }
+                    None => indent_reply(reply),
+                },
+                frame = out_rx.recv() => match frame {
+                    Some((frame, encoded)) => {
+                        frames_sent += 1;
+                        writer_bandwidth.record_frame(frame.len(), encoded);
+                        frame
+                    }
+                    None => break,
+                },
+                }
+            };
+            if let Err(e) = ws_sender.send(msg).await {
+                debug!("Failed to send to {}: {}", peer, e);
+                return; // Client disconnected
+            }
+        }
+        
+        // Clean shutdown, with a summary of the session first. Best effort:
+        // once the client has closed its side the summary can't be sent.
+        if let Ok((skipped, plain_frame_len)) = skipped_rx.await {
+            let bandwidth = writer_bandwidth.report(|| plain_frame_len);
+            let stats = serde_json::json!({
+                "type": "session_stats",
+                "frames_sent": frames_sent,
+                "skipped": skipped,
+                "duration_secs": connected_at.elapsed().as_secs_f64(),
+                "bytes_sent": bandwidth.bytes_sent,
+                "frame_bytes": bandwidth.frame_bytes,
+                "raw_frame_bytes": bandwidth.raw_frame_bytes,
+                "compression_ratio": bandwidth.compression_ratio,
+            });
+            let _ = ws_sender.send(Message::Text(indent(stats.to_string()))).await;
+        }
+        let _ = ws_sender.send(Message::Close(None)).await;
     });
     
     // Main loop: broadcast sensor data to this client
     loop {
         tokio::select! {
             // Receive sensor data from broadcast channel
-            result = sensor_rx.recv() => {
+            result = recv_broadcast(&mut sensor_rx) => {
                 match result {
-                    Ok(sensor_data) => {
-                        // Serialize sensor data to JSON
-                        match serde_json::to_string(&sensor_data) {
-                            Ok(json) => {
-                                // Send to client
-                                if let Err(e) = ws_sender.send(Message::Text(json)).await {
-                                    debug!("Failed to send to {}: {}", peer_addr, e);
-                                    break; // Client disconnected
-                                }
-                            }
-                            Err(e) => {
-                                error!("Serialization error: {}", e);
-                            }
-                        }
-                    }
+                    Ok(sensor_data) => queue_frame(&out_tx, &writer_tx, &mut encoder, &sensor_data),
                     Err(broadcast::error::RecvError::Lagged(skipped)) => {
-                        warn!("⚠️  Client {} lagged, skipped {} messages", peer_addr, skipped);
+                        warn!("⚠️  Client {} lagged, skipped {} messages", peer, skipped);
+                        lagged += skipped;
                         // Continue receiving - client is slow but still connected
                     }
                     Err(broadcast::error::RecvError::Closed) => {
@@ -153,53 +870,314 @@ async fn handle_connection(
                 }
             }
             
+            // Server-pushed messages (analysis results)
+            result = recv_broadcast(&mut notices_rx) => {
+                match result {
+                    Ok(notice) => {
+                        let wanted = encoder.settings().subscriptions.is_none()
+                            || StreamType::of_notice(&notice).is_some_and(|kind| encoder.settings().subscribed(kind));
+                        if wanted {
+                            let _ = notice_tx.send(Message::Text(notice.to_string()));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!("Client {} missed {} notices", peer, skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => notices_rx = None,
+                }
+            }
+            
+            // Keepalive pings, independent of frame traffic
+            _ = tick_or_pending(&mut ping_ticker) => {
+                let _ = notice_tx.send(Message::Ping(Vec::new()));
+            }
+            
+            // Bandwidth reports, when the client asked for them
+            _ = tick_or_pending(&mut stats_ticker) => {
+                let report = bandwidth.report(|| encoder.plain_len(&latest_rx.borrow()));
+                let mut report = serde_json::to_value(report).unwrap_or_default();
+                report["type"] = "bandwidth".into();
+                let _ = notice_tx.send(Message::Text(report.to_string()));
+            }
+            
+            // Latest-only clients read the watch channel, which never lags
+            result = latest_rx.changed(), if sensor_rx.is_none() && endpoint.streams() => {
+                if result.is_err() {
+                    info!("📡 Latest-frame channel closed");
+                    break;
+                }
+                let sensor_data = latest_rx.borrow_and_update().clone();
+                queue_frame(&out_tx, &writer_tx, &mut encoder, &sensor_data);
+            }
+            
+            // Apply settings changes requested by the client
+            Ok(()) = settings_rx.changed() => {
+                let settings = settings_rx.borrow_and_update().clone();
+                apply_settings(settings, &mut encoder, &mut sensor_rx, &mut latest_rx, &sensor_tx, endpoint, peer);
+                restart_stats_ticker(encoder.settings().stats_interval, &mut stats_interval, &mut stats_ticker);
+                count_combined(encoder.settings().frame_format, connection_stats.as_ref(), &mut combined);
+            }
+            
+            // Replies to the client's requests, after the settings changes
+            // it asked for earlier
+            Some(reply) = request_replies.recv() => {
+                if settings_rx.has_changed().unwrap_or(false) {
+                    let settings = settings_rx.borrow_and_update().clone();
+                    apply_settings(settings, &mut encoder, &mut sensor_rx, &mut latest_rx, &sensor_tx, endpoint, peer);
+                    restart_stats_ticker(encoder.settings().stats_interval, &mut stats_interval, &mut stats_ticker);
+                    count_combined(encoder.settings().frame_format, connection_stats.as_ref(), &mut combined);
+                }
+                let _ = writer_tx.send(reply);
+            }
+            
+            // Server shutting down: close as if the client had asked to
+            _ = shutdown_signalled(&mut shutdown) => {
+                debug!("Closing {} for shutdown", peer);
+                break;
+            }
+            
             // Check if receive task has completed (client disconnected)
             _ = &mut receive_task => {
-                debug!("Receive task completed for {}", peer_addr);
+                debug!("Receive task completed for {}", peer);
+                break;
+            }
+            
+            // Check if send task has completed (socket write failed)
+            _ = &mut send_task => {
+                debug!("Send task completed for {}", peer);
                 break;
             }
         }
     }
     
-    // Clean shutdown
-    let _ = ws_sender.send(Message::Close(None)).await;
+    if out_tx.dropped() > 0 {
+        debug!("Coalesced {} stale frames for {}", out_tx.dropped(), peer);
+    }
+    
+    // Closing the queue lets the writer flush the last frame and send Close
+    let plain_frame_len = encoder.plain_len(&latest_rx.borrow());
+    let _ = skipped_tx.send((lagged + out_tx.dropped(), plain_frame_len));
+    drop(out_tx);
+    if !send_task.is_finished() {
+        let _ = send_task.await;
+    }
     
     Ok(())
 }
 
+/// Hold a combined-frame count for a connection while its format is
+/// combined, and release it once it switches back
+fn count_combined(format: FrameFormat, stats: Option<&Arc<ConnectionStats>>, counted: &mut Option<CombinedGuard>) {
+    match (format, stats) {
+        (FrameFormat::Combined, Some(stats)) => {
+            counted.get_or_insert_with(|| stats.take_combined());
+        }
+        _ => *counted = None,
+    }
+}
+
+/// Switch a connection's encoder to new settings, moving between
+/// every-frame and latest-only delivery if that changed
+fn apply_settings(
+    settings: ClientSettings,
+    encoder: &mut ClientEncoder,
+    sensor_rx: &mut Option<broadcast::Receiver<FusedSensorData>>,
+    latest_rx: &mut watch::Receiver<FusedSensorData>,
+    sensor_tx: &broadcast::Sender<FusedSensorData>,
+    endpoint: Endpoint,
+    peer: Peer,
+) {
+    encoder.set_settings(settings);
+    match (encoder.settings().delivery, sensor_rx.is_some()) {
+        (DeliveryMode::Latest, true) => {
+            debug!("Client {} switched to latest-only delivery", peer);
+            *sensor_rx = None;
+            latest_rx.mark_unchanged();
+        }
+        (DeliveryMode::All, false) if endpoint.streams() => {
+            debug!("Client {} switched to every-frame delivery", peer);
+            *sensor_rx = Some(sensor_tx.subscribe());
+        }
+        _ => {}
+    }
+}
+
+/// Restart the bandwidth report ticker when its period changed
+fn restart_stats_ticker(
+    interval: Option<std::time::Duration>,
+    current: &mut Option<std::time::Duration>,
+    ticker: &mut Option<tokio::time::Interval>,
+) {
+    if interval != *current {
+        *current = interval;
+        *ticker = interval.map(|period| tokio::time::interval_at(tokio::time::Instant::now() + period, period));
+    }
+}
+
+/// Parse, validate, and rate-check a pushed sensor reading
+fn parse_sample(
+    msg_type: &str,
+    json: &serde_json::Value,
+    source: &mut SourceSession,
+    limits: &ExternalLimits,
+) -> Result<ExternalSample, ExternalDataError> {
+    let now = std::time::Instant::now();
+    if msg_type == "imu_data" {
+        let imu = ImuData::deserialize(json)?;
+        validate_imu(&imu, limits)?;
+        source.imu_limiter.check(now)?;
+        Ok(ExternalSample::Imu(imu))
+    } else {
+        let gps = GpsData::deserialize(json)?;
+        validate_gps(&gps, limits)?;
+        source.gps_limiter.check(now)?;
+        Ok(ExternalSample::Gps(gps))
+    }
+}
+
+/// Compare tokens without short-circuiting on the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Receive the next broadcast item, or wait forever when unsubscribed
+async fn recv_broadcast<T: Clone>(
+    rx: &mut Option<broadcast::Receiver<T>>,
+) -> Result<T, broadcast::error::RecvError> {
+    match rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait until shutdown is signalled, or forever without a signal
+async fn shutdown_signalled(shutdown: &mut Option<watch::Receiver<bool>>) {
+    if let Some(rx) = shutdown {
+        if rx.wait_for(|stop| *stop).await.is_ok() {
+            return;
+        }
+    }
+    std::future::pending().await
+}
+
+/// Give open connections `SHUTDOWN_GRACE` to close, then drop the rest
+async fn finish_connections(mut connections: tokio::task::JoinSet<()>) {
+    while connections.try_join_next().is_some() {}
+    if connections.is_empty() {
+        return;
+    }
+    info!("🛑 Closing {} open connections", connections.len());
+    let drained = tokio::time::timeout(SHUTDOWN_GRACE, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        warn!("⚠️  {} connections still open after {:?}; dropping them", connections.len(), SHUTDOWN_GRACE);
+    }
+}
+
+/// Wait for the next tick, or forever when there is no ticker
+async fn tick_or_pending(ticker: &mut Option<tokio::time::Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Encode a frame for this client and queue it for the writer task
+/// 
+/// A frame that fails to encode is replaced by an error message on the
+/// reply path, so the client knows it missed one.
+fn queue_frame(
+    out_tx: &CoalescingSender<(Message, bool)>,
+    errors: &tokio::sync::mpsc::UnboundedSender<Message>,
+    encoder: &mut ClientEncoder,
+    sensor_data: &FusedSensorData,
+) {
+    // Not subscribed to frames (replies and notices still flow)
+    if !encoder.settings().subscribed(StreamType::Fused) {
+        return;
+    }
+    // An unsent frame is about to be replaced, so the client never sees
+    // the frame a delta would be based on: start again from a keyframe
+    if out_tx.is_pending() {
+        encoder.force_keyframe();
+    }
+    let sanitizing = encoder.sanitizing();
+    match encoder.encode(sensor_data, std::time::Instant::now()) {
+        // Suppressed by on-change mode
+        None => {}
+        Some(Ok(json)) => {
+            // Queue for the writer, replacing any unsent frame
+            out_tx.push((Message::Text(json), encoder.encodes()));
+        }
+        Some(Err(e)) => {
+            error!("Serialization error: {}", e);
+            if encoder.sanitizing() && !sanitizing {
+                warn!("⚠️  Sanitizing non-finite values in frames from now on");
+            }
+            let reply = serde_json::json!({
+                "type": "error",
+                "detail": "serialization_failed",
+                "message": e.to_string(),
+            });
+            let _ = errors.send(Message::Text(reply.to_string()));
+        }
+    }
+}
+
 /// Handle incoming messages from a client
 /// 
 /// This allows bidirectional communication for commands, anomaly scores, and control
-async fn handle_incoming_messages(
-    ws_receiver: &mut SplitStream<WebSocketStream<TcpStream>>,
-    peer_addr: SocketAddr,
-    cmd_tx: Arc<tokio::sync::mpsc::UnboundedSender<String>>,
-    anomaly_score: Arc<tokio::sync::RwLock<Option<f64>>>,
-) {
+async fn handle_incoming_messages<S>(
+    ws_receiver: &mut SplitStream<WebSocketStream<S>>,
+    peer: Peer,
+    context: MessageContext,
+    settings: watch::Sender<ClientSettings>,
+    replies: tokio::sync::mpsc::UnboundedSender<Message>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut source = SourceSession::new();
+    
     while let Some(msg_result) = ws_receiver.next().await {
         match msg_result {
             Ok(msg) => {
                 match msg {
                     Message::Text(text) => {
-                        debug!("📥 Received from {}: {}", peer_addr, text);
+                        debug!("📥 Received from {}: {}", peer, text);
                         
                         // Parse incoming JSON messages
                         if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-                            handle_client_message(json, peer_addr, &cmd_tx, &anomaly_score).await;
+                            // Server-side close, so the session stats go out
+                            // before the Close frame instead of being refused
+                            // after the client's
+                            if json.get("type").and_then(|v| v.as_str()) == Some("bye") {
+                                info!("👋 Client {} said bye", peer);
+                                break;
+                            }
+                            handle_client_message(json, peer, &context, &settings, &replies, &mut source).await;
                         }
                     }
                     Message::Binary(data) => {
-                        debug!("📥 Received binary from {}: {} bytes", peer_addr, data.len());
+                        debug!("📥 Received binary from {}: {} bytes", peer, data.len());
                     }
                     Message::Ping(_data) => {
-                        debug!("🏓 Ping from {}", peer_addr);
+                        debug!("🏓 Ping from {}", peer);
                         // Pong is automatically handled by tungstenite
                     }
                     Message::Pong(_) => {
-                        debug!("🏓 Pong from {}", peer_addr);
+                        debug!("🏓 Pong from {}", peer);
                     }
                     Message::Close(frame) => {
-                        info!("🚪 Close frame from {}: {:?}", peer_addr, frame);
+                        info!("🚪 Close frame from {}: {:?}", peer, frame);
                         break;
                     }
                     Message::Frame(_) => {
@@ -208,55 +1186,488 @@ async fn handle_incoming_messages(
                 }
             }
             Err(e) => {
-                warn!("⚠️  Error receiving from {}: {}", peer_addr, e);
+                warn!("⚠️  Error receiving from {}: {}", peer, e);
                 break;
             }
         }
     }
+    
+    if source.authenticated {
+        let rejected = source.imu_limiter.rejected() + source.gps_limiter.rejected();
+        info!("🛰️  Sensor source {} disconnected ({} readings rate-limited)", peer, rejected);
+    }
 }
 
 /// Handle specific client messages
 async fn handle_client_message(
     json: serde_json::Value,
-    peer_addr: SocketAddr,
-    cmd_tx: &tokio::sync::mpsc::UnboundedSender<String>,
-    anomaly_score: &Arc<tokio::sync::RwLock<Option<f64>>>,
+    peer: Peer,
+    context: &MessageContext,
+    settings: &watch::Sender<ClientSettings>,
+    replies: &tokio::sync::mpsc::UnboundedSender<Message>,
+    source: &mut SourceSession,
 ) {
+    let cmd_tx = &context.cmd_tx;
+    let anomaly_score = &context.anomaly_score;
+
     // Extract message type
     if let Some(msg_type) = json.get("type").and_then(|v| v.as_str()) {
+        // Read-only subscribers may not change system state
+        if !context.endpoint.accepts_control()
+            && matches!(msg_type, "command" | "anomaly_prediction" | "sensor_source" | "imu_data" | "gps_data")
+        {
+            debug!("🚫 Refused {} from read-only client {}", msg_type, peer);
+            let reply = serde_json::json!({
+                "type": "error",
+                "request": msg_type,
+                "message": "read-only endpoint",
+            });
+            let _ = replies.send(Message::Text(reply.to_string()));
+            return;
+        }
+        
         match msg_type {
             "command" => {
-                // Handle control commands (fault injection)
+                // Handle control commands (fault injection, simulation control)
                 if let Some(action) = json.get("action").and_then(|v| v.as_str()) {
-                    info!("⚡ Command from {}: {}", peer_addr, action);
+                    info!("⚡ Command from {}: {}", peer, action);
                     
-                    // Extract fault type from parameters
-                    if action == "inject_fault" {
-                        if let Some(params) = json.get("parameters") {
-                            if let Some(fault_type) = params.get("fault_type").and_then(|v| v.as_str()) {
-                                info!("🎯 Fault injection request: {}", fault_type);
-                                // Send command to sensor loop
-                                let _ = cmd_tx.send(fault_type.to_string());
+                    match action {
+                        "inject_fault" => {
+                            // Extract fault type from parameters
+                            if let Some(params) = json.get("parameters") {
+                                if let Some(fault_type) = params.get("fault_type").and_then(|v| v.as_str()) {
+                                    // Optional auto-reset after a positive number of seconds
+                                    let duration = match params.get("duration_secs") {
+                                        None | Some(serde_json::Value::Null) => None,
+                                        Some(value) => match value
+                                            .as_f64()
+                                            .and_then(|secs| std::time::Duration::try_from_secs_f64(secs).ok())
+                                            .filter(|d| !d.is_zero())
+                                        {
+                                            Some(duration) => Some(duration),
+                                            None => {
+                                                let reply = serde_json::json!({
+                                                    "type": "error",
+                                                    "request": "inject_fault",
+                                                    "message": "duration_secs must be a positive number of seconds",
+                                                });
+                                                let _ = replies.send(Message::Text(reply.to_string()));
+                                                return;
+                                            }
+                                        },
+                                    };
+                                    info!("🎯 Fault injection request: {} (duration {:?})", fault_type, duration);
+                                    // Send command to sensor loop
+                                    let command = ControlCommand::new(fault_type).with_duration(duration);
+                                    context.command_log.push(peer, &command, true, false);
+                                    let _ = cmd_tx.send(command);
+                                }
                             }
                         }
+                        "pause" | "resume" | "chaos_on" | "chaos_off" | "recenter" | "calibrate_accel"
+                        | "zero_orientation" | "clear_reference" => {
+                            // Simulation control is handled by the sensor loop
+                            let command = ControlCommand::new(action);
+                            context.command_log.push(peer, &command, false, false);
+                            let _ = cmd_tx.send(command);
+                        }
+                        "set_filter" => {
+                            // Swap the fusion filter in the sensor loop
+                            let name = json.get("parameters")
+                                .and_then(|params| params.get("name"))
+                                .and_then(|v| v.as_str())
+                                .unwrap_or_default();
+                            match FilterKind::from_name(name) {
+                                Some(filter) => match context.unavailable_filters.iter().find(|(kind, _)| *kind == filter) {
+                                    Some((_, reason)) => {
+                                        let reply = serde_json::json!({
+                                            "type": "error",
+                                            "request": "set_filter",
+                                            "message": format!("filter {:?} unavailable: {}", filter.name(), reason),
+                                        });
+                                        let _ = replies.send(Message::Text(reply.to_string()));
+                                    }
+                                    None => {
+                                        info!("🔀 Switching fusion filter to {} for {}", filter.name(), peer);
+                                        let command = ControlCommand::set_filter(filter);
+                                        context.command_log.push(peer, &command, false, false);
+                                        let _ = cmd_tx.send(command);
+                                    }
+                                },
+                                None => {
+                                    let reply = serde_json::json!({
+                                        "type": "error",
+                                        "request": "set_filter",
+                                        "message": format!("unknown filter {:?} (available: {})", name, FilterKind::available()),
+                                    });
+                                    let _ = replies.send(Message::Text(reply.to_string()));
+                                }
+                            }
+                        }
+                        "replay_last" => {
+                            // Re-run the most recent fault (never a reset)
+                            match context.command_log.last_fault() {
+                                Some(command) => {
+                                    info!("🔁 Replaying fault {} for {}", command.action, peer);
+                                    context.command_log.push(peer, &command, true, true);
+                                    let _ = cmd_tx.send(command);
+                                }
+                                None => {
+                                    let reply = serde_json::json!({
+                                        "type": "error",
+                                        "request": "replay_last",
+                                        "message": "no fault command to replay",
+                                    });
+                                    let _ = replies.send(Message::Text(reply.to_string()));
+                                }
+                            }
+                        }
+                        _ => {
+                            debug!("❓ Unknown command action from {}: {}", peer, action);
+                        }
                     }
                 }
             }
             "anomaly_prediction" => {
                 // Handle anomaly score updates from ML service
                 if let Some(score) = json.get("score").and_then(|v| v.as_f64()) {
-                    debug!("🤖 Anomaly score from {}: {:.3}", peer_addr, score);
+                    debug!("🤖 Anomaly score from {}: {:.3}", peer, score);
                     
                     // Update shared anomaly score state
                     let mut anomaly_state = anomaly_score.write().await;
                     *anomaly_state = Some(score);
                 }
             }
+            "set_delivery" => {
+                // Choose between every broadcast frame and latest-only delivery
+                let mode = match json.get("mode").and_then(|v| v.as_str()) {
+                    Some("all") => DeliveryMode::All,
+                    Some("latest") => DeliveryMode::Latest,
+                    other => {
+                        debug!("❓ Unknown delivery mode from {}: {:?}", peer, other);
+                        return;
+                    }
+                };
+                info!("📬 Client {} delivery mode: {:?}", peer, mode);
+                settings.send_modify(|s| s.delivery = mode);
+            }
+            "set_coords" => {
+                // Choose between geodetic and local ENU position output
+                let mode = match json.get("mode").and_then(|v| v.as_str()) {
+                    Some("geodetic") => CoordinateMode::Geodetic,
+                    Some("local") => CoordinateMode::Local,
+                    other => {
+                        debug!("❓ Unknown coordinate mode from {}: {:?}", peer, other);
+                        return;
+                    }
+                };
+                info!("🧭 Client {} coordinate mode: {:?}", peer, mode);
+                settings.send_modify(|s| s.coords = mode);
+            }
+            "set_on_change" => {
+                // Only send frames that changed noticeably, plus keepalives
+                let enabled = json.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+                let thresholds = enabled.then(|| {
+                    let defaults = ChangeThresholds::default();
+                    let field = |name: &str, default: f64| {
+                        json.get(name)
+                            .and_then(|v| v.as_f64())
+                            .filter(|v| v.is_finite() && *v >= 0.0)
+                            .unwrap_or(default)
+                    };
+                    ChangeThresholds {
+                        position_m: field("position_m", defaults.position_m),
+                        orientation_deg: field("orientation_deg", defaults.orientation_deg),
+                        velocity_mps: field("velocity_mps", defaults.velocity_mps),
+                        keepalive: json.get("keepalive_ms")
+                            .and_then(|v| v.as_u64())
+                            .map(std::time::Duration::from_millis)
+                            .unwrap_or(defaults.keepalive),
+                    }
+                });
+                info!("🔕 Client {} on-change mode: {:?}", peer, thresholds);
+                settings.send_modify(|s| s.on_change = thresholds);
+            }
+            "set_delta" => {
+                // Only send changed fields between periodic keyframes
+                let enabled = json.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+                let delta = enabled.then(|| {
+                    let defaults = DeltaSettings::default();
+                    DeltaSettings {
+                        threshold: json.get("threshold")
+                            .and_then(|v| v.as_f64())
+                            .filter(|v| v.is_finite() && *v >= 0.0)
+                            .unwrap_or(defaults.threshold),
+                        keyframe_every: json.get("keyframe_every")
+                            .and_then(|v| v.as_u64())
+                            .filter(|n| *n >= 1)
+                            .map_or(defaults.keyframe_every, |n| n.min(u32::MAX as u64) as u32),
+                    }
+                });
+                info!("🧩 Client {} delta encoding: {:?}", peer, delta);
+                settings.send_modify(|s| s.delta = delta);
+            }
+            "resync" => {
+                // Re-applying the settings resets the encoder, so the next
+                // frame is a full keyframe
+                debug!("🧩 Client {} requested a keyframe", peer);
+                settings.send_modify(|_| {});
+            }
+            "set_wire_type" => {
+                // Choose between double and single precision floats
+                let wire_type = match json.get("mode").and_then(|v| v.as_str()) {
+                    Some("f64") => WireType::F64,
+                    Some("f32") => WireType::F32,
+                    other => {
+                        debug!("❓ Unknown wire type from {}: {:?}", peer, other);
+                        return;
+                    }
+                };
+                info!("🗜️  Client {} wire type: {:?}", peer, wire_type);
+                settings.send_modify(|s| s.wire_type = wire_type);
+            }
+            "set_orientation_format" => {
+                // Drop the orientation representation the client doesn't render
+                let format = match json.get("format").and_then(|v| v.as_str()) {
+                    Some("both") => OrientationFormat::Both,
+                    Some("euler") => OrientationFormat::Euler,
+                    Some("quaternion") => OrientationFormat::Quaternion,
+                    other => {
+                        debug!("❓ Unknown orientation format from {}: {:?}", peer, other);
+                        return;
+                    }
+                };
+                info!("🧭 Client {} orientation format: {:?}", peer, format);
+                settings.send_modify(|s| s.orientation = format);
+            }
+            "set_frame_convention" => {
+                // Aerospace (NED/FRD) or ROS (ENU/FLU) orientation axes
+                let convention = match json.get("convention").and_then(|v| v.as_str()) {
+                    Some("ned") => FrameConvention::Ned,
+                    Some("enu") => FrameConvention::Enu,
+                    other => {
+                        debug!("❓ Unknown frame convention from {}: {:?}", peer, other);
+                        return;
+                    }
+                };
+                info!("🧭 Client {} frame convention: {:?}", peer, convention);
+                settings.send_modify(|s| s.convention = convention);
+            }
+            "set_frame_format" => {
+                // Fused frames alone, or combined with their raw readings
+                let format = match json.get("format").and_then(|v| v.as_str()) {
+                    Some("fused") => FrameFormat::Fused,
+                    Some("combined") => FrameFormat::Combined,
+                    other => {
+                        debug!("❓ Unknown frame format from {}: {:?}", peer, other);
+                        return;
+                    }
+                };
+                info!("🧩 Client {} frame format: {:?}", peer, format);
+                settings.send_modify(|s| s.frame_format = format);
+            }
+            "set_accel_units" => {
+                // Report acceleration in m/s² or in g
+                let units = match json.get("units").and_then(|v| v.as_str()) {
+                    Some("mps2") => AccelUnits::Mps2,
+                    Some("g") => AccelUnits::G,
+                    other => {
+                        debug!("❓ Unknown acceleration units from {}: {:?}", peer, other);
+                        return;
+                    }
+                };
+                info!("📏 Client {} acceleration units: {:?}", peer, units);
+                settings.send_modify(|s| s.accel_units = units);
+            }
+            "subscribe_types" => {
+                // Only stream these message types (all again when `types` is null)
+                let subscriptions = match json.get("types") {
+                    None | Some(serde_json::Value::Null) => None,
+                    Some(serde_json::Value::Array(names)) => {
+                        let mut types = Vec::new();
+                        for name in names {
+                            match name.as_str().and_then(StreamType::from_name) {
+                                Some(kind) if !types.contains(&kind) => types.push(kind),
+                                Some(_) => {}
+                                None => warn!("❓ Client {} subscribed to unknown message type {}", peer, name),
+                            }
+                        }
+                        Some(types)
+                    }
+                    Some(other) => {
+                        debug!("❓ Invalid subscription types from {}: {}", peer, other);
+                        return;
+                    }
+                };
+                info!("📬 Client {} subscriptions: {:?}", peer, subscriptions);
+                settings.send_modify(|s| s.subscriptions = subscriptions);
+            }
+            "set_pretty" => {
+                // Indented JSON for debugging with a raw WebSocket tool
+                let enabled = json.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+                info!("📝 Client {} pretty-printing: {}", peer, enabled);
+                settings.send_modify(|s| s.pretty = enabled);
+            }
+            "set_stats_interval" => {
+                // Periodic bandwidth reports; 0 turns them off
+                let Some(interval_ms) = json.get("interval_ms").and_then(|v| v.as_u64()) else {
+                    debug!("❓ Invalid stats interval from {}: {:?}", peer, json.get("interval_ms"));
+                    return;
+                };
+                let interval = Some(std::time::Duration::from_millis(interval_ms)).filter(|i| !i.is_zero());
+                info!("📊 Client {} bandwidth reports every {:?}", peer, interval);
+                settings.send_modify(|s| s.stats_interval = interval);
+            }
+            "set_timestamp_format" => {
+                // Choose between RFC 3339 strings and epoch milliseconds
+                let format = match json.get("format").and_then(|v| v.as_str()) {
+                    Some("rfc3339") => TimestampFormat::Rfc3339,
+                    Some("epoch_millis") => TimestampFormat::EpochMillis,
+                    other => {
+                        debug!("❓ Unknown timestamp format from {}: {:?}", peer, other);
+                        return;
+                    }
+                };
+                info!("🕒 Client {} timestamp format: {:?}", peer, format);
+                settings.send_modify(|s| s.timestamp_format = format);
+            }
             "heartbeat" => {
-                debug!("💓 Heartbeat from {}", peer_addr);
+                debug!("💓 Heartbeat from {}", peer);
+            }
+            "get_metadata" => {
+                // Coordinate metadata as of this client's current settings
+                let metadata = FrameMetadata::new(&context.metadata, &settings.borrow());
+                let reply = serde_json::json!({ "type": "metadata", "metadata": metadata });
+                let _ = replies.send(Message::Text(reply.to_string()));
+            }
+            "sensor_source" => {
+                // Authenticate as a source of pushed sensor readings
+                let token = json.get("token").and_then(|v| v.as_str()).unwrap_or_default();
+                source.authenticated = context
+                    .sensor_source
+                    .as_ref()
+                    .is_some_and(|s| tokens_match(token, &s.token));
+                
+                let status = if source.authenticated {
+                    info!("🛰️  Sensor source authenticated: {}", peer);
+                    "accepted"
+                } else {
+                    warn!("🚫 Sensor source rejected: {}", peer);
+                    "rejected"
+                };
+                let reply = serde_json::json!({ "type": "sensor_source", "status": status });
+                let _ = replies.send(Message::Text(reply.to_string()));
+            }
+            "imu_data" | "gps_data" => {
+                // Pushed readings from an authenticated sensor source
+                let Some(sensor_source) = context.sensor_source.as_ref().filter(|_| source.authenticated) else {
+                    debug!("🚫 Unauthenticated {} from {}", msg_type, peer);
+                    return;
+                };
+                
+                match parse_sample(msg_type, &json, source, &context.external_limits) {
+                    Ok(sample) => {
+                        if sensor_source.samples.try_send(sample).is_err() {
+                            debug!("Fusion loop busy, dropping {} from {}", msg_type, peer);
+                        }
+                    }
+                    // Dropped quietly; a nack per excess sample would add to the flood
+                    Err(ExternalDataError::RateLimited) => {
+                        debug!("🚫 Rate-limited {} from {}", msg_type, peer);
+                    }
+                    Err(e) => {
+                        debug!("🚫 Rejected {} from {}: {}", msg_type, peer, e);
+                        let reply = serde_json::json!({
+                            "type": "error",
+                            "request": msg_type,
+                            "message": e.to_string(),
+                        });
+                        let _ = replies.send(Message::Text(reply.to_string()));
+                    }
+                }
+            }
+            "health_log" => {
+                // Recorded health transitions, oldest first
+                let events = context.health_log.as_ref().map(|log| log.events()).unwrap_or_default();
+                debug!("🩺 Sending {} health events to {}", events.len(), peer);
+                let reply = serde_json::json!({
+                    "type": "health_log",
+                    "events": events,
+                });
+                let _ = replies.send(Message::Text(reply.to_string()));
+            }
+            "command_history" => {
+                // Recent commands, oldest first
+                let commands = context.command_log.records();
+                debug!("⚡ Sending {} commands to {}", commands.len(), peer);
+                let reply = serde_json::json!({
+                    "type": "command_history",
+                    "commands": commands,
+                });
+                let _ = replies.send(Message::Text(reply.to_string()));
+            }
+            "get_config" => {
+                // Running configuration (secrets redacted)
+                let reply = match &context.config {
+                    Some(config) => {
+                        debug!("📋 Sending configuration to {}", peer);
+                        serde_json::json!({
+                            "type": "config",
+                            "config": *config.borrow(),
+                        })
+                    }
+                    None => serde_json::json!({
+                        "type": "error",
+                        "request": "get_config",
+                        "message": "configuration not available",
+                    }),
+                };
+                let _ = replies.send(Message::Text(reply.to_string()));
+            }
+            "query_at" => {
+                // Estimated state at a past time, from the frame history
+                let timestamp = match json.get("timestamp").cloned().map(timestamp::deserialize) {
+                    Some(Ok(timestamp)) => timestamp,
+                    Some(Err(e)) => {
+                        let reply = serde_json::json!({
+                            "type": "error",
+                            "request": "query_at",
+                            "message": format!("invalid timestamp: {}", e),
+                        });
+                        let _ = replies.send(Message::Text(reply.to_string()));
+                        return;
+                    }
+                    None => {
+                        let reply = serde_json::json!({
+                            "type": "error",
+                            "request": "query_at",
+                            "message": "timestamp is required",
+                        });
+                        let _ = replies.send(Message::Text(reply.to_string()));
+                        return;
+                    }
+                };
+                let reply = match context.history.as_ref().and_then(|history| history.at(timestamp)) {
+                    Some(at) => {
+                        debug!("🕰️  Sending state at {} to {} (out of range: {})", timestamp, peer, at.out_of_range);
+                        serde_json::json!({
+                            "type": "query_at",
+                            "timestamp": timestamp.to_rfc3339(),
+                            "out_of_range": at.out_of_range,
+                            "frame": at.frame,
+                        })
+                    }
+                    None => serde_json::json!({
+                        "type": "error",
+                        "request": "query_at",
+                        "message": if context.history.is_some() { "no frames buffered yet" } else { "frame history not enabled" },
+                    }),
+                };
+                let _ = replies.send(Message::Text(reply.to_string()));
             }
             _ => {
-                debug!("❓ Unknown message type from {}: {}", peer_addr, msg_type);
+                debug!("❓ Unknown message type from {}: {}", peer, msg_type);
             }
         }
     }
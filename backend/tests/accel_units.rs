@@ -0,0 +1,31 @@
+//! Per-client acceleration units
+
+mod common;
+
+use common::{frame, TestServer};
+use sensor_fusion_backend::models::Vec3;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_g_units_report_stationary_z_as_one() {
+    let server = TestServer::start().await;
+    let mut in_g = server.connect("/").await;
+    let mut in_si = server.connect("/").await;
+    in_g.send(json!({"type": "set_accel_units", "units": "g"})).await;
+    in_g.sync().await;
+
+    let mut stationary = frame(1);
+    stationary.raw_acceleration = Vec3::new(0.0, 0.0, 9.81);
+    server.publish(&stationary);
+
+    let z = in_g.recv_frame().await["raw_acceleration"]["z"].as_f64().unwrap();
+    assert!((z - 1.0).abs() < 1e-9, "z = {z} g");
+    let z = in_si.recv_frame().await["raw_acceleration"]["z"].as_f64().unwrap();
+    assert!((z - 9.81).abs() < 1e-9, "z = {z} m/s²");
+
+    // Back to SI on request
+    in_g.send(json!({"type": "set_accel_units", "units": "mps2"})).await;
+    in_g.sync().await;
+    server.publish(&stationary);
+    assert_eq!(in_g.recv_frame().await["raw_acceleration"]["z"], 9.81);
+}
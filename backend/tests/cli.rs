@@ -0,0 +1,65 @@
+//! The backend binary's command-line modes
+
+use sensor_fusion_backend::models::FusedSensorData;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+/// Run the backend with `args` to completion, logging off
+/// 
+/// The WebSocket server gets a port the OS picks, so runs neither collide
+/// with each other nor with a backend already on the default port.
+fn run_backend(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sensor-fusion-backend"))
+        .args(["--ws-port", "0"])
+        .args(args)
+        .env("RUST_LOG", "off")
+        .stdin(Stdio::null())
+        .output()
+        .expect("backend binary should start")
+}
+
+#[test]
+fn test_stdout_jsonl_prints_one_frame_per_line() {
+    let output = run_backend(&["--stdout-jsonl", "--duration-secs", "1"]);
+    assert!(output.status.success(), "exited with {}", output.status);
+
+    let frames: Vec<FusedSensorData> = String::from_utf8(output.stdout)
+        .expect("stdout should be UTF-8")
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("{line:?} is not a frame: {e}")))
+        .collect();
+    assert!(frames.len() >= 10, "only {} frames printed in 1s", frames.len());
+    assert!(frames.windows(2).all(|pair| pair[0].timestamp <= pair[1].timestamp));
+}
+
+#[test]
+fn test_timed_run_exits_cleanly_with_a_summary() {
+    let started = Instant::now();
+    let output = run_backend(&["--duration-secs", "1"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(started.elapsed() < Duration::from_secs(8), "took {:?} to exit", started.elapsed());
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let summary = stderr.lines().find(|line| line.contains("Run summary")).expect("no run summary on stderr");
+    assert!(summary.contains("frames published"), "{summary}");
+}
+
+#[test]
+fn test_scenario_runs_are_identical() {
+    let path = std::env::temp_dir().join(format!("scenario-{}.json", std::process::id()));
+    let scenario = r#"{ "seed": 3, "duration_secs": 2, "faults": [{ "at_secs": 1, "fault": "high_noise" }] }"#;
+    std::fs::write(&path, scenario).unwrap();
+    let path = path.to_str().unwrap();
+
+    let first = run_backend(&["--scenario", path]);
+    assert!(first.status.success(), "exited with {}", first.status);
+    assert_eq!(String::from_utf8_lossy(&first.stdout).lines().count(), 100);
+    assert_eq!(first.stdout, run_backend(&["--scenario", path]).stdout);
+
+    // Unknown references fail before anything runs
+    std::fs::write(path, r#"{ "seed": 3, "duration_secs": 2, "motion": "figure_eight" }"#).unwrap();
+    let rejected = run_backend(&["--scenario", path]);
+    assert!(!rejected.status.success());
+    assert!(String::from_utf8_lossy(&rejected.stderr).contains("unknown motion profile"));
+    let _ = std::fs::remove_file(path);
+}
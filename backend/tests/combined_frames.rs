@@ -0,0 +1,82 @@
+//! Combined raw and fused frames, selected per connection
+
+mod common;
+
+use std::sync::Arc;
+
+use common::{frame, TestServer};
+use pretty_assertions::assert_eq;
+use sensor_fusion_backend::models::{GpsData, ImuData, SensorInputs, Vec3};
+use sensor_fusion_backend::websocket::ConnectionStats;
+use serde_json::json;
+
+/// A frame carrying the readings it was fused from
+fn frame_with_inputs(seq: u32) -> sensor_fusion_backend::models::FusedSensorData {
+    let mut fused = frame(seq);
+    fused.inputs = Some(Arc::new(SensorInputs {
+        imu: ImuData::new(Vec3::new(0.0, 0.0, 9.81), Vec3::zero()),
+        gps: GpsData::new(37.7749, -122.4194, 10.0),
+        gps_stale: true,
+    }));
+    fused
+}
+
+#[tokio::test]
+async fn test_set_frame_format_switches_to_combined_frames() {
+    let clients = Arc::new(ConnectionStats::new());
+    let server = TestServer::start_with(|server| server.with_connection_stats(clients.clone())).await;
+    let mut client = server.connect("/").await;
+    assert_eq!(clients.combined(), 0);
+
+    client.send(json!({"type": "set_frame_format", "format": "combined"})).await;
+    client.sync().await;
+    assert_eq!(clients.combined(), 1);
+    server.publish(&frame_with_inputs(1));
+    let combined = client.recv_type("combined").await;
+    assert_eq!(combined["imu"]["acceleration"]["z"], 9.81);
+    assert_eq!(combined["gps"]["latitude"], 37.7749);
+    assert_eq!(combined["gps_stale"], true);
+    assert_eq!(combined["fused"]["gps_speed"], 1.0);
+
+    // Back to plain fused frames, no longer counted
+    client.send(json!({"type": "set_frame_format", "format": "fused"})).await;
+    client.sync().await;
+    assert_eq!(clients.combined(), 0);
+    server.publish(&frame_with_inputs(2));
+    assert_eq!(client.recv_frame().await["gps_speed"], 2.0);
+}
+
+#[tokio::test]
+async fn test_combined_frames_are_sent_in_full_in_delta_mode() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_delta", "keyframe_every": 50})).await;
+    client.send(json!({"type": "set_frame_format", "format": "combined"})).await;
+    client.sync().await;
+
+    for seq in 1..=3 {
+        server.publish(&frame_with_inputs(seq));
+        let combined = client.recv_type("combined").await;
+        assert_eq!(combined["fused"]["gps_speed"], seq as f64);
+        assert!(combined["fused"]["orientation"].is_object(), "fused frame not sent in full: {combined}");
+    }
+}
+
+#[tokio::test]
+async fn test_combined_client_is_uncounted_once_disconnected() {
+    let clients = Arc::new(ConnectionStats::new());
+    let server = TestServer::start_with(|server| server.with_connection_stats(clients.clone())).await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_frame_format", "format": "combined"})).await;
+    client.sync().await;
+    assert_eq!(clients.combined(), 1);
+
+    drop(client);
+    for _ in 0..100 {
+        if clients.current() == 0 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert_eq!(clients.combined(), 0);
+}
@@ -0,0 +1,107 @@
+//! Command history queries and `replay_last`
+
+mod common;
+
+use common::TestServer;
+use sensor_fusion_backend::fusion::FilterKind;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::time::Duration;
+
+fn inject(fault: &str, duration_secs: Option<f64>) -> serde_json::Value {
+    json!({
+        "type": "command",
+        "action": "inject_fault",
+        "parameters": {"fault_type": fault, "duration_secs": duration_secs},
+    })
+}
+
+#[tokio::test]
+async fn test_history_lists_injected_faults_in_order() {
+    let mut server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+
+    client.send(inject("accel_spike", None)).await;
+    client.send(inject("gps_signal_loss", Some(5.0))).await;
+    assert_eq!(server.next_command().await.action, "accel_spike");
+    assert_eq!(server.next_command().await.action, "gps_signal_loss");
+
+    client.send(json!({"type": "command_history"})).await;
+    let history = client.recv_type("command_history").await;
+    let commands = history["commands"].as_array().unwrap();
+    let names: Vec<_> = commands.iter().map(|c| c["command"].as_str().unwrap()).collect();
+    assert_eq!(names, ["accel_spike", "gps_signal_loss"]);
+    assert_eq!(commands[1]["duration_secs"], 5.0);
+    assert!(commands.iter().all(|c| c["fault"] == true && c["replayed"] == false));
+    assert!(commands[0]["peer"].as_str().unwrap().starts_with("127.0.0.1:"));
+}
+
+#[tokio::test]
+async fn test_replay_last_repeats_the_last_fault_not_a_reset() {
+    let mut server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+
+    client.send(inject("high_noise", Some(2.0))).await;
+    client.send(inject("reset", None)).await;
+    server.next_command().await;
+    assert_eq!(server.next_command().await.action, "reset");
+
+    client.send(json!({"type": "command", "action": "replay_last"})).await;
+    let replayed = server.next_command().await;
+    assert_eq!(replayed.action, "high_noise");
+    assert_eq!(replayed.duration, Some(Duration::from_secs(2)));
+}
+
+#[tokio::test]
+async fn test_replay_last_without_faults_is_an_error() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+
+    client.send(json!({"type": "command", "action": "replay_last"})).await;
+    let error = client.recv_type("error").await;
+    assert_eq!(error["request"], "replay_last");
+}
+
+#[tokio::test]
+async fn test_set_filter_forwards_known_filters_and_rejects_others() {
+    let mut server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+
+    let set_filter = |name: &str| json!({"type": "command", "action": "set_filter", "parameters": {"name": name}});
+    client.send(set_filter("complementary")).await;
+    let command = server.next_command().await;
+    assert_eq!(command.action, "set_filter");
+    assert_eq!(command.filter, Some(FilterKind::Complementary));
+
+    client.send(set_filter("ekf")).await;
+    let command = server.next_command().await;
+    assert_eq!(command.filter, Some(FilterKind::Ekf));
+
+    client.send(set_filter("ukf")).await;
+    let error = client.recv_type("error").await;
+    assert_eq!(error["request"], "set_filter");
+    assert!(error["message"].as_str().unwrap().contains("available: complementary, ekf"), "{error}");
+
+    client.send(json!({"type": "command_history"})).await;
+    let history = client.recv_type("command_history").await;
+    let commands = history["commands"].as_array().unwrap();
+    assert_eq!(commands.len(), 2, "rejected switch was logged: {commands:?}");
+    assert_eq!(commands[0]["filter"], "complementary");
+    assert_eq!(commands[1]["filter"], "ekf");
+    assert_eq!(commands[0]["fault"], false);
+}
+
+#[tokio::test]
+async fn test_set_filter_refuses_an_unavailable_filter() {
+    let mut server = TestServer::start_with(|server| server.with_unavailable_filter(FilterKind::Ekf, "it can't honor gyro_deadband")).await;
+    let mut client = server.connect("/").await;
+
+    client.send(json!({"type": "command", "action": "set_filter", "parameters": {"name": "ekf"}})).await;
+    let error = client.recv_type("error").await;
+    assert_eq!(error["request"], "set_filter");
+    assert!(error["message"].as_str().unwrap().contains("gyro_deadband"), "{error}");
+
+    // Other filters still switch
+    client.send(json!({"type": "command", "action": "set_filter", "parameters": {"name": "complementary"}})).await;
+    assert_eq!(server.next_command().await.filter, Some(FilterKind::Complementary));
+}
@@ -0,0 +1,198 @@
+//! Shared harness for the server integration tests: a `WebSocketServer`
+//! on a free local port fed by hand-published frames, and a JSON client.
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use sensor_fusion_backend::models::FusedSensorData;
+use sensor_fusion_backend::websocket::{ControlCommand, WebSocketServer};
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// How long a test waits for any single message
+pub const RECV_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A port nothing is listening on
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+/// A running server and the channels that feed it
+pub struct TestServer {
+    pub port: u16,
+    pub sensor_tx: Arc<broadcast::Sender<FusedSensorData>>,
+    pub latest_tx: watch::Sender<FusedSensorData>,
+    pub commands: mpsc::UnboundedReceiver<ControlCommand>,
+    pub anomaly_score: Arc<tokio::sync::RwLock<Option<f64>>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl TestServer {
+    /// Start a server with default settings
+    pub async fn start() -> Self {
+        Self::start_with(|server| server).await
+    }
+
+    /// Start a server after applying `configure` to it
+    pub async fn start_with(configure: impl FnOnce(WebSocketServer) -> WebSocketServer) -> Self {
+        Self::start_with_capacity(100, configure).await
+    }
+
+    /// Start a server whose broadcast channel holds `capacity` frames
+    pub async fn start_with_capacity(
+        capacity: usize,
+        configure: impl FnOnce(WebSocketServer) -> WebSocketServer,
+    ) -> Self {
+        let port = free_port();
+        let (sensor_tx, _) = broadcast::channel(capacity);
+        let sensor_tx = Arc::new(sensor_tx);
+        let (latest_tx, latest_rx) = watch::channel(FusedSensorData::default());
+        let (cmd_tx, commands) = mpsc::unbounded_channel();
+        let anomaly_score = Arc::new(tokio::sync::RwLock::new(None));
+        let server = configure(WebSocketServer::new(
+            port,
+            sensor_tx.clone(),
+            latest_rx,
+            Arc::new(cmd_tx),
+            anomaly_score.clone(),
+        ));
+        let task = tokio::spawn(async move {
+            server.run().await.expect("server failed");
+        });
+
+        // Wait for the listener
+        for _ in 0..100 {
+            if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Self { port, sensor_tx, latest_tx, commands, anomaly_score, task }
+    }
+
+    /// Publish a frame to broadcast and latest-only subscribers
+    pub fn publish(&self, frame: &FusedSensorData) {
+        let _ = self.sensor_tx.send(frame.clone());
+        self.latest_tx.send_replace(frame.clone());
+    }
+
+    /// Connect a client to `path` and consume the welcome message
+    pub async fn connect(&self, path: &str) -> TestClient {
+        let url = format!("ws://127.0.0.1:{}{}", self.port, path);
+        let (ws, _) = tokio_tungstenite::connect_async(url).await.expect("connect failed");
+        let mut client = TestClient { ws };
+        let welcome = client.recv().await;
+        assert_eq!(welcome["type"], "connection");
+        client
+    }
+
+    /// Next command forwarded to the fusion loop
+    pub async fn next_command(&mut self) -> ControlCommand {
+        tokio::time::timeout(RECV_TIMEOUT, self.commands.recv())
+            .await
+            .expect("no command forwarded")
+            .expect("command channel closed")
+    }
+
+    /// Plain HTTP GET on the server port, returning status code and body
+    pub async fn http_get(&self, path: &str) -> (u16, String) {
+        http_get(self.port, path).await
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A WebSocket client speaking JSON text messages
+pub struct TestClient {
+    pub ws: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl TestClient {
+    /// Send a JSON message
+    pub async fn send(&mut self, message: Value) {
+        self.ws.send(Message::Text(message.to_string())).await.expect("send failed");
+    }
+
+    /// Next text message, parsed; pings and pongs are skipped
+    pub async fn recv(&mut self) -> Value {
+        self.try_recv(RECV_TIMEOUT).await.expect("no message within timeout")
+    }
+
+    /// Next text message within `timeout`, if any
+    pub async fn try_recv(&mut self, timeout: Duration) -> Option<Value> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let message = tokio::time::timeout_at(deadline, self.ws.next()).await.ok()??;
+            match message.expect("websocket error") {
+                Message::Text(text) => return Some(serde_json::from_str(&text).expect("invalid JSON")),
+                Message::Close(_) => return None,
+                _ => continue,
+            }
+        }
+    }
+
+    /// Next message of the given `type`, skipping frames and other messages
+    pub async fn recv_type(&mut self, kind: &str) -> Value {
+        loop {
+            let message = self.recv().await;
+            if message["type"] == kind {
+                return message;
+            }
+        }
+    }
+
+    /// Next fused frame (a message without a `type`)
+    pub async fn recv_frame(&mut self) -> Value {
+        loop {
+            let message = self.recv().await;
+            if message.get("type").is_none() {
+                return message;
+            }
+        }
+    }
+
+    /// Wait until the server has applied every message sent so far
+    ///
+    /// Messages are handled in order, and a reply goes out only once
+    /// earlier settings changes are in effect, so every frame after the
+    /// reply to a request sent now uses them.
+    pub async fn sync(&mut self) {
+        self.send(serde_json::json!({"type": "health_log"})).await;
+        self.recv_type("health_log").await;
+    }
+}
+
+/// Plain HTTP GET against a local port, returning status code and body
+pub async fn http_get(port: u16, path: &str) -> (u16, String) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.expect("connect failed");
+    let request = format!("GET {} HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut response))
+        .await
+        .expect("no HTTP response")
+        .unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").expect("malformed response");
+    let status = head.split_whitespace().nth(1).and_then(|code| code.parse().ok()).expect("no status code");
+    (status, body.to_string())
+}
+
+/// A default frame marked with `seq` in its GPS speed, to tell frames apart
+pub fn frame(seq: u32) -> FusedSensorData {
+    FusedSensorData {
+        gps_speed: seq as f64,
+        ..FusedSensorData::default()
+    }
+}
@@ -0,0 +1,61 @@
+//! Per-connection coordinate output
+
+mod common;
+
+use common::TestServer;
+use pretty_assertions::assert_eq;
+use sensor_fusion_backend::models::{FusedSensorData, Vec3};
+use serde_json::json;
+
+fn frame() -> FusedSensorData {
+    FusedSensorData {
+        position: (37.7749, -122.4194, 10.0),
+        local_position: Vec3::new(1.5, -2.0, 0.25),
+        ..FusedSensorData::default()
+    }
+}
+
+#[tokio::test]
+async fn test_geodetic_by_default() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+
+    server.publish(&frame());
+    let received = client.recv_frame().await;
+    assert_eq!(received["position"], json!([37.7749, -122.4194, 10.0]));
+    assert!(received.get("local_position").is_none());
+}
+
+#[tokio::test]
+async fn test_local_mode_sends_local_position_only() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_coords", "mode": "local"})).await;
+    client.sync().await;
+
+    server.publish(&frame());
+    let received = client.recv_frame().await;
+    assert_eq!(received["local_position"], json!({"x": 1.5, "y": -2.0, "z": 0.25}));
+    assert!(received.get("position").is_none());
+
+    // Same choice on the f32 wire format
+    client.send(json!({"type": "set_wire_type", "mode": "f32"})).await;
+    client.sync().await;
+    server.publish(&frame());
+    let received = client.recv_frame().await;
+    assert_eq!(received["local_position"], json!({"x": 1.5, "y": -2.0, "z": 0.25}));
+    assert!(received.get("position").is_none());
+}
+
+#[tokio::test]
+async fn test_coordinate_mode_is_per_connection() {
+    let server = TestServer::start().await;
+    let mut local = server.connect("/").await;
+    let mut geodetic = server.connect("/").await;
+    local.send(json!({"type": "set_coords", "mode": "local"})).await;
+    local.sync().await;
+
+    server.publish(&frame());
+    assert!(local.recv_frame().await.get("position").is_none());
+    assert!(geodetic.recv_frame().await.get("local_position").is_none());
+}
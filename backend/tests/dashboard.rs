@@ -0,0 +1,48 @@
+//! Embedded debug dashboard page
+
+mod common;
+
+use common::{frame, free_port, http_get, TestServer};
+use pretty_assertions::assert_eq;
+use sensor_fusion_backend::websocket::DashboardServer;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_dashboard_page_points_at_the_websocket() {
+    let port = free_port();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let server = DashboardServer::new(port, "ws://127.0.0.1:8080/stream").with_shutdown(shutdown_rx);
+    let task = tokio::spawn(server.run());
+    for _ in 0..100 {
+        if tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let (status, body) = http_get(port, "/").await;
+    assert_eq!(status, 200);
+    assert!(body.starts_with("<!DOCTYPE html>"));
+    assert!(body.contains(r#"data-ws-url="ws://127.0.0.1:8080/stream""#), "WebSocket URL not filled in");
+    assert!(!body.contains("{{WS_URL}}"));
+
+    // The page asks for the coordinates its track plots: a client sending
+    // its setup message gets frames with a usable local_position
+    assert!(body.contains("frame.local_position"));
+    let setup = body.split("const SETUP = '").nth(1).and_then(|rest| rest.split('\'').next()).expect("no setup message");
+    let setup: serde_json::Value = serde_json::from_str(setup).unwrap();
+    let ws = TestServer::start().await;
+    let mut client = ws.connect("/").await;
+    client.send(setup).await;
+    client.sync().await;
+    ws.publish(&frame(1));
+    let local = &client.recv_frame().await["local_position"];
+    assert!(local["x"].is_f64() && local["y"].is_f64(), "no local_position in {local}");
+
+    let (status, _) = http_get(port, "/poll").await;
+    assert_eq!(status, 404);
+
+    shutdown_tx.send(true).unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(2), task).await.expect("dashboard still running");
+    assert!(result.unwrap().is_ok());
+}
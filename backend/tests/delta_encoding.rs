@@ -0,0 +1,102 @@
+//! Per-field delta encoding with periodic keyframes
+
+mod common;
+
+use common::{frame, TestServer};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+/// Next frame or delta message
+async fn next_frame_or_delta(client: &mut common::TestClient) -> serde_json::Value {
+    loop {
+        let message = client.recv().await;
+        if message.get("type").is_none() || message["type"] == "delta" {
+            return message;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_deltas_omit_unchanged_fields_and_keyframes_resync() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_delta", "keyframe_every": 3})).await;
+    client.sync().await;
+
+    // Frames differ only in GPS speed
+    let mut received = Vec::new();
+    for seq in 1..=6 {
+        server.publish(&frame(seq));
+        received.push(next_frame_or_delta(&mut client).await);
+    }
+    let kinds: Vec<_> = received.iter().map(|m| m["type"].as_str().unwrap_or("key")).collect();
+    assert_eq!(kinds, ["key", "delta", "delta", "key", "delta", "delta"]);
+    for delta in received.iter().filter(|m| m["type"] == "delta") {
+        let mut fields: Vec<_> = delta.as_object().unwrap().keys().map(String::as_str).collect();
+        fields.sort();
+        assert_eq!(fields, ["gps_speed", "timestamp", "type"]);
+    }
+    assert_eq!(received[4]["gps_speed"], 5.0);
+
+    // A resynced client gets a keyframe next
+    client.send(json!({"type": "resync"})).await;
+    client.sync().await;
+    server.publish(&frame(7));
+    let key = next_frame_or_delta(&mut client).await;
+    assert!(key.get("type").is_none(), "expected a keyframe, got {key}");
+    assert_eq!(key["gps_speed"], 7.0);
+
+    // Full frames again once disabled
+    client.send(json!({"type": "set_delta", "enabled": false})).await;
+    client.sync().await;
+    for seq in 8..=9 {
+        server.publish(&frame(seq));
+        assert!(next_frame_or_delta(&mut client).await.get("type").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_coalesced_deltas_never_leave_the_client_out_of_step() {
+    let server = TestServer::start_with_capacity(1000, |server| server).await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_delta", "keyframe_every": 100000})).await;
+    client.sync().await;
+
+    // GPS speed changes every frame, confidence only on odd ones, so a
+    // dropped odd frame's change must still reach the client
+    let published = |seq: u32| {
+        let mut fused = frame(seq);
+        fused.confidence = (seq - (1 - seq % 2)) as f64;
+        fused
+    };
+    // The client reads nothing while frames go out faster than the writer
+    // sends them, so unsent ones are replaced by newer ones
+    for seq in 1..=500 {
+        server.publish(&published(seq));
+        if seq % 50 == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    let mut state = serde_json::Map::new();
+    let mut received = 0;
+    while let Some(message) = client.try_recv(std::time::Duration::from_millis(200)).await {
+        match message["type"].as_str() {
+            None => state = message.as_object().unwrap().clone(),
+            Some("delta") => {
+                for (key, value) in message.as_object().unwrap() {
+                    match value {
+                        serde_json::Value::Null => state.remove(key),
+                        value => state.insert(key.clone(), value.clone()),
+                    };
+                }
+            }
+            Some(_) => continue,
+        }
+        received += 1;
+        let seq = state["gps_speed"].as_f64().unwrap() as u32;
+        assert_eq!(state["confidence"], published(seq).confidence, "client state drifted at frame {seq}");
+    }
+    assert!(received < 500, "no frame was coalesced");
+    assert_eq!(state["gps_speed"], 500.0);
+}
@@ -0,0 +1,52 @@
+//! Path routing: read-only `/stream` and command-only `/control`
+
+mod common;
+
+use common::{frame, TestServer};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::time::Duration;
+
+fn accel_spike() -> serde_json::Value {
+    json!({"type": "command", "action": "inject_fault", "parameters": {"fault_type": "accel_spike"}})
+}
+
+#[tokio::test]
+async fn test_stream_endpoint_refuses_commands() {
+    let mut server = TestServer::start().await;
+    let mut client = server.connect("/stream").await;
+
+    client.send(accel_spike()).await;
+    let error = client.recv_type("error").await;
+    assert_eq!(error["request"], "command");
+    assert_eq!(error["message"], "read-only endpoint");
+    assert!(tokio::time::timeout(Duration::from_millis(200), server.commands.recv()).await.is_err());
+
+    server.publish(&frame(1));
+    assert_eq!(client.recv_frame().await["gps_speed"], 1.0);
+}
+
+#[tokio::test]
+async fn test_control_endpoint_forwards_commands_without_frames() {
+    let mut server = TestServer::start().await;
+    let mut client = server.connect("/control/").await;
+
+    client.send(accel_spike()).await;
+    assert_eq!(server.next_command().await.action, "accel_spike");
+
+    server.publish(&frame(1));
+    while let Some(message) = client.try_recv(Duration::from_millis(300)).await {
+        assert!(message.get("type").is_some(), "frame streamed to /control: {message}");
+    }
+}
+
+#[tokio::test]
+async fn test_other_paths_stream_and_accept_commands() {
+    let mut server = TestServer::start().await;
+    let mut client = server.connect("/anything").await;
+
+    client.send(accel_spike()).await;
+    assert_eq!(server.next_command().await.action, "accel_spike");
+    server.publish(&frame(2));
+    assert_eq!(client.recv_frame().await["gps_speed"], 2.0);
+}
@@ -0,0 +1,27 @@
+//! Typed errors at the server boundary
+
+use sensor_fusion_backend::websocket::WebSocketServer;
+use sensor_fusion_backend::SensorFusionError;
+use std::sync::Arc;
+
+/// A server on `port` whose channels nothing feeds
+fn server_on(port: u16) -> WebSocketServer {
+    let (tx, _) = tokio::sync::broadcast::channel(1);
+    let (_, latest_rx) = tokio::sync::watch::channel(Default::default());
+    let (cmd_tx, _) = tokio::sync::mpsc::unbounded_channel();
+    WebSocketServer::new(port, Arc::new(tx), latest_rx, Arc::new(cmd_tx), Arc::new(tokio::sync::RwLock::new(None)))
+}
+
+#[tokio::test]
+async fn test_port_in_use_is_a_bind_error() {
+    let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = taken.local_addr().unwrap().port();
+
+    match server_on(port).run().await {
+        Err(SensorFusionError::Bind { addr, source }) => {
+            assert_eq!(addr, format!("127.0.0.1:{}", port));
+            assert_eq!(source.kind(), std::io::ErrorKind::AddrInUse);
+        }
+        other => panic!("expected a Bind error, got {other:?}"),
+    }
+}
@@ -0,0 +1,127 @@
+//! Readings pushed by authenticated sensor sources
+
+mod common;
+
+use common::TestServer;
+use pretty_assertions::assert_eq;
+use sensor_fusion_backend::sensors::{ExternalLimits, ExternalSample};
+use serde_json::json;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+fn gps_message(latitude: f64) -> serde_json::Value {
+    json!({
+        "type": "gps_data",
+        "latitude": latitude,
+        "longitude": -122.4194,
+        "altitude": 52.0,
+        "hdop": 0.9,
+        "satellites": 9,
+    })
+}
+
+fn imu_message() -> serde_json::Value {
+    json!({
+        "type": "imu_data",
+        "acceleration": {"x": 1.25, "y": -0.5, "z": 9.5},
+        "gyroscope": {"x": 0.0, "y": 0.0, "z": 0.1},
+    })
+}
+
+#[tokio::test]
+async fn test_authenticated_source_forwards_imu_reading() {
+    let (samples_tx, mut samples) = mpsc::channel(16);
+    let server = TestServer::start_with(|server| server.with_sensor_source("secret", samples_tx)).await;
+    let mut client = server.connect("/").await;
+
+    client.send(json!({"type": "sensor_source", "token": "secret"})).await;
+    assert_eq!(client.recv_type("sensor_source").await["status"], "accepted");
+    client.send(imu_message()).await;
+
+    let sample = tokio::time::timeout(Duration::from_secs(2), samples.recv()).await.unwrap().unwrap();
+    let ExternalSample::Imu(imu) = sample else {
+        panic!("expected an IMU sample, got {sample:?}");
+    };
+    assert_eq!((imu.acceleration.x, imu.acceleration.y, imu.acceleration.z), (1.25, -0.5, 9.5));
+    assert_eq!((imu.gyroscope.x, imu.gyroscope.y, imu.gyroscope.z), (0.0, 0.0, 0.1));
+    assert_eq!(imu.health, 1.0);
+}
+
+#[tokio::test]
+async fn test_unauthenticated_readings_are_dropped() {
+    let (samples_tx, mut samples) = mpsc::channel(16);
+    let server = TestServer::start_with(|server| server.with_sensor_source("secret", samples_tx)).await;
+    let mut client = server.connect("/").await;
+
+    client.send(imu_message()).await;
+    client.send(json!({"type": "sensor_source", "token": "wrong"})).await;
+    assert_eq!(client.recv_type("sensor_source").await["status"], "rejected");
+    client.send(imu_message()).await;
+    client.sync().await;
+
+    assert!(samples.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_implausible_reading_is_rejected_with_error() {
+    let (samples_tx, mut samples) = mpsc::channel(16);
+    let server = TestServer::start_with(|server| server.with_sensor_source("secret", samples_tx)).await;
+    let mut client = server.connect("/").await;
+
+    client.send(json!({"type": "sensor_source", "token": "secret"})).await;
+    client.recv_type("sensor_source").await;
+    client.send(json!({
+        "type": "imu_data",
+        "acceleration": {"x": 1000.0, "y": 0.0, "z": 0.0},
+        "gyroscope": {"x": 0.0, "y": 0.0, "z": 0.0},
+    })).await;
+
+    let error = client.recv_type("error").await;
+    assert_eq!(error["request"], "imu_data");
+    client.sync().await;
+    assert!(samples.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_latitude_out_of_range_is_rejected() {
+    let (samples_tx, mut samples) = mpsc::channel(16);
+    let server = TestServer::start_with(|server| server.with_sensor_source("secret", samples_tx)).await;
+    let mut client = server.connect("/").await;
+
+    client.send(json!({"type": "sensor_source", "token": "secret"})).await;
+    client.recv_type("sensor_source").await;
+    client.send(gps_message(200.0)).await;
+
+    let error = client.recv_type("error").await;
+    assert_eq!(error["request"], "gps_data");
+    let message = error["message"].as_str().unwrap();
+    assert!(message.contains("latitude") && message.contains("200"), "{message}");
+    client.sync().await;
+    assert!(samples.try_recv().is_err());
+
+    // The same fix at a real latitude goes through
+    client.send(gps_message(37.7749)).await;
+    let sample = tokio::time::timeout(Duration::from_secs(2), samples.recv()).await.unwrap().unwrap();
+    assert!(matches!(sample, ExternalSample::Gps(gps) if gps.latitude == 37.7749));
+}
+
+#[tokio::test]
+async fn test_raised_limits_accept_extreme_maneuvers() {
+    let (samples_tx, mut samples) = mpsc::channel(16);
+    let limits = ExternalLimits { max_acceleration: 2000.0, ..ExternalLimits::default() };
+    let server = TestServer::start_with(|server| {
+        server.with_sensor_source("secret", samples_tx).with_external_limits(limits)
+    }).await;
+    let mut client = server.connect("/").await;
+
+    client.send(json!({"type": "sensor_source", "token": "secret"})).await;
+    client.recv_type("sensor_source").await;
+    client.send(json!({
+        "type": "imu_data",
+        "acceleration": {"x": 1000.0, "y": 0.0, "z": 0.0},
+        "gyroscope": {"x": 0.0, "y": 0.0, "z": 0.0},
+    })).await;
+
+    let sample = tokio::time::timeout(Duration::from_secs(2), samples.recv()).await.unwrap().unwrap();
+    assert!(matches!(sample, ExternalSample::Imu(imu) if imu.acceleration.x == 1000.0));
+}
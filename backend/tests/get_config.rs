@@ -0,0 +1,33 @@
+//! Configuration served to get_config requests
+
+mod common;
+
+use common::TestServer;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_get_config_serves_the_current_configuration() {
+    let (config_tx, config_rx) = tokio::sync::watch::channel(json!({"filter": "complementary", "filter_alpha": 0.98}));
+    let server = TestServer::start_with(|server| server.with_config(config_rx)).await;
+    let mut client = server.connect("/").await;
+
+    client.send(json!({"type": "get_config"})).await;
+    let reply = client.recv_type("config").await;
+    assert_eq!(reply["config"], json!({"filter": "complementary", "filter_alpha": 0.98}));
+
+    // Settings changed at runtime show up in the next reply
+    config_tx.send_modify(|config| config["filter_alpha"] = json!(0.9));
+    client.send(json!({"type": "get_config"})).await;
+    assert_eq!(client.recv_type("config").await["config"]["filter_alpha"], 0.9);
+}
+
+#[tokio::test]
+async fn test_get_config_without_a_configuration_is_an_error() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+
+    client.send(json!({"type": "get_config"})).await;
+    let reply = client.recv_type("error").await;
+    assert_eq!(reply["request"], "get_config");
+}
@@ -0,0 +1,35 @@
+//! Server-initiated keepalive pings
+
+mod common;
+
+use common::TestServer;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Pings received on an idle connection over `window`
+async fn pings_within(server: &TestServer, window: Duration) -> usize {
+    let mut client = server.connect("/").await;
+    let deadline = tokio::time::Instant::now() + window;
+    let mut pings = 0;
+    while let Ok(Some(message)) = tokio::time::timeout_at(deadline, client.ws.next()).await {
+        match message.unwrap() {
+            Message::Ping(_) => pings += 1,
+            other => panic!("unexpected message on an idle connection: {other:?}"),
+        }
+    }
+    pings
+}
+
+#[tokio::test]
+async fn test_idle_connection_receives_periodic_pings() {
+    let server = TestServer::start_with(|server| server.with_keepalive(Duration::from_millis(100))).await;
+    let pings = pings_within(&server, Duration::from_millis(550)).await;
+    assert!((3..=7).contains(&pings), "{pings} pings in 550 ms at a 100 ms interval");
+}
+
+#[tokio::test]
+async fn test_no_pings_without_keepalive() {
+    let server = TestServer::start().await;
+    assert_eq!(pings_within(&server, Duration::from_millis(300)).await, 0);
+}
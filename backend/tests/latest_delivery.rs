@@ -0,0 +1,50 @@
+//! Latest-only delivery over the watch channel
+
+mod common;
+
+use common::{frame, TestServer};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_slow_latest_only_client_gets_newest_frame_without_lag() {
+    // A tiny broadcast buffer lags every-frame clients almost at once
+    let server = TestServer::start_with_capacity(4, |server| server).await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_delivery", "mode": "latest"})).await;
+    client.sync().await;
+
+    // The client reads nothing while far more frames than the buffer holds go out
+    for seq in 1..=500 {
+        server.publish(&frame(seq));
+        if seq % 50 == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut speeds = Vec::new();
+    while let Some(message) = client.try_recv(Duration::from_millis(200)).await {
+        assert!(message.get("type").is_none(), "unexpected message {message}");
+        speeds.push(message["gps_speed"].as_f64().unwrap());
+    }
+    assert!(!speeds.is_empty());
+    assert!(speeds.len() < 500, "every frame was delivered");
+    assert!(speeds.windows(2).all(|pair| pair[0] < pair[1]), "frames out of order: {speeds:?}");
+    assert_eq!(speeds.last(), Some(&500.0));
+}
+
+#[tokio::test]
+async fn test_switching_back_to_all_delivers_every_frame() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_delivery", "mode": "latest"})).await;
+    client.send(json!({"type": "set_delivery", "mode": "all"})).await;
+    client.sync().await;
+
+    for seq in 1..=5 {
+        server.publish(&frame(seq));
+        assert_eq!(client.recv_frame().await["gps_speed"], seq as f64);
+    }
+}
@@ -0,0 +1,122 @@
+//! HTTP long-poll fallback
+
+mod common;
+
+use common::{frame, TestServer};
+use pretty_assertions::assert_eq;
+use sensor_fusion_backend::models::FusedSensorData;
+use sensor_fusion_backend::sinks::{BroadcastSink, Sink};
+use sensor_fusion_backend::websocket::ConnectionStats;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Sequence numbers and GPS speed markers of the frames in a poll response
+fn polled(body: &str) -> (Vec<u64>, Vec<f64>, Value) {
+    let slice: Value = serde_json::from_str(body).unwrap();
+    let frames = slice["frames"].as_array().unwrap();
+    let seqs = frames.iter().map(|f| f["seq"].as_u64().unwrap()).collect();
+    let speeds = frames.iter().map(|f| f["frame"]["gps_speed"].as_f64().unwrap()).collect();
+    (seqs, speeds, slice)
+}
+
+#[tokio::test]
+async fn test_poll_with_stale_since_returns_buffered_frames() {
+    let server = TestServer::start_with(|server| server.with_long_poll(16, Duration::from_millis(200))).await;
+    for seq in 1..=5 {
+        server.publish(&frame(seq));
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let (status, body) = server.http_get("/poll?since=2").await;
+    assert_eq!(status, 200);
+    let (seqs, speeds, slice) = polled(&body);
+    assert_eq!(seqs, [3, 4, 5]);
+    assert_eq!(speeds, [3.0, 4.0, 5.0]);
+    assert_eq!(slice["latest_seq"], 5);
+    assert_eq!(slice["gap"], false);
+
+    // Without a cursor, start from the newest frame
+    let (_, body) = server.http_get("/poll").await;
+    assert_eq!(polled(&body).0, [5]);
+}
+
+#[tokio::test]
+async fn test_poll_behind_the_buffer_reports_a_gap() {
+    let server = TestServer::start_with(|server| server.with_long_poll(4, Duration::from_millis(200))).await;
+    for seq in 1..=10 {
+        server.publish(&frame(seq));
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let (_, body) = server.http_get("/poll?since=1").await;
+    let (seqs, _, slice) = polled(&body);
+    assert_eq!(seqs, [10]);
+    assert_eq!(slice["gap"], true);
+}
+
+#[tokio::test]
+async fn test_poll_ahead_of_the_buffer_reports_a_gap_right_away() {
+    let server = TestServer::start_with(|server| server.with_long_poll(16, Duration::from_secs(5))).await;
+    for seq in 1..=3 {
+        server.publish(&frame(seq));
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // A cursor from before a restart: answered now, not after the timeout
+    let started = std::time::Instant::now();
+    let (_, body) = server.http_get("/poll?since=500").await;
+    assert!(started.elapsed() < Duration::from_secs(1), "waited {:?}", started.elapsed());
+    let (seqs, _, slice) = polled(&body);
+    assert_eq!(seqs, [3]);
+    assert_eq!(slice["latest_seq"], 3);
+    assert_eq!(slice["gap"], true);
+}
+
+#[tokio::test]
+async fn test_poll_waits_for_the_next_frame() {
+    let server = TestServer::start_with(|server| server.with_long_poll(16, Duration::from_secs(2))).await;
+    server.publish(&frame(1));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let poll = tokio::spawn(common::http_get(server.port, "/poll?since=1"));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    server.publish(&frame(2));
+    let (status, body) = poll.await.unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(polled(&body).0, [2]);
+
+    // Nothing new: an empty slice once the timeout passes
+    let (_, body) = server.http_get("/poll?since=2").await;
+    assert!(polled(&body).0.is_empty());
+}
+
+#[tokio::test]
+async fn test_poll_rejects_bad_cursor_and_unknown_paths() {
+    let server = TestServer::start_with(|server| server.with_long_poll(16, Duration::from_millis(200))).await;
+    assert_eq!(server.http_get("/poll?since=abc").await.0, 400);
+    assert_eq!(server.http_get("/nope").await.0, 404);
+}
+
+#[tokio::test]
+async fn test_history_fills_with_no_clients_connected() {
+    let clients = Arc::new(ConnectionStats::new());
+    let server = TestServer::start_with(|server| {
+        server
+            .with_long_poll(16, Duration::from_millis(200))
+            .with_connection_stats(clients.clone())
+    })
+    .await;
+    let (latest_tx, _) = watch::channel(FusedSensorData::default());
+    let mut sink = BroadcastSink::new(server.sensor_tx.clone(), latest_tx).with_connection_stats(clients.clone());
+
+    for seq in 1..=4 {
+        sink.send(&frame(seq)).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(clients.total(), 0);
+    let (_, body) = server.http_get("/poll?since=0").await;
+    assert_eq!(polled(&body).1, [1.0, 2.0, 3.0, 4.0]);
+}
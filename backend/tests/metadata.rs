@@ -0,0 +1,74 @@
+//! Coordinate metadata: frame IDs, axis convention and units
+
+mod common;
+
+use common::{frame, TestClient, TestServer};
+use pretty_assertions::assert_eq;
+use sensor_fusion_backend::fusion::AccelFrame;
+use sensor_fusion_backend::websocket::{FrameIds, MetadataConfig};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_metadata_describes_the_active_frame_and_units() {
+    let server = TestServer::start_with(|server| {
+        server.with_metadata(MetadataConfig {
+            frame_ids: FrameIds { world: "odom".to_string(), ..FrameIds::default() },
+            accel_frame: AccelFrame::World,
+            every_frame: false,
+        })
+    })
+    .await;
+    let url = format!("ws://127.0.0.1:{}/", server.port);
+    let (ws, _) = tokio_tungstenite::connect_async(url).await.expect("connect failed");
+    let mut client = TestClient { ws };
+
+    let welcome = client.recv().await;
+    let metadata = &welcome["metadata"];
+    assert_eq!(metadata["frame_ids"], json!({"world": "odom", "body": "base_link", "imu": "imu_link", "gps": "gps_link"}));
+    assert_eq!(metadata["acceleration_frame"], "odom");
+    assert_eq!(metadata["axis_convention"], "ned");
+    assert_eq!(metadata["position"], "geodetic");
+    assert_eq!(metadata["units"]["acceleration"], "m/s^2");
+    assert_eq!(metadata["units"]["horizontal_position"], "deg");
+    assert_eq!(metadata["units"]["timestamp"], "rfc3339");
+
+    // Frames stay lean by default
+    server.publish(&frame(1));
+    assert!(client.recv_frame().await.get("metadata").is_none());
+
+    // After changing settings, a request returns the metadata as it now is
+    client.send(json!({"type": "set_accel_units", "units": "g"})).await;
+    client.send(json!({"type": "set_frame_convention", "convention": "enu"})).await;
+    client.send(json!({"type": "set_coords", "mode": "local"})).await;
+    client.send(json!({"type": "set_timestamp_format", "format": "epoch_millis"})).await;
+    client.send(json!({"type": "get_metadata"})).await;
+    let metadata = client.recv_type("metadata").await["metadata"].clone();
+    assert_eq!(metadata["units"]["acceleration"], "g");
+    assert_eq!(metadata["axis_convention"], "enu");
+    assert_eq!(metadata["position"], "local");
+    assert_eq!(metadata["units"]["horizontal_position"], "m");
+    assert_eq!(metadata["units"]["timestamp"], "epoch_millis");
+}
+
+#[tokio::test]
+async fn test_metadata_in_every_frame_when_configured() {
+    let server = TestServer::start_with(|server| {
+        server.with_metadata(MetadataConfig { every_frame: true, ..MetadataConfig::default() })
+    })
+    .await;
+    let mut client = server.connect("/").await;
+
+    server.publish(&frame(1));
+    let received = client.recv_frame().await;
+    assert_eq!(received["metadata"]["acceleration_frame"], "imu_link");
+    assert_eq!(received["gps_speed"], 1.0);
+
+    // Unchanged metadata drops out of delta frames after the keyframe
+    client.send(json!({"type": "set_delta", "enabled": true})).await;
+    client.sync().await;
+    server.publish(&frame(2));
+    assert!(client.recv_frame().await.get("metadata").is_some());
+    server.publish(&frame(3));
+    let delta = client.recv_type("delta").await;
+    assert!(delta.get("metadata").is_none());
+}
@@ -0,0 +1,60 @@
+//! Per-client choice of orientation representation
+
+mod common;
+
+use common::{frame, TestServer};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_euler_only_client_gets_no_quaternion() {
+    let server = TestServer::start().await;
+    let mut euler_only = server.connect("/").await;
+    let mut default = server.connect("/").await;
+    euler_only.send(json!({"type": "set_orientation_format", "format": "euler"})).await;
+    euler_only.sync().await;
+
+    server.publish(&frame(1));
+    let message = euler_only.recv_frame().await;
+    assert!(message.get("orientation").is_none(), "quaternion sent: {}", message["orientation"]);
+    assert!(message["euler_degrees"].is_array());
+
+    // Other clients keep both
+    let message = default.recv_frame().await;
+    assert!(message["orientation"].is_object() && message["euler_degrees"].is_array());
+}
+
+#[tokio::test]
+async fn test_quaternion_only_and_unknown_formats() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_orientation_format", "format": "quaternion"})).await;
+    // Neither representation is never an option; an unknown format changes nothing
+    client.send(json!({"type": "set_orientation_format", "format": "none"})).await;
+    client.sync().await;
+
+    server.publish(&frame(1));
+    let message = client.recv_frame().await;
+    assert!(message["orientation"].is_object());
+    assert!(message.get("euler_degrees").is_none());
+}
+
+#[tokio::test]
+async fn test_ros_client_gets_enu_orientation() {
+    let server = TestServer::start().await;
+    let mut ros = server.connect("/").await;
+    let mut default = server.connect("/").await;
+    ros.send(json!({"type": "set_frame_convention", "convention": "enu"})).await;
+    ros.sync().await;
+
+    // Level, heading east: the ENU and FLU axes line up
+    let mut east = frame(1);
+    east.orientation = sensor_fusion_backend::models::Quaternion::from_euler(0.0, 0.0, 90f64.to_radians());
+    server.publish(&east);
+    let message = ros.recv_frame().await;
+    assert!((message["orientation"]["w"].as_f64().unwrap() - 1.0).abs() < 1e-9, "{}", message["orientation"]);
+    assert!(message["euler_degrees"][2].as_f64().unwrap().abs() < 1e-9);
+
+    // Other clients keep the aerospace convention
+    let message = default.recv_frame().await;
+    assert!((message["orientation"]["z"].as_f64().unwrap() - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-9);
+}
@@ -0,0 +1,42 @@
+//! Origin checking during the WebSocket handshake
+
+mod common;
+
+use common::TestServer;
+use pretty_assertions::assert_eq;
+use sensor_fusion_backend::websocket::OriginPolicy;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Error;
+
+const DASHBOARD: &str = "https://dashboard.example.com";
+
+async fn handshake(port: u16, origin: Option<&str>) -> Result<(), Error> {
+    let mut request = format!("ws://127.0.0.1:{port}/").into_client_request().unwrap();
+    if let Some(origin) = origin {
+        request.headers_mut().insert("origin", origin.parse().unwrap());
+    }
+    tokio_tungstenite::connect_async(request).await.map(|_| ())
+}
+
+#[tokio::test]
+async fn test_disallowed_origin_is_rejected() {
+    let server = TestServer::start_with(|server| server.with_origin_policy(OriginPolicy::new([DASHBOARD], true))).await;
+
+    match handshake(server.port, Some("https://evil.example.com")).await {
+        Err(Error::Http(response)) => {
+            assert_eq!(response.status().as_u16(), 403);
+            assert_eq!(response.body().as_deref(), Some(&b"origin not allowed"[..]));
+        }
+        other => panic!("expected a 403 rejection, got {other:?}"),
+    }
+    handshake(server.port, Some(DASHBOARD)).await.expect("allowed origin rejected");
+    handshake(server.port, None).await.expect("origin-less client rejected");
+}
+
+#[tokio::test]
+async fn test_missing_origin_rejected_when_required() {
+    let server = TestServer::start_with(|server| server.with_origin_policy(OriginPolicy::new([DASHBOARD], false))).await;
+
+    assert!(matches!(handshake(server.port, None).await, Err(Error::Http(_))));
+    handshake(server.port, Some(DASHBOARD)).await.expect("allowed origin rejected");
+}
@@ -0,0 +1,43 @@
+//! Per-client pretty-printed JSON for debugging
+
+mod common;
+
+use common::{frame, TestServer, RECV_TIMEOUT};
+use futures_util::StreamExt;
+use serde_json::json;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Next fused frame's raw text, skipping replies and notices
+async fn next_frame_text(client: &mut common::TestClient) -> String {
+    loop {
+        let message = tokio::time::timeout(RECV_TIMEOUT, client.ws.next())
+            .await
+            .expect("no frame within timeout")
+            .expect("connection closed")
+            .expect("websocket error");
+        if let Message::Text(text) = message {
+            let value: serde_json::Value = serde_json::from_str(&text).expect("invalid JSON");
+            if value.get("type").is_none() {
+                return text;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_pretty_client_receives_indented_frames() {
+    let server = TestServer::start().await;
+    let mut pretty = server.connect("/").await;
+    let mut compact = server.connect("/").await;
+    pretty.send(json!({"type": "set_pretty", "enabled": true})).await;
+    pretty.sync().await;
+
+    server.publish(&frame(7));
+    let text = next_frame_text(&mut pretty).await;
+    assert!(text.starts_with("{\n  \""), "not indented: {text}");
+    let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(value["gps_speed"], 7.0);
+
+    // Off by default for everyone else
+    assert!(!next_frame_text(&mut compact).await.contains('\n'));
+}
@@ -0,0 +1,40 @@
+//! Client-facing handling of frames that fail to serialize
+
+mod common;
+
+use common::{frame, TestServer};
+use sensor_fusion_backend::models::FusedSensorData;
+use sensor_fusion_backend::websocket::client::SANITIZE_AFTER;
+
+/// A frame with NaN in a field every client receives
+fn nan_frame(seq: u32) -> FusedSensorData {
+    FusedSensorData {
+        confidence: f64::NAN,
+        ..frame(seq)
+    }
+}
+
+#[tokio::test]
+async fn test_failed_frame_sends_an_error_then_sanitizes() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+
+    // Each corrupt frame is reported instead of silently skipped
+    for seq in 1..=SANITIZE_AFTER {
+        server.publish(&nan_frame(seq));
+        let error = client.recv().await;
+        assert_eq!(error["type"], "error");
+        assert_eq!(error["detail"], "serialization_failed");
+    }
+
+    // Failures persisted, so later corrupt frames go out sanitized
+    server.publish(&nan_frame(10));
+    let sanitized = client.recv().await;
+    assert!(sanitized.get("type").is_none(), "expected a frame, got {sanitized}");
+    assert_eq!(sanitized["gps_speed"], 10.0);
+    assert_eq!(sanitized["confidence"], 0.0);
+
+    // Clean frames are untouched
+    server.publish(&frame(11));
+    assert_eq!(client.recv_frame().await["confidence"], 1.0);
+}
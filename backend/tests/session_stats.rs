@@ -0,0 +1,62 @@
+//! Per-connection statistics sent when a session ends
+
+mod common;
+
+use common::{frame, TestServer, RECV_TIMEOUT};
+use futures_util::StreamExt;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+async fn test_bye_gets_session_stats_before_close() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    for seq in 1..=3 {
+        server.publish(&frame(seq));
+        client.recv_frame().await;
+    }
+
+    client.send(json!({"type": "bye"})).await;
+    let stats = client.recv_type("session_stats").await;
+    assert_eq!(stats["frames_sent"], 3);
+    assert_eq!(stats["skipped"], 0);
+    assert!(stats["duration_secs"].as_f64().is_some_and(|secs| secs > 0.0));
+
+    let closing = tokio::time::timeout(RECV_TIMEOUT, client.ws.next()).await.unwrap();
+    assert!(matches!(closing, Some(Ok(Message::Close(_)))), "expected Close, got {closing:?}");
+}
+
+#[tokio::test]
+async fn test_plain_frames_report_a_compression_ratio_of_one() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    server.publish(&frame(1));
+    client.recv_frame().await;
+    client.sync().await; // replies count towards bytes_sent too
+
+    client.send(json!({"type": "bye"})).await;
+    let stats = client.recv_type("session_stats").await;
+    assert_eq!(stats["compression_ratio"], 1.0);
+    assert_eq!(stats["frame_bytes"], stats["raw_frame_bytes"]);
+    assert!(stats["bytes_sent"].as_u64() > stats["frame_bytes"].as_u64());
+}
+
+#[tokio::test]
+async fn test_delta_encoding_reports_a_compression_ratio_above_one() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_delta"})).await;
+    client.send(json!({"type": "set_stats_interval", "interval_ms": 50})).await;
+    client.sync().await;
+    for seq in 1..=5 {
+        server.publish(&frame(seq));
+        // Keyframe, then deltas
+        while client.recv().await["type"] == "bandwidth" {}
+    }
+
+    let report = client.recv_type("bandwidth").await;
+    let ratio = report["compression_ratio"].as_f64().unwrap();
+    assert!(ratio > 1.0, "ratio {ratio}");
+    assert!(report["raw_frame_bytes"].as_u64() > report["frame_bytes"].as_u64());
+}
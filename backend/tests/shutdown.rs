@@ -0,0 +1,29 @@
+//! Server shutdown closes open connections and stops accepting
+
+mod common;
+
+use common::{frame, TestServer};
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+async fn test_shutdown_closes_connections_with_session_stats() {
+    let (shutdown, shutdown_rx) = watch::channel(false);
+    let server = TestServer::start_with(|server| server.with_shutdown(shutdown_rx)).await;
+    let mut client = server.connect("/").await;
+    server.publish(&frame(1));
+    client.recv_frame().await;
+
+    shutdown.send(true).unwrap();
+    let stats = client.recv_type("session_stats").await;
+    assert_eq!(stats["frames_sent"], 1);
+    let closing = tokio::time::timeout(Duration::from_secs(2), client.ws.next()).await.unwrap();
+    assert!(matches!(closing, Some(Ok(Message::Close(_)))), "expected Close, got {closing:?}");
+
+    // The listener is gone once the server has stopped
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(TcpStream::connect(("127.0.0.1", server.port)).await.is_err());
+}
@@ -0,0 +1,28 @@
+//! Latest frame sent to a client right after the welcome message
+
+mod common;
+
+use common::{frame, TestServer};
+use pretty_assertions::assert_eq;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_client_connecting_mid_stream_gets_latest_frame_at_once() {
+    let server = TestServer::start().await;
+    for seq in 1..=3 {
+        server.publish(&frame(seq));
+    }
+
+    // Within one 50 Hz tick, with nothing new published
+    let mut client = server.connect("/").await;
+    let snapshot = client.try_recv(Duration::from_millis(20)).await.expect("no snapshot after welcome");
+    assert!(snapshot.get("type").is_none(), "expected a frame, got {snapshot}");
+    assert_eq!(snapshot["gps_speed"], 3.0);
+}
+
+#[tokio::test]
+async fn test_client_connecting_before_first_frame_gets_only_welcome() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    assert_eq!(client.try_recv(Duration::from_millis(200)).await, None);
+}
@@ -0,0 +1,45 @@
+//! Per-connection subscriptions to streamed message types
+
+mod common;
+
+use common::{frame, TestServer};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+#[tokio::test]
+async fn test_unknown_only_subscription_gets_no_frames() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "subscribe_types", "types": ["alert"]})).await;
+    client.sync().await;
+
+    server.publish(&frame(1));
+    assert_eq!(client.try_recv(Duration::from_millis(200)).await, None);
+
+    // Replies still arrive, and clearing the subscription restores frames
+    client.send(json!({"type": "subscribe_types", "types": null})).await;
+    client.sync().await;
+    server.publish(&frame(2));
+    assert_eq!(client.recv_frame().await["gps_speed"], 2.0);
+}
+
+#[tokio::test]
+async fn test_subscription_filters_notices_and_frames() {
+    let (notices, _) = broadcast::channel::<Arc<str>>(16);
+    let server = TestServer::start_with(|server| server.with_notices(notices.clone())).await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "subscribe_types", "types": ["spectrum"]})).await;
+    client.sync().await;
+
+    server.publish(&frame(1));
+    notices.send(Arc::from(r#"{"type":"field_stats"}"#)).unwrap();
+    notices.send(Arc::from(r#"{"type":"spectrum","peak_hz":2.0}"#)).unwrap();
+
+    // Only the spectrum gets through, not the frame or the field stats
+    let message = client.recv().await;
+    assert_eq!(message["type"], "spectrum");
+    assert_eq!(client.try_recv(Duration::from_millis(200)).await, None);
+}
@@ -0,0 +1,51 @@
+//! Timestamp format of streamed frames
+
+mod common;
+
+use chrono::{DateTime, TimeZone, Utc};
+use common::{frame, TestServer};
+use pretty_assertions::assert_eq;
+use sensor_fusion_backend::models::{FusedSensorData, TimestampFormat};
+use serde_json::json;
+
+/// A timestamp with sub-millisecond digits to lose
+fn measured_at() -> DateTime<Utc> {
+    Utc.timestamp_opt(1_733_567_400, 123_456_789).unwrap()
+}
+
+#[tokio::test]
+async fn test_epoch_millis_timestamps_round_trip() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+    client.send(json!({"type": "set_timestamp_format", "format": "epoch_millis"})).await;
+    client.sync().await;
+
+    server.publish(&FusedSensorData { timestamp: measured_at(), ..frame(1) });
+    let message = client.recv_frame().await;
+    assert_eq!(message["timestamp"], json!(1_733_567_400_123_i64));
+
+    // Sub-millisecond precision is truncated
+    let decoded: FusedSensorData = serde_json::from_value(message).unwrap();
+    assert_eq!(decoded.timestamp, Utc.timestamp_opt(1_733_567_400, 123_000_000).unwrap());
+}
+
+#[tokio::test]
+async fn test_rfc3339_is_the_default_and_keeps_nanoseconds() {
+    let server = TestServer::start().await;
+    let mut client = server.connect("/").await;
+
+    server.publish(&FusedSensorData { timestamp: measured_at(), ..frame(1) });
+    let message = client.recv_frame().await;
+    assert_eq!(message["timestamp"], json!("2024-12-07T10:30:00.123456789Z"));
+    let decoded: FusedSensorData = serde_json::from_value(message).unwrap();
+    assert_eq!(decoded.timestamp, measured_at());
+}
+
+#[tokio::test]
+async fn test_server_default_format_applies_to_new_clients() {
+    let server = TestServer::start_with(|server| server.with_timestamp_format(TimestampFormat::EpochMillis)).await;
+    let mut client = server.connect("/").await;
+
+    server.publish(&FusedSensorData { timestamp: measured_at(), ..frame(1) });
+    assert!(client.recv_frame().await["timestamp"].is_i64());
+}
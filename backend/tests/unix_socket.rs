@@ -0,0 +1,93 @@
+//! WebSocket clients on a Unix domain socket instead of the TCP port
+
+#![cfg(unix)]
+
+mod common;
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{frame, RECV_TIMEOUT};
+use futures_util::{SinkExt, StreamExt};
+use sensor_fusion_backend::models::FusedSensorData;
+use sensor_fusion_backend::websocket::WebSocketServer;
+use serde_json::{json, Value};
+use tokio::net::UnixStream;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+/// Connect to `path` on the server's socket and consume the welcome message
+async fn connect(socket: &Path, path: &str) -> WebSocketStream<UnixStream> {
+    let stream = UnixStream::connect(socket).await.expect("socket connect failed");
+    let (mut ws, _) = tokio_tungstenite::client_async(format!("ws://localhost{}", path), stream)
+        .await
+        .expect("handshake failed");
+    assert_eq!(recv(&mut ws).await["type"], "connection");
+    ws
+}
+
+/// Next text message, parsed
+async fn recv(ws: &mut WebSocketStream<UnixStream>) -> Value {
+    loop {
+        let message = tokio::time::timeout(RECV_TIMEOUT, ws.next())
+            .await
+            .expect("no message within timeout")
+            .expect("connection closed")
+            .expect("websocket error");
+        if let Message::Text(text) = message {
+            return serde_json::from_str(&text).expect("invalid JSON");
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_client_on_unix_socket_receives_frames() {
+    let socket = std::env::temp_dir().join(format!("sensor-fusion-ws-{}.sock", std::process::id()));
+    let (sensor_tx, _) = broadcast::channel::<FusedSensorData>(100);
+    let sensor_tx = Arc::new(sensor_tx);
+    let (_latest_tx, latest_rx) = watch::channel(FusedSensorData::default());
+    let (cmd_tx, mut commands) = mpsc::unbounded_channel();
+    let server = WebSocketServer::new(0, sensor_tx.clone(), latest_rx, Arc::new(cmd_tx), Arc::new(tokio::sync::RwLock::new(None)))
+        .with_unix_socket(&socket);
+    let task = tokio::spawn(server.run());
+    for _ in 0..100 {
+        if socket.exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let mut client = connect(&socket, "/").await;
+    sensor_tx.send(frame(7)).unwrap();
+    let received = loop {
+        let message = recv(&mut client).await;
+        if message.get("type").is_none() {
+            break message;
+        }
+    };
+    assert_eq!(received["gps_speed"], 7.0);
+
+    // Commands work too, and are logged against the socket client
+    let command = json!({"type": "command", "action": "inject_fault", "parameters": {"fault_type": "gyro_spike"}});
+    client.send(Message::Text(command.to_string())).await.unwrap();
+    let forwarded = tokio::time::timeout(RECV_TIMEOUT, commands.recv()).await.unwrap().unwrap();
+    assert_eq!(forwarded.action, "gyro_spike");
+    client.send(Message::Text(json!({"type": "command_history"}).to_string())).await.unwrap();
+    let history = loop {
+        let message = recv(&mut client).await;
+        if message["type"] == "command_history" {
+            break message;
+        }
+    };
+    assert_eq!(history["commands"][0]["peer"], "unix#1");
+
+    // The request path still selects the endpoint
+    let mut reader = connect(&socket, "/stream").await;
+    reader.send(Message::Text(command.to_string())).await.unwrap();
+    assert_eq!(recv(&mut reader).await["message"], "read-only endpoint");
+
+    task.abort();
+    let _ = std::fs::remove_file(&socket);
+}
@@ -0,0 +1,19 @@
+//! The connection message sent first on every connection
+
+mod common;
+
+use common::{TestClient, TestServer};
+use pretty_assertions::assert_eq;
+use sensor_fusion_backend::models::SCHEMA_VERSION;
+
+#[tokio::test]
+async fn test_welcome_announces_the_schema_version() {
+    let server = TestServer::start().await;
+    let url = format!("ws://127.0.0.1:{}/", server.port);
+    let (ws, _) = tokio_tungstenite::connect_async(url).await.expect("connect failed");
+    let mut client = TestClient { ws };
+
+    let welcome = client.recv().await;
+    assert_eq!(welcome["type"], "connection");
+    assert_eq!(welcome["schema_version"], SCHEMA_VERSION);
+}